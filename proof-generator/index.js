@@ -23,24 +23,40 @@ const {
   populateProvedTransfer,
   generateTransferProof: railgunGenerateTransferProof,
   getShieldPrivateKeySignatureMessage,
+  getWalletTransactionHistory,
+  stopRailgunEngine,
+  getProver,
+  ArtifactStore,
 } = require('@railgun-community/wallet');
 const LevelDOWN = require('leveldown');
+const { groth16 } = require('snarkjs');
+const crypto = require('crypto');
+const fs = require('fs');
+const path = require('path');
+const zlib = require('zlib');
 const {
   NetworkName,
   TXIDVersion,
   NETWORK_CONFIG,
   RailgunWalletBalanceBucket,
+  TransactionHistoryItemCategory,
+  ChainType,
   calculateGasPrice,
 } = require('@railgun-community/shared-models');
-const { parseUnits, randomBytes, hexlify, keccak256 } = require('ethers');
+const { parseUnits, randomBytes, hexlify, keccak256, JsonRpcProvider } = require('ethers');
+const readline = require('readline');
 
-// Chain-specific contract addresses
-const CHAIN_CONTRACTS = {
+// Supported chains: network, RPC and contract addresses
+const CHAINS = {
   1: { // Ethereum Mainnet
+    networkName: NetworkName.Ethereum,
+    rpcUrl: 'https://eth.llamarpc.com',
     railgun: '0xFA7093CDD9EE6932B4eb2c9e1cde21625Cb940291',
     weth: '0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2',
   },
   11155111: { // Sepolia Testnet
+    networkName: NetworkName.EthereumSepolia,
+    rpcUrl: 'https://ethereum-sepolia-rpc.publicnode.com',
     railgun: '0xeCFCf3b4eC647c4Ca6D49108b311b7a7C9543fea',
     weth: '0xfFf9976782d46CC05630D1f6eBAb18b2324d6B14',
   },
@@ -48,15 +64,47 @@ const CHAIN_CONTRACTS = {
 
 // Global state
 let railgunInitialized = false;
+let railgunInitPromise = null;
+// Chain ID -> provider load, one per chain
+const providerPromises = new Map();
+// `${chainId}:${walletId}` -> balances by bucket, and the scans waiting on them
 const balanceCache = new Map();
-let balanceUpdatePromise = null;
-let currentNetworkName = null;
+const balanceWaiters = new Map();
+// Chain ID -> progress listeners of the scans running on it
+const scanProgressListeners = new Map();
+
+/**
+ * Chain a request is for, from its `chainId`
+ *
+ * Every request names its chain; unknown chains are refused rather than
+ * served from another network.
+ */
+function chainConfig(params) {
+  const rawChainId = params.chainId ?? params.chain_id;
+  if (rawChainId === undefined || rawChainId === null) {
+    throw new Error('Chain ID required');
+  }
+  const chainId = Number(rawChainId);
+  const chain = CHAINS[chainId];
+  if (!chain) {
+    throw new Error(`Unsupported chain ID ${rawChainId}`);
+  }
+  return { chainId, ...chain };
+}
+
+function balanceKey(chainId, walletId) {
+  return `${chainId}:${walletId}`;
+}
 
 /**
  * Balance callback system - according to RAILGUN docs
  */
 const onUTXOMerkletreeScanCallback = (eventData) => {
   console.log('📊 UTXO scan:', eventData.progress, eventData.scanStatus);
+  const listeners = scanProgressListeners.get(eventData.chain?.id);
+  if (listeners) {
+    listeners.forEach((listener) => listener(eventData));
+  }
 };
 
 const onTXIDMerkletreeScanCallback = (eventData) => {
@@ -72,63 +120,155 @@ const onBalanceUpdateCallback = (balancesFormatted) => {
     })));
   }
   
-  // Store in cache
-  balanceCache.set(balancesFormatted.balanceBucket, balancesFormatted);
-  
-  // Resolve promise if waiting for spendable balance
-  if (balanceUpdatePromise && balancesFormatted.balanceBucket === RailgunWalletBalanceBucket.Spendable) {
-    balanceUpdatePromise();
-    balanceUpdatePromise = null;
+  // Store in cache, per wallet and chain
+  const key = balanceKey(balancesFormatted.chain?.id, balancesFormatted.railgunWalletID);
+  const buckets = balanceCache.get(key) || new Map();
+  buckets.set(balancesFormatted.balanceBucket, balancesFormatted);
+  balanceCache.set(key, buckets);
+
+  // Resolve the scans waiting for this wallet's spendable balance
+  const waiters = balanceWaiters.get(key);
+  if (waiters && balancesFormatted.balanceBucket === RailgunWalletBalanceBucket.Spendable) {
+    balanceWaiters.delete(key);
+    waiters.forEach((resolve) => resolve());
   }
 };
 
-const waitForBalanceUpdate = () => {
+const waitForBalanceUpdate = (key) => {
   return new Promise((resolve) => {
-    balanceUpdatePromise = resolve;
+    const waiters = balanceWaiters.get(key) || new Set();
+    waiters.add(resolve);
+    balanceWaiters.set(key, waiters);
     // 30 second timeout
     setTimeout(() => {
-      if (balanceUpdatePromise) {
+      if (waiters.delete(resolve)) {
         console.log('⚠️  Balance update timeout (30s) - continuing anyway');
-        balanceUpdatePromise = null;
+        if (waiters.size === 0 && balanceWaiters.get(key) === waiters) {
+          balanceWaiters.delete(key);
+        }
         resolve();
       }
     }, 30000);
   });
 };
 
-const getSpendableBalances = () => {
-  return balanceCache.get(RailgunWalletBalanceBucket.Spendable);
+const getSpendableBalances = (chainId, walletId) => {
+  return balanceCache.get(balanceKey(chainId, walletId))?.get(RailgunWalletBalanceBucket.Spendable);
 };
 
+// Circuit artifacts (zkey, wasm, vkey.json) downloaded by the SDK, shared
+// with the wallet's native prover
+const ARTIFACTS_DIR = process.env.CEPWALLET_CIRCUIT_ARTIFACTS || './circuit_artifacts';
+const ARTIFACTS_SUBDIR = 'artifacts-v2.1';
+
+// SHA-256 of each variant's artifacts, pinned by the wallet
+// (src-tauri/circuits/manifest.json); passed in by the wallet, read from the
+// source tree when run standalone
+const ARTIFACT_MANIFEST_PATH = path.join(__dirname, '..', 'src-tauri', 'circuits', 'manifest.json');
+
 /**
- * Simple artifact store
+ * Pinned hashes by variant: `{ "01x02": { zkey, wasm, vkey } }`
+ *
+ * An unreadable manifest pins nothing, so every artifact is refused.
  */
-class SimpleArtifactStore {
-  async getArtifacts(path) {
-    return null;
+function loadArtifactManifest() {
+  try {
+    const json = process.env.CEPWALLET_CIRCUIT_MANIFEST
+      || fs.readFileSync(ARTIFACT_MANIFEST_PATH, 'utf8');
+    return JSON.parse(json).circuits || {};
+  } catch (error) {
+    console.error(`[ProofGen] No circuit artifact manifest, refusing all artifacts: ${error.message}`);
+    return {};
   }
-  
-  async storeArtifacts(path, artifacts) {
-    return true;
+}
+
+/**
+ * Check an artifact against its pinned hash; throws for unpinned variants
+ * and files that do not match
+ *
+ * `file` is relative to the store (`artifacts-v2.1/<variant>/<name>`).
+ * Compressed `.br` files are hashed decompressed, like the wallet does.
+ */
+function checkPinnedArtifact(manifest, file, data) {
+  const [subdir, variant, name] = file.split(/[\\/]/);
+  const kind = { zkey: 'zkey', wasm: 'wasm', 'vkey.json': 'vkey' }[name?.replace(/\.br$/, '')];
+  const pinned = subdir === ARTIFACTS_SUBDIR && kind && manifest[variant]?.[kind];
+  if (!pinned) {
+    throw new Error(`Circuit artifact ${file} is not pinned in the wallet's manifest`);
+  }
+  let contents = data;
+  if (name.endsWith('.br')) {
+    try {
+      contents = zlib.brotliDecompressSync(data);
+    } catch {
+      // Stored already decompressed
+    }
+  }
+  const hash = crypto.createHash('sha256').update(contents).digest('hex');
+  if (hash !== pinned.toLowerCase()) {
+    throw new Error(`Circuit artifact ${file} does not match its pinned SHA-256 (got ${hash})`);
   }
 }
 
 /**
- * Initialize RAILGUN SDK with balance callbacks
+ * File-backed artifact store under ARTIFACTS_DIR
+ *
+ * Only artifacts of variants the wallet pins are stored or handed to the
+ * SDK, and only when they match their pinned hash: proofs are never
+ * generated with artifacts the wallet cannot verify.
  */
-async function initRailgun(chainId = 11155111) {
-  if (railgunInitialized) {
-    return true;
+function createArtifactStore(dir = ARTIFACTS_DIR, manifest = loadArtifactManifest()) {
+  return new ArtifactStore(
+    async (file) => {
+      const data = await fs.promises.readFile(path.join(dir, file)).catch(() => null);
+      if (data) {
+        checkPinnedArtifact(manifest, file, data);
+      }
+      return data;
+    },
+    async (subdir, file, item) => {
+      checkPinnedArtifact(manifest, file, Buffer.from(item));
+      await fs.promises.mkdir(path.join(dir, subdir), { recursive: true });
+      // Write and rename, so the wallet never maps a partial file
+      const partial = `${path.join(dir, file)}.partial`;
+      await fs.promises.writeFile(partial, item);
+      await fs.promises.rename(partial, path.join(dir, file));
+    },
+    async (file) => fs.promises.access(path.join(dir, file)).then(() => true, () => false)
+  );
+}
+
+/**
+ * Initialize RAILGUN SDK with balance callbacks and load `chainId`'s provider
+ *
+ * The engine is started once; each chain's provider is loaded the first time
+ * a request names it. Concurrent callers (worker mode) share both.
+ */
+async function initRailgun(chainId) {
+  const chain = chainConfig({ chainId });
+  if (!railgunInitialized) {
+    if (!railgunInitPromise) {
+      railgunInitPromise = startEngine().finally(() => {
+        railgunInitPromise = null;
+      });
+    }
+    await railgunInitPromise;
   }
+  if (!providerPromises.has(chain.chainId)) {
+    providerPromises.set(chain.chainId, loadChainProvider(chain).catch((error) => {
+      providerPromises.delete(chain.chainId);
+      throw error;
+    }));
+  }
+  return providerPromises.get(chain.chainId);
+}
 
-  console.log(`[ProofGen] Initializing RAILGUN SDK for chain ${chainId}...`);
+async function startEngine() {
+  console.log('[ProofGen] Initializing RAILGUN SDK...');
   
   try {
-    const networkName = chainIdToNetworkName(chainId);
-    currentNetworkName = networkName;
-    
-    const db = new LevelDOWN('./railgun_db');
-    const artifactStore = new SimpleArtifactStore();
+    const db = new LevelDOWN(process.env.CEPWALLET_RAILGUN_DB || './railgun_db');
+    const artifactStore = createArtifactStore();
     
     // Start RAILGUN engine
     await startRailgunEngine(
@@ -144,6 +284,9 @@ async function initRailgun(chainId = 11155111) {
     );
     
     console.log('[ProofGen] RAILGUN Engine started');
+
+    // Transfer and unshield proofs run on snarkjs
+    getProver().setSnarkJSGroth16(groth16);
     
     // Setup balance callbacks - CRITICAL FOR BALANCE TRACKING
     console.log('[ProofGen] Setting up balance callbacks...');
@@ -152,28 +295,6 @@ async function initRailgun(chainId = 11155111) {
     setOnBalanceUpdateCallback(onBalanceUpdateCallback);
     console.log('[ProofGen] Balance callbacks configured ✅');
     
-    // Load provider
-    let rpcUrl;
-    if (networkName === NetworkName.EthereumSepolia) {
-      rpcUrl = 'https://ethereum-sepolia-rpc.publicnode.com';
-    } else if (networkName === NetworkName.Ethereum) {
-      rpcUrl = 'https://eth.llamarpc.com';
-    } else {
-      rpcUrl = 'https://ethereum-sepolia-rpc.publicnode.com';
-    }
-    
-    const fallbackProviderJsonConfig = {
-      chainId: chainId,
-      providers: [{
-        provider: rpcUrl,
-        priority: 1,
-        weight: 2,
-      }],
-    };
-    
-    await loadProvider(fallbackProviderJsonConfig, networkName, 10000);
-    console.log(`[ProofGen] Provider loaded for ${networkName}`);
-    
     railgunInitialized = true;
     return true;
     
@@ -183,20 +304,22 @@ async function initRailgun(chainId = 11155111) {
   }
 }
 
-function chainIdToNetworkName(chainId) {
-  const chainIdNum = typeof chainId === 'string' ? parseInt(chainId) : chainId;
-  
-  switch (chainIdNum) {
-    case 1: return NetworkName.Ethereum;
-    case 11155111: return NetworkName.EthereumSepolia;
-    case 137: return NetworkName.Polygon;
-    case 42161: return NetworkName.Arbitrum;
-    case 10: return NetworkName.Optimism;
-    case 56: return NetworkName.BNBChain;
-    default:
-      console.warn(`[ProofGen] Unknown chain ID ${chainId}, defaulting to Sepolia`);
-      return NetworkName.EthereumSepolia;
-  }
+/**
+ * Load a chain's RPC provider into the engine
+ */
+async function loadChainProvider(chain) {
+  const fallbackProviderJsonConfig = {
+    chainId: chain.chainId,
+    providers: [{
+      provider: chain.rpcUrl,
+      priority: 1,
+      weight: 2,
+    }],
+  };
+
+  await loadProvider(fallbackProviderJsonConfig, chain.networkName, 10000);
+  console.log(`[ProofGen] Provider loaded for ${chain.networkName}`);
+  return true;
 }
 
 /**
@@ -225,6 +348,21 @@ function normalizeAmount(amountInput, decimals = 18) {
   return trimmed;
 }
 
+/**
+ * Token amount in base units (e.g. 1 USDC = "1000000")
+ *
+ * The wallet converts user input with each token's decimals before calling
+ * the proof generator, so amounts are never scaled here; anything but a
+ * non-negative integer is refused.
+ */
+function baseUnitAmount(amount) {
+  const value = typeof amount === 'string' ? amount.trim() : String(amount);
+  if (!/^[0-9]+$/.test(value)) {
+    throw new Error(`Invalid amount ${amount}: expected an integer in base units`);
+  }
+  return BigInt(value);
+}
+
 /**
  * Generate Shield Proof
  */
@@ -239,9 +377,10 @@ async function generateShieldProof(params) {
       populateShield,
       gasEstimateForShield,
     } = require('@railgun-community/wallet');
-    const { parseEther, parseUnits, randomBytes, hexlify } = require('ethers');
+    const { randomBytes, hexlify } = require('ethers');
 
-    const networkName = chainIdToNetworkName(params.chain_id || params.chainId || 11155111);
+    const chain = chainConfig(params);
+    const networkName = chain.networkName;
 
     // Generate a random shield private key if not provided or if placeholder
     let shieldPrivateKey = params.shield_private_key || params.shieldPrivateKey;
@@ -251,37 +390,14 @@ async function generateShieldProof(params) {
       console.log('  Generated random shield private key');
     }
 
-    // Convert amount to wei (BigInt)
-    // If amount is already a large number (>1e10), assume it's already in wei
-    let amountWei;
-    if (typeof params.amount === 'string') {
-      const amountFloat = parseFloat(params.amount);
-      if (amountFloat > 1e10) {
-        // Already in wei format
-        amountWei = BigInt(params.amount.split('.')[0]); // Remove any decimals
-      } else {
-        // Assume it's in ETH/token units, convert to wei (18 decimals)
-        amountWei = parseEther(params.amount);
-      }
-    } else {
-      amountWei = parseEther(params.amount.toString());
-    }
+    // The wallet sends base units, converted with the token's decimals
+    const amountWei = baseUnitAmount(params.amount);
 
-    console.log('  Amount (wei):', amountWei.toString());
+    console.log('  Amount (base units):', amountWei.toString());
 
-    // IMPORTANT: RAILGUN doesn't support native ETH directly
-    // For native ETH (0x0), we use WETH instead
-    const chainId = params.chain_id || params.chainId || 11155111;
-    const contracts = CHAIN_CONTRACTS[chainId] || CHAIN_CONTRACTS[11155111]; // Fallback to Sepolia
+    // RAILGUN only holds ERC-20s: native ETH (0x0) is wrapped and shielded
+    // in one call through relay adapt, sending the amount as value
     const isNativeETH = params.token.toLowerCase() === '0x0000000000000000000000000000000000000000';
-    const tokenAddress = isNativeETH ? contracts.weth : params.token;
-
-    // Prepare ERC20 amount recipient
-    const erc20AmountRecipients = [{
-      tokenAddress: tokenAddress,
-      amount: amountWei,
-      recipientAddress: params.recipient, // Use 'recipient' field
-    }];
 
     // Prepare gas details (Sepolia uses EIP-1559)
     const gasDetails = {
@@ -291,6 +407,35 @@ async function generateShieldProof(params) {
       maxPriorityFeePerGas: undefined, // Let SDK determine
     };
 
+    if (isNativeETH) {
+      const { transaction } = await populateShieldBaseToken(
+        TXIDVersion.V2_PoseidonMerkle,
+        networkName,
+        params.recipient,
+        shieldPrivateKey,
+        { tokenAddress: chain.weth, amount: amountWei },
+        gasDetails
+      );
+      console.log('[ProofGen] Base token shield via relay adapt:', transaction.to);
+
+      return {
+        success: true,
+        transaction: {
+          to: transaction.to,
+          data: transaction.data,
+          value: (transaction.value ?? amountWei).toString(),
+        },
+        shieldPrivateKey,
+      };
+    }
+
+    // Prepare ERC20 amount recipient
+    const erc20AmountRecipients = [{
+      tokenAddress: params.token,
+      amount: amountWei,
+      recipientAddress: params.recipient, // Use 'recipient' field
+    }];
+
     // Generate shield transaction with proof
     const shieldTx = await populateShield(
       TXIDVersion.V2_PoseidonMerkle, // Use V2 for RAILGUN v3
@@ -310,8 +455,7 @@ async function generateShieldProof(params) {
     const transaction = {
       to: shieldTx.transaction.to,
       data: shieldTx.transaction.data,
-      // For WETH (which we're using for native ETH), value should be 0
-      // SDK handles the token transfer
+      // The proxy pulls the tokens (after approval); no value is sent
       value: '0',
     };
 
@@ -392,10 +536,7 @@ async function generateBaseTokenShieldProof(params, networkName, txidVersion, am
   const railgunWalletAddress = params.recipient;
 
   // Get wrapped token address for this chain
-  const wrappedTokenAddress = CHAIN_CONTRACTS[params.chain_id || params.chainId || 11155111]?.weth;
-  if (!wrappedTokenAddress) {
-    throw new Error('Wrapped token address not found for chain');
-  }
+  const wrappedTokenAddress = chainConfig(params).weth;
 
   // Create wrapped ERC20 amount
   const wrappedERC20Amount = {
@@ -443,10 +584,327 @@ async function generateBaseTokenShieldProof(params, networkName, txidVersion, am
   };
 }
 
+/**
+ * Load the sending wallet of a shielded spend (private transfer or unshield)
+ */
+async function loadSpendWallet(params) {
+  const { chainId, networkName } = chainConfig(params);
+
+  let encryptionKey = params.encryptionKey || params.encryption_key;
+  if (!encryptionKey) {
+    throw new Error('Encryption key required');
+  }
+  if (encryptionKey.startsWith('0x')) {
+    encryptionKey = encryptionKey.slice(2);
+  }
+
+  const walletId = params.railgunWalletId || params.railgun_wallet_id;
+  if (!walletId) {
+    throw new Error('Wallet ID required');
+  }
+
+  const railgunWallet = await loadWalletByID(encryptionKey, walletId, false);
+  if (!railgunWallet) {
+    throw new Error('Failed to load wallet');
+  }
+
+  // Prepare gas details (Sepolia uses EIP-1559)
+  const gasDetails = {
+    evmGasType: 2, // Type 2 = EIP-1559 (required for Sepolia)
+    gasEstimate: BigInt(500000), // 500k gas limit as BigInt
+    maxFeePerGas: undefined, // Let SDK determine
+    maxPriorityFeePerGas: undefined, // Let SDK determine
+  };
+
+  return {
+    chainId,
+    networkName,
+    walletId,
+    encryptionKey,
+    railgunWallet,
+    gasDetails,
+  };
+}
+
+/**
+ * Token RAILGUN holds for `token`: native ETH (0x0) is held as WETH
+ */
+function shieldedTokenAddress(chainId, token) {
+  const isNativeETH = token.toLowerCase() === '0x0000000000000000000000000000000000000000';
+  return isNativeETH ? chainConfig({ chainId }).weth : token;
+}
+
+/**
+ * Add `amount` (base units) to the total spent of `tokenAddress`
+ */
+function addSpendTotal(totals, tokenAddress, amount) {
+  const key = tokenAddress.toLowerCase();
+  totals.set(key, (totals.get(key) || 0n) + amount);
+}
+
+/**
+ * Add the broadcaster fee of `params`, if any, to the totals: the
+ * broadcaster is paid from the same shielded balance as the outputs
+ */
+function addBroadcasterFee(totals, chainId, params) {
+  const fee = params.broadcasterFee || params.broadcaster_fee;
+  if (fee) {
+    addSpendTotal(totals, shieldedTokenAddress(chainId, fee.token), baseUnitAmount(fee.amount));
+  }
+}
+
+/**
+ * Check the spending wallet's shielded balance covers each token's total
+ */
+async function checkShieldedBalances(spend, totals) {
+  for (const [tokenAddress, total] of totals) {
+    const balance = await getBalance(spend.railgunWallet, tokenAddress, spend.networkName);
+    console.log(`[ProofGen] Available balance of ${tokenAddress}:`, balance);
+    if (BigInt(balance) < total) {
+      throw new Error(`Insufficient RAILGUN balance of ${tokenAddress}. Required: ${total.toString()}, Available: ${balance} (base units). Please shield funds first.`);
+    }
+  }
+}
+
+/**
+ * Load the sending wallet and check its balance for a shielded spend
+ * (private transfer or unshield)
+ */
+async function prepareSpend(params) {
+  const spend = await loadSpendWallet(params);
+
+  // The wallet sends base units, converted with the token's decimals
+  const amountWei = baseUnitAmount(params.amount);
+
+  console.log('  Amount (base units):', amountWei.toString());
+
+  // IMPORTANT: RAILGUN doesn't support native ETH directly
+  // For native ETH (0x0), we use WETH instead
+  const tokenAddress = shieldedTokenAddress(spend.chainId, params.token);
+
+  // Check balance before attempting the spend
+  console.log('[ProofGen] Checking RAILGUN wallet balance...');
+  const totals = new Map();
+  addSpendTotal(totals, tokenAddress, amountWei);
+  addBroadcasterFee(totals, spend.chainId, params);
+  await checkShieldedBalances(spend, totals);
+
+  return {
+    ...spend,
+    amountWei,
+    tokenAddress,
+  };
+}
+
+/**
+ * Wallet and outputs of a private transfer
+ *
+ * `params.outputs` ([{ recipient, token, amount, memo }], base units) sends
+ * several tokens to several recipients in one transaction; the SDK proves
+ * the outputs of each token together. Without outputs it is the single
+ * `recipient`, `token` and `amount`. `params.memoText` is encrypted to
+ * every recipient.
+ */
+async function prepareTransfer(params) {
+  if (!params.outputs) {
+    const spend = await prepareSpend(params);
+    return {
+      spend,
+      erc20AmountRecipients: [{
+        tokenAddress: spend.tokenAddress,
+        amount: spend.amountWei,
+        recipientAddress: params.recipient || params.to,
+      }],
+      memoText: params.memoText || undefined,
+    };
+  }
+
+  if (params.outputs.length === 0) {
+    throw new Error('Transfer has no outputs');
+  }
+  const spend = await loadSpendWallet(params);
+  const totals = new Map();
+  const erc20AmountRecipients = params.outputs.map((output) => {
+    const tokenAddress = shieldedTokenAddress(spend.chainId, output.token);
+    const amount = baseUnitAmount(output.amount);
+    addSpendTotal(totals, tokenAddress, amount);
+    return { tokenAddress, amount, recipientAddress: output.recipient };
+  });
+  addBroadcasterFee(totals, spend.chainId, params);
+
+  console.log('[ProofGen] Checking RAILGUN wallet balances...');
+  await checkShieldedBalances(spend, totals);
+
+  return {
+    spend,
+    erc20AmountRecipients,
+    memoText: params.memoText || params.outputs[0].memo || undefined,
+  };
+}
+
+/**
+ * Broadcaster options of a transfer or unshield
+ *
+ * With `params.broadcasterFee` ({ token, amount, recipient, minGasPrice })
+ * the proof pays that broadcaster from the shielded balance and is bound to
+ * its minimum gas price; without one it is sent from a public wallet.
+ */
+function broadcasterOptions(params) {
+  const fee = params.broadcasterFee || params.broadcaster_fee;
+  if (!fee) {
+    return {
+      broadcasterFeeERC20AmountRecipient: undefined,
+      sendWithPublicWallet: true,
+      overallBatchMinGasPrice: undefined,
+    };
+  }
+  return {
+    broadcasterFeeERC20AmountRecipient: {
+      tokenAddress: fee.token,
+      amount: baseUnitAmount(fee.amount),
+      recipientAddress: fee.recipient,
+    },
+    sendWithPublicWallet: false,
+    overallBatchMinGasPrice: BigInt(fee.minGasPrice),
+  };
+}
+
+/**
+ * SDK gas estimate of a shield, transfer or unshield
+ *
+ * Takes the parameters of the matching proof command plus the EIP-1559 fees
+ * (`maxFeePerGas`, `maxPriorityFeePerGas`) and, for shields, the sending
+ * `fromWalletAddress`. Spends are estimated with dummy proofs, as sent from
+ * a public wallet; a broadcaster's gas limit is the same.
+ */
+async function estimateGas(params) {
+  const {
+    gasEstimateForUnprovenUnshield,
+  } = require('@railgun-community/wallet');
+  const { hexlify, randomBytes } = require('ethers');
+
+  const proofType = params.proofType || params.proof_type;
+  const chain = chainConfig(params);
+  const networkName = chain.networkName;
+  const originalGasDetails = {
+    evmGasType: 2,
+    gasEstimate: 0n,
+    maxFeePerGas: BigInt(params.maxFeePerGas),
+    maxPriorityFeePerGas: BigInt(params.maxPriorityFeePerGas),
+  };
+
+  let response;
+  switch (proofType) {
+    case 'shield': {
+      const fromWalletAddress = params.fromWalletAddress;
+      if (!fromWalletAddress) {
+        throw new Error('Sending account required');
+      }
+      // The estimate doesn't depend on the key: don't ask for a signature
+      const shieldPrivateKey = hexlify(randomBytes(32));
+      const amount = baseUnitAmount(params.amount);
+      if (params.token.toLowerCase() === '0x0000000000000000000000000000000000000000') {
+        response = await gasEstimateForShieldBaseToken(
+          TXIDVersion.V2_PoseidonMerkle,
+          networkName,
+          params.recipient,
+          shieldPrivateKey,
+          { tokenAddress: chain.weth, amount },
+          fromWalletAddress
+        );
+      } else {
+        response = await gasEstimateForShield(
+          TXIDVersion.V2_PoseidonMerkle,
+          networkName,
+          shieldPrivateKey,
+          [{ tokenAddress: params.token, amount, recipientAddress: params.recipient }],
+          [],
+          fromWalletAddress
+        );
+      }
+      break;
+    }
+
+    case 'transfer': {
+      const { spend, erc20AmountRecipients, memoText } = await prepareTransfer(params);
+      response = await gasEstimateForUnprovenTransfer(
+        TXIDVersion.V2_PoseidonMerkle,
+        spend.networkName,
+        spend.walletId,
+        spend.encryptionKey,
+        memoText,
+        erc20AmountRecipients,
+        [],
+        originalGasDetails,
+        undefined, // feeTokenDetails
+        true // sendWithPublicWallet
+      );
+      break;
+    }
+
+    case 'unshield': {
+      const spend = await prepareSpend(params);
+      response = await gasEstimateForUnprovenUnshield(
+        TXIDVersion.V2_PoseidonMerkle,
+        spend.networkName,
+        spend.walletId,
+        spend.encryptionKey,
+        [{ tokenAddress: spend.tokenAddress, amount: spend.amountWei, recipientAddress: params.recipient }],
+        [],
+        originalGasDetails,
+        undefined, // feeTokenDetails
+        true // sendWithPublicWallet
+      );
+      break;
+    }
+
+    default:
+      throw new Error(`Cannot estimate gas of ${proofType}`);
+  }
+
+  return {
+    success: true,
+    gasEstimate: response.gasEstimate.toString(),
+  };
+}
+
+/**
+ * Forward SDK proof progress (0 - 100) as `proofProgress` events (0 - 1)
+ *
+ * Throws once `signal` is aborted, which stops the prover at its next step.
+ */
+function proofProgressCallback(notify, signal) {
+  return (progress) => {
+    signal?.throwIfAborted();
+    notify('proofProgress', { progress: Math.min(Math.max(progress / 100, 0), 1) });
+  };
+}
+
+// JSON-RPC error code of a request stopped by a `cancel` notification
+const REQUEST_CANCELLED = -32800;
+
+/**
+ * `promise`, or a rejection as soon as `signal` is aborted
+ */
+function untilCancelled(promise, signal) {
+  if (!signal) {
+    return promise;
+  }
+  return new Promise((resolve, reject) => {
+    const onAbort = () => reject(new Error('Request cancelled'));
+    if (signal.aborted) {
+      onAbort();
+      return;
+    }
+    signal.addEventListener('abort', onAbort, { once: true });
+    promise.then(resolve, reject).finally(() => signal.removeEventListener('abort', onAbort));
+  });
+}
+
 /**
  * Generate Transfer Proof - WITH BALANCE CALLBACK SYSTEM
  */
-async function generateTransferProof(params) {
+async function generateTransferProof(params, notify = printEvent, signal = undefined) {
   console.log('[ProofGen] Generating transfer proof...');
   console.log('  From Wallet ID:', params.railgunWalletId || params.from_wallet_id);
   console.log('  To RAILGUN Address:', params.recipient || params.to);
@@ -455,124 +913,154 @@ async function generateTransferProof(params) {
 
   try {
     const {
+      generateTransferProof: generateSdkTransferProof,
       populateProvedTransfer,
-      gasEstimateForUnprovenTransfer,
-      loadWalletByID,
     } = require('@railgun-community/wallet');
-    const { parseEther, parseUnits, randomBytes, hexlify } = require('ethers');
 
-    const networkName = chainIdToNetworkName(params.chainId || params.chain_id || 11155111);
+    const { spend, erc20AmountRecipients, memoText } = await prepareTransfer(params);
+    signal?.throwIfAborted();
+    const fromRailgunAddress = spend.railgunWallet.railgunAddress;
 
-    // Load wallet to get RAILGUN address
-    let encryptionKey = params.encryptionKey || params.encryption_key || hexlify(randomBytes(32));
-    if (encryptionKey.startsWith('0x')) {
-      encryptionKey = encryptionKey.slice(2);
-    }
+    console.log('  From RAILGUN Address:', fromRailgunAddress);
+    console.log('  Outputs:', erc20AmountRecipients.length);
 
-    const walletId = params.railgunWalletId || params.railgun_wallet_id;
-    if (!walletId) {
-      throw new Error('Wallet ID required for transfer');
+    // Check if this is a self-transfer (same address)
+    if (erc20AmountRecipients.some((output) => output.recipientAddress === fromRailgunAddress)) {
+      throw new Error('Cannot transfer to the same RAILGUN address');
     }
 
-    const railgunWallet = await loadWalletByID(
-      encryptionKey,
-      walletId,
-      false
-    );
-
-    if (!railgunWallet) {
-      throw new Error('Failed to load wallet');
-    }
+    const showSenderAddressToRecipient = false;
+    const {
+      broadcasterFeeERC20AmountRecipient,
+      sendWithPublicWallet,
+      overallBatchMinGasPrice,
+    } = broadcasterOptions(params);
 
-    const fromRailgunAddress = railgunWallet.railgunAddress;
-    const toRailgunAddress = params.recipient || params.to;
+    await generateSdkTransferProof(
+      TXIDVersion.V2_PoseidonMerkle,
+      spend.networkName,
+      spend.walletId,
+      spend.encryptionKey,
+      showSenderAddressToRecipient,
+      memoText,
+      erc20AmountRecipients,
+      [],
+      broadcasterFeeERC20AmountRecipient,
+      sendWithPublicWallet,
+      overallBatchMinGasPrice,
+      proofProgressCallback(notify, signal)
+    );
+    signal?.throwIfAborted();
 
-    console.log('  From RAILGUN Address:', fromRailgunAddress);
-    console.log('  To RAILGUN Address:', toRailgunAddress);
+    const { transaction, nullifiers, preTransactionPOIsPerTxidLeafPerList } = await populateProvedTransfer(
+      TXIDVersion.V2_PoseidonMerkle,
+      spend.networkName,
+      spend.walletId,
+      showSenderAddressToRecipient,
+      memoText,
+      erc20AmountRecipients,
+      [],
+      broadcasterFeeERC20AmountRecipient,
+      sendWithPublicWallet,
+      overallBatchMinGasPrice,
+      spend.gasDetails
+    );
+    console.log('[ProofGen] Transfer transaction generated successfully');
 
-    // Check if this is a self-transfer (same address)
-    if (fromRailgunAddress === toRailgunAddress) {
-      throw new Error('Cannot transfer to the same RAILGUN address');
-    }
+    return {
+      success: true,
+      transaction: {
+        to: transaction.to,
+        data: transaction.data,
+        value: (transaction.value || 0).toString(),
+        // Quoted back to the broadcaster on submission
+        ...(sendWithPublicWallet ? {} : { nullifiers, preTransactionPOIsPerTxidLeafPerList }),
+      },
+    };
 
-    // Convert amount to wei (BigInt)
-    let amountWei;
-    if (typeof params.amount === 'string') {
-      const amountFloat = parseFloat(params.amount);
-      if (amountFloat > 1e10) {
-        // Already in wei format
-        amountWei = BigInt(params.amount.split('.')[0]); // Remove any decimals
-      } else {
-        // Assume it's in ETH/token units, convert to wei (18 decimals)
-        amountWei = parseEther(params.amount);
-      }
-    } else {
-      amountWei = parseEther(params.amount.toString());
-    }
+  } catch (error) {
+    console.error('[ProofGen] Transfer proof generation failed:', error);
+    return {
+      success: false,
+      error: error.message,
+    };
+  }
+}
 
-    console.log('  Amount (wei):', amountWei.toString());
+/**
+ * Generate Unshield Proof (shielded → public)
+ */
+async function generateUnshieldProof(params, notify = printEvent, signal = undefined) {
+  console.log('[ProofGen] Generating unshield proof...');
+  console.log('  From Wallet ID:', params.railgunWalletId || params.railgun_wallet_id);
+  console.log('  To Address:', params.recipient);
+  console.log('  Token:', params.token);
+  console.log('  Amount:', params.amount);
 
-    // Check balance before attempting transfer
-    console.log('[ProofGen] Checking RAILGUN wallet balance...');
-    const balance = await getBalance(railgunWallet, params.token, networkName);
-    console.log('[ProofGen] Available balance:', balance);
+  try {
+    const {
+      generateUnshieldProof: generateSdkUnshieldProof,
+      populateProvedUnshield,
+    } = require('@railgun-community/wallet');
 
-    if (BigInt(balance) < amountWei) {
-      throw new Error(`Insufficient RAILGUN balance. Required: ${amountWei.toString()} wei, Available: ${balance} wei. Please shield funds first before transferring.`);
+    if (!params.recipient) {
+      throw new Error('Recipient address required');
     }
 
-    // IMPORTANT: RAILGUN doesn't support native ETH directly
-    // For native ETH (0x0), we use WETH instead
-    const chainId = params.chainId || params.chain_id || 11155111;
-    const contracts = CHAIN_CONTRACTS[chainId] || CHAIN_CONTRACTS[11155111]; // Fallback to Sepolia
-    const isNativeETH = params.token.toLowerCase() === '0x0000000000000000000000000000000000000000';
-    const tokenAddress = isNativeETH ? contracts.weth : params.token;
-
-    // Prepare erc20AmountRecipients
+    const spend = await prepareSpend(params);
+    signal?.throwIfAborted();
     const erc20AmountRecipients = [{
-      tokenAddress: tokenAddress,
-      amount: amountWei,
-      recipientAddress: toRailgunAddress,
+      tokenAddress: spend.tokenAddress,
+      amount: spend.amountWei,
+      recipientAddress: params.recipient,
     }];
 
-    // Calculate broadcaster fee (typically 0.001 ETH or equivalent)
-    const broadcasterFeeERC20Amount = {
-      tokenAddress: '0x0000000000000000000000000000000000000000', // ETH
-      amount: parseEther('0.001'), // 0.001 ETH in wei
-    };
-
-    // Prepare gas details (Sepolia uses EIP-1559)
-    const gasDetails = {
-      evmGasType: 2, // Type 2 = EIP-1559 (required for Sepolia)
-      gasEstimate: BigInt(500000), // 500k gas limit as BigInt
-      maxFeePerGas: undefined, // Let SDK determine
-      maxPriorityFeePerGas: undefined, // Let SDK determine
-    };
+    const {
+      broadcasterFeeERC20AmountRecipient,
+      sendWithPublicWallet,
+      overallBatchMinGasPrice,
+    } = broadcasterOptions(params);
 
-    const transferTxResponse = await populateProvedTransfer(
+    await generateSdkUnshieldProof(
       TXIDVersion.V2_PoseidonMerkle,
-      networkName,
-      fromRailgunAddress,
+      spend.networkName,
+      spend.walletId,
+      spend.encryptionKey,
       erc20AmountRecipients,
       [],
-      broadcasterFeeERC20Amount,
-      gasDetails
+      broadcasterFeeERC20AmountRecipient,
+      sendWithPublicWallet,
+      overallBatchMinGasPrice,
+      proofProgressCallback(notify, signal)
     );
+    signal?.throwIfAborted();
 
-    const { transaction } = transferTxResponse;
-    console.log('[ProofGen] Transfer transaction generated successfully');
+    const { transaction, nullifiers, preTransactionPOIsPerTxidLeafPerList } = await populateProvedUnshield(
+      TXIDVersion.V2_PoseidonMerkle,
+      spend.networkName,
+      spend.walletId,
+      erc20AmountRecipients,
+      [],
+      broadcasterFeeERC20AmountRecipient,
+      sendWithPublicWallet,
+      overallBatchMinGasPrice,
+      spend.gasDetails
+    );
+    console.log('[ProofGen] Unshield transaction generated successfully');
 
     return {
       success: true,
       transaction: {
         to: transaction.to,
         data: transaction.data,
-        value: transaction.value || '0',
+        value: (transaction.value || 0).toString(),
+        // Quoted back to the broadcaster on submission
+        ...(sendWithPublicWallet ? {} : { nullifiers, preTransactionPOIsPerTxidLeafPerList }),
       },
     };
 
   } catch (error) {
-    console.error('[ProofGen] Transfer proof generation failed:', error);
+    console.error('[ProofGen] Unshield proof generation failed:', error);
     return {
       success: false,
       error: error.message,
@@ -580,6 +1068,98 @@ async function generateTransferProof(params) {
   }
 }
 
+/**
+ * Waku broadcaster client, started on first use
+ *
+ * `@railgun-community/waku-broadcaster-client-node` is loaded lazily, so
+ * the proof generator runs without it when broadcasters are not used.
+ */
+const BROADCASTER_DISCOVERY_MS = 30000;
+let broadcasterClientPromise = null;
+let broadcasterChainId = null;
+
+function wakuBroadcasterModule() {
+  try {
+    return require('@railgun-community/waku-broadcaster-client-node');
+  } catch (error) {
+    throw new Error('Broadcasters need @railgun-community/waku-broadcaster-client-node installed');
+  }
+}
+
+async function startBroadcasterClient(chainId) {
+  const { WakuBroadcasterClient } = wakuBroadcasterModule();
+  const chain = { type: ChainType.EVM, id: chainId };
+  if (!broadcasterClientPromise) {
+    broadcasterChainId = chainId;
+    broadcasterClientPromise = WakuBroadcasterClient.start(chain, {}, (_chain, status) => {
+      console.error('[ProofGen] Broadcaster status:', status);
+    }).catch((error) => {
+      broadcasterClientPromise = null;
+      throw error;
+    });
+  }
+  await broadcasterClientPromise;
+  if (broadcasterChainId !== chainId) {
+    await WakuBroadcasterClient.setChain(chain);
+    broadcasterChainId = chainId;
+  }
+  return { WakuBroadcasterClient, chain };
+}
+
+/**
+ * Broadcaster fee quotes for a token
+ *
+ * Fee messages arrive over Waku after connecting, so this waits up to
+ * `BROADCASTER_DISCOVERY_MS` for the first quotes.
+ */
+async function broadcasterFeeQuotes(params) {
+  const { WakuBroadcasterClient, chain } = await startBroadcasterClient(chainConfig(params).chainId);
+  const useRelayAdapt = false;
+
+  const deadline = Date.now() + BROADCASTER_DISCOVERY_MS;
+  let broadcasters = WakuBroadcasterClient.findBroadcastersForToken(chain, params.token, useRelayAdapt);
+  while (!(broadcasters && broadcasters.length) && Date.now() < deadline) {
+    await new Promise((resolve) => setTimeout(resolve, 1000));
+    broadcasters = WakuBroadcasterClient.findBroadcastersForToken(chain, params.token, useRelayAdapt);
+  }
+
+  const quotes = (broadcasters || []).map((broadcaster) => ({
+    railgunAddress: broadcaster.railgunAddress,
+    token: broadcaster.tokenAddress,
+    feePerUnitGas: broadcaster.tokenFee.feePerUnitGas.toString(),
+    feesId: broadcaster.tokenFee.feesID,
+    expiration: broadcaster.tokenFee.expiration,
+    availableWallets: broadcaster.tokenFee.availableWallets,
+    // Unknown reliability ranks below every measured broadcaster
+    reliability: broadcaster.tokenFee.reliability ?? 0,
+  }));
+  return { success: true, quotes };
+}
+
+/**
+ * Hand a populated transaction to a broadcaster; resolves with its tx hash
+ */
+async function broadcasterSubmit(params) {
+  const { BroadcasterTransaction } = wakuBroadcasterModule();
+  const { chain } = await startBroadcasterClient(chainConfig(params).chainId);
+
+  const transaction = await BroadcasterTransaction.create(
+    TXIDVersion.V2_PoseidonMerkle,
+    params.to,
+    params.data,
+    params.broadcaster,
+    params.feesId,
+    chain,
+    params.nullifiers,
+    BigInt(params.minGasPrice),
+    params.useRelayAdapt,
+    params.preTransactionPois
+  );
+  const txHash = await transaction.send();
+  console.error('[ProofGen] Broadcaster sent transaction:', txHash);
+  return { success: true, txHash };
+}
+
 /**
  * Create RAILGUN Wallet
  */
@@ -645,7 +1225,7 @@ async function getShieldKey(params) {
     const walletId = params.railgunWalletId || params.railgun_wallet_id;
     
     if (!walletId) {
-      console.log('[ProofGen] Params received:', JSON.stringify(params, null, 2));
+      console.log('[ProofGen] Params received:', Object.keys(params).join(', '));
       throw new Error('Wallet ID required');
     }
     
@@ -756,51 +1336,150 @@ async function getBalance(wallet, tokenAddress, networkName) {
 }
 
 /**
- * Main command handler
+ * Notes other wallets sent to a wallet since `startBlock`, with the memo
+ * text the engine decrypted with the viewing key
+ *
+ * Own shields are left out; they are recorded when made.
  */
-async function handleCommand(command, params) {
+async function receivedNotes(chain, walletId, startBlock) {
+  const items = await getWalletTransactionHistory(chain, walletId, startBlock ?? undefined);
+  const notes = [];
+  for (const item of items) {
+    if (item.category === TransactionHistoryItemCategory.ShieldERC20s) {
+      continue;
+    }
+    for (const received of item.receiveERC20Amounts || []) {
+      notes.push({
+        txid: item.txid,
+        blockNumber: item.blockNumber,
+        timestamp: item.timestamp ?? null,
+        tokenAddress: received.tokenAddress,
+        amount: BigInt(received.amount).toString(),
+        memoText: received.memoText || null,
+      });
+    }
+  }
+  return notes;
+}
+
+/**
+ * Scan the merkletree for a wallet and report its balances per bucket and
+ * the notes it received
+ *
+ * The engine resumes from what it already stored in railgun_db; progress is
+ * reported through `notify('scanProgress', {...})` while scanning.
+ */
+async function scanMerkletree(params, notify = printEvent, signal = undefined) {
+  console.log('[ProofGen] Scanning merkletree...');
+
   try {
-    // Initialize RAILGUN for commands that require the engine
-    const chainId = params.chain_id || params.chainId || 11155111;
-    const commandsRequiringInit = new Set([
-      'create_wallet',
-      'generate_shield_proof',
-      'generate_transfer_proof',
-    ]);
+    const walletId = params.railgunWalletId || params.railgun_wallet_id;
+    if (!walletId) {
+      throw new Error('Wallet ID required');
+    }
 
-    if (commandsRequiringInit.has(command)) {
-      await initRailgun(chainId);
+    let encryptionKey = params.encryptionKey || params.encryption_key;
+    if (!encryptionKey) {
+      throw new Error('Encryption key required');
     }
-    
-    switch (command) {
-      case 'create_wallet':
-        return await createWallet(params);
-        
-      case 'get_shield_key':
-        return await getShieldKey(params);
-        
-      case 'generate_shield_proof':
-        return await generateShieldProof(params);
-        
-      case 'generate_transfer_proof':
-        // Map frontend parameters to expected format
-        const transferParams = {
-          ...params,
-          from_wallet_id: params.railgunWalletId || params.from_wallet_id,
-          to_address: params.recipient || params.to,
-          token: params.token,
-          amount: params.amount,
-          chain_id: params.chainId || params.chain_id,
-        };
-        return await generateTransferProof(transferParams);
-        
-      default:
-        return {
-          success: false,
-          error: `Unknown command: ${command}`,
-        };
+    if (encryptionKey.startsWith('0x')) {
+      encryptionKey = encryptionKey.slice(2);
+    }
+
+    const { chainId, networkName, rpcUrl } = chainConfig(params);
+    await loadWalletByID(encryptionKey, walletId, false);
+
+    const startBlock = params.startBlock ?? params.start_block ?? null;
+    const targetBlock = await new JsonRpcProvider(rpcUrl).getBlockNumber();
+    const onProgress = (eventData) => {
+      notify('scanProgress', {
+        progress: eventData.progress,
+        scanStatus: eventData.scanStatus,
+        startBlock,
+        targetBlock,
+      });
+    };
+    const listeners = scanProgressListeners.get(chainId) || new Set();
+    listeners.add(onProgress);
+    scanProgressListeners.set(chainId, listeners);
+
+    // Balance callbacks fire per bucket once the scan has decrypted the notes
+    const key = balanceKey(chainId, walletId);
+    balanceCache.delete(key);
+    try {
+      const balanceUpdate = waitForBalanceUpdate(key);
+      await untilCancelled(
+        refreshBalances(NETWORK_CONFIG[networkName].chain, [walletId]).then(() => balanceUpdate),
+        signal
+      );
+    } finally {
+      listeners.delete(onProgress);
+      if (listeners.size === 0 && scanProgressListeners.get(chainId) === listeners) {
+        scanProgressListeners.delete(chainId);
+      }
+    }
+
+    const balances = [];
+    for (const [bucket, balancesFormatted] of balanceCache.get(key) || []) {
+      for (const erc20Amount of balancesFormatted.erc20Amounts) {
+        balances.push({
+          bucket,
+          tokenAddress: erc20Amount.tokenAddress,
+          amount: BigInt(erc20Amount.amount ?? erc20Amount.amountString).toString(),
+        });
+      }
+    }
+
+    // Balances are the scan's result; missing history only hides memos
+    let received = [];
+    try {
+      received = await receivedNotes(NETWORK_CONFIG[networkName].chain, walletId, startBlock);
+    } catch (error) {
+      console.error('[ProofGen] Failed to read received notes:', error);
     }
+
+    console.log(`[ProofGen] Scan complete (${balances.length} balances, ${received.length} received notes)`);
+    return {
+      success: true,
+      balances,
+      lastBlock: targetBlock,
+      received,
+    };
+
+  } catch (error) {
+    console.error('[ProofGen] Merkletree scan failed:', error);
+    return {
+      success: false,
+      error: error.message,
+    };
+  }
+}
+
+/**
+ * Print a progress event as a JSON line (CLI mode)
+ */
+function printEvent(event, data) {
+  console.log(JSON.stringify({ event, ...data }));
+}
+
+/**
+ * Main command handler
+ *
+ * `notify(event, data)` receives progress events of long-running commands.
+ * Aborting `signal` answers the command as cancelled at once; proofs and
+ * scans stop at their next step.
+ */
+async function handleCommand(command, params, notify = printEvent, signal = undefined) {
+  try {
+    return await untilCancelled(runCommand(command, params, notify, signal), signal);
   } catch (error) {
+    if (signal?.aborted) {
+      return {
+        success: false,
+        code: REQUEST_CANCELLED,
+        error: 'Request cancelled',
+      };
+    }
     console.error('[ProofGen] Command failed:', error);
     return {
       success: false,
@@ -809,36 +1488,230 @@ async function handleCommand(command, params) {
   }
 }
 
+async function runCommand(command, params, notify, signal) {
+  // Initialize RAILGUN and the request's chain for commands that need them
+  const commandsRequiringInit = new Set([
+    'create_wallet',
+    'generate_shield_proof',
+    'generate_transfer_proof',
+    'generate_unshield_proof',
+    'scan_merkletree',
+    'broadcaster_submit',
+    'estimate_gas',
+  ]);
+
+  if (commandsRequiringInit.has(command)) {
+    await initRailgun(chainConfig(params).chainId);
+  }
+  
+  switch (command) {
+    case 'create_wallet':
+      return await createWallet(params);
+      
+    case 'get_shield_key':
+      return await getShieldKey(params);
+      
+    case 'generate_shield_proof':
+      return await generateShieldProof(params);
+      
+    case 'generate_transfer_proof':
+      // Map frontend parameters to expected format
+      const transferParams = {
+        ...params,
+        from_wallet_id: params.railgunWalletId || params.from_wallet_id,
+        to_address: params.recipient || params.to,
+        token: params.token,
+        amount: params.amount,
+        chain_id: params.chainId || params.chain_id,
+      };
+      return await generateTransferProof(transferParams, notify, signal);
+
+    case 'generate_unshield_proof':
+      return await generateUnshieldProof(params, notify, signal);
+
+    case 'scan_merkletree':
+      return await scanMerkletree(params, notify, signal);
+
+    case 'broadcaster_fee_quotes':
+      return await broadcasterFeeQuotes(params);
+
+    case 'broadcaster_submit':
+      return await broadcasterSubmit(params);
+
+    case 'estimate_gas':
+      return await estimateGas(params);
+      
+    default:
+      return {
+        success: false,
+        code: -32601,
+        error: `Unknown command: ${command}`,
+      };
+  }
+}
+
+/**
+ * Read all of stdin (CLI params that must not appear in argv)
+ */
+function readStdin() {
+  return new Promise((resolve, reject) => {
+    let data = '';
+    process.stdin.setEncoding('utf8');
+    process.stdin.on('data', chunk => { data += chunk; });
+    process.stdin.on('end', () => resolve(data.trim()));
+    process.stdin.on('error', reject);
+  });
+}
+
+/**
+ * Worker mode: newline-delimited JSON-RPC 2.0 over stdin/stdout
+ *
+ * Requests `{"jsonrpc":"2.0","id":1,"method":"<command>","params":{...}}` run
+ * concurrently; each gets a `{"id":1,"result":{...}}` or `{"id":1,"error":{...}}`
+ * reply. Progress is sent as `progress` notifications carrying the request id.
+ * A `cancel` notification (`{"id":1}`) stops that request, which is answered
+ * with a -32800 error; `ping` is answered at once, so the wallet can tell a
+ * busy worker from a wedged one. A `shutdown` notification or closing stdin
+ * stops the engine and exits.
+ */
+function runWorker() {
+  // stdout carries protocol messages only
+  const log = (...args) => console.error(...args);
+  console.log = log;
+  console.info = log;
+  console.warn = log;
+  console.debug = log;
+
+  const send = (message) => {
+    process.stdout.write(JSON.stringify({ jsonrpc: '2.0', ...message }) + '\n');
+  };
+  const inFlight = new Set();
+  // Request id -> AbortController of the requests still running
+  const controllers = new Map();
+
+  const handleLine = async (line) => {
+    let message;
+    try {
+      message = JSON.parse(line);
+    } catch (error) {
+      send({ id: null, error: { code: -32700, message: 'Parse error' } });
+      return;
+    }
+
+    const { id, method, params } = message;
+    if (method === 'shutdown') {
+      input.close();
+      return;
+    }
+    if (method === 'cancel') {
+      controllers.get(params?.id)?.abort();
+      return;
+    }
+    if (typeof method !== 'string') {
+      send({ id: id ?? null, error: { code: -32600, message: 'Invalid request' } });
+      return;
+    }
+    if (method === 'ping') {
+      send({ id: id ?? null, result: { success: true } });
+      return;
+    }
+
+    const controller = new AbortController();
+    if (id !== undefined && id !== null) {
+      controllers.set(id, controller);
+    }
+    const notify = (event, data) => {
+      if (!controller.signal.aborted) {
+        send({ method: 'progress', params: { id, event, ...data } });
+      }
+    };
+    let result;
+    try {
+      result = await handleCommand(method, params || {}, notify, controller.signal);
+    } finally {
+      controllers.delete(id);
+    }
+    if (id === undefined || id === null) {
+      return;
+    }
+    if (result && result.success === false) {
+      const code = result.code ?? -32000;
+      send({ id, error: { code, message: result.error || 'Command failed' } });
+    } else {
+      send({ id, result });
+    }
+  };
+
+  const input = readline.createInterface({ input: process.stdin });
+  input.on('line', (line) => {
+    if (!line.trim()) {
+      return;
+    }
+    const request = handleLine(line).catch((error) => {
+      console.error('[ProofGen] Worker request failed:', error);
+    });
+    inFlight.add(request);
+    request.finally(() => inFlight.delete(request));
+  });
+  input.on('close', async () => {
+    await Promise.allSettled([...inFlight]);
+    if (broadcasterClientPromise) {
+      await wakuBroadcasterModule().WakuBroadcasterClient.stop().catch(() => {});
+    }
+    if (railgunInitialized) {
+      await stopRailgunEngine().catch(() => {});
+    }
+    process.exit(0);
+  });
+
+  console.error('[ProofGen] Worker ready');
+}
+
 // CLI interface
 if (require.main === module) {
   const args = process.argv.slice(2);
-  
-  if (args.length < 2) {
-    console.error('Usage: node index.js <command> <params_json>');
-    process.exit(1);
-  }
-  
-  const command = args[0];
-  const params = JSON.parse(args[1]);
-  
-  handleCommand(command, params)
-    .then(result => {
-      console.log(JSON.stringify(result));
-      process.exit(result.success ? 0 : 1);
-    })
-    .catch(error => {
-      console.error('Fatal error:', error);
+
+  if (args[0] === '--worker') {
+    runWorker();
+  } else {
+    if (args.length < 1) {
+      console.error('Usage: node index.js <command> [params_json | -] | node index.js --worker');
+      console.error('Params containing secrets should be piped on stdin (`-` or omitted).');
       process.exit(1);
-    });
+    }
+
+    const command = args[0];
+    const readParams = (args[1] === undefined || args[1] === '-')
+      ? readStdin()
+      : Promise.resolve(args[1]);
+
+    readParams
+      .then(json => handleCommand(command, JSON.parse(json || '{}')))
+      .then(result => {
+        console.log(JSON.stringify(result));
+        process.exit(result.success ? 0 : 1);
+      })
+      .catch(error => {
+        console.error('Fatal error:', error);
+        process.exit(1);
+      });
+  }
 }
 
 module.exports = {
   initRailgun,
   createWallet,
   getShieldKey,
+  baseUnitAmount,
   generateShieldProof,
   generateTransferProof,
+  generateUnshieldProof,
+  scanMerkletree,
+  broadcasterFeeQuotes,
+  broadcasterSubmit,
+  estimateGas,
   handleCommand,
+  runWorker,
   getShieldSignature,
   serializeERC20Transfer,
   getOriginalGasDetailsForTransaction,
@@ -18,18 +18,22 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("Test 2: ProofRequest serialization...");
     let request = ProofRequest {
         proof_type: "shield".to_string(),
-        chain_id: Some(11155111),
+        chain_id: 11155111,
         token: Some("0x0000000000000000000000000000000000000000".to_string()),
         amount: Some("1000000000000000000".to_string()),
         commitment: None,
-        shield_private_key: Some(format!("0x{}", "0".repeat(64))),
+        shield_private_key: Some(format!("0x{}", "0".repeat(64)).into()),
         merkle_root: None,
         nullifier: None,
         output_commitment: None,
+        outputs: None,
+        memo_text: None,
         recipient: Some("0zk1234test".to_string()),
+        broadcaster_fee: None,
         railgun_wallet_id: None,
         encryption_key: None,
         mnemonic: None,
+        start_block: None,
     };
 
     let json = serde_json::to_string(&request)?;
@@ -1,12 +1,191 @@
-use crate::hardware::{DeviceInfo, TrezorManager};
-use crate::privacy::{PrivacyLevel, PrivacyManager, PrivacyPoolOperation, ShieldedTransaction};
+use crate::crypto::account_store::{AccountStore, SoftwareAccount};
+use crate::crypto::keystore::{decrypt_keystore, encrypt_keystore, KdfConfig};
+use crate::crypto::SecretString;
+use crate::error::CepWalletError;
+use crate::hardware::{DeviceInfo, TrezorManager, WalletSigner};
+use crate::history::{
+    export_entries, ExportFormat, HistoryEntry, HistoryFilter, HistoryPage, HistoryStatus,
+    HistoryStore, DEFAULT_PAGE_SIZE,
+};
+use crate::privacy::ffi::{batch_transfer_request, shield_request, spend_request, ProofRequest};
+use crate::privacy::types::{validate_memo, ShieldedTxType};
+use crate::privacy::{
+    bundled_vkeys_dir, circuit_artifacts_dir, derive_railgun_secrets, estimate_gas, plan_private,
+    plan_shield, quote_gas, sign_and_send, wait_for_success, ArtifactManager, BridgeConfig,
+    BroadcasterFee, BroadcasterQuote, CircuitAvailability, GasQuote, NodeBridge, NoteStatus,
+    PaymentRequest, PaymentRequestPayload, PrivacyFeeEstimate, PrivacyLevel, PrivacyManager,
+    PrivacyPoolOperation, PrivacyStore, ProofControl, ProofEncoding, ProofEnvelope, ProofJob,
+    ProofJobs, ProofType, RailgunAddressInfo, RailgunContracts, RailgunManager, ShieldedBalances,
+    ShieldedTransaction, SyncManager, SyncProgress, TransferBatch, TransferOutput,
+    VerificationKeys, WorkerBridge, DEFAULT_RELAYED_GAS_LIMIT, PROOF_PROGRESS_EVENT,
+    SYNC_PROGRESS_EVENT,
+};
+use crate::tokens::portfolio::fetch_public_balances;
+use crate::tokens::{
+    fetch_balance, Portfolio, PortfolioCache, TokenInfo, TokenRegistry, TokenSource,
+    NATIVE_TOKEN_ADDRESS,
+};
+use crate::utils::{
+    chain_info, format_display, parse_amount, AmountInput, DisplayOptions, NumberLocale, Rounding,
+};
+use ethers::providers::{Http, Middleware, Provider};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{Address, TransactionRequest as EthTransactionRequest, U256};
 use serde::{Deserialize, Serialize};
-use tauri::State;
-use tokio::sync::Mutex;
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::sync::{watch, Mutex};
 
 pub struct AppState {
-    pub trezor: Mutex<Option<TrezorManager>>,
+    /// Active signer (Trezor, or the software signer in development builds)
+    pub signer: Mutex<Option<Box<dyn WalletSigner>>>,
     pub privacy: Mutex<Option<PrivacyManager>>,
+    /// Persisted privacy state (scanned shielded balances)
+    pub privacy_store: PrivacyStore,
+    /// Background merkletree syncs
+    pub sync: SyncManager,
+    /// Running proofs (progress, cancellation)
+    pub proof_jobs: Arc<ProofJobs>,
+    /// Node.js proof generator
+    pub bridge: Arc<dyn NodeBridge>,
+    /// Pinned circuit artifacts (proving keys, witness generators)
+    pub artifacts: Arc<ArtifactManager>,
+    /// Groth16 verification keys (bundled, then pinned artifacts)
+    pub verification_keys: Arc<VerificationKeys>,
+    /// Local transaction history
+    pub history: HistoryStore,
+    /// ERC-20 token registry (bundled list + custom tokens)
+    pub tokens: Mutex<TokenRegistry>,
+    /// Cached account portfolios
+    pub portfolio: Mutex<PortfolioCache>,
+    /// App data directory (account store, local databases)
+    pub data_dir: PathBuf,
+}
+
+impl AppState {
+    /// State for an app data directory; `resource_dir` holds the bundled
+    /// proof generator in production builds
+    pub fn new(data_dir: PathBuf, resource_dir: Option<PathBuf>) -> Result<Self, CepWalletError> {
+        let artifacts = Arc::new(ArtifactManager::pinned(&circuit_artifacts_dir(&data_dir)));
+        let verification_keys = VerificationKeys::new(
+            Some(bundled_vkeys_dir(resource_dir.as_deref())),
+            Some(artifacts.clone()),
+        );
+        let privacy_store = PrivacyStore::open(&data_dir)?;
+        let interrupted = privacy_store.interrupt_proof_jobs()?;
+        if interrupted > 0 {
+            tracing::warn!(
+                "{} proof jobs were interrupted by the last shutdown",
+                interrupted
+            );
+        }
+        let bridge = WorkerBridge::new(BridgeConfig {
+            resource_dir,
+            railgun_db: Some(data_dir.join("railgun_db")),
+            circuit_artifacts: Some(circuit_artifacts_dir(&data_dir)),
+        });
+
+        Ok(Self {
+            signer: Mutex::new(None),
+            privacy: Mutex::new(None),
+            privacy_store,
+            sync: SyncManager::default(),
+            proof_jobs: Arc::default(),
+            bridge: Arc::new(bridge),
+            artifacts,
+            verification_keys: Arc::new(verification_keys),
+            history: HistoryStore::open(&data_dir)?,
+            tokens: Mutex::new(TokenRegistry::load(&data_dir)?),
+            portfolio: Mutex::new(PortfolioCache::default()),
+            data_dir,
+        })
+    }
+
+    /// RAILGUN manager, cloned so the privacy lock is not held across calls
+    pub async fn railgun(&self) -> Result<RailgunManager, String> {
+        let privacy = self.privacy.lock().await;
+        Ok(privacy
+            .as_ref()
+            .ok_or("Privacy features not initialized".to_string())?
+            .railgun()
+            .clone())
+    }
+
+    /// `chain_id`, or the chain of the RAILGUN network in use
+    pub async fn railgun_chain_id(&self, chain_id: Option<u64>) -> Result<u64, String> {
+        match chain_id {
+            Some(chain_id) => Ok(chain_id),
+            None => Ok(self.railgun().await?.chain_id()),
+        }
+    }
+
+    /// Resolve a token symbol or address to an address on a chain
+    pub async fn resolve_token_address(
+        &self,
+        chain_id: u64,
+        token: &str,
+    ) -> Result<String, String> {
+        self.tokens
+            .lock()
+            .await
+            .resolve_address(chain_id, token)
+            .map_err(|e| e.to_string())
+    }
+
+    /// Resolve a token (symbol or address) and an amount input on a chain
+    ///
+    /// Amounts may be base units, `"<amount> <symbol>"`, an ether unit for the
+    /// native token (`"250 gwei"`), or `"max"`. Unknown token addresses only
+    /// accept base units and `"max"`.
+    pub async fn parse_token_amount(
+        &self,
+        chain_id: u64,
+        token: &str,
+        amount: &str,
+    ) -> Result<(String, AmountInput), String> {
+        let tokens = self.tokens.lock().await;
+        let address = tokens
+            .resolve_address(chain_id, token)
+            .map_err(|e| e.to_string())?;
+
+        let input = match tokens.get(chain_id, &address) {
+            Some(info) => parse_amount(amount, info.decimals, &info.symbol, info.is_native()),
+            None => parse_amount(amount, 0, &address, false),
+        }
+        .map_err(|e| e.to_string())?;
+
+        Ok((address, input))
+    }
+
+    /// Record a history entry; failures are logged and never fail the operation
+    pub fn record_history(&self, entry: &HistoryEntry) {
+        if let Err(e) = self.history.record(entry) {
+            tracing::warn!("Failed to record transaction history: {}", e);
+        }
+    }
+
+    /// Record a privacy operation and tag the transaction with its history id
+    fn record_shielded(
+        &self,
+        chain_id: u64,
+        account: &str,
+        mut tx: ShieldedTransaction,
+    ) -> ShieldedTransaction {
+        let entry = HistoryEntry::shielded(chain_id, account, &tx);
+        self.record_history(&entry);
+        tx.history_id = Some(entry.id);
+        tx
+    }
+
+    /// Encrypted software account store
+    pub fn account_store(&self) -> AccountStore {
+        AccountStore::open(&self.data_dir)
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -20,6 +199,111 @@ pub struct TransactionRequest {
     pub chain_id: u64,
 }
 
+impl TransactionRequest {
+    /// Convert to an ethers legacy transaction for signing
+    pub fn to_typed_transaction(&self) -> Result<TypedTransaction, String> {
+        let to = Address::from_str(&self.to).map_err(|e| format!("Invalid recipient: {}", e))?;
+
+        let mut tx = EthTransactionRequest::new()
+            .to(to)
+            .value(parse_native_value(self.chain_id, &self.value)?)
+            .chain_id(self.chain_id);
+
+        if let Some(data) = &self.data {
+            let bytes = hex::decode(data.trim_start_matches("0x"))
+                .map_err(|e| format!("Invalid data: {}", e))?;
+            tx = tx.data(bytes);
+        }
+        if let Some(gas_limit) = &self.gas_limit {
+            tx = tx.gas(parse_quantity(gas_limit)?);
+        }
+        if let Some(gas_price) = &self.gas_price {
+            tx = tx.gas_price(parse_native_value(self.chain_id, gas_price)?);
+        }
+        if let Some(nonce) = self.nonce {
+            tx = tx.nonce(nonce);
+        }
+
+        Ok(tx.into())
+    }
+}
+
+/// RPC endpoint for a chain (explicit URL, or the chain's public default)
+fn rpc_url_for(chain_id: u64, rpc_url: Option<String>) -> Result<String, String> {
+    match rpc_url {
+        Some(url) => Ok(url),
+        None => chain_info(chain_id)
+            .map(|chain| chain.default_rpc_url.to_string())
+            .ok_or_else(|| format!("No default RPC for chain {}; provide rpc_url", chain_id)),
+    }
+}
+
+/// Parse a native currency value ("1.5 ETH", "30 gwei", or wei)
+fn parse_native_value(chain_id: u64, value: &str) -> Result<U256, String> {
+    let symbol = chain_info(chain_id)
+        .map(|chain| chain.native_symbol)
+        .unwrap_or("ETH");
+
+    match parse_amount(value, 18, symbol, true).map_err(|e| e.to_string())? {
+        AmountInput::Exact(amount) => Ok(amount),
+        AmountInput::Max => Err(format!("\"{}\" is not supported here", value.trim())),
+    }
+}
+
+/// Gas units kept back when shielding the whole native balance
+const NATIVE_SHIELD_GAS_RESERVE: u64 = 500_000;
+
+/// Public balance available to shield ("max")
+///
+/// For the native token, gas for the shield transaction itself is kept back.
+async fn public_max_amount(
+    chain_id: u64,
+    token: &str,
+    account: &str,
+    rpc_url: Option<String>,
+) -> Result<U256, String> {
+    let owner = Address::from_str(account).map_err(|e| format!("Invalid account: {}", e))?;
+    let provider = Provider::<Http>::try_from(rpc_url_for(chain_id, rpc_url)?)
+        .map_err(|e| format!("Invalid RPC URL: {}", e))?;
+
+    let balance = fetch_balance(&provider, token, owner)
+        .await
+        .map_err(|e| e.to_string())?;
+    if token != NATIVE_TOKEN_ADDRESS {
+        return Ok(balance);
+    }
+
+    let gas_price = provider
+        .get_gas_price()
+        .await
+        .map_err(|e| format!("RPC error: {}", e))?;
+    Ok(balance.saturating_sub(gas_price * NATIVE_SHIELD_GAS_RESERVE))
+}
+
+/// Shielded balance available to spend ("max"), from the latest scan
+fn shielded_max_amount(
+    state: &AppState,
+    railgun: &RailgunManager,
+    railgun_wallet_id: &str,
+    token: &str,
+) -> Result<U256, String> {
+    let balance = railgun
+        .get_shielded_balance(&state.privacy_store, railgun_wallet_id, token)
+        .map_err(|e| e.to_string())?;
+
+    U256::from_dec_str(&balance).map_err(|e| format!("Invalid shielded balance: {}", e))
+}
+
+/// Parse a decimal or 0x-prefixed hex quantity
+fn parse_quantity(value: &str) -> Result<U256, String> {
+    let value = value.trim();
+    let parsed = match value.strip_prefix("0x") {
+        Some(hex_value) => U256::from_str_radix(hex_value, 16).map_err(|e| e.to_string()),
+        None => U256::from_dec_str(value).map_err(|e| e.to_string()),
+    };
+    parsed.map_err(|e| format!("Invalid quantity '{}': {}", value, e))
+}
+
 /// Connect to Trezor device
 #[tauri::command]
 pub async fn connect_device(state: State<'_, AppState>) -> Result<DeviceInfo, String> {
@@ -27,30 +311,81 @@ pub async fn connect_device(state: State<'_, AppState>) -> Result<DeviceInfo, St
 
     let device_info = manager.connect().await.map_err(|e| e.to_string())?;
 
-    let mut trezor = state.trezor.lock().await;
-    *trezor = Some(manager);
+    let mut signer = state.signer.lock().await;
+    *signer = Some(Box::new(manager));
 
     Ok(device_info)
 }
 
+/// Connect the software signer (development/testing builds only)
+///
+/// Loads a BIP-39 mnemonic, a V3 JSON keystore file, or a software account
+/// from the encrypted local store (`password` is then the store password).
+#[tauri::command]
+pub async fn connect_software_signer(
+    state: State<'_, AppState>,
+    mnemonic: Option<String>,
+    keystore_path: Option<String>,
+    account: Option<String>,
+    password: Option<String>,
+) -> Result<DeviceInfo, String> {
+    #[cfg(feature = "software-signer")]
+    {
+        use crate::hardware::SoftwareSigner;
+
+        let password = password.as_deref();
+        let software = match (mnemonic, keystore_path, account) {
+            (Some(phrase), None, None) => SoftwareSigner::from_mnemonic(&phrase, password),
+            (None, Some(path), None) => {
+                SoftwareSigner::from_keystore_file(path, password.unwrap_or(""))
+            }
+            (None, None, Some(address)) => SoftwareSigner::from_account_store(
+                &state.account_store(),
+                &address,
+                password.unwrap_or(""),
+            ),
+            _ => {
+                return Err("Provide exactly one of mnemonic, keystore path or account".to_string())
+            }
+        }
+        .map_err(|e| e.to_string())?;
+
+        let device_info = software
+            .get_device_info()
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let mut signer = state.signer.lock().await;
+        *signer = Some(Box::new(software));
+
+        Ok(device_info)
+    }
+
+    #[cfg(not(feature = "software-signer"))]
+    {
+        let _ = (state, mnemonic, keystore_path, account, password);
+        Err("Software signer is not enabled in this build".to_string())
+    }
+}
+
 /// Disconnect from Trezor device
 #[tauri::command]
 pub async fn disconnect_device(state: State<'_, AppState>) -> Result<(), String> {
-    let mut trezor = state.trezor.lock().await;
+    let mut signer = state.signer.lock().await;
 
-    if let Some(manager) = trezor.as_ref() {
+    if let Some(manager) = signer.as_ref() {
         manager.disconnect().await.map_err(|e| e.to_string())?;
     }
 
-    *trezor = None;
+    *signer = None;
     Ok(())
 }
 
 /// Get device information
 #[tauri::command]
 pub async fn get_device_info(state: State<'_, AppState>) -> Result<DeviceInfo, String> {
-    let trezor = state.trezor.lock().await;
-    let manager = trezor.as_ref().ok_or("Device not connected".to_string())?;
+    let signer = state.signer.lock().await;
+    let manager = signer.as_ref().ok_or("Device not connected".to_string())?;
 
     manager.get_device_info().await.map_err(|e| e.to_string())
 }
@@ -58,8 +393,8 @@ pub async fn get_device_info(state: State<'_, AppState>) -> Result<DeviceInfo, S
 /// Get public key for a derivation path
 #[tauri::command]
 pub async fn get_public_key(state: State<'_, AppState>, path: String) -> Result<String, String> {
-    let trezor = state.trezor.lock().await;
-    let manager = trezor.as_ref().ok_or("Device not connected".to_string())?;
+    let signer = state.signer.lock().await;
+    let manager = signer.as_ref().ok_or("Device not connected".to_string())?;
 
     let pubkey = manager
         .get_public_key(&path)
@@ -72,8 +407,8 @@ pub async fn get_public_key(state: State<'_, AppState>, path: String) -> Result<
 /// Get Ethereum address for a derivation path
 #[tauri::command]
 pub async fn get_address(state: State<'_, AppState>, path: String) -> Result<String, String> {
-    let trezor = state.trezor.lock().await;
-    let manager = trezor.as_ref().ok_or("Device not connected".to_string())?;
+    let signer = state.signer.lock().await;
+    let manager = signer.as_ref().ok_or("Device not connected".to_string())?;
 
     manager.get_address(&path).await.map_err(|e| e.to_string())
 }
@@ -85,8 +420,8 @@ pub async fn get_addresses(
     start_index: u32,
     count: u32,
 ) -> Result<Vec<String>, String> {
-    let trezor = state.trezor.lock().await;
-    let manager = trezor.as_ref().ok_or("Device not connected".to_string())?;
+    let signer = state.signer.lock().await;
+    let manager = signer.as_ref().ok_or("Device not connected".to_string())?;
 
     manager
         .get_addresses(start_index, count)
@@ -101,17 +436,26 @@ pub async fn sign_transaction(
     path: String,
     tx: TransactionRequest,
 ) -> Result<String, String> {
-    let trezor = state.trezor.lock().await;
-    let manager = trezor.as_ref().ok_or("Device not connected".to_string())?;
+    let signer = state.signer.lock().await;
+    let manager = signer.as_ref().ok_or("Device not connected".to_string())?;
 
-    // Serialize transaction to bytes (simplified)
-    let tx_data = serde_json::to_vec(&tx).map_err(|e| e.to_string())?;
+    let typed_tx = tx.to_typed_transaction()?;
 
     let signed = manager
-        .sign_transaction(&path, tx_data)
+        .sign_transaction(&path, &typed_tx)
         .await
         .map_err(|e| e.to_string())?;
 
+    let account = manager
+        .get_address(&path)
+        .await
+        .map_err(|e| e.to_string())?;
+    state.record_history(
+        &HistoryEntry::public(tx.chain_id, &account, HistoryStatus::Signed)
+            .with_value(None, typed_tx.value().map(|value| value.to_string()))
+            .with_recipient(Some(tx.to.clone())),
+    );
+
     Ok(signed.signature)
 }
 
@@ -122,8 +466,8 @@ pub async fn sign_message(
     path: String,
     message: String,
 ) -> Result<String, String> {
-    let trezor = state.trezor.lock().await;
-    let manager = trezor.as_ref().ok_or("Device not connected".to_string())?;
+    let signer = state.signer.lock().await;
+    let manager = signer.as_ref().ok_or("Device not connected".to_string())?;
 
     manager
         .sign_message(&path, &message)
@@ -144,8 +488,8 @@ pub async fn sign_typed_data(
     path: String,
     data: serde_json::Value,
 ) -> Result<String, String> {
-    let trezor = state.trezor.lock().await;
-    let manager = trezor.as_ref().ok_or("Device not connected".to_string())?;
+    let signer = state.signer.lock().await;
+    let manager = signer.as_ref().ok_or("Device not connected".to_string())?;
 
     manager
         .sign_typed_data(&path, data)
@@ -153,6 +497,475 @@ pub async fn sign_typed_data(
         .map_err(|e| e.to_string())
 }
 
+// ============================================================================
+// SOFTWARE ACCOUNT COMMANDS (V3 keystore import/export)
+// ============================================================================
+
+/// Import a V3 JSON keystore as a software account
+///
+/// The keystore is decrypted with `keystore_password` and the key is re-sealed
+/// in the local account store under `store_password`.
+#[tauri::command]
+pub async fn import_keystore(
+    state: State<'_, AppState>,
+    keystore_json: String,
+    keystore_password: String,
+    store_password: String,
+    name: Option<String>,
+) -> Result<SoftwareAccount, String> {
+    let private_key =
+        decrypt_keystore(&keystore_json, &keystore_password).map_err(|e| e.to_string())?;
+
+    state
+        .account_store()
+        .import_private_key(
+            name.as_deref().unwrap_or("Imported account"),
+            &private_key,
+            &store_password,
+        )
+        .map_err(|e| e.to_string())
+}
+
+/// Export a software account as a V3 JSON keystore (scrypt, geth standard params)
+#[tauri::command]
+pub async fn export_keystore(
+    state: State<'_, AppState>,
+    address: String,
+    store_password: String,
+    keystore_password: String,
+) -> Result<String, String> {
+    let private_key = state
+        .account_store()
+        .private_key(&address, &store_password)
+        .map_err(|e| e.to_string())?;
+
+    let keystore = encrypt_keystore(&private_key, &keystore_password, KdfConfig::standard())
+        .map_err(|e| e.to_string())?;
+
+    serde_json::to_string_pretty(&keystore).map_err(|e| e.to_string())
+}
+
+/// List software accounts in the local store
+#[tauri::command]
+pub async fn list_software_accounts(
+    state: State<'_, AppState>,
+) -> Result<Vec<SoftwareAccount>, String> {
+    state
+        .account_store()
+        .list_accounts()
+        .map_err(|e| e.to_string())
+}
+
+/// Remove a software account from the local store
+#[tauri::command]
+pub async fn remove_software_account(
+    state: State<'_, AppState>,
+    address: String,
+) -> Result<(), String> {
+    state
+        .account_store()
+        .remove_account(&address)
+        .map_err(|e| e.to_string())
+}
+
+// ============================================================================
+// TRANSACTION HISTORY COMMANDS
+// ============================================================================
+
+/// Query transaction history (newest first, paginated)
+#[tauri::command]
+pub async fn get_transaction_history(
+    state: State<'_, AppState>,
+    filter: Option<HistoryFilter>,
+    offset: Option<u32>,
+    limit: Option<u32>,
+) -> Result<HistoryPage, String> {
+    state
+        .history
+        .query(
+            &filter.unwrap_or_default(),
+            offset.unwrap_or(0),
+            limit.unwrap_or(DEFAULT_PAGE_SIZE),
+        )
+        .map_err(|e| e.to_string())
+}
+
+/// Export transaction history as CSV or JSON
+#[tauri::command]
+pub async fn export_transaction_history(
+    state: State<'_, AppState>,
+    filter: Option<HistoryFilter>,
+    format: ExportFormat,
+) -> Result<String, String> {
+    let entries = state
+        .history
+        .query_all(&filter.unwrap_or_default())
+        .map_err(|e| e.to_string())?;
+
+    export_entries(&entries, format).map_err(|e| e.to_string())
+}
+
+/// Record a broadcast transaction
+///
+/// Updates the given history entry (e.g. a shield returned by `shield_transaction`)
+/// or records a new public entry when `history_id` is omitted.
+#[tauri::command]
+pub async fn record_transaction_broadcast(
+    state: State<'_, AppState>,
+    tx_hash: String,
+    chain_id: u64,
+    account: String,
+    history_id: Option<String>,
+) -> Result<HistoryEntry, String> {
+    match history_id {
+        Some(id) => state
+            .history
+            .update_status(&id, HistoryStatus::Broadcast, Some(&tx_hash), None)
+            .map_err(|e| e.to_string()),
+        None => {
+            let entry = HistoryEntry::public(chain_id, &account, HistoryStatus::Broadcast)
+                .with_tx_hash(Some(tx_hash));
+            state.history.record(&entry).map_err(|e| e.to_string())?;
+            Ok(entry)
+        }
+    }
+}
+
+/// Update the status of a history entry (confirmation or failure)
+#[tauri::command]
+pub async fn update_transaction_status(
+    state: State<'_, AppState>,
+    history_id: String,
+    status: HistoryStatus,
+    error: Option<String>,
+) -> Result<HistoryEntry, String> {
+    state
+        .history
+        .update_status(&history_id, status, None, error.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+// ============================================================================
+// TOKEN REGISTRY COMMANDS
+// ============================================================================
+
+/// List known tokens for a chain (native token first)
+#[tauri::command]
+pub async fn list_tokens(
+    state: State<'_, AppState>,
+    chain_id: u64,
+) -> Result<Vec<TokenInfo>, String> {
+    Ok(state.tokens.lock().await.tokens(chain_id))
+}
+
+/// Resolve a token symbol or address to its metadata
+#[tauri::command]
+pub async fn resolve_token(
+    state: State<'_, AppState>,
+    chain_id: u64,
+    token: String,
+) -> Result<TokenInfo, String> {
+    state
+        .tokens
+        .lock()
+        .await
+        .resolve(chain_id, &token)
+        .map_err(|e| e.to_string())
+}
+
+/// Detect token metadata over RPC (not persisted)
+#[tauri::command]
+pub async fn detect_token(
+    chain_id: u64,
+    address: String,
+    rpc_url: Option<String>,
+) -> Result<TokenInfo, String> {
+    let rpc_url = rpc_url_for(chain_id, rpc_url)?;
+
+    crate::tokens::detect_token(&rpc_url, chain_id, &address)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Add a custom token
+///
+/// Metadata that is not provided is detected over RPC.
+#[allow(clippy::too_many_arguments)]
+#[tauri::command]
+pub async fn add_custom_token(
+    state: State<'_, AppState>,
+    chain_id: u64,
+    address: String,
+    symbol: Option<String>,
+    name: Option<String>,
+    decimals: Option<u8>,
+    logo_uri: Option<String>,
+    rpc_url: Option<String>,
+) -> Result<TokenInfo, String> {
+    let token = match (symbol, decimals) {
+        (Some(symbol), Some(decimals)) => TokenInfo {
+            chain_id,
+            address,
+            name: name.unwrap_or_default(),
+            symbol,
+            decimals,
+            logo_uri,
+            source: TokenSource::Custom,
+        },
+        (symbol, decimals) => {
+            let rpc_url = rpc_url_for(chain_id, rpc_url)?;
+            let detected = crate::tokens::detect_token(&rpc_url, chain_id, &address)
+                .await
+                .map_err(|e| e.to_string())?;
+
+            TokenInfo {
+                symbol: symbol.unwrap_or(detected.symbol),
+                name: name.unwrap_or(detected.name),
+                decimals: decimals.unwrap_or(detected.decimals),
+                logo_uri,
+                ..detected
+            }
+        }
+    };
+
+    let token = state
+        .tokens
+        .lock()
+        .await
+        .add_custom(token)
+        .map_err(|e| e.to_string())?;
+    state.portfolio.lock().await.invalidate_chain(chain_id);
+
+    Ok(token)
+}
+
+/// Remove a custom token
+#[tauri::command]
+pub async fn remove_custom_token(
+    state: State<'_, AppState>,
+    chain_id: u64,
+    address: String,
+) -> Result<(), String> {
+    state
+        .tokens
+        .lock()
+        .await
+        .remove_custom(chain_id, &address)
+        .map_err(|e| e.to_string())?;
+    state.portfolio.lock().await.invalidate_chain(chain_id);
+
+    Ok(())
+}
+
+/// Convert an amount input ("1.5 USDC", "250 gwei", base units) to base units
+#[tauri::command]
+pub async fn to_base_units(
+    state: State<'_, AppState>,
+    chain_id: u64,
+    token: String,
+    amount: String,
+) -> Result<String, String> {
+    match state.parse_token_amount(chain_id, &token, &amount).await? {
+        (_, AmountInput::Exact(amount)) => Ok(amount.to_string()),
+        (_, AmountInput::Max) => Err("\"max\" depends on the balance; pass an amount".to_string()),
+    }
+}
+
+/// Format a base-unit amount for display in the user's locale
+#[allow(clippy::too_many_arguments)]
+#[tauri::command]
+pub async fn format_amount(
+    state: State<'_, AppState>,
+    chain_id: u64,
+    token: String,
+    amount: String,
+    locale: Option<String>,
+    max_fraction_digits: Option<u8>,
+    min_fraction_digits: Option<u8>,
+    rounding: Option<Rounding>,
+) -> Result<String, String> {
+    let info = state
+        .tokens
+        .lock()
+        .await
+        .resolve(chain_id, &token)
+        .map_err(|e| e.to_string())?;
+    let amount = parse_quantity(&amount)?;
+
+    let defaults = DisplayOptions::default();
+    let options = DisplayOptions {
+        locale: locale
+            .as_deref()
+            .map(NumberLocale::from_tag)
+            .unwrap_or_default(),
+        max_fraction_digits: max_fraction_digits.unwrap_or(defaults.max_fraction_digits),
+        min_fraction_digits: min_fraction_digits.unwrap_or(defaults.min_fraction_digits),
+        rounding: rounding.unwrap_or(defaults.rounding),
+    };
+
+    Ok(format_display(amount, info.decimals, &options))
+}
+
+// ============================================================================
+// PORTFOLIO COMMANDS
+// ============================================================================
+
+/// Shielded balances (spendable and POI-pending) of a RAILGUN wallet on a chain
+///
+/// Empty when the wallet has not been scanned on that chain yet.
+fn shielded_balances(
+    state: &AppState,
+    chain_id: u64,
+    railgun_wallet_id: &str,
+    tokens: &[TokenInfo],
+) -> Result<HashMap<String, U256>, String> {
+    let Some(scan) = state
+        .privacy_store
+        .balances(railgun_wallet_id, chain_id)
+        .map_err(|e| e.to_string())?
+    else {
+        return Ok(HashMap::new());
+    };
+
+    Ok(tokens
+        .iter()
+        .map(|token| {
+            let owned = scan
+                .amount(&token.address, NoteStatus::Spendable)
+                .saturating_add(scan.amount(&token.address, NoteStatus::PendingPoi));
+            (token.address.clone(), owned)
+        })
+        .collect())
+}
+
+/// Fetch and cache portfolios for accounts on one chain
+async fn fetch_portfolios(
+    state: &AppState,
+    chain_id: u64,
+    accounts: &[String],
+    railgun: Option<(&str, &str)>,
+    rpc_url: Option<String>,
+) -> Result<Vec<Portfolio>, String> {
+    let tokens = state.tokens.lock().await.tokens(chain_id);
+    let provider = Provider::<Http>::try_from(rpc_url_for(chain_id, rpc_url)?)
+        .map_err(|e| format!("Invalid RPC URL: {}", e))?;
+
+    let public = fetch_public_balances(&provider, accounts, &tokens)
+        .await
+        .map_err(|e| e.to_string())?;
+    let (railgun_account, shielded) = match railgun {
+        Some((account, wallet_id)) => (
+            Some(account),
+            shielded_balances(state, chain_id, wallet_id, &tokens)?,
+        ),
+        None => (None, HashMap::new()),
+    };
+    let no_shielded = HashMap::new();
+
+    let portfolios: Vec<Portfolio> = accounts
+        .iter()
+        .zip(public)
+        .map(|(account, public)| {
+            let is_railgun_account =
+                railgun_account.is_some_and(|owner| owner.eq_ignore_ascii_case(account));
+            let shielded = if is_railgun_account {
+                &shielded
+            } else {
+                &no_shielded
+            };
+            Portfolio::new(chain_id, account, &tokens, &public, shielded)
+        })
+        .collect();
+
+    let mut cache = state.portfolio.lock().await;
+    for portfolio in &portfolios {
+        cache.insert(portfolio.clone());
+    }
+
+    Ok(portfolios)
+}
+
+/// Get native and registered ERC-20 balances for accounts on chains
+///
+/// Public balances are fetched in one Multicall3 batch per chain. When a
+/// `railgun_wallet_id` is given, its scanned shielded balances are merged into
+/// `railgun_account`'s portfolio (defaults to the first account). Results are
+/// served from the cache until the refresh interval passes, unless
+/// `force_refresh` is set.
+#[tauri::command]
+pub async fn get_portfolio(
+    state: State<'_, AppState>,
+    accounts: Vec<String>,
+    chain_ids: Vec<u64>,
+    railgun_wallet_id: Option<String>,
+    railgun_account: Option<String>,
+    rpc_urls: Option<HashMap<u64, String>>,
+    force_refresh: Option<bool>,
+) -> Result<Vec<Portfolio>, String> {
+    let mut rpc_urls = rpc_urls.unwrap_or_default();
+    let force_refresh = force_refresh.unwrap_or(false);
+    let railgun_account = railgun_account.or_else(|| accounts.first().cloned());
+    let railgun = railgun_account.as_deref().zip(railgun_wallet_id.as_deref());
+
+    let mut portfolios = Vec::new();
+    for chain_id in chain_ids {
+        let cached: Vec<Option<Portfolio>> = {
+            let cache = state.portfolio.lock().await;
+            accounts
+                .iter()
+                .map(|account| {
+                    (!force_refresh)
+                        .then(|| cache.get(chain_id, account))
+                        .flatten()
+                })
+                .collect()
+        };
+
+        let stale: Vec<String> = accounts
+            .iter()
+            .zip(&cached)
+            .filter(|(_, portfolio)| portfolio.is_none())
+            .map(|(account, _)| account.clone())
+            .collect();
+        let mut fetched = if stale.is_empty() {
+            Vec::new()
+        } else {
+            fetch_portfolios(
+                &state,
+                chain_id,
+                &stale,
+                railgun,
+                rpc_urls.remove(&chain_id),
+            )
+            .await?
+        }
+        .into_iter();
+
+        for portfolio in cached {
+            match portfolio.or_else(|| fetched.next()) {
+                Some(portfolio) => portfolios.push(portfolio),
+                None => return Err("Portfolio fetch returned too few accounts".to_string()),
+            }
+        }
+    }
+
+    Ok(portfolios)
+}
+
+/// Set how long fetched portfolios are served from the cache
+#[tauri::command]
+pub async fn set_portfolio_refresh_interval(
+    state: State<'_, AppState>,
+    seconds: u64,
+) -> Result<(), String> {
+    state
+        .portfolio
+        .lock()
+        .await
+        .set_refresh_interval(Duration::from_secs(seconds));
+    Ok(())
+}
+
 // ============================================================================
 // PRIVACY COMMANDS (Kohaku Integration)
 // ============================================================================
@@ -166,7 +979,10 @@ pub async fn initialize_privacy(state: State<'_, AppState>, chain_id: u64) -> Re
         return Ok(()); // Already initialized
     }
 
-    let mut manager = PrivacyManager::new(chain_id).map_err(|e| e.to_string())?;
+    let mut manager = PrivacyManager::new(chain_id, state.bridge.clone())
+        .map_err(|e| e.to_string())?
+        .with_circuit_artifacts(state.artifacts.clone())
+        .with_verification_keys(state.verification_keys.clone());
     manager.initialize().await.map_err(|e| e.to_string())?;
 
     *privacy = Some(manager);
@@ -189,93 +1005,1076 @@ pub async fn is_privacy_ready(state: State<'_, AppState>) -> Result<bool, String
     }
 }
 
+/// Shield parameters of a proof job
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShieldJobParams {
+    pub token: String,
+    /// Base units, `"1.5 USDC"`-style amount or `"max"`
+    pub amount: String,
+    pub railgun_address: String,
+    pub shield_private_key: SecretString,
+    /// Sending account; needed for `"max"`
+    pub account: Option<String>,
+    pub rpc_url: Option<String>,
+}
+
+/// Private transfer or unshield parameters of a proof job
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpendJobParams {
+    /// RAILGUN address (transfer) or public address (unshield)
+    pub recipient: String,
+    pub token: String,
+    /// Base units, `"1.5 USDC"`-style amount or `"max"`
+    pub amount: String,
+    pub railgun_wallet_id: String,
+    /// Omitted for hardware-derived wallets (see `wallet_secrets`)
+    #[serde(default)]
+    pub encryption_key: SecretString,
+    #[serde(default)]
+    pub mnemonic: SecretString,
+    /// Prove for this broadcaster instead of a self-signed transaction
+    #[serde(default)]
+    pub broadcaster_fee: Option<BroadcasterFee>,
+    /// Memo text encrypted to the recipient (transfers only)
+    #[serde(default)]
+    pub memo: Option<String>,
+}
+
+impl SpendJobParams {
+    /// Resolve the token and amount to an address and base units, check
+    /// the memo and fill in the wallet's secrets
+    async fn resolve(mut self, state: &AppState, railgun: &RailgunManager) -> Result<Self, String> {
+        (self.encryption_key, self.mnemonic) = wallet_secrets(
+            state,
+            &self.railgun_wallet_id,
+            std::mem::take(&mut self.encryption_key),
+            std::mem::take(&mut self.mnemonic),
+            true,
+        )
+        .await?;
+        self.memo = self.memo.filter(|memo| !memo.is_empty());
+        if let Some(memo) = &self.memo {
+            validate_memo(memo).map_err(|e| e.to_string())?;
+        }
+        let (token, amount) = state
+            .parse_token_amount(railgun.chain_id(), &self.token, &self.amount)
+            .await?;
+        let amount = match amount {
+            AmountInput::Exact(amount) => amount,
+            AmountInput::Max => {
+                shielded_max_amount(state, railgun, &self.railgun_wallet_id, &token)?
+            }
+        };
+        self.token = token;
+        self.amount = amount.to_string();
+        Ok(self)
+    }
+}
+
+/// Multi-output private transfer parameters of a proof job
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferBatchJobParams {
+    /// Amounts in base units or `"1.5 USDC"`-style; `"max"` is not accepted
+    pub outputs: Vec<TransferOutput>,
+    pub railgun_wallet_id: String,
+    /// Omitted for hardware-derived wallets (see `wallet_secrets`)
+    #[serde(default)]
+    pub encryption_key: SecretString,
+    #[serde(default)]
+    pub mnemonic: SecretString,
+    #[serde(default)]
+    pub broadcaster_fee: Option<BroadcasterFee>,
+}
+
+impl TransferBatchJobParams {
+    /// Resolve every output's token and amount, group the outputs and fill
+    /// in the wallet's secrets
+    async fn resolve(mut self, state: &AppState, chain_id: u64) -> Result<Self, String> {
+        (self.encryption_key, self.mnemonic) = wallet_secrets(
+            state,
+            &self.railgun_wallet_id,
+            std::mem::take(&mut self.encryption_key),
+            std::mem::take(&mut self.mnemonic),
+            true,
+        )
+        .await?;
+        for output in &mut self.outputs {
+            check_railgun_address(&output.recipient, chain_id)?;
+            let (token, amount) = state
+                .parse_token_amount(chain_id, &output.token, &output.amount)
+                .await?;
+            let AmountInput::Exact(amount) = amount else {
+                return Err("\"max\" is not supported in multi-output transfers".to_string());
+            };
+            output.token = token;
+            output.amount = amount.to_string();
+        }
+        self.outputs = self.batch()?.outputs().to_vec();
+        Ok(self)
+    }
+
+    fn batch(&self) -> Result<TransferBatch, String> {
+        TransferBatch::new(self.outputs.clone()).map_err(|e| e.to_string())
+    }
+}
+
+/// Encryption key and mnemonic of a RAILGUN wallet
+///
+/// Hardware-derived wallets take none from the caller: theirs are
+/// re-derived from the connected signer, so they never cross IPC. Other
+/// wallets must pass theirs in; scans only need the encryption key.
+async fn wallet_secrets(
+    state: &AppState,
+    railgun_wallet_id: &str,
+    encryption_key: SecretString,
+    mnemonic: SecretString,
+    needs_mnemonic: bool,
+) -> Result<(SecretString, SecretString), String> {
+    let account = state
+        .privacy_store
+        .hardware_wallet_account(railgun_wallet_id)
+        .map_err(|e| e.to_string())?;
+    let Some(account) = account else {
+        if encryption_key.is_empty() {
+            return Err("Encryption key is required".to_string());
+        }
+        if needs_mnemonic && mnemonic.is_empty() {
+            return Err("Mnemonic is required".to_string());
+        }
+        return Ok((encryption_key, mnemonic));
+    };
+    if !encryption_key.is_empty() || !mnemonic.is_empty() {
+        return Err(
+            "Hardware-derived wallets do not accept an encryption key or mnemonic".to_string(),
+        );
+    }
+
+    let signer = state.signer.lock().await;
+    let signer = signer.as_ref().ok_or("Device not connected".to_string())?;
+    let secrets = derive_railgun_secrets(signer.as_ref(), account)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok((
+        secrets.encryption_key.as_str().into(),
+        secrets.mnemonic.as_str().into(),
+    ))
+}
+
+/// Check a 0zk address before a proof is requested for it, so a typo or an
+/// address for another chain fails here rather than in the proof generator
+fn check_railgun_address(address: &str, chain_id: u64) -> Result<(), String> {
+    crate::privacy::validate_railgun_address(address, chain_id)
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+/// A proof to generate, by operation (`{"type": "shield", ...}`)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ProofJobRequest {
+    Shield(ShieldJobParams),
+    Transfer(SpendJobParams),
+    /// Several recipients and tokens in one transaction
+    TransferBatch(TransferBatchJobParams),
+    Unshield(SpendJobParams),
+}
+
+impl ProofJobRequest {
+    fn proof_type(&self) -> &'static str {
+        match self {
+            Self::Shield(_) => "shield",
+            Self::Transfer(_) | Self::TransferBatch(_) => "transfer",
+            Self::Unshield(_) => "unshield",
+        }
+    }
+
+    fn tx_type(&self) -> ShieldedTxType {
+        match self {
+            Self::Shield(_) => ShieldedTxType::Shield,
+            Self::Transfer(_) | Self::TransferBatch(_) => ShieldedTxType::Transfer,
+            Self::Unshield(_) => ShieldedTxType::Unshield,
+        }
+    }
+
+    /// Token and amount (base units once resolved); the first token's
+    /// total for multi-output transfers
+    fn token_amount(&self) -> Result<(String, String), String> {
+        Ok(match self {
+            Self::Shield(params) => (params.token.clone(), params.amount.clone()),
+            Self::Transfer(params) | Self::Unshield(params) => {
+                (params.token.clone(), params.amount.clone())
+            }
+            Self::TransferBatch(params) => {
+                let tx = params.batch()?.transaction();
+                (tx.token, tx.amount)
+            }
+        })
+    }
+
+    /// The operation as a proof-generator request
+    fn proof_request(&self, chain_id: u64) -> Result<ProofRequest, String> {
+        Ok(match self {
+            Self::Shield(params) => shield_request(
+                &params.token,
+                &params.amount,
+                &params.railgun_address,
+                params.shield_private_key.expose(),
+                chain_id,
+            ),
+            Self::Transfer(params) | Self::Unshield(params) => {
+                let mut request = spend_request(
+                    self.proof_type(),
+                    &params.railgun_wallet_id,
+                    params.encryption_key.expose(),
+                    params.mnemonic.expose(),
+                    &params.recipient,
+                    &params.token,
+                    &params.amount,
+                    params.broadcaster_fee.as_ref(),
+                    chain_id,
+                );
+                request.memo_text = params.memo.clone();
+                request
+            }
+            Self::TransferBatch(params) => batch_transfer_request(
+                &params.railgun_wallet_id,
+                params.encryption_key.expose(),
+                params.mnemonic.expose(),
+                &params.batch()?,
+                params.broadcaster_fee.as_ref(),
+                chain_id,
+            ),
+        })
+    }
+
+    /// Account the history entry is recorded under
+    fn account(&self) -> &str {
+        match self {
+            Self::Shield(params) => &params.railgun_address,
+            Self::Transfer(params) | Self::Unshield(params) => &params.railgun_wallet_id,
+            Self::TransferBatch(params) => &params.railgun_wallet_id,
+        }
+    }
+
+    /// Resolve the token and amount to an address and base units
+    async fn resolve(self, state: &AppState, railgun: &RailgunManager) -> Result<Self, String> {
+        let chain_id = railgun.chain_id();
+        Ok(match self {
+            Self::Shield(mut params) => {
+                check_railgun_address(&params.railgun_address, chain_id)?;
+                let (token, amount) = state
+                    .parse_token_amount(chain_id, &params.token, &params.amount)
+                    .await?;
+                let amount = match amount {
+                    AmountInput::Exact(amount) => amount,
+                    AmountInput::Max => {
+                        let account = params
+                            .account
+                            .clone()
+                            .ok_or("\"max\" requires the sending account".to_string())?;
+                        public_max_amount(chain_id, &token, &account, params.rpc_url.clone())
+                            .await?
+                    }
+                };
+                params.token = token;
+                params.amount = amount.to_string();
+                Self::Shield(params)
+            }
+            Self::Transfer(params) => {
+                check_railgun_address(&params.recipient, chain_id)?;
+                Self::Transfer(params.resolve(state, railgun).await?)
+            }
+            Self::TransferBatch(params) => {
+                Self::TransferBatch(params.resolve(state, chain_id).await?)
+            }
+            Self::Unshield(params) => {
+                if params.memo.as_deref().is_some_and(|memo| !memo.is_empty()) {
+                    return Err("Unshields carry no memo".to_string());
+                }
+                Self::Unshield(params.resolve(state, railgun).await?)
+            }
+        })
+    }
+
+    async fn prove(
+        &self,
+        railgun: &RailgunManager,
+        control: ProofControl,
+    ) -> Result<ShieldedTransaction, CepWalletError> {
+        match self {
+            Self::Shield(params) => {
+                railgun
+                    .shield(
+                        &params.token,
+                        &params.amount,
+                        &params.railgun_address,
+                        params.shield_private_key.expose(),
+                        control,
+                    )
+                    .await
+            }
+            Self::Transfer(params) => {
+                railgun
+                    .shielded_transfer(
+                        &params.railgun_wallet_id,
+                        params.encryption_key.expose(),
+                        params.mnemonic.expose(),
+                        &params.recipient,
+                        &params.token,
+                        &params.amount,
+                        params.memo.as_deref(),
+                        params.broadcaster_fee.as_ref(),
+                        control,
+                    )
+                    .await
+            }
+            Self::TransferBatch(params) => {
+                railgun
+                    .shielded_transfer_batch(
+                        &params.railgun_wallet_id,
+                        params.encryption_key.expose(),
+                        params.mnemonic.expose(),
+                        &TransferBatch::new(params.outputs.clone())?,
+                        params.broadcaster_fee.as_ref(),
+                        control,
+                    )
+                    .await
+            }
+            Self::Unshield(params) => {
+                railgun
+                    .unshield(
+                        &params.railgun_wallet_id,
+                        params.encryption_key.expose(),
+                        params.mnemonic.expose(),
+                        &params.recipient,
+                        &params.token,
+                        &params.amount,
+                        params.broadcaster_fee.as_ref(),
+                        control,
+                    )
+                    .await
+            }
+        }
+    }
+}
+
+/// Validate a proof and add it to the job queue
+///
+/// The job is persisted as queued; an id already used by another job is
+/// refused. Returns the job and the future that runs it: it waits for a
+/// queue slot, generates the proof, records it in history and persists the
+/// job's final state. Progress is emitted as `proof-progress` events.
+async fn queue_proof_job(
+    app: &AppHandle,
+    state: &AppState,
+    job_id: Option<String>,
+    request: ProofJobRequest,
+) -> Result<
+    (
+        ProofJob,
+        impl Future<Output = Result<ShieldedTransaction, String>> + Send + 'static,
+    ),
+    String,
+> {
+    // Proofs run without the privacy lock, so they don't block each other
+    let railgun = state.railgun().await?;
+    let request = request.resolve(state, &railgun).await?;
+
+    let (job, cancel) = state
+        .proof_jobs
+        .begin(job_id, request.proof_type())
+        .map_err(|e| e.to_string())?;
+    if let Err(e) = state.privacy_store.create_proof_job(&job) {
+        state.proof_jobs.remove(&job.job_id);
+        return Err(e.to_string());
+    }
+    let _ = app.emit(PROOF_PROGRESS_EVENT, &job);
+
+    let app = app.clone();
+    let job_id = job.job_id.clone();
+    let run = async move {
+        let state = app.state::<AppState>();
+        let result = run_proof_job(&app, &state, &job_id, cancel, &railgun, &request).await;
+
+        if let Some(finished) = state.proof_jobs.finish(&job_id, result.clone()) {
+            if let Err(e) = state.privacy_store.save_proof_job(&finished) {
+                tracing::warn!("Failed to persist proof job {}: {}", job_id, e);
+            }
+            let _ = app.emit(PROOF_PROGRESS_EVENT, finished);
+        }
+        state.proof_jobs.remove(&job_id);
+        result
+    };
+    Ok((job, run))
+}
+
+async fn run_proof_job(
+    app: &AppHandle,
+    state: &AppState,
+    job_id: &str,
+    mut cancel: watch::Receiver<bool>,
+    railgun: &RailgunManager,
+    request: &ProofJobRequest,
+) -> Result<ShieldedTransaction, String> {
+    let (running, _slot) = state
+        .proof_jobs
+        .acquire(job_id, &mut cancel)
+        .await
+        .map_err(|e| e.to_string())?;
+    let _ = app.emit(PROOF_PROGRESS_EVENT, &running);
+
+    let emitter = app.clone();
+    let control = state.proof_jobs.control(job_id, cancel, move |job| {
+        let _ = emitter.emit(PROOF_PROGRESS_EVENT, job);
+    });
+    let tx = request
+        .prove(railgun, control)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(state.record_shielded(railgun.chain_id(), request.account(), tx))
+}
+
 /// Shield ETH/tokens (Public → Private via RAILGUN)
+///
+/// `amount` accepts base units, `"1.5 USDC"`-style amounts or `"max"`; `"max"`
+/// needs the sending `account` to look up its public balance.
+///
+/// The proof runs through the job queue as job `job_id` (generated when
+/// omitted) and this waits for it; see `submit_proof_job`.
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn shield_transaction(
+    app: AppHandle,
     state: State<'_, AppState>,
     token: String,
     amount: String,
     railgun_address: String,
     shield_private_key: String,
+    account: Option<String>,
+    rpc_url: Option<String>,
+    job_id: Option<String>,
 ) -> Result<ShieldedTransaction, String> {
-    let privacy = state.privacy.lock().await;
-    let manager = privacy
-        .as_ref()
-        .ok_or("Privacy features not initialized".to_string())?;
+    let request = ProofJobRequest::Shield(ShieldJobParams {
+        token,
+        amount,
+        railgun_address,
+        shield_private_key: shield_private_key.into(),
+        account,
+        rpc_url,
+    });
+    let (_, run) = queue_proof_job(&app, &state, job_id, request).await?;
+    run.await
+}
 
-    manager
-        .railgun()
-        .shield(&token, &amount, &railgun_address, &shield_private_key)
+/// Result of `broadcast_shield`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShieldBroadcast {
+    /// ERC-20 approval of the RAILGUN proxy, mined before the shield
+    pub approve_tx_hash: Option<String>,
+    pub tx_hash: String,
+    /// History entry of the shield
+    pub history: HistoryEntry,
+}
+
+/// Sign a proved shield with the connected signer and broadcast it
+///
+/// `tx` is the result of `shield_transaction`, sent from the account at
+/// `path`. ERC-20 shields are preceded by an `approve` of the RAILGUN proxy
+/// when the account's allowance falls short; the approval must be mined
+/// before the shield is sent. Native token shields go through relay adapt.
+/// Both transactions are recorded in history, the shield as `Broadcast`.
+#[tauri::command]
+pub async fn broadcast_shield(
+    state: State<'_, AppState>,
+    path: String,
+    tx: ShieldedTransaction,
+    rpc_url: Option<String>,
+) -> Result<ShieldBroadcast, String> {
+    let (chain_id, contracts, provider) = railgun_network(&state, rpc_url).await?;
+
+    // The signer is only locked while signing: other commands may use it
+    // while the approval is mined
+    let (account, from, shield, approve_hash) = {
+        let signer = state.signer.lock().await;
+        let signer = signer
+            .as_deref()
+            .ok_or("Device not connected".to_string())?;
+        let (account, from) = signing_account(signer, &path).await?;
+
+        let plan = plan_shield(&provider, &contracts, chain_id, from, &tx)
+            .await
+            .map_err(|e| e.to_string())?;
+        let approve_hash = match plan.approve {
+            Some(approve) => Some(
+                sign_and_send(&provider, signer, &path, approve)
+                    .await
+                    .map_err(|e| e.to_string())?,
+            ),
+            None => None,
+        };
+        (account, from, plan.shield, approve_hash)
+    };
+
+    let approve_tx_hash = match approve_hash {
+        Some(hash) => {
+            let entry = HistoryEntry::public(chain_id, &account, HistoryStatus::Broadcast)
+                .with_recipient(Some(tx.token.clone()))
+                .with_tx_hash(Some(format!("{:?}", hash)));
+            state.record_history(&entry);
+
+            let mined = wait_for_success(&provider, hash).await;
+            record_mined(&state, &entry.id, &mined);
+            mined.map_err(|e| format!("Approval failed: {}", e))?;
+            Some(format!("{:?}", hash))
+        }
+        None => None,
+    };
+
+    let signer = state.signer.lock().await;
+    let signer = signer
+        .as_deref()
+        .ok_or("Device not connected".to_string())?;
+    // The device may have been swapped while the approval was mined
+    if approve_tx_hash.is_some() && signing_account(signer, &path).await?.1 != from {
+        return Err("Signing account changed while the approval was mined".to_string());
+    }
+    let tx_hash = sign_and_send(&provider, signer, &path, shield)
+        .await
+        .map(|hash| format!("{:?}", hash))
+        .map_err(|e| e.to_string())?;
+    let history = record_broadcast(&state, chain_id, &account, &tx, &tx_hash)?;
+
+    Ok(ShieldBroadcast {
+        approve_tx_hash,
+        tx_hash,
+        history,
+    })
+}
+
+/// Gas and fees of a proved transfer or unshield, shown before submission
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrivateBroadcastPreview {
+    /// Public account that signs and pays gas (self-signed only)
+    pub from: Option<String>,
+    /// Gas of the self-signed transaction
+    pub gas: Option<GasQuote>,
+    /// Fee the broadcaster is paid from the shielded balance
+    pub broadcaster_fee: Option<BroadcasterFee>,
+}
+
+/// Preview the submission of a proved transfer or unshield
+///
+/// Self-signed transactions (proved without a broadcaster fee) are checked
+/// and their gas is estimated for the account at `path`; for broadcaster
+/// transactions the fee bound into the proof is returned.
+#[tauri::command]
+pub async fn preview_private_broadcast(
+    state: State<'_, AppState>,
+    path: Option<String>,
+    tx: ShieldedTransaction,
+    rpc_url: Option<String>,
+) -> Result<PrivateBroadcastPreview, String> {
+    if let Some(fee) = tx.broadcaster_fee {
+        return Ok(PrivateBroadcastPreview {
+            from: None,
+            gas: None,
+            broadcaster_fee: Some(fee),
+        });
+    }
+    let path = path.ok_or("Self-signed transactions need the signing account".to_string())?;
+    let (chain_id, contracts, provider) = railgun_network(&state, rpc_url).await?;
+    let signer = state.signer.lock().await;
+    let signer = signer
+        .as_deref()
+        .ok_or("Device not connected".to_string())?;
+    let (account, from) = signing_account(signer, &path).await?;
+
+    let planned = plan_private(&contracts, chain_id, from, &tx).map_err(|e| e.to_string())?;
+    let gas = quote_gas(&provider, &planned)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(PrivateBroadcastPreview {
+        from: Some(account),
+        gas: Some(gas),
+        broadcaster_fee: None,
+    })
+}
+
+/// Result of `broadcast_private_transaction`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrivateBroadcast {
+    pub tx_hash: String,
+    /// History entry of the transaction
+    pub history: HistoryEntry,
+}
+
+/// Sign a proved transfer or unshield with the connected signer and send it
+///
+/// The account at `path` pays the gas and is visible on-chain as the
+/// sender; transactions proved for a broadcaster are refused. The
+/// transaction's history entry is marked `Broadcast`.
+#[tauri::command]
+pub async fn broadcast_private_transaction(
+    state: State<'_, AppState>,
+    path: String,
+    tx: ShieldedTransaction,
+    rpc_url: Option<String>,
+) -> Result<PrivateBroadcast, String> {
+    let (chain_id, contracts, provider) = railgun_network(&state, rpc_url).await?;
+    let signer = state.signer.lock().await;
+    let signer = signer
+        .as_deref()
+        .ok_or("Device not connected".to_string())?;
+    let (account, from) = signing_account(signer, &path).await?;
+
+    let planned = plan_private(&contracts, chain_id, from, &tx).map_err(|e| e.to_string())?;
+    let tx_hash = sign_and_send(&provider, signer, &path, planned)
+        .await
+        .map(|hash| format!("{:?}", hash))
+        .map_err(|e| e.to_string())?;
+    let history = record_broadcast(&state, chain_id, &account, &tx, &tx_hash)?;
+
+    Ok(PrivateBroadcast { tx_hash, history })
+}
+
+/// Normalize a fee token for broadcasters: native shields are held as the
+/// wrapped token, which is what broadcasters quote
+async fn broadcaster_token(state: &AppState, chain_id: u64, token: &str) -> Result<String, String> {
+    let token = state.resolve_token_address(chain_id, token).await?;
+    if token.eq_ignore_ascii_case(NATIVE_TOKEN_ADDRESS) {
+        let contracts = RailgunContracts::for_chain_id(chain_id)
+            .ok_or_else(|| format!("RAILGUN is not deployed on chain {}", chain_id))?;
+        return Ok(format!("{:?}", contracts.wrapped_native));
+    }
+    Ok(token)
+}
+
+/// Fee quotes RAILGUN broadcasters currently advertise for `token`
+#[tauri::command]
+pub async fn get_broadcaster_quotes(
+    state: State<'_, AppState>,
+    token: String,
+) -> Result<Vec<BroadcasterQuote>, String> {
+    let railgun = state.railgun().await?;
+    let token = broadcaster_token(&state, railgun.chain_id(), &token).await?;
+    railgun
+        .broadcaster()
+        .quotes(&token)
         .await
         .map_err(|e| e.to_string())
 }
 
+/// Broadcaster picked for a transfer or unshield, with the fee to prove for
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BroadcasterSelection {
+    pub quote: BroadcasterQuote,
+    /// `broadcasterFee` for `private_transfer` or `unshield_transaction`
+    pub fee: BroadcasterFee,
+    /// Gas limit the fee covers
+    pub gas_limit: String,
+}
+
+/// Pick a broadcaster by fee and reliability and price the fee in `token`
+///
+/// The fee covers `gas_limit` (default `DEFAULT_RELAYED_GAS_LIMIT`) at the
+/// current gas price, which becomes the proof's minimum gas price.
+#[tauri::command]
+pub async fn select_broadcaster(
+    state: State<'_, AppState>,
+    token: String,
+    gas_limit: Option<String>,
+    rpc_url: Option<String>,
+) -> Result<BroadcasterSelection, String> {
+    let (chain_id, _, provider) = railgun_network(&state, rpc_url).await?;
+    let railgun = state.railgun().await?;
+    let token = broadcaster_token(&state, chain_id, &token).await?;
+    let gas_limit = match gas_limit {
+        Some(limit) => {
+            U256::from_dec_str(&limit).map_err(|e| format!("Invalid gas limit: {}", e))?
+        }
+        None => U256::from(DEFAULT_RELAYED_GAS_LIMIT),
+    };
+    let gas_price = provider
+        .get_gas_price()
+        .await
+        .map_err(|e| format!("RPC error: {}", e))?;
+
+    let quote = railgun
+        .broadcaster()
+        .select(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+    let fee = quote.fee(gas_limit, gas_price).map_err(|e| e.to_string())?;
+    Ok(BroadcasterSelection {
+        quote,
+        fee,
+        gas_limit: gas_limit.to_string(),
+    })
+}
+
+/// Fees of a shield, transfer or unshield before it is proved
+///
+/// Runs the RAILGUN SDK gas estimate for exactly `request` (as queued with
+/// `submit_proof_job`) at the current EIP-1559 fees, and adds the protocol
+/// fee, the broadcaster fee if one is set and the amount the recipient
+/// receives. Shields are estimated from their sending `account`.
+#[tauri::command]
+pub async fn estimate_privacy_fee(
+    state: State<'_, AppState>,
+    request: ProofJobRequest,
+    rpc_url: Option<String>,
+) -> Result<PrivacyFeeEstimate, String> {
+    let (chain_id, _, provider) = railgun_network(&state, rpc_url).await?;
+    let railgun = state.railgun().await?;
+    let request = request.resolve(&state, &railgun).await?;
+
+    let (max_fee, priority_fee) = provider
+        .estimate_eip1559_fees(None)
+        .await
+        .map_err(|e| format!("RPC error: {}", e))?;
+    let (from, broadcaster_fee) = match &request {
+        ProofJobRequest::Shield(params) => (params.account.as_deref(), None),
+        ProofJobRequest::Transfer(params) | ProofJobRequest::Unshield(params) => {
+            (None, params.broadcaster_fee.clone())
+        }
+        ProofJobRequest::TransferBatch(params) => (None, params.broadcaster_fee.clone()),
+    };
+    let gas_limit = estimate_gas(
+        railgun.bridge(),
+        request.proof_request(chain_id)?,
+        from,
+        max_fee,
+        priority_fee,
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let (token, amount) = request.token_amount()?;
+    let amount = U256::from_dec_str(&amount).map_err(|e| format!("Invalid amount: {}", e))?;
+    Ok(PrivacyFeeEstimate::new(
+        request.tx_type(),
+        &token,
+        amount,
+        GasQuote::new(gas_limit, max_fee, priority_fee),
+        broadcaster_fee,
+    ))
+}
+
+/// Hand a transfer or unshield proved for a broadcaster over
+///
+/// The transaction is recorded as `Broadcast` under `railgun_wallet_id` and
+/// then tracked until mined, when its history entry becomes `Confirmed` or
+/// `Failed`.
+#[tauri::command]
+pub async fn relay_private_transaction(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    railgun_wallet_id: String,
+    tx: ShieldedTransaction,
+    rpc_url: Option<String>,
+) -> Result<PrivateBroadcast, String> {
+    let (chain_id, _, provider) = railgun_network(&state, rpc_url).await?;
+    let railgun = state.railgun().await?;
+
+    let hash = railgun
+        .broadcaster()
+        .submit(&tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    let tx_hash = format!("{:?}", hash);
+    let history = record_broadcast(&state, chain_id, &railgun_wallet_id, &tx, &tx_hash)?;
+
+    let entry_id = history.id.clone();
+    tauri::async_runtime::spawn(async move {
+        let mined = wait_for_success(&provider, hash).await;
+        record_mined(&app.state::<AppState>(), &entry_id, &mined);
+    });
+
+    Ok(PrivateBroadcast { tx_hash, history })
+}
+
+/// Chain, RAILGUN contracts and provider for broadcasting privacy transactions
+async fn railgun_network(
+    state: &AppState,
+    rpc_url: Option<String>,
+) -> Result<(u64, RailgunContracts, Provider<Http>), String> {
+    let chain_id = state.railgun().await?.chain_id();
+    let contracts = RailgunContracts::for_chain_id(chain_id)
+        .ok_or_else(|| format!("RAILGUN is not deployed on chain {}", chain_id))?;
+    let provider = Provider::<Http>::try_from(rpc_url_for(chain_id, rpc_url)?)
+        .map_err(|e| format!("Invalid RPC URL: {}", e))?;
+    Ok((chain_id, contracts, provider))
+}
+
+/// Address of the signer's account at `path`, as returned and parsed
+async fn signing_account(
+    signer: &dyn WalletSigner,
+    path: &str,
+) -> Result<(String, Address), String> {
+    let account = signer.get_address(path).await.map_err(|e| e.to_string())?;
+    let from = Address::from_str(&account).map_err(|e| format!("Invalid account: {}", e))?;
+    Ok((account, from))
+}
+
+/// Mark a history entry `Confirmed` or `Failed` once its transaction is mined
+fn record_mined(state: &AppState, entry_id: &str, mined: &Result<(), CepWalletError>) {
+    let (status, error) = match mined {
+        Ok(()) => (HistoryStatus::Confirmed, None),
+        Err(e) => (HistoryStatus::Failed, Some(e.to_string())),
+    };
+    if let Err(e) = state
+        .history
+        .update_status(entry_id, status, None, error.as_deref())
+    {
+        tracing::warn!("Failed to update transaction history: {}", e);
+    }
+}
+
+/// Mark a privacy transaction's history entry `Broadcast`, recording it if new
+fn record_broadcast(
+    state: &AppState,
+    chain_id: u64,
+    account: &str,
+    tx: &ShieldedTransaction,
+    tx_hash: &str,
+) -> Result<HistoryEntry, String> {
+    match &tx.history_id {
+        Some(id) => state
+            .history
+            .update_status(id, HistoryStatus::Broadcast, Some(tx_hash), None)
+            .map_err(|e| e.to_string()),
+        None => {
+            let mut entry = HistoryEntry::shielded(chain_id, account, tx)
+                .with_tx_hash(Some(tx_hash.to_string()));
+            entry.status = HistoryStatus::Broadcast;
+            state.history.record(&entry).map_err(|e| e.to_string())?;
+            Ok(entry)
+        }
+    }
+}
+
 /// Unshield ETH/tokens (Private → Public via RAILGUN)
+///
+/// Runs through the job queue like `shield_transaction`. With a
+/// `broadcaster_fee` the transaction is proved for that broadcaster to
+/// submit; without one it is for `broadcast_private_transaction`.
+/// `encryption_key` and `mnemonic` are omitted for hardware-derived wallets,
+/// whose secrets are re-derived from the connected signer.
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn unshield_transaction(
+    app: AppHandle,
     state: State<'_, AppState>,
     token: String,
     amount: String,
     recipient: String,
     railgun_wallet_id: String,
-    encryption_key: String,
-    mnemonic: String,
+    encryption_key: Option<String>,
+    mnemonic: Option<String>,
+    broadcaster_fee: Option<BroadcasterFee>,
+    job_id: Option<String>,
 ) -> Result<ShieldedTransaction, String> {
-    let privacy = state.privacy.lock().await;
-    let manager = privacy
-        .as_ref()
-        .ok_or("Privacy features not initialized".to_string())?;
-
-    manager
-        .railgun()
-        .unshield(
-            &railgun_wallet_id,
-            &encryption_key,
-            &mnemonic,
-            &recipient,
-            &token,
-            &amount,
-        )
-        .await
-        .map_err(|e| e.to_string())
+    let request = ProofJobRequest::Unshield(SpendJobParams {
+        recipient,
+        token,
+        amount,
+        railgun_wallet_id,
+        encryption_key: encryption_key.unwrap_or_default().into(),
+        mnemonic: mnemonic.unwrap_or_default().into(),
+        broadcaster_fee,
+        memo: None,
+    });
+    let (_, run) = queue_proof_job(&app, &state, job_id, request).await?;
+    run.await
 }
 
 /// Private transfer (Private → Private via RAILGUN)
+///
+/// Runs through the job queue like `shield_transaction`; `broadcaster_fee`
+/// as for `unshield_transaction`. `memo` (e.g. an invoice reference, at most
+/// `MAX_MEMO_BYTES`) is encrypted to the recipient.
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn private_transfer(
+    app: AppHandle,
     state: State<'_, AppState>,
     recipient: String,
     token: String,
     amount: String,
     railgun_wallet_id: String,
-    encryption_key: String,
-    mnemonic: String,
+    encryption_key: Option<String>,
+    mnemonic: Option<String>,
+    broadcaster_fee: Option<BroadcasterFee>,
+    memo: Option<String>,
+    job_id: Option<String>,
 ) -> Result<ShieldedTransaction, String> {
+    let request = ProofJobRequest::Transfer(SpendJobParams {
+        recipient,
+        token,
+        amount,
+        railgun_wallet_id,
+        encryption_key: encryption_key.unwrap_or_default().into(),
+        mnemonic: mnemonic.unwrap_or_default().into(),
+        broadcaster_fee,
+        memo,
+    });
+    let (_, run) = queue_proof_job(&app, &state, job_id, request).await?;
+    run.await
+}
+
+/// Private transfer to several recipients, in one or more tokens, as one
+/// transaction (e.g. a payroll run)
+///
+/// Outputs are grouped by token and proved together, as few joinsplits as
+/// the circuits allow. RAILGUN encrypts one memo per transaction, so the
+/// outputs must share theirs; outputs with different memos are rejected with
+/// the outputs of each memo listed, to be sent as separate transfers once the
+/// previous one is mined. Returns a single transaction listing every output.
+/// Runs through the job queue like `private_transfer`.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn private_transfer_batch(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    outputs: Vec<TransferOutput>,
+    railgun_wallet_id: String,
+    encryption_key: Option<String>,
+    mnemonic: Option<String>,
+    broadcaster_fee: Option<BroadcasterFee>,
+    job_id: Option<String>,
+) -> Result<ShieldedTransaction, String> {
+    let request = ProofJobRequest::TransferBatch(TransferBatchJobParams {
+        outputs,
+        railgun_wallet_id,
+        encryption_key: encryption_key.unwrap_or_default().into(),
+        mnemonic: mnemonic.unwrap_or_default().into(),
+        broadcaster_fee,
+    });
+    let (_, run) = queue_proof_job(&app, &state, job_id, request).await?;
+    run.await
+}
+
+/// Decode a 0zk address and check that it can receive on `chain_id`
+/// (default: the RAILGUN network in use)
+///
+/// Proof requests run the same check on their recipients.
+#[tauri::command]
+pub async fn validate_railgun_address(
+    state: State<'_, AppState>,
+    address: String,
+    chain_id: Option<u64>,
+) -> Result<RailgunAddressInfo, String> {
+    let chain_id = state.railgun_chain_id(chain_id).await?;
+    let decoded =
+        crate::privacy::validate_railgun_address(&address, chain_id).map_err(|e| e.to_string())?;
+    Ok(RailgunAddressInfo::new(&address, &decoded))
+}
+
+/// Request a private payment to a 0zk address on the RAILGUN network in use
+///
+/// The returned `uri` is the QR code payload. `token` and `amount` are
+/// resolved like `private_transfer`'s; `memo` is the one the payer should
+/// attach (e.g. an invoice reference).
+#[tauri::command]
+pub async fn create_payment_request(
+    state: State<'_, AppState>,
+    address: String,
+    token: String,
+    amount: String,
+    memo: Option<String>,
+) -> Result<PaymentRequestPayload, String> {
+    let chain_id = state.railgun().await?.chain_id();
+    let (token, amount) = state.parse_token_amount(chain_id, &token, &amount).await?;
+    let AmountInput::Exact(amount) = amount else {
+        return Err("A payment request needs an exact amount".to_string());
+    };
+    let request =
+        PaymentRequest::new(&address, chain_id, &token, amount, memo).map_err(|e| e.to_string())?;
+    Ok(request.into())
+}
+
+/// Read a payment request URI (e.g. a scanned QR code)
+#[tauri::command]
+pub async fn parse_payment_request(uri: String) -> Result<PaymentRequest, String> {
+    PaymentRequest::parse_uri(&uri).map_err(|e| e.to_string())
+}
+
+/// Queue a shield, transfer or unshield proof and return at once
+///
+/// The returned job (status `Queued`) carries the id for `get_proof_job`
+/// and `cancel_proof`. Progress and the final state, with the proved
+/// transaction, are emitted as `proof-progress` events.
+#[tauri::command]
+pub async fn submit_proof_job(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    request: ProofJobRequest,
+    job_id: Option<String>,
+) -> Result<ProofJob, String> {
+    let (job, run) = queue_proof_job(&app, &state, job_id, request).await?;
+    tauri::async_runtime::spawn(run);
+    Ok(job)
+}
+
+/// State of a proof job: queued, running or finished (kept across restarts)
+#[tauri::command]
+pub async fn get_proof_job(
+    state: State<'_, AppState>,
+    job_id: String,
+) -> Result<Option<ProofJob>, String> {
+    if let Some(job) = state.proof_jobs.status(&job_id) {
+        return Ok(Some(job));
+    }
+    state
+        .privacy_store
+        .proof_job(&job_id)
+        .map_err(|e| e.to_string())
+}
+
+/// Cancel a queued or running proof by job id
+///
+/// The job finishes as `Cancelled`. Returns false when no such job is
+/// queued or running.
+#[tauri::command]
+pub async fn cancel_proof(state: State<'_, AppState>, job_id: String) -> Result<bool, String> {
+    state.proof_jobs.cancel(&job_id).map_err(|e| e.to_string())
+}
+
+/// Get the spendable shielded balance of a token (from the latest scan)
+#[tauri::command]
+pub async fn get_shielded_balance(
+    state: State<'_, AppState>,
+    railgun_wallet_id: String,
+    token: String,
+) -> Result<String, String> {
     let privacy = state.privacy.lock().await;
     let manager = privacy
         .as_ref()
         .ok_or("Privacy features not initialized".to_string())?;
+    let token = state
+        .resolve_token_address(manager.railgun().chain_id(), &token)
+        .await?;
 
     manager
         .railgun()
-        .shielded_transfer(
-            &railgun_wallet_id,
-            &encryption_key,
-            &mnemonic,
-            &recipient,
-            &token,
-            &amount,
-        )
-        .await
+        .get_shielded_balance(&state.privacy_store, &railgun_wallet_id, &token)
         .map_err(|e| e.to_string())
 }
 
-/// Get shielded balance
+/// Get all shielded balances per token and note status (from the latest scan)
 #[tauri::command]
-pub async fn get_shielded_balance(
+pub async fn get_shielded_balances(
     state: State<'_, AppState>,
-    token: String,
-) -> Result<String, String> {
+    railgun_wallet_id: String,
+) -> Result<ShieldedBalances, String> {
     let privacy = state.privacy.lock().await;
     let manager = privacy
         .as_ref()
@@ -283,27 +2082,144 @@ pub async fn get_shielded_balance(
 
     manager
         .railgun()
-        .get_shielded_balance(&token)
-        .await
+        .get_shielded_balances(&state.privacy_store, &railgun_wallet_id)
         .map_err(|e| e.to_string())
 }
 
-/// Scan merkletree for wallet balance updates
+/// Scan merkletree, decrypt the wallet's notes and store its balances
+///
+/// Notes received from other wallets are added to the transaction history
+/// as `Receive` entries, with their memo text. `encryption_key` is omitted
+/// for hardware-derived wallets.
 #[tauri::command]
 pub async fn scan_merkletree(
     state: State<'_, AppState>,
     railgun_wallet_id: String,
-) -> Result<(), String> {
+    encryption_key: Option<String>,
+) -> Result<ShieldedBalances, String> {
+    let (encryption_key, _) = wallet_secrets(
+        &state,
+        &railgun_wallet_id,
+        encryption_key.unwrap_or_default().into(),
+        SecretString::default(),
+        false,
+    )
+    .await?;
     let privacy = state.privacy.lock().await;
     let manager = privacy
         .as_ref()
         .ok_or("Privacy features not initialized".to_string())?;
+    if state.sync.is_running(manager.railgun().chain_id()) {
+        return Err("A merkletree sync is already running on this chain".to_string());
+    }
 
-    manager
+    let balances = manager
         .railgun()
-        .scan_merkletree(&railgun_wallet_id)
+        .scan_merkletree(
+            &state.privacy_store,
+            &state.history,
+            &railgun_wallet_id,
+            encryption_key.expose(),
+        )
         .await
-        .map_err(|e| e.to_string())
+        .map_err(|e| e.to_string())?;
+    state
+        .portfolio
+        .lock()
+        .await
+        .invalidate_chain(balances.chain_id);
+
+    Ok(balances)
+}
+
+/// Start an incremental merkletree sync in the background
+///
+/// Resumes from the last synced block of the chain. Progress is emitted as
+/// `merkletree-sync-progress` events; the final event has status
+/// `Completed`, `Cancelled` or `Failed`. `encryption_key` as for
+/// `scan_merkletree`.
+#[tauri::command]
+pub async fn start_merkletree_sync(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    railgun_wallet_id: String,
+    encryption_key: Option<String>,
+) -> Result<SyncProgress, String> {
+    let (encryption_key, _) = wallet_secrets(
+        &state,
+        &railgun_wallet_id,
+        encryption_key.unwrap_or_default().into(),
+        SecretString::default(),
+        false,
+    )
+    .await?;
+    let chain_id = {
+        let privacy = state.privacy.lock().await;
+        let manager = privacy
+            .as_ref()
+            .filter(|manager| manager.is_ready())
+            .ok_or("Privacy features not initialized".to_string())?;
+        manager.railgun().chain_id()
+    };
+
+    let start_block = state
+        .privacy_store
+        .last_synced_block(chain_id)
+        .map_err(|e| e.to_string())?;
+    let started = SyncProgress::started(chain_id, &railgun_wallet_id, start_block);
+    let cancel = state
+        .sync
+        .begin(started.clone())
+        .map_err(|e| e.to_string())?;
+    let _ = app.emit(SYNC_PROGRESS_EVENT, started.clone());
+
+    tauri::async_runtime::spawn(async move {
+        let state = app.state::<AppState>();
+        let result = crate::privacy::sync::sync_merkletree(
+            state.bridge.as_ref(),
+            &state.privacy_store,
+            &state.history,
+            chain_id,
+            &railgun_wallet_id,
+            encryption_key.expose(),
+            cancel,
+            |progress| {
+                state.sync.update(progress);
+                let _ = app.emit(SYNC_PROGRESS_EVENT, progress.clone());
+            },
+        )
+        .await;
+
+        if result.is_ok() {
+            state.portfolio.lock().await.invalidate_chain(chain_id);
+        }
+        let result = result.map(|_| ()).map_err(|e| e.to_string());
+        if let Some(finished) = state.sync.finish(chain_id, result) {
+            let _ = app.emit(SYNC_PROGRESS_EVENT, finished);
+        }
+    });
+
+    Ok(started)
+}
+
+/// Cancel the running merkletree sync on a chain
+///
+/// Returns false when no sync is running.
+#[tauri::command]
+pub async fn cancel_merkletree_sync(
+    state: State<'_, AppState>,
+    chain_id: u64,
+) -> Result<bool, String> {
+    state.sync.cancel(chain_id).map_err(|e| e.to_string())
+}
+
+/// Progress of the current or last merkletree sync on a chain
+#[tauri::command]
+pub async fn get_merkletree_sync_status(
+    state: State<'_, AppState>,
+    chain_id: u64,
+) -> Result<Option<SyncProgress>, String> {
+    Ok(state.sync.status(chain_id))
 }
 
 /// Join a privacy pool
@@ -362,50 +2278,87 @@ pub async fn privacy_pool_swap(
         .map_err(|e| e.to_string())
 }
 
-/// Generate ZK proof
+/// Generate a Groth16 `proof_type` proof natively for a circuit variant
+/// (e.g. `"01x02"`)
+///
+/// `inputs` is circom input JSON; the result is a proof envelope (snarkjs
+/// proof, public signals and metadata). Only non-RAILGUN proof types are
+/// proven natively; RAILGUN proofs go through `submit_proof_job`. Runs
+/// without holding the privacy lock.
 #[tauri::command]
 pub async fn generate_zk_proof(
     state: State<'_, AppState>,
     proof_type: String,
-    public_inputs: Vec<String>,
-    private_inputs: Vec<String>,
-) -> Result<Vec<u8>, String> {
-    let privacy = state.privacy.lock().await;
-    let manager = privacy
-        .as_ref()
-        .ok_or("Privacy features not initialized".to_string())?;
-
-    // Parse proof type
-    use crate::privacy::ProofType;
-    let ptype = match proof_type.as_str() {
-        "shield" => ProofType::Shield,
-        "transfer" => ProofType::Transfer,
-        "unshield" => ProofType::Unshield,
-        "pool_membership" => ProofType::PoolMembership,
-        "compliance" => ProofType::Compliance,
-        _ => return Err(format!("Invalid proof type: {}", proof_type)),
+    circuit: String,
+    inputs: serde_json::Value,
+) -> Result<ProofEnvelope, String> {
+    let proof_type: ProofType = proof_type
+        .parse()
+        .map_err(|e: CepWalletError| e.to_string())?;
+    proof_type.check_native().map_err(|e| e.to_string())?;
+    let name = circuit;
+    let circuit = {
+        let privacy = state.privacy.lock().await;
+        let generator = privacy
+            .as_ref()
+            .ok_or("Privacy features not initialized".to_string())?
+            .zk_generator();
+        if !generator.is_ready() {
+            return Err("ZK proof generator not initialized".to_string());
+        }
+        generator.circuit(&name).map_err(|e| e.to_string())?
     };
 
-    manager
-        .zk_generator()
-        .generate_proof(ptype, public_inputs, private_inputs)
+    let proof = tokio::task::spawn_blocking(move || circuit.prove(&inputs))
         .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())?;
+    Ok(ProofEnvelope::new(proof_type, &name, proof))
+}
+
+/// Report which pinned circuits have their artifacts in place
+///
+/// Files are only hashed when a circuit is first used; a circuit whose
+/// files failed the check is reported as `corrupt` from then on.
+#[tauri::command]
+pub fn get_circuit_availability(state: State<'_, AppState>) -> Vec<CircuitAvailability> {
+    state.artifacts.availability()
+}
+
+/// Verify a Groth16 proof envelope
+///
+/// Checked against the circuit's bundled (or downloaded) verification key;
+/// public signals are decimal or `0x` hex field elements. Errors when no
+/// key is available for the circuit.
+#[tauri::command]
+pub async fn verify_zk_proof(
+    state: State<'_, AppState>,
+    proof: ProofEnvelope,
+) -> Result<bool, String> {
+    state
+        .verification_keys
+        .verify(&proof)
         .map_err(|e| e.to_string())
 }
 
+/// Export a proof envelope as snarkjs-compatible JSON or `0x` hex binary
+#[tauri::command]
+pub fn export_zk_proof(proof: ProofEnvelope, encoding: ProofEncoding) -> Result<String, String> {
+    proof.export(encoding).map_err(|e| e.to_string())
+}
+
+/// Import a proof envelope exported in either encoding
+#[tauri::command]
+pub fn import_zk_proof(data: String) -> Result<ProofEnvelope, String> {
+    ProofEnvelope::import(&data).map_err(|e| e.to_string())
+}
+
 /// Get proof generation time estimate
 #[tauri::command]
 pub fn estimate_proof_time(proof_type: String) -> Result<u64, String> {
-    use crate::privacy::ProofType;
-
-    let ptype = match proof_type.as_str() {
-        "shield" => ProofType::Shield,
-        "transfer" => ProofType::Transfer,
-        "unshield" => ProofType::Unshield,
-        "pool_membership" => ProofType::PoolMembership,
-        "compliance" => ProofType::Compliance,
-        _ => return Err(format!("Invalid proof type: {}", proof_type)),
-    };
+    let ptype: ProofType = proof_type
+        .parse()
+        .map_err(|e: CepWalletError| e.to_string())?;
 
     // This would come from ZKProofGenerator, but we'll hardcode for now
     let time = match ptype {
@@ -423,38 +2376,90 @@ pub fn estimate_proof_time(proof_type: String) -> Result<u64, String> {
 // RAILGUN WALLET MANAGEMENT COMMANDS
 // ============================================================================
 
-/// Create a new RAILGUN wallet
+/// Create a new RAILGUN wallet on `chain_id` (default: the RAILGUN network
+/// in use)
+///
+/// With `use_hardware`, the encryption key and spending seed are derived from the
+/// connected signer and `encryption_key`/`mnemonic` must be omitted. The wallet
+/// is registered as hardware-derived, so later proofs and scans re-derive its
+/// secrets from the signer instead of taking them from the frontend. Signers
+/// without CipherKeyValue, which currently includes Trezor, are refused.
 #[tauri::command]
 pub async fn create_railgun_wallet(
-    encryption_key: String,
+    state: State<'_, AppState>,
+    encryption_key: Option<String>,
     mnemonic: Option<String>,
+    use_hardware: Option<bool>,
+    account_index: Option<u32>,
+    chain_id: Option<u64>,
 ) -> Result<serde_json::Value, String> {
     use crate::privacy::wallet;
 
     tracing::info!("Creating RAILGUN wallet");
-    tracing::debug!(
-        "Encryption key: {}...",
-        &encryption_key[..10.min(encryption_key.len())]
-    );
-
-    let response = wallet::create_railgun_wallet(&encryption_key, mnemonic.as_deref())
+    let chain_id = state.railgun_chain_id(chain_id).await?;
+
+    let response = if use_hardware.unwrap_or(false) {
+        if encryption_key.is_some() || mnemonic.is_some() {
+            return Err(
+                "Hardware-derived wallets do not accept an encryption key or mnemonic".to_string(),
+            );
+        }
+
+        let signer = state.signer.lock().await;
+        let manager = signer.as_ref().ok_or("Device not connected".to_string())?;
+        if !manager.supports_cipher_key_value() {
+            return Err(
+                "Hardware-derived RAILGUN wallets are not available for this device".to_string(),
+            );
+        }
+        let account = account_index.unwrap_or(0);
+
+        let response = wallet::create_hardware_railgun_wallet(
+            state.bridge.as_ref(),
+            chain_id,
+            manager.as_ref(),
+            account,
+        )
+        .await;
+        if let Ok(response) = &response {
+            state
+                .privacy_store
+                .save_hardware_wallet(&response.railgun_wallet_id, account)
+                .map_err(|e| e.to_string())?;
+        }
+        response
+    } else {
+        let encryption_key = encryption_key.ok_or("Encryption key is required".to_string())?;
+
+        wallet::create_railgun_wallet(
+            state.bridge.as_ref(),
+            chain_id,
+            &encryption_key,
+            mnemonic.as_deref(),
+        )
         .await
-        .map_err(|e| {
-            tracing::error!("Wallet creation failed: {}", e);
-            e.to_string()
-        })?;
+    }
+    .map_err(|e| {
+        tracing::error!("Wallet creation failed: {}", e);
+        e.to_string()
+    })?;
 
     Ok(serde_json::json!({
         "success": true,
         "railgunWalletId": response.railgun_wallet_id,
         "railgunAddress": response.railgun_address,
-        "mnemonic": response.mnemonic,
+        "mnemonic": response.mnemonic.expose(),
     }))
 }
 
-/// Get shield private key for a RAILGUN wallet
+/// Get shield private key for a RAILGUN wallet on `chain_id` (default: the
+/// RAILGUN network in use)
 #[tauri::command]
-pub async fn get_shield_key(railgun_wallet_id: String) -> Result<serde_json::Value, String> {
+pub async fn get_shield_key(
+    state: State<'_, AppState>,
+    railgun_wallet_id: String,
+    chain_id: Option<u64>,
+) -> Result<serde_json::Value, String> {
     use crate::privacy::wallet;
 
     tracing::info!(
@@ -462,12 +2467,14 @@ pub async fn get_shield_key(railgun_wallet_id: String) -> Result<serde_json::Val
         railgun_wallet_id
     );
 
-    let response = wallet::get_shield_private_key(&railgun_wallet_id)
-        .await
-        .map_err(|e| e.to_string())?;
+    let chain_id = state.railgun_chain_id(chain_id).await?;
+    let response =
+        wallet::get_shield_private_key(state.bridge.as_ref(), chain_id, &railgun_wallet_id)
+            .await
+            .map_err(|e| e.to_string())?;
 
     Ok(serde_json::json!({
         "success": true,
-        "shieldPrivateKey": response.shield_private_key,
+        "shieldPrivateKey": response.shield_private_key.expose(),
     }))
 }
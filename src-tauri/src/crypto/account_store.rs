@@ -0,0 +1,291 @@
+// Encrypted local store for software accounts
+//
+// Account metadata is kept in clear so accounts can be listed without a password;
+// each private key is sealed with AES-256-GCM under a scrypt-derived key from the
+// store password, with a fresh salt and nonce per entry. The store key and
+// unsealed private keys are zeroized on drop.
+
+use super::keystore::{address_for_key, scrypt};
+use crate::error::{CepWalletError, Result};
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Nonce};
+use ethers::core::rand::{thread_rng, RngCore};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use zeroize::Zeroizing;
+
+/// Store file name inside the app data directory
+pub const ACCOUNT_STORE_FILE: &str = "accounts.json";
+
+const STORE_VERSION: u32 = 1;
+
+/// scrypt parameters for the store key (n = 2^15, r = 8, p = 1)
+const STORE_SCRYPT_N: u32 = 1 << 15;
+const STORE_SCRYPT_R: u32 = 8;
+const STORE_SCRYPT_P: u32 = 1;
+
+/// Public metadata of a software account
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SoftwareAccount {
+    pub address: String,
+    pub name: String,
+    /// Creation time (unix seconds)
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SealedAccount {
+    #[serde(flatten)]
+    account: SoftwareAccount,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct StoreFile {
+    version: u32,
+    accounts: Vec<SealedAccount>,
+}
+
+impl Default for StoreFile {
+    fn default() -> Self {
+        Self {
+            version: STORE_VERSION,
+            accounts: Vec::new(),
+        }
+    }
+}
+
+/// Encrypted software account store
+pub struct AccountStore {
+    path: PathBuf,
+    scrypt_n: u32,
+}
+
+fn store_error(message: impl Into<String>) -> CepWalletError {
+    CepWalletError::KeystoreError(message.into())
+}
+
+impl AccountStore {
+    /// Open the store in a data directory (the file is created on first import)
+    pub fn open<P: AsRef<Path>>(data_dir: P) -> Self {
+        Self {
+            path: data_dir.as_ref().join(ACCOUNT_STORE_FILE),
+            scrypt_n: STORE_SCRYPT_N,
+        }
+    }
+
+    #[cfg(test)]
+    fn with_scrypt_n(mut self, n: u32) -> Self {
+        self.scrypt_n = n;
+        self
+    }
+
+    fn load(&self) -> Result<StoreFile> {
+        if !self.path.exists() {
+            return Ok(StoreFile::default());
+        }
+
+        let data = std::fs::read_to_string(&self.path)
+            .map_err(|e| store_error(format!("Failed to read account store: {}", e)))?;
+        let store: StoreFile = serde_json::from_str(&data)
+            .map_err(|e| store_error(format!("Corrupt account store: {}", e)))?;
+
+        if store.version != STORE_VERSION {
+            return Err(store_error(format!(
+                "Unsupported account store version: {}",
+                store.version
+            )));
+        }
+        Ok(store)
+    }
+
+    fn save(&self, store: &StoreFile) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| store_error(format!("Failed to create data directory: {}", e)))?;
+        }
+
+        let data = serde_json::to_string_pretty(store)
+            .map_err(|e| store_error(format!("Failed to serialize account store: {}", e)))?;
+
+        // Write to a temp file first so a crash never leaves a truncated store
+        let tmp_path = self.path.with_extension("json.tmp");
+        std::fs::write(&tmp_path, data)
+            .map_err(|e| store_error(format!("Failed to write account store: {}", e)))?;
+        std::fs::rename(&tmp_path, &self.path)
+            .map_err(|e| store_error(format!("Failed to write account store: {}", e)))
+    }
+
+    fn cipher(&self, password: &str, salt: &[u8]) -> Result<Aes256Gcm> {
+        let mut key = Zeroizing::new([0u8; 32]);
+        scrypt(
+            password.as_bytes(),
+            salt,
+            self.scrypt_n,
+            STORE_SCRYPT_R,
+            STORE_SCRYPT_P,
+            key.as_mut(),
+        )?;
+
+        Aes256Gcm::new_from_slice(key.as_ref()).map_err(|_| store_error("Invalid store key"))
+    }
+
+    /// List stored software accounts
+    pub fn list_accounts(&self) -> Result<Vec<SoftwareAccount>> {
+        Ok(self
+            .load()?
+            .accounts
+            .into_iter()
+            .map(|sealed| sealed.account)
+            .collect())
+    }
+
+    /// Seal and store a raw private key
+    pub fn import_private_key(
+        &self,
+        name: &str,
+        private_key: &[u8],
+        store_password: &str,
+    ) -> Result<SoftwareAccount> {
+        if store_password.is_empty() {
+            return Err(CepWalletError::InvalidInput(
+                "Store password must not be empty".to_string(),
+            ));
+        }
+
+        let address = address_for_key(private_key)?;
+        let mut store = self.load()?;
+
+        if store
+            .accounts
+            .iter()
+            .any(|sealed| sealed.account.address.eq_ignore_ascii_case(&address))
+        {
+            return Err(store_error(format!("Account {} already exists", address)));
+        }
+
+        let mut rng = thread_rng();
+        let mut salt = [0u8; 32];
+        let mut nonce = [0u8; 12];
+        rng.fill_bytes(&mut salt);
+        rng.fill_bytes(&mut nonce);
+
+        let ciphertext = self
+            .cipher(store_password, &salt)?
+            .encrypt(&Nonce::from(nonce), private_key)
+            .map_err(|_| store_error("Failed to encrypt private key"))?;
+
+        let account = SoftwareAccount {
+            address,
+            name: name.to_string(),
+            created_at: chrono::Utc::now().timestamp(),
+        };
+
+        store.accounts.push(SealedAccount {
+            account: account.clone(),
+            salt: hex::encode(salt),
+            nonce: hex::encode(nonce),
+            ciphertext: hex::encode(ciphertext),
+        });
+        self.save(&store)?;
+
+        Ok(account)
+    }
+
+    /// Unseal the private key of a stored account
+    pub fn private_key(&self, address: &str, store_password: &str) -> Result<Zeroizing<Vec<u8>>> {
+        let store = self.load()?;
+        let sealed = store
+            .accounts
+            .iter()
+            .find(|sealed| sealed.account.address.eq_ignore_ascii_case(address))
+            .ok_or_else(|| store_error(format!("Unknown account: {}", address)))?;
+
+        let decode = |field: &str, value: &str| {
+            hex::decode(value).map_err(|_| store_error(format!("Corrupt account {}", field)))
+        };
+        let salt = decode("salt", &sealed.salt)?;
+        let nonce: [u8; 12] = decode("nonce", &sealed.nonce)?
+            .try_into()
+            .map_err(|_| store_error("Corrupt account nonce"))?;
+        let ciphertext = decode("ciphertext", &sealed.ciphertext)?;
+
+        self.cipher(store_password, &salt)?
+            .decrypt(&Nonce::from(nonce), ciphertext.as_ref())
+            .map(Zeroizing::new)
+            .map_err(|_| store_error("Wrong store password"))
+    }
+
+    /// Remove an account from the store
+    pub fn remove_account(&self, address: &str) -> Result<()> {
+        let mut store = self.load()?;
+        let before = store.accounts.len();
+        store
+            .accounts
+            .retain(|sealed| !sealed.account.address.eq_ignore_ascii_case(address));
+
+        if store.accounts.len() == before {
+            return Err(store_error(format!("Unknown account: {}", address)));
+        }
+        self.save(&store)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PRIVATE_KEY: &str = "7a28b5ba57c53603b0b07b56bba752f7784bf506fa95edc395f5cf6c7514fe9d";
+
+    fn test_store(dir: &Path) -> AccountStore {
+        AccountStore::open(dir).with_scrypt_n(16)
+    }
+
+    #[test]
+    fn test_import_and_unseal() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = test_store(dir.path());
+        let key = hex::decode(PRIVATE_KEY).unwrap();
+
+        let account = store
+            .import_private_key("imported", &key, "store-pw")
+            .unwrap();
+        assert_eq!(account.address, address_for_key(&key).unwrap());
+        assert_eq!(store.list_accounts().unwrap(), vec![account.clone()]);
+
+        let unsealed = store.private_key(&account.address, "store-pw").unwrap();
+        assert_eq!(*unsealed, key);
+
+        assert!(store.private_key(&account.address, "wrong").is_err());
+    }
+
+    #[test]
+    fn test_store_does_not_contain_plaintext_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = test_store(dir.path());
+        let key = hex::decode(PRIVATE_KEY).unwrap();
+
+        store
+            .import_private_key("imported", &key, "store-pw")
+            .unwrap();
+
+        let contents = std::fs::read_to_string(dir.path().join(ACCOUNT_STORE_FILE)).unwrap();
+        assert!(!contents.contains(PRIVATE_KEY));
+    }
+
+    #[test]
+    fn test_duplicate_and_remove() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = test_store(dir.path());
+        let key = hex::decode(PRIVATE_KEY).unwrap();
+
+        let account = store.import_private_key("a", &key, "pw").unwrap();
+        assert!(store.import_private_key("b", &key, "pw").is_err());
+
+        store.remove_account(&account.address).unwrap();
+        assert!(store.list_accounts().unwrap().is_empty());
+        assert!(store.remove_account(&account.address).is_err());
+    }
+}
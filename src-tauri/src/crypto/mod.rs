@@ -1,5 +1,10 @@
+pub mod account_store;
+pub mod keystore;
+pub mod secret;
 pub mod wallet;
 
+pub use secret::SecretString;
+
 // TODO: Add more crypto modules as needed
 // pub mod signing;
 // pub mod keys;
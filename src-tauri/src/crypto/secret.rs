@@ -0,0 +1,61 @@
+/// Secret strings (encryption keys, mnemonics, shield private keys)
+///
+/// `Debug` never prints the value, so secrets can sit inside request and
+/// response structs that are logged. The value is only reachable through
+/// `expose`, and serializes as the plain string for the proof generator.
+use serde::{Deserialize, Serialize};
+
+/// A string holding secret material
+#[derive(Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SecretString(String);
+
+impl SecretString {
+    pub fn new(value: impl Into<String>) -> Self {
+        Self(value.into())
+    }
+
+    /// The secret value; keep it out of logs and error messages
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl From<String> for SecretString {
+    fn from(value: String) -> Self {
+        Self(value)
+    }
+}
+
+impl From<&str> for SecretString {
+    fn from(value: &str) -> Self {
+        Self(value.to_string())
+    }
+}
+
+impl std::fmt::Debug for SecretString {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("<redacted>")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_debug_is_redacted() {
+        let secret = SecretString::from("abandon abandon about");
+        assert_eq!(format!("{:?}", secret), "<redacted>");
+        assert_eq!(format!("{:?}", Some(secret.clone())), "Some(<redacted>)");
+        assert_eq!(
+            serde_json::to_string(&secret).unwrap(),
+            "\"abandon abandon about\""
+        );
+        assert_eq!(secret.expose(), "abandon abandon about");
+    }
+}
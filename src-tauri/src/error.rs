@@ -45,6 +45,15 @@ pub enum CepWalletError {
 
     #[error("Pool error: {0}")]
     PoolError(String),
+
+    #[error("Keystore error: {0}")]
+    KeystoreError(String),
+
+    #[error("History error: {0}")]
+    HistoryError(String),
+
+    #[error("Token error: {0}")]
+    TokenError(String),
 }
 
 pub type Result<T> = std::result::Result<T, CepWalletError>;
@@ -74,3 +83,15 @@ impl From<anyhow::Error> for CepWalletError {
         CepWalletError::PrivacyError(err.to_string())
     }
 }
+
+// Convert proof generator bridge failures
+impl From<crate::privacy::bridge::BridgeError> for CepWalletError {
+    fn from(err: crate::privacy::bridge::BridgeError) -> Self {
+        use crate::privacy::bridge::BridgeError;
+        match err {
+            BridgeError::Unavailable(_) => CepWalletError::NotInitialized(err.to_string()),
+            BridgeError::InvalidParams(message) => CepWalletError::InvalidInput(message),
+            other => CepWalletError::PrivacyError(other.to_string()),
+        }
+    }
+}
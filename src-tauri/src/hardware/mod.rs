@@ -1,8 +1,15 @@
 pub mod device;
+pub mod signer;
+#[cfg(feature = "software-signer")]
+pub mod software;
 pub mod trezor;
 
 // Re-export commonly used types
 pub use device::{detect_devices, is_trezor_device};
+pub use signer::{encode_signed_transaction, WalletSigner};
+#[cfg(feature = "software-signer")]
+pub use software::SoftwareSigner;
 pub use trezor::{
-    DerivationPath, PublicKeyResponse, SignatureResponse, TrezorDevice as DeviceInfo, TrezorManager,
+    parse_bip32_path, DerivationPath, PublicKeyResponse, SignatureResponse,
+    TrezorDevice as DeviceInfo, TrezorManager, HARDENED_OFFSET,
 };
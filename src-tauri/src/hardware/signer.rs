@@ -0,0 +1,273 @@
+// Signer abstraction - common signing surface for hardware and software signers
+
+use super::trezor::{PublicKeyResponse, SignatureResponse, TrezorDevice, TrezorManager};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{Bytes, Signature, U256};
+
+/// Signing surface shared by the Trezor manager and the software signer
+#[async_trait]
+pub trait WalletSigner: Send + Sync {
+    /// Signer bilgisini al
+    async fn get_device_info(&self) -> Result<TrezorDevice>;
+
+    /// Bağlı mı kontrol et
+    fn is_connected(&self) -> bool;
+
+    /// Bağlantıyı kes
+    async fn disconnect(&self) -> Result<()>;
+
+    /// Public key al (HD wallet derivation)
+    async fn get_public_key(&self, path: &str) -> Result<PublicKeyResponse>;
+
+    /// Ethereum address al
+    async fn get_address(&self, path: &str) -> Result<String> {
+        Ok(self.get_public_key(path).await?.address)
+    }
+
+    /// Çoklu address al (m/44'/60'/0'/0/x)
+    async fn get_addresses(&self, start_index: u32, count: u32) -> Result<Vec<String>> {
+        let mut addresses = Vec::new();
+
+        for i in start_index..(start_index + count) {
+            let path = format!("m/44'/60'/0'/0/{}", i);
+            addresses.push(self.get_address(&path).await?);
+        }
+
+        Ok(addresses)
+    }
+
+    /// Transaction imzala
+    async fn sign_transaction(
+        &self,
+        path: &str,
+        tx: &TypedTransaction,
+    ) -> Result<SignatureResponse>;
+
+    /// Mesajı imzala (EIP-191)
+    async fn sign_message(&self, path: &str, message: &str) -> Result<String>;
+
+    /// EIP-712 typed data imzala
+    async fn sign_typed_data(&self, path: &str, data: serde_json::Value) -> Result<String>;
+
+    /// CipherKeyValue cihazda hesaplanabiliyor mu
+    fn supports_cipher_key_value(&self) -> bool {
+        true
+    }
+
+    /// SLIP-0011 CipherKeyValue
+    async fn cipher_key_value(
+        &self,
+        path: &str,
+        key: &str,
+        value: &[u8],
+        encrypt: bool,
+        ask_on_encrypt: bool,
+        ask_on_decrypt: bool,
+    ) -> Result<Vec<u8>>;
+}
+
+#[async_trait]
+impl WalletSigner for TrezorManager {
+    async fn get_device_info(&self) -> Result<TrezorDevice> {
+        TrezorManager::get_device_info(self).await
+    }
+
+    fn is_connected(&self) -> bool {
+        TrezorManager::is_connected(self)
+    }
+
+    async fn disconnect(&self) -> Result<()> {
+        TrezorManager::disconnect(self).await
+    }
+
+    async fn get_public_key(&self, path: &str) -> Result<PublicKeyResponse> {
+        TrezorManager::get_public_key(self, path).await
+    }
+
+    async fn get_addresses(&self, start_index: u32, count: u32) -> Result<Vec<String>> {
+        TrezorManager::get_addresses(self, start_index, count).await
+    }
+
+    async fn sign_transaction(
+        &self,
+        path: &str,
+        tx: &TypedTransaction,
+    ) -> Result<SignatureResponse> {
+        TrezorManager::sign_transaction(self, path, tx.rlp().to_vec()).await
+    }
+
+    async fn sign_message(&self, path: &str, message: &str) -> Result<String> {
+        TrezorManager::sign_message(self, path, message).await
+    }
+
+    async fn sign_typed_data(&self, path: &str, data: serde_json::Value) -> Result<String> {
+        TrezorManager::sign_typed_data(self, path, data).await
+    }
+
+    fn supports_cipher_key_value(&self) -> bool {
+        // USB transport olmadan CipherKeyValue gönderilemez
+        false
+    }
+
+    async fn cipher_key_value(
+        &self,
+        path: &str,
+        key: &str,
+        value: &[u8],
+        encrypt: bool,
+        ask_on_encrypt: bool,
+        ask_on_decrypt: bool,
+    ) -> Result<Vec<u8>> {
+        TrezorManager::cipher_key_value(
+            self,
+            path,
+            key,
+            value,
+            encrypt,
+            ask_on_encrypt,
+            ask_on_decrypt,
+        )
+        .await
+    }
+}
+
+/// Signed raw transaction (RLP) from a signer's signature over `tx`
+///
+/// The signer's `v` is not trusted: a recovery id of 0/1 cannot be told
+/// apart from an EIP-155 value truncated to a byte. The y-parity is the one
+/// whose signature recovers to `tx.from`, and `v` is recomputed from it and
+/// the transaction's chain id for its type. A signature by another key
+/// recovers to neither and is never broadcast.
+pub fn encode_signed_transaction(
+    tx: &TypedTransaction,
+    signature: &SignatureResponse,
+) -> Result<Bytes> {
+    let word = |value: &str| {
+        U256::from_str_radix(value.trim_start_matches("0x"), 16)
+            .map_err(|e| anyhow!("Invalid signature: {}", e))
+    };
+    let (r, s) = (word(&signature.r)?, word(&signature.s)?);
+    let chain_id = tx
+        .chain_id()
+        .ok_or_else(|| anyhow!("Transaction has no chain id"))?
+        .as_u64();
+    let from = tx
+        .from()
+        .ok_or_else(|| anyhow!("Transaction has no sender"))?;
+
+    let sighash = tx.sighash();
+    let mut recovered = Vec::new();
+    for y_parity in [0u64, 1] {
+        // Recover with the plain 27/28 form, then encode for the tx type
+        let candidate = Signature {
+            r,
+            s,
+            v: 27 + y_parity,
+        };
+        match candidate.recover(sighash) {
+            Ok(signer) if signer == *from => {
+                let v = match tx {
+                    TypedTransaction::Legacy(_) => chain_id
+                        .checked_mul(2)
+                        .and_then(|v| v.checked_add(35 + y_parity))
+                        .ok_or_else(|| anyhow!("Chain id {} is too large", chain_id))?,
+                    _ => y_parity,
+                };
+                return Ok(tx.rlp_signed(&Signature { r, s, v }));
+            }
+            Ok(signer) => recovered.push(signer),
+            Err(e) => return Err(anyhow!("Invalid signature: {}", e)),
+        }
+    }
+    Err(anyhow!(
+        "Signature recovers to {:?}, not the sending account {:?}",
+        recovered,
+        from
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::signers::{LocalWallet, Signer};
+    use ethers::types::{Address, TransactionRequest};
+
+    #[test]
+    fn test_legacy_v_for_large_chain_id() {
+        let wallet = LocalWallet::from_bytes(&[0x42; 32]).unwrap();
+        let chain_id = 11155111u64;
+        let tx: TypedTransaction = TransactionRequest::new()
+            .from(wallet.address())
+            .to(Address::repeat_byte(0x11))
+            .value(1u64)
+            .gas(21_000)
+            .gas_price(1_000_000_000u64)
+            .nonce(0)
+            .chain_id(chain_id)
+            .into();
+        let signature = wallet.sign_hash(tx.sighash()).unwrap();
+        let parity = signature.v - 27;
+        let response = |v: u8| SignatureResponse {
+            signature: hex::encode(signature.to_vec()),
+            v,
+            r: format!("{:x}", signature.r),
+            s: format!("{:x}", signature.s),
+        };
+
+        // Recovery id, 27/28, EIP-155 `v` truncated to a byte, and a 0/1
+        // that is really a truncated EIP-155 value of the other parity: the
+        // parity comes from recovery, not from `v`
+        let truncated = (chain_id * 2 + 35 + parity) as u8;
+        for v in [parity as u8, signature.v as u8, truncated, 1 - parity as u8] {
+            let raw = encode_signed_transaction(&tx, &response(v)).unwrap();
+            let (_, signed) =
+                TypedTransaction::decode_signed(&ethers::utils::rlp::Rlp::new(&raw)).unwrap();
+            assert_eq!(signed.v, chain_id * 2 + 35 + parity);
+            assert_eq!(signed.recover(tx.sighash()).unwrap(), wallet.address());
+        }
+
+        // A signature by another key is refused
+        let other = LocalWallet::from_bytes(&[0x43; 32]).unwrap();
+        let other = other.sign_hash(tx.sighash()).unwrap();
+        let forged = SignatureResponse {
+            signature: hex::encode(other.to_vec()),
+            v: other.v as u8,
+            r: format!("{:x}", other.r),
+            s: format!("{:x}", other.s),
+        };
+        let err = encode_signed_transaction(&tx, &forged).unwrap_err();
+        assert!(
+            err.to_string().contains("not the sending account"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_typed_transaction_parity() {
+        let wallet = LocalWallet::from_bytes(&[0x42; 32]).unwrap();
+        let tx: TypedTransaction = ethers::types::Eip1559TransactionRequest::new()
+            .from(wallet.address())
+            .to(Address::repeat_byte(0x11))
+            .value(1u64)
+            .gas(21_000)
+            .nonce(0)
+            .chain_id(1u64)
+            .into();
+        let signature = wallet.sign_hash(tx.sighash()).unwrap();
+        let response = SignatureResponse {
+            signature: hex::encode(signature.to_vec()),
+            v: 0,
+            r: format!("{:x}", signature.r),
+            s: format!("{:x}", signature.s),
+        };
+
+        let raw = encode_signed_transaction(&tx, &response).unwrap();
+        let (_, signed) =
+            TypedTransaction::decode_signed(&ethers::utils::rlp::Rlp::new(&raw)).unwrap();
+        assert_eq!(signed.v, signature.v - 27);
+        assert_eq!(signed.recover(tx.sighash()).unwrap(), wallet.address());
+    }
+}
@@ -0,0 +1,389 @@
+// Software signer - development/testing signer without a hardware device
+//
+// Implements the same `WalletSigner` surface as `TrezorManager` with keys held in
+// memory: either a BIP-39 mnemonic (BIP-32 derivation per path) or a single raw
+// key loaded from a V3 JSON keystore. Only compiled with the `software-signer` feature.
+
+use super::signer::WalletSigner;
+use super::trezor::{parse_bip32_path, PublicKeyResponse, SignatureResponse, TrezorDevice};
+use crate::crypto::account_store::AccountStore;
+use crate::crypto::keystore::decrypt_keystore;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use coins_bip32::primitives::XKeyInfo;
+use ethers::core::k256::ecdsa::SigningKey;
+use ethers::signers::coins_bip39::{English, Mnemonic};
+use ethers::signers::LocalWallet;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::transaction::eip712::{Eip712, TypedData};
+use ethers::types::{Signature, H256};
+use ethers::utils::{hash_message, to_checksum};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Key source for the software signer
+enum KeySource {
+    /// BIP-39 mnemonic with optional passphrase, derived per path
+    Mnemonic {
+        mnemonic: Mnemonic<English>,
+        password: Option<String>,
+    },
+    /// Single raw key (path is ignored)
+    RawKey(LocalWallet),
+}
+
+/// Software signer
+pub struct SoftwareSigner {
+    source: KeySource,
+    connected: AtomicBool,
+}
+
+impl SoftwareSigner {
+    /// Create from a BIP-39 mnemonic phrase
+    pub fn from_mnemonic(phrase: &str, password: Option<&str>) -> Result<Self> {
+        let mnemonic = Mnemonic::<English>::new_from_phrase(phrase.trim())
+            .map_err(|e| anyhow!("Invalid mnemonic: {}", e))?;
+
+        Ok(Self {
+            source: KeySource::Mnemonic {
+                mnemonic,
+                password: password.map(str::to_string),
+            },
+            connected: AtomicBool::new(true),
+        })
+    }
+
+    /// Create from a raw secp256k1 private key
+    pub fn from_private_key(key: &[u8]) -> Result<Self> {
+        let wallet =
+            LocalWallet::from_bytes(key).map_err(|e| anyhow!("Invalid private key: {}", e))?;
+
+        Ok(Self {
+            source: KeySource::RawKey(wallet),
+            connected: AtomicBool::new(true),
+        })
+    }
+
+    /// Load a raw key from an encrypted V3 JSON keystore file
+    pub fn from_keystore_file<P: AsRef<Path>>(path: P, password: &str) -> Result<Self> {
+        let json =
+            std::fs::read_to_string(path).map_err(|e| anyhow!("Failed to read keystore: {}", e))?;
+        let key = decrypt_keystore(&json, password)
+            .map_err(|e| anyhow!("Failed to decrypt keystore: {}", e))?;
+
+        Self::from_private_key(&key)
+    }
+
+    /// Load a software account from the encrypted local account store
+    pub fn from_account_store(
+        store: &AccountStore,
+        address: &str,
+        store_password: &str,
+    ) -> Result<Self> {
+        let key = store
+            .private_key(address, store_password)
+            .map_err(|e| anyhow!("{}", e))?;
+
+        Self::from_private_key(&key)
+    }
+
+    /// Signing key and chain code for a derivation path
+    fn key_for_path(&self, path: &str) -> Result<(SigningKey, Option<[u8; 32]>)> {
+        match &self.source {
+            KeySource::Mnemonic { mnemonic, password } => {
+                parse_bip32_path(path)?;
+                let xpriv = mnemonic
+                    .derive_key(path, password.as_deref())
+                    .map_err(|e| anyhow!("Key derivation failed: {}", e))?;
+                let key: &SigningKey = xpriv.as_ref();
+                let info: &XKeyInfo = xpriv.as_ref();
+                Ok((key.clone(), Some(info.chain_code.0)))
+            }
+            KeySource::RawKey(wallet) => Ok((wallet.signer().clone(), None)),
+        }
+    }
+
+    /// Local wallet for a derivation path
+    fn wallet_for_path(&self, path: &str) -> Result<LocalWallet> {
+        if !self.is_connected() {
+            return Err(anyhow!("Signer not connected"));
+        }
+        let (key, _) = self.key_for_path(path)?;
+        Ok(LocalWallet::from(key))
+    }
+}
+
+/// Convert an ethers signature (v = 27/28) to the common response format
+fn signature_response(signature: &Signature) -> SignatureResponse {
+    let mut r = [0u8; 32];
+    let mut s = [0u8; 32];
+    signature.r.to_big_endian(&mut r);
+    signature.s.to_big_endian(&mut s);
+
+    SignatureResponse {
+        signature: hex::encode(signature.to_vec()),
+        v: signature.v as u8,
+        r: hex::encode(r),
+        s: hex::encode(s),
+    }
+}
+
+#[async_trait]
+impl WalletSigner for SoftwareSigner {
+    async fn get_device_info(&self) -> Result<TrezorDevice> {
+        if !self.is_connected() {
+            return Err(anyhow!("Signer not connected"));
+        }
+
+        let kind = match self.source {
+            KeySource::Mnemonic { .. } => "mnemonic",
+            KeySource::RawKey(_) => "keystore",
+        };
+
+        Ok(TrezorDevice {
+            model: "Software Signer".to_string(),
+            firmware_version: env!("CARGO_PKG_VERSION").to_string(),
+            serial_number: format!("SOFTWARE-{}", kind.to_uppercase()),
+            connected: true,
+            path: format!("software://{}", kind),
+        })
+    }
+
+    fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::SeqCst)
+    }
+
+    async fn disconnect(&self) -> Result<()> {
+        if self.connected.swap(false, Ordering::SeqCst) {
+            Ok(())
+        } else {
+            Err(anyhow!("No device connected"))
+        }
+    }
+
+    async fn get_public_key(&self, path: &str) -> Result<PublicKeyResponse> {
+        if !self.is_connected() {
+            return Err(anyhow!("Signer not connected"));
+        }
+
+        let (key, chain_code) = self.key_for_path(path)?;
+        let public_key = key.verifying_key().to_encoded_point(true);
+        let address = ethers::utils::secret_key_to_address(&key);
+
+        Ok(PublicKeyResponse {
+            public_key: hex::encode(public_key.as_bytes()),
+            address: to_checksum(&address, None),
+            chain_code: chain_code.map(hex::encode).unwrap_or_default(),
+            path: path.to_string(),
+        })
+    }
+
+    async fn sign_transaction(
+        &self,
+        path: &str,
+        tx: &TypedTransaction,
+    ) -> Result<SignatureResponse> {
+        let wallet = self.wallet_for_path(path)?;
+        let signature = wallet
+            .sign_hash(tx.sighash())
+            .map_err(|e| anyhow!("Transaction signing failed: {}", e))?;
+
+        Ok(signature_response(&signature))
+    }
+
+    async fn sign_message(&self, path: &str, message: &str) -> Result<String> {
+        let wallet = self.wallet_for_path(path)?;
+        let signature = wallet
+            .sign_hash(hash_message(message))
+            .map_err(|e| anyhow!("Message signing failed: {}", e))?;
+
+        Ok(hex::encode(signature.to_vec()))
+    }
+
+    async fn sign_typed_data(&self, path: &str, data: serde_json::Value) -> Result<String> {
+        let wallet = self.wallet_for_path(path)?;
+        let typed_data: TypedData =
+            serde_json::from_value(data).map_err(|e| anyhow!("Invalid typed data: {}", e))?;
+        let hash = typed_data
+            .encode_eip712()
+            .map_err(|e| anyhow!("EIP-712 encoding failed: {}", e))?;
+        let signature = wallet
+            .sign_hash(H256::from(hash))
+            .map_err(|e| anyhow!("Typed data signing failed: {}", e))?;
+
+        Ok(hex::encode(signature.to_vec()))
+    }
+
+    async fn cipher_key_value(
+        &self,
+        path: &str,
+        key: &str,
+        value: &[u8],
+        encrypt: bool,
+        ask_on_encrypt: bool,
+        ask_on_decrypt: bool,
+    ) -> Result<Vec<u8>> {
+        if value.is_empty() || value.len() % 16 != 0 {
+            return Err(anyhow!(
+                "CipherKeyValue value length must be a multiple of 16 bytes"
+            ));
+        }
+
+        // Emulates SLIP-0011 with a hash keyed by the derived private key
+        let wallet = self.wallet_for_path(path)?;
+        let secret = wallet.signer().to_bytes();
+
+        let mut output = Vec::with_capacity(value.len());
+        let mut counter: u32 = 0;
+        while output.len() < value.len() {
+            let mut hasher = Sha256::new();
+            hasher.update(secret);
+            hasher.update(key.as_bytes());
+            hasher.update(value);
+            hasher.update([encrypt as u8, ask_on_encrypt as u8, ask_on_decrypt as u8]);
+            hasher.update(counter.to_be_bytes());
+            output.extend_from_slice(&hasher.finalize());
+            counter += 1;
+        }
+        output.truncate(value.len());
+
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::signers::Signer;
+    use ethers::types::{Address, TransactionRequest};
+    use std::str::FromStr;
+
+    const TEST_MNEMONIC: &str = "test test test test test test test test test test test junk";
+    const TEST_ADDRESS: &str = "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266";
+    const TEST_PATH: &str = "m/44'/60'/0'/0/0";
+
+    #[tokio::test]
+    async fn test_mnemonic_derivation() {
+        let signer = SoftwareSigner::from_mnemonic(TEST_MNEMONIC, None).unwrap();
+
+        let address = signer.get_address(TEST_PATH).await.unwrap();
+        assert_eq!(address, TEST_ADDRESS);
+
+        let addresses = signer.get_addresses(0, 2).await.unwrap();
+        assert_eq!(addresses[0], TEST_ADDRESS);
+        assert_eq!(addresses[1], "0x70997970C51812dc3A010C7d01b50e0d17dc79C8");
+
+        let pubkey = signer.get_public_key(TEST_PATH).await.unwrap();
+        assert_eq!(pubkey.public_key.len(), 66);
+        assert_eq!(pubkey.chain_code.len(), 64);
+    }
+
+    #[tokio::test]
+    async fn test_sign_message_recovers_address() {
+        let signer = SoftwareSigner::from_mnemonic(TEST_MNEMONIC, None).unwrap();
+
+        let signature = signer.sign_message(TEST_PATH, "hello").await.unwrap();
+        let signature = Signature::from_str(&signature).unwrap();
+
+        signature
+            .verify("hello", Address::from_str(TEST_ADDRESS).unwrap())
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_sign_transaction_recovers_address() {
+        let signer = SoftwareSigner::from_mnemonic(TEST_MNEMONIC, None).unwrap();
+
+        let tx: TypedTransaction = TransactionRequest::new()
+            .to(Address::from_str("0x70997970C51812dc3A010C7d01b50e0d17dc79C8").unwrap())
+            .value(1_000_000_000_000_000_000u64)
+            .gas(21_000)
+            .gas_price(1_000_000_000u64)
+            .nonce(0)
+            .chain_id(11155111)
+            .into();
+
+        let response = signer.sign_transaction(TEST_PATH, &tx).await.unwrap();
+        let signature = Signature::from_str(&response.signature).unwrap();
+
+        assert_eq!(
+            signature.recover(tx.sighash()).unwrap(),
+            Address::from_str(TEST_ADDRESS).unwrap()
+        );
+        assert!(response.v == 27 || response.v == 28);
+    }
+
+    #[tokio::test]
+    async fn test_encode_signed_transaction() {
+        use crate::hardware::encode_signed_transaction;
+        use ethers::types::Eip1559TransactionRequest;
+
+        let signer = SoftwareSigner::from_mnemonic(TEST_MNEMONIC, None).unwrap();
+        let from = Address::from_str(TEST_ADDRESS).unwrap();
+        let to = Address::from_str("0x70997970C51812dc3A010C7d01b50e0d17dc79C8").unwrap();
+
+        let legacy: TypedTransaction = TransactionRequest::new()
+            .from(from)
+            .to(to)
+            .value(1u64)
+            .gas(21_000)
+            .gas_price(1_000_000_000u64)
+            .nonce(0)
+            .chain_id(11155111)
+            .into();
+        let eip1559: TypedTransaction = Eip1559TransactionRequest::new()
+            .from(from)
+            .to(to)
+            .value(1u64)
+            .gas(21_000)
+            .max_fee_per_gas(2_000_000_000u64)
+            .max_priority_fee_per_gas(1_000_000_000u64)
+            .nonce(1)
+            .chain_id(11155111)
+            .into();
+
+        for tx in [legacy, eip1559] {
+            let response = signer.sign_transaction(TEST_PATH, &tx).await.unwrap();
+            let raw = encode_signed_transaction(&tx, &response).unwrap();
+            let (decoded, signature) =
+                TypedTransaction::decode_signed(&ethers::utils::rlp::Rlp::new(&raw)).unwrap();
+            assert_eq!(decoded.sighash(), tx.sighash());
+            assert_eq!(signature.recover(tx.sighash()).unwrap(), from);
+
+            // A signature by another key is refused
+            let mut other = tx.clone();
+            other.set_from(to);
+            assert!(encode_signed_transaction(&other, &response).is_err());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_keystore_signer() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut rng = ethers::core::rand::thread_rng();
+        let (wallet, name) =
+            LocalWallet::new_keystore(dir.path(), &mut rng, "password", None).unwrap();
+
+        assert!(SoftwareSigner::from_keystore_file(dir.path().join(&name), "wrong").is_err());
+
+        let signer =
+            SoftwareSigner::from_keystore_file(dir.path().join(&name), "password").unwrap();
+        let address = signer.get_address(TEST_PATH).await.unwrap();
+        assert_eq!(address, to_checksum(&wallet.address(), None));
+
+        let signature = signer.sign_message(TEST_PATH, "hello").await.unwrap();
+        let signature = Signature::from_str(&signature).unwrap();
+        signature.verify("hello", wallet.address()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_disconnect() {
+        let signer = SoftwareSigner::from_mnemonic(TEST_MNEMONIC, None).unwrap();
+        assert!(signer.is_connected());
+
+        signer.disconnect().await.unwrap();
+        assert!(!signer.is_connected());
+        assert!(signer.sign_message(TEST_PATH, "hello").await.is_err());
+    }
+}
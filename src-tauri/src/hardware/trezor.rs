@@ -66,6 +66,38 @@ impl DerivationPath {
     }
 }
 
+/// BIP-32 hardened index offset
+pub const HARDENED_OFFSET: u32 = 0x8000_0000;
+
+/// BIP-32 path'ini `address_n` listesine çevir (hardened indeksler `'` ile işaretlenir)
+pub fn parse_bip32_path(path: &str) -> Result<Vec<u32>> {
+    let trimmed = path.trim();
+    if !trimmed.starts_with("m/") {
+        return Err(anyhow!("Derivation path must start with m/"));
+    }
+
+    trimmed[2..]
+        .split('/')
+        .map(|part| {
+            let (index, hardened) = match part.strip_suffix('\'') {
+                Some(index) => (index, true),
+                None => (part, false),
+            };
+            let index: u32 = index
+                .parse()
+                .map_err(|_| anyhow!("Invalid path component: {}", part))?;
+            if index >= HARDENED_OFFSET {
+                return Err(anyhow!("Path component out of range: {}", part));
+            }
+            Ok(if hardened {
+                index | HARDENED_OFFSET
+            } else {
+                index
+            })
+        })
+        .collect()
+}
+
 /// Public key ve address bilgisi
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PublicKeyResponse {
@@ -263,6 +295,39 @@ impl TrezorManager {
         // TODO: Gerçek EIP-712 signing
         Ok(hex::encode(vec![0x74, 0x79, 0x70, 0x65, 0x64]))
     }
+
+    /// SLIP-0011 CipherKeyValue - cihazdan deterministik anahtar materyali türet
+    ///
+    /// Aynı cihaz, path, key ve value için her zaman aynı çıktıyı üretir.
+    /// `value` uzunluğu 16 byte'ın katı olmalıdır.
+    ///
+    /// Çıktı yalnızca cihazdaki anahtarla hesaplanabilmelidir; USB transport
+    /// henüz olmadığından host tarafında taklit edilmez, hata döner.
+    pub async fn cipher_key_value(
+        &self,
+        path: &str,
+        _key: &str,
+        value: &[u8],
+        _encrypt: bool,
+        _ask_on_encrypt: bool,
+        _ask_on_decrypt: bool,
+    ) -> Result<Vec<u8>> {
+        if !self.is_connected() {
+            return Err(anyhow!("Device not connected"));
+        }
+
+        if value.is_empty() || value.len() % 16 != 0 {
+            return Err(anyhow!(
+                "CipherKeyValue value length must be a multiple of 16 bytes"
+            ));
+        }
+        parse_bip32_path(path)?;
+
+        // TODO: Gerçek CipherKeyValue mesajı (cihaz transport'u ile)
+        Err(anyhow!(
+            "CipherKeyValue is not supported by this Trezor connection yet"
+        ))
+    }
 }
 
 #[cfg(test)]
@@ -282,6 +347,50 @@ mod tests {
         assert_eq!(path.coin_type, 60);
     }
 
+    #[test]
+    fn test_bip32_path_parsing() {
+        let path = parse_bip32_path("m/10016'/1984'/0'").unwrap();
+        assert_eq!(
+            path,
+            vec![
+                10016 | HARDENED_OFFSET,
+                1984 | HARDENED_OFFSET,
+                HARDENED_OFFSET
+            ]
+        );
+
+        let path = parse_bip32_path("m/44'/60'/0'/0/5").unwrap();
+        assert_eq!(path[4], 5);
+
+        assert!(parse_bip32_path("44'/60'").is_err());
+        assert!(parse_bip32_path("m/abc").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_cipher_key_value_is_not_emulated() {
+        let manager = TrezorManager::new(5000).unwrap();
+        let value = [0u8; 32];
+        assert!(manager
+            .cipher_key_value("m/10016'/0'", "label", &value, true, true, true)
+            .await
+            .is_err());
+
+        // Never a host-computable stand-in for the device's output
+        manager.connect().await.unwrap();
+        let err = manager
+            .cipher_key_value("m/10016'/0'", "label", &value, true, true, true)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("not supported"));
+
+        assert!(manager
+            .cipher_key_value("m/10016'/0'", "label", &[0u8; 20], true, true, true)
+            .await
+            .unwrap_err()
+            .to_string()
+            .contains("multiple of 16"));
+    }
+
     #[tokio::test]
     async fn test_manager_creation() {
         let manager = TrezorManager::new(5000).unwrap();
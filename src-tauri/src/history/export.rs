@@ -0,0 +1,123 @@
+/// History export (CSV / JSON) for accounting
+use super::HistoryEntry;
+use crate::error::{CepWalletError, Result};
+use serde::{Deserialize, Serialize};
+
+/// Export format
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+const CSV_HEADER: [&str; 13] = [
+    "id",
+    "date",
+    "chain_id",
+    "account",
+    "type",
+    "status",
+    "token",
+    "amount",
+    "recipient",
+    "tx_hash",
+    "error",
+    "updated",
+    "memo",
+];
+
+/// Quote a CSV field when needed (RFC 4180)
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// RFC 3339 timestamp (UTC) for a unix time
+fn format_timestamp(timestamp: i64) -> String {
+    chrono::DateTime::from_timestamp(timestamp, 0)
+        .map(|date| date.to_rfc3339())
+        .unwrap_or_else(|| timestamp.to_string())
+}
+
+fn to_csv(entries: &[HistoryEntry]) -> String {
+    let mut output = CSV_HEADER.join(",");
+    output.push_str("\r\n");
+
+    for entry in entries {
+        let fields = [
+            entry.id.clone(),
+            format_timestamp(entry.created_at),
+            entry.chain_id.to_string(),
+            entry.account.clone(),
+            entry.tx_type.as_str().to_string(),
+            entry.status.as_str().to_string(),
+            entry.token.clone().unwrap_or_default(),
+            entry.amount.clone().unwrap_or_default(),
+            entry.recipient.clone().unwrap_or_default(),
+            entry.tx_hash.clone().unwrap_or_default(),
+            entry.error.clone().unwrap_or_default(),
+            format_timestamp(entry.updated_at),
+            entry
+                .shielded
+                .as_ref()
+                .and_then(|tx| tx.memo.clone())
+                .unwrap_or_default(),
+        ];
+
+        let row: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+        output.push_str(&row.join(","));
+        output.push_str("\r\n");
+    }
+
+    output
+}
+
+/// Export entries in the given format
+pub fn export_entries(entries: &[HistoryEntry], format: ExportFormat) -> Result<String> {
+    match format {
+        ExportFormat::Csv => Ok(to_csv(entries)),
+        ExportFormat::Json => serde_json::to_string_pretty(entries)
+            .map_err(|e| CepWalletError::HistoryError(e.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::{HistoryStatus, HistoryTxType};
+
+    fn sample_entry() -> HistoryEntry {
+        let mut entry = HistoryEntry::public(1, "0xabc", HistoryStatus::Failed)
+            .with_value(Some("0x0".to_string()), Some("42".to_string()));
+        entry.error = Some("execution reverted: \"nope\", sorry".to_string());
+        entry.created_at = 1_700_000_000;
+        entry.updated_at = 1_700_000_060;
+        entry
+    }
+
+    #[test]
+    fn test_csv_export() {
+        let csv = export_entries(&[sample_entry()], ExportFormat::Csv).unwrap();
+        let lines: Vec<&str> = csv.split("\r\n").collect();
+
+        assert_eq!(lines[0], CSV_HEADER.join(","));
+        assert!(lines[1].contains(",2023-11-14T22:13:20+00:00,1,0xabc,Public,Failed,0x0,42,"));
+        assert!(lines[1].contains("\"execution reverted: \"\"nope\"\", sorry\""));
+    }
+
+    #[test]
+    fn test_json_export_roundtrip() {
+        let entry = sample_entry();
+        let json = export_entries(std::slice::from_ref(&entry), ExportFormat::Json).unwrap();
+        let parsed: Vec<HistoryEntry> = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].id, entry.id);
+        assert_eq!(parsed[0].tx_type, HistoryTxType::Public);
+        assert_eq!(parsed[0].error, entry.error);
+    }
+}
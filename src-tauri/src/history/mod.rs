@@ -0,0 +1,799 @@
+/// Transaction History - persistent record of public and shielded activity
+///
+/// Every signed, broadcast and privacy operation is stored in a local SQLite
+/// database (`history.db` in the app data directory) so the wallet can show
+/// history across restarts and export it for accounting.
+///
+/// Architecture:
+/// - mod.rs: Entry types, filters and the SQLite store
+/// - export.rs: CSV/JSON export
+pub mod export;
+
+pub use export::{export_entries, ExportFormat};
+
+use crate::error::{CepWalletError, Result};
+use crate::privacy::types::{ReceivedNote, ShieldedTransaction, ShieldedTxType};
+use rusqlite::types::ToSql;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Mutex;
+
+/// Database file name inside the app data directory
+pub const HISTORY_DB_FILE: &str = "history.db";
+
+/// Default page size for history queries
+pub const DEFAULT_PAGE_SIZE: u32 = 50;
+
+/// Maximum page size for history queries
+pub const MAX_PAGE_SIZE: u32 = 500;
+
+const SCHEMA_VERSION: i32 = 1;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS transactions (
+    id          TEXT PRIMARY KEY,
+    chain_id    INTEGER NOT NULL,
+    account     TEXT NOT NULL,
+    tx_type     TEXT NOT NULL,
+    status      TEXT NOT NULL,
+    token       TEXT,
+    amount      TEXT,
+    recipient   TEXT,
+    tx_hash     TEXT,
+    error       TEXT,
+    shielded    TEXT,
+    created_at  INTEGER NOT NULL,
+    updated_at  INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_transactions_account ON transactions (account, created_at);
+CREATE INDEX IF NOT EXISTS idx_transactions_hash ON transactions (tx_hash);
+";
+
+/// Kind of recorded operation
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum HistoryTxType {
+    /// Public Ethereum transaction
+    Public,
+    /// Shield: Public -> Private
+    Shield,
+    /// Transfer: Private -> Private
+    Transfer,
+    /// Unshield: Private -> Public
+    Unshield,
+    /// Note received from another RAILGUN wallet
+    Receive,
+}
+
+impl HistoryTxType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HistoryTxType::Public => "Public",
+            HistoryTxType::Shield => "Shield",
+            HistoryTxType::Transfer => "Transfer",
+            HistoryTxType::Unshield => "Unshield",
+            HistoryTxType::Receive => "Receive",
+        }
+    }
+
+    fn parse(value: &str) -> Result<Self> {
+        match value {
+            "Public" => Ok(HistoryTxType::Public),
+            "Shield" => Ok(HistoryTxType::Shield),
+            "Transfer" => Ok(HistoryTxType::Transfer),
+            "Unshield" => Ok(HistoryTxType::Unshield),
+            "Receive" => Ok(HistoryTxType::Receive),
+            other => Err(history_error(format!(
+                "Unknown transaction type: {}",
+                other
+            ))),
+        }
+    }
+}
+
+impl From<&ShieldedTxType> for HistoryTxType {
+    fn from(tx_type: &ShieldedTxType) -> Self {
+        match tx_type {
+            ShieldedTxType::Shield => HistoryTxType::Shield,
+            ShieldedTxType::Transfer => HistoryTxType::Transfer,
+            ShieldedTxType::Unshield => HistoryTxType::Unshield,
+            ShieldedTxType::Receive => HistoryTxType::Receive,
+        }
+    }
+}
+
+/// Lifecycle status of a recorded operation
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum HistoryStatus {
+    /// Proof generated / transaction built, not yet signed
+    Created,
+    /// Signed by the device
+    Signed,
+    /// Broadcast to the network, waiting for confirmation
+    Broadcast,
+    /// Confirmed on-chain
+    Confirmed,
+    /// Failed (see `error`)
+    Failed,
+}
+
+impl HistoryStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HistoryStatus::Created => "Created",
+            HistoryStatus::Signed => "Signed",
+            HistoryStatus::Broadcast => "Broadcast",
+            HistoryStatus::Confirmed => "Confirmed",
+            HistoryStatus::Failed => "Failed",
+        }
+    }
+
+    fn parse(value: &str) -> Result<Self> {
+        match value {
+            "Created" => Ok(HistoryStatus::Created),
+            "Signed" => Ok(HistoryStatus::Signed),
+            "Broadcast" => Ok(HistoryStatus::Broadcast),
+            "Confirmed" => Ok(HistoryStatus::Confirmed),
+            "Failed" => Ok(HistoryStatus::Failed),
+            other => Err(history_error(format!("Unknown status: {}", other))),
+        }
+    }
+}
+
+/// Recorded transaction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// Local entry id (UUID)
+    pub id: String,
+    pub chain_id: u64,
+    /// Public address for public transactions, RAILGUN wallet id or 0zk address for privacy operations
+    pub account: String,
+    pub tx_type: HistoryTxType,
+    pub status: HistoryStatus,
+    /// Token address (0x0 for ETH)
+    pub token: Option<String>,
+    /// Amount in wei (as string to avoid precision loss)
+    pub amount: Option<String>,
+    pub recipient: Option<String>,
+    pub tx_hash: Option<String>,
+    pub error: Option<String>,
+    /// Full shielded transaction for privacy operations
+    pub shielded: Option<ShieldedTransaction>,
+    /// Creation time (unix seconds)
+    pub created_at: i64,
+    /// Last status change (unix seconds)
+    pub updated_at: i64,
+}
+
+impl HistoryEntry {
+    /// New entry for a public transaction
+    pub fn public(chain_id: u64, account: &str, status: HistoryStatus) -> Self {
+        let now = chrono::Utc::now().timestamp();
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            chain_id,
+            account: account.to_string(),
+            tx_type: HistoryTxType::Public,
+            status,
+            token: None,
+            amount: None,
+            recipient: None,
+            tx_hash: None,
+            error: None,
+            shielded: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// New entry for a privacy operation
+    pub fn shielded(chain_id: u64, account: &str, tx: &ShieldedTransaction) -> Self {
+        let mut entry = Self::public(chain_id, account, HistoryStatus::Created);
+        entry.tx_type = HistoryTxType::from(&tx.tx_type);
+        entry.token = Some(tx.token.clone());
+        entry.amount = Some(tx.amount.clone());
+        entry.recipient = tx.recipient.clone();
+        entry.shielded = Some(tx.clone());
+        entry
+    }
+
+    /// Entry for a note received by a RAILGUN wallet, confirmed at the
+    /// block time; `index` tells apart notes of the same transaction
+    ///
+    /// The id is derived from the note, so recording it again is a no-op.
+    pub fn received(chain_id: u64, account: &str, note: &ReceivedNote, index: usize) -> Self {
+        let tx = note.transaction();
+        let mut entry = Self::shielded(chain_id, account, &tx);
+        entry.id = format!(
+            "receive:{}:{}:{}:{}",
+            chain_id,
+            account.to_lowercase(),
+            note.txid.to_lowercase(),
+            index
+        );
+        entry.status = HistoryStatus::Confirmed;
+        entry.tx_hash = Some(note.txid.clone());
+        if let Some(timestamp) = note.timestamp {
+            entry.created_at = timestamp;
+            entry.updated_at = timestamp;
+        }
+        entry
+    }
+
+    /// Set the token and amount
+    pub fn with_value(mut self, token: Option<String>, amount: Option<String>) -> Self {
+        self.token = token;
+        self.amount = amount;
+        self
+    }
+
+    /// Set the recipient
+    pub fn with_recipient(mut self, recipient: Option<String>) -> Self {
+        self.recipient = recipient;
+        self
+    }
+
+    /// Set the transaction hash
+    pub fn with_tx_hash(mut self, tx_hash: Option<String>) -> Self {
+        self.tx_hash = tx_hash;
+        self
+    }
+}
+
+/// History query filter (all fields optional)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HistoryFilter {
+    /// Account (case-insensitive)
+    pub account: Option<String>,
+    pub chain_id: Option<u64>,
+    /// Token address (case-insensitive)
+    pub token: Option<String>,
+    /// Transaction types (empty = all)
+    pub tx_types: Vec<HistoryTxType>,
+    pub status: Option<HistoryStatus>,
+    /// Created at or after (unix seconds)
+    pub from: Option<i64>,
+    /// Created at or before (unix seconds)
+    pub to: Option<i64>,
+}
+
+/// One page of history results
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryPage {
+    pub entries: Vec<HistoryEntry>,
+    /// Total number of matching entries
+    pub total: u64,
+    pub offset: u32,
+    pub limit: u32,
+}
+
+fn history_error(message: impl Into<String>) -> CepWalletError {
+    CepWalletError::HistoryError(message.into())
+}
+
+fn sql_error(e: rusqlite::Error) -> CepWalletError {
+    history_error(e.to_string())
+}
+
+const SELECT_COLUMNS: &str = "id, chain_id, account, tx_type, status, token, amount, recipient, \
+     tx_hash, error, shielded, created_at, updated_at";
+
+/// Raw row before enum/JSON decoding
+struct RawEntry {
+    id: String,
+    chain_id: i64,
+    account: String,
+    tx_type: String,
+    status: String,
+    token: Option<String>,
+    amount: Option<String>,
+    recipient: Option<String>,
+    tx_hash: Option<String>,
+    error: Option<String>,
+    shielded: Option<String>,
+    created_at: i64,
+    updated_at: i64,
+}
+
+impl RawEntry {
+    fn from_row(row: &Row<'_>) -> rusqlite::Result<Self> {
+        Ok(Self {
+            id: row.get(0)?,
+            chain_id: row.get(1)?,
+            account: row.get(2)?,
+            tx_type: row.get(3)?,
+            status: row.get(4)?,
+            token: row.get(5)?,
+            amount: row.get(6)?,
+            recipient: row.get(7)?,
+            tx_hash: row.get(8)?,
+            error: row.get(9)?,
+            shielded: row.get(10)?,
+            created_at: row.get(11)?,
+            updated_at: row.get(12)?,
+        })
+    }
+
+    fn decode(self) -> Result<HistoryEntry> {
+        let shielded = self
+            .shielded
+            .map(|json| serde_json::from_str(&json))
+            .transpose()
+            .map_err(|e| history_error(format!("Corrupt shielded transaction: {}", e)))?;
+
+        Ok(HistoryEntry {
+            id: self.id,
+            chain_id: self.chain_id as u64,
+            account: self.account,
+            tx_type: HistoryTxType::parse(&self.tx_type)?,
+            status: HistoryStatus::parse(&self.status)?,
+            token: self.token,
+            amount: self.amount,
+            recipient: self.recipient,
+            tx_hash: self.tx_hash,
+            error: self.error,
+            shielded,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        })
+    }
+}
+
+/// Build the WHERE clause and parameters for a filter
+fn filter_clause(filter: &HistoryFilter) -> (String, Vec<Box<dyn ToSql>>) {
+    let mut conditions: Vec<String> = Vec::new();
+    let mut values: Vec<Box<dyn ToSql>> = Vec::new();
+
+    if let Some(account) = &filter.account {
+        conditions.push("lower(account) = lower(?)".to_string());
+        values.push(Box::new(account.clone()));
+    }
+    if let Some(chain_id) = filter.chain_id {
+        conditions.push("chain_id = ?".to_string());
+        values.push(Box::new(chain_id as i64));
+    }
+    if let Some(token) = &filter.token {
+        conditions.push("lower(token) = lower(?)".to_string());
+        values.push(Box::new(token.clone()));
+    }
+    if !filter.tx_types.is_empty() {
+        let placeholders = vec!["?"; filter.tx_types.len()].join(", ");
+        conditions.push(format!("tx_type IN ({})", placeholders));
+        for tx_type in &filter.tx_types {
+            values.push(Box::new(tx_type.as_str()));
+        }
+    }
+    if let Some(status) = filter.status {
+        conditions.push("status = ?".to_string());
+        values.push(Box::new(status.as_str()));
+    }
+    if let Some(from) = filter.from {
+        conditions.push("created_at >= ?".to_string());
+        values.push(Box::new(from));
+    }
+    if let Some(to) = filter.to {
+        conditions.push("created_at <= ?".to_string());
+        values.push(Box::new(to));
+    }
+
+    let clause = if conditions.is_empty() {
+        String::new()
+    } else {
+        format!(" WHERE {}", conditions.join(" AND "))
+    };
+    (clause, values)
+}
+
+/// SQLite-backed transaction history
+pub struct HistoryStore {
+    conn: Mutex<Connection>,
+}
+
+impl HistoryStore {
+    /// Open (or create) the history database in a data directory
+    pub fn open<P: AsRef<Path>>(data_dir: P) -> Result<Self> {
+        let conn = Connection::open(data_dir.as_ref().join(HISTORY_DB_FILE)).map_err(sql_error)?;
+        Self::with_connection(conn)
+    }
+
+    /// In-memory store (tests)
+    pub fn in_memory() -> Result<Self> {
+        Self::with_connection(Connection::open_in_memory().map_err(sql_error)?)
+    }
+
+    fn with_connection(conn: Connection) -> Result<Self> {
+        let version: i32 = conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .map_err(sql_error)?;
+        if version > SCHEMA_VERSION {
+            return Err(history_error(format!(
+                "History database version {} is newer than supported ({})",
+                version, SCHEMA_VERSION
+            )));
+        }
+
+        conn.execute_batch(SCHEMA).map_err(sql_error)?;
+        conn.pragma_update(None, "user_version", SCHEMA_VERSION)
+            .map_err(sql_error)?;
+
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    fn conn(&self) -> Result<std::sync::MutexGuard<'_, Connection>> {
+        self.conn
+            .lock()
+            .map_err(|_| history_error("History database lock poisoned"))
+    }
+
+    /// Insert a new entry
+    pub fn record(&self, entry: &HistoryEntry) -> Result<()> {
+        let shielded = entry
+            .shielded
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()
+            .map_err(|e| history_error(e.to_string()))?;
+
+        self.conn()?
+            .execute(
+                "INSERT INTO transactions (id, chain_id, account, tx_type, status, token, amount, \
+                 recipient, tx_hash, error, shielded, created_at, updated_at) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+                params![
+                    entry.id,
+                    entry.chain_id as i64,
+                    entry.account,
+                    entry.tx_type.as_str(),
+                    entry.status.as_str(),
+                    entry.token,
+                    entry.amount,
+                    entry.recipient,
+                    entry.tx_hash,
+                    entry.error,
+                    shielded,
+                    entry.created_at,
+                    entry.updated_at,
+                ],
+            )
+            .map_err(sql_error)?;
+        Ok(())
+    }
+
+    /// Record the notes a RAILGUN wallet received that are not yet in the
+    /// history; returns how many were added
+    pub fn record_received(
+        &self,
+        chain_id: u64,
+        account: &str,
+        notes: &[ReceivedNote],
+    ) -> Result<usize> {
+        let mut added = 0;
+        for (i, note) in notes.iter().enumerate() {
+            let index = notes[..i]
+                .iter()
+                .filter(|other| other.txid.eq_ignore_ascii_case(&note.txid))
+                .count();
+            let entry = HistoryEntry::received(chain_id, account, note, index);
+            if self.get(&entry.id)?.is_none() {
+                self.record(&entry)?;
+                added += 1;
+            }
+        }
+        Ok(added)
+    }
+
+    /// Update the status of an entry, optionally setting the tx hash / error
+    pub fn update_status(
+        &self,
+        id: &str,
+        status: HistoryStatus,
+        tx_hash: Option<&str>,
+        error: Option<&str>,
+    ) -> Result<HistoryEntry> {
+        let updated = self
+            .conn()?
+            .execute(
+                "UPDATE transactions SET status = ?2, tx_hash = COALESCE(?3, tx_hash), \
+                 error = ?4, updated_at = ?5 WHERE id = ?1",
+                params![
+                    id,
+                    status.as_str(),
+                    tx_hash,
+                    error,
+                    chrono::Utc::now().timestamp()
+                ],
+            )
+            .map_err(sql_error)?;
+
+        if updated == 0 {
+            return Err(history_error(format!("Unknown history entry: {}", id)));
+        }
+        self.get(id)?
+            .ok_or_else(|| history_error(format!("Unknown history entry: {}", id)))
+    }
+
+    /// Get an entry by id
+    pub fn get(&self, id: &str) -> Result<Option<HistoryEntry>> {
+        let raw = self
+            .conn()?
+            .query_row(
+                &format!("SELECT {} FROM transactions WHERE id = ?1", SELECT_COLUMNS),
+                params![id],
+                RawEntry::from_row,
+            )
+            .optional()
+            .map_err(sql_error)?;
+
+        raw.map(RawEntry::decode).transpose()
+    }
+
+    /// Find an entry by transaction hash
+    pub fn find_by_hash(&self, tx_hash: &str) -> Result<Option<HistoryEntry>> {
+        let raw = self
+            .conn()?
+            .query_row(
+                &format!(
+                    "SELECT {} FROM transactions WHERE lower(tx_hash) = lower(?1) \
+                     ORDER BY created_at DESC LIMIT 1",
+                    SELECT_COLUMNS
+                ),
+                params![tx_hash],
+                RawEntry::from_row,
+            )
+            .optional()
+            .map_err(sql_error)?;
+
+        raw.map(RawEntry::decode).transpose()
+    }
+
+    /// Query entries, newest first
+    pub fn query(&self, filter: &HistoryFilter, offset: u32, limit: u32) -> Result<HistoryPage> {
+        let limit = limit.clamp(1, MAX_PAGE_SIZE);
+        let (clause, mut values) = filter_clause(filter);
+        let conn = self.conn()?;
+
+        let total: i64 = conn
+            .query_row(
+                &format!("SELECT COUNT(*) FROM transactions{}", clause),
+                rusqlite::params_from_iter(values.iter()),
+                |row| row.get(0),
+            )
+            .map_err(sql_error)?;
+
+        values.push(Box::new(limit as i64));
+        values.push(Box::new(offset as i64));
+
+        let mut statement = conn
+            .prepare(&format!(
+                "SELECT {} FROM transactions{} ORDER BY created_at DESC, rowid DESC LIMIT ? OFFSET ?",
+                SELECT_COLUMNS, clause
+            ))
+            .map_err(sql_error)?;
+        let rows = statement
+            .query_map(
+                rusqlite::params_from_iter(values.iter()),
+                RawEntry::from_row,
+            )
+            .map_err(sql_error)?;
+
+        let mut entries = Vec::new();
+        for row in rows {
+            entries.push(row.map_err(sql_error)?.decode()?);
+        }
+
+        Ok(HistoryPage {
+            entries,
+            total: total as u64,
+            offset,
+            limit,
+        })
+    }
+
+    /// All entries matching a filter, newest first (for export)
+    pub fn query_all(&self, filter: &HistoryFilter) -> Result<Vec<HistoryEntry>> {
+        let mut entries = Vec::new();
+        let mut offset = 0;
+
+        loop {
+            let page = self.query(filter, offset, MAX_PAGE_SIZE)?;
+            let fetched = page.entries.len() as u32;
+            entries.extend(page.entries);
+
+            if fetched < MAX_PAGE_SIZE {
+                return Ok(entries);
+            }
+            offset += fetched;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ACCOUNT: &str = "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266";
+    const TOKEN: &str = "0x1c7D4B196Cb0C7B01d743Fbc6116a902379C7238";
+
+    fn shielded_tx(tx_type: ShieldedTxType) -> ShieldedTransaction {
+        ShieldedTransaction {
+            tx_type,
+            token: TOKEN.to_string(),
+            amount: "1000000".to_string(),
+            recipient: Some("0zk1qexample".to_string()),
+            proof: Some("proof-id".to_string()),
+            merkle_root: None,
+            nullifier: None,
+            transaction: None,
+            history_id: None,
+            proofs: Vec::new(),
+            broadcaster_fee: None,
+            outputs: Vec::new(),
+            memo: None,
+        }
+    }
+
+    fn entry_at(entry: HistoryEntry, created_at: i64) -> HistoryEntry {
+        HistoryEntry {
+            created_at,
+            updated_at: created_at,
+            ..entry
+        }
+    }
+
+    #[test]
+    fn test_record_and_get() {
+        let store = HistoryStore::in_memory().unwrap();
+        let entry =
+            HistoryEntry::shielded(11155111, "wallet-1", &shielded_tx(ShieldedTxType::Shield));
+
+        store.record(&entry).unwrap();
+
+        let loaded = store.get(&entry.id).unwrap().unwrap();
+        assert_eq!(
+            serde_json::to_value(&loaded).unwrap(),
+            serde_json::to_value(&entry).unwrap()
+        );
+        assert_eq!(loaded.tx_type, HistoryTxType::Shield);
+        assert!(store.get("missing").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_update_status() {
+        let store = HistoryStore::in_memory().unwrap();
+        let entry = HistoryEntry::public(1, ACCOUNT, HistoryStatus::Signed);
+        store.record(&entry).unwrap();
+
+        let updated = store
+            .update_status(&entry.id, HistoryStatus::Broadcast, Some("0xabc"), None)
+            .unwrap();
+        assert_eq!(updated.status, HistoryStatus::Broadcast);
+        assert_eq!(updated.tx_hash.as_deref(), Some("0xabc"));
+
+        // Hash is kept when not provided again
+        let failed = store
+            .update_status(&entry.id, HistoryStatus::Failed, None, Some("reverted"))
+            .unwrap();
+        assert_eq!(failed.tx_hash.as_deref(), Some("0xabc"));
+        assert_eq!(failed.error.as_deref(), Some("reverted"));
+
+        assert_eq!(store.find_by_hash("0xABC").unwrap().unwrap().id, entry.id);
+        assert!(store
+            .update_status("missing", HistoryStatus::Confirmed, None, None)
+            .is_err());
+    }
+
+    #[test]
+    fn test_filters_and_pagination() {
+        let store = HistoryStore::in_memory().unwrap();
+
+        for i in 0..5 {
+            let entry = HistoryEntry::public(1, ACCOUNT, HistoryStatus::Signed)
+                .with_value(Some(TOKEN.to_string()), Some(i.to_string()));
+            store.record(&entry_at(entry, 1_000 + i)).unwrap();
+        }
+        store
+            .record(&entry_at(
+                HistoryEntry::shielded(1, "wallet-1", &shielded_tx(ShieldedTxType::Transfer)),
+                2_000,
+            ))
+            .unwrap();
+        store
+            .record(&entry_at(
+                HistoryEntry::public(137, ACCOUNT, HistoryStatus::Signed),
+                3_000,
+            ))
+            .unwrap();
+
+        let all = store.query(&HistoryFilter::default(), 0, 50).unwrap();
+        assert_eq!(all.total, 7);
+        assert_eq!(all.entries[0].created_at, 3_000);
+
+        let by_account = HistoryFilter {
+            account: Some(ACCOUNT.to_lowercase()),
+            chain_id: Some(1),
+            ..Default::default()
+        };
+        let first_page = store.query(&by_account, 0, 2).unwrap();
+        assert_eq!(first_page.total, 5);
+        assert_eq!(first_page.entries.len(), 2);
+        assert_eq!(first_page.entries[0].amount.as_deref(), Some("4"));
+        let last_page = store.query(&by_account, 4, 2).unwrap();
+        assert_eq!(last_page.entries.len(), 1);
+        assert_eq!(last_page.entries[0].amount.as_deref(), Some("0"));
+
+        let by_type = HistoryFilter {
+            tx_types: vec![HistoryTxType::Shield, HistoryTxType::Transfer],
+            ..Default::default()
+        };
+        assert_eq!(store.query(&by_type, 0, 50).unwrap().total, 1);
+
+        let by_token = HistoryFilter {
+            token: Some(TOKEN.to_uppercase().replace("0X", "0x")),
+            ..Default::default()
+        };
+        assert_eq!(store.query(&by_token, 0, 50).unwrap().total, 6);
+
+        let by_date = HistoryFilter {
+            from: Some(1_002),
+            to: Some(2_000),
+            ..Default::default()
+        };
+        assert_eq!(store.query(&by_date, 0, 50).unwrap().total, 4);
+
+        assert_eq!(store.query_all(&HistoryFilter::default()).unwrap().len(), 7);
+    }
+
+    #[test]
+    fn test_record_received_notes() {
+        let store = HistoryStore::in_memory().unwrap();
+        let note = |amount: &str, memo: Option<&str>| ReceivedNote {
+            txid: "0xfeed".to_string(),
+            block_number: 5_000_000,
+            timestamp: Some(1_700_000_000),
+            token_address: TOKEN.to_string(),
+            amount: amount.to_string(),
+            memo_text: memo.map(str::to_string),
+        };
+        let notes = vec![note("100", Some("INV-42")), note("5", None)];
+
+        assert_eq!(store.record_received(1, "wallet-1", &notes).unwrap(), 2);
+        // Rescans don't duplicate them
+        assert_eq!(store.record_received(1, "wallet-1", &notes).unwrap(), 0);
+
+        let filter = HistoryFilter {
+            tx_types: vec![HistoryTxType::Receive],
+            ..Default::default()
+        };
+        let entries = store.query_all(&filter).unwrap();
+        assert_eq!(entries.len(), 2);
+        let invoice = entries
+            .iter()
+            .find(|entry| entry.amount.as_deref() == Some("100"))
+            .unwrap();
+        assert_eq!(invoice.status, HistoryStatus::Confirmed);
+        assert_eq!(invoice.created_at, 1_700_000_000);
+        assert_eq!(invoice.tx_hash.as_deref(), Some("0xfeed"));
+        let tx = invoice.shielded.as_ref().unwrap();
+        assert_eq!(tx.tx_type, ShieldedTxType::Receive);
+        assert_eq!(tx.memo.as_deref(), Some("INV-42"));
+    }
+
+    #[test]
+    fn test_persists_across_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let entry = HistoryEntry::public(1, ACCOUNT, HistoryStatus::Signed);
+
+        HistoryStore::open(dir.path())
+            .unwrap()
+            .record(&entry)
+            .unwrap();
+
+        let reopened = HistoryStore::open(dir.path()).unwrap();
+        let loaded = reopened.get(&entry.id).unwrap().unwrap();
+        assert_eq!(loaded.id, entry.id);
+        assert_eq!(loaded.account, ACCOUNT);
+    }
+}
@@ -4,5 +4,7 @@ pub mod commands;
 pub mod crypto;
 pub mod error;
 pub mod hardware;
+pub mod history;
 pub mod privacy;
+pub mod tokens;
 pub mod utils;
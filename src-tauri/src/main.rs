@@ -7,51 +7,98 @@ mod commands;
 mod crypto;
 mod error;
 mod hardware;
+mod history;
 mod privacy;
+mod tokens;
 mod utils;
 
 use commands::{
+    add_custom_token,
+    broadcast_private_transaction,
+    broadcast_shield,
+    cancel_merkletree_sync,
+    cancel_proof,
     connect_device,
+    connect_software_signer,
+    create_payment_request,
     // RAILGUN Wallet Management
     create_railgun_wallet,
+    detect_token,
     disconnect_device,
+    estimate_privacy_fee,
     estimate_proof_time,
     exit_privacy_pool,
+    export_keystore,
+    export_transaction_history,
+    export_zk_proof,
+    format_amount,
     generate_zk_proof,
     get_address,
     get_addresses,
+    get_broadcaster_quotes,
+    get_circuit_availability,
     get_device_info,
+    get_merkletree_sync_status,
+    get_portfolio,
+    get_proof_job,
     get_public_key,
     get_shield_key,
     get_shielded_balance,
+    get_shielded_balances,
+    get_transaction_history,
     greet,
+    // Software accounts
+    import_keystore,
+    import_zk_proof,
     // Privacy commands
     initialize_privacy,
     is_privacy_ready,
     join_privacy_pool,
+    list_software_accounts,
+    list_tokens,
+    parse_payment_request,
+    preview_private_broadcast,
     privacy_pool_swap,
     private_transfer,
+    private_transfer_batch,
+    record_transaction_broadcast,
+    relay_private_transaction,
+    remove_custom_token,
+    remove_software_account,
+    resolve_token,
     scan_merkletree,
+    select_broadcaster,
+    set_portfolio_refresh_interval,
     shield_transaction,
     sign_message,
     sign_transaction,
     sign_typed_data,
+    start_merkletree_sync,
+    submit_proof_job,
+    to_base_units,
     unshield_transaction,
+    update_transaction_status,
+    validate_railgun_address,
+    verify_zk_proof,
     AppState,
 };
-use tokio::sync::Mutex;
+use tauri::Manager;
 
 fn main() {
     tauri::Builder::default()
-        .manage(AppState {
-            trezor: Mutex::new(None),
-            privacy: Mutex::new(None),
+        .setup(|app| {
+            let data_dir = app.path().app_data_dir()?;
+            std::fs::create_dir_all(&data_dir)?;
+            let resource_dir = app.path().resource_dir().ok();
+            app.manage(AppState::new(data_dir, resource_dir)?);
+            Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             // Test Commands
             greet,
             // Device Commands
             connect_device,
+            connect_software_signer,
             disconnect_device,
             get_device_info,
             // Wallet Commands
@@ -62,23 +109,73 @@ fn main() {
             sign_transaction,
             sign_message,
             sign_typed_data,
+            // Software Account Commands
+            import_keystore,
+            export_keystore,
+            list_software_accounts,
+            remove_software_account,
+            // Transaction History Commands
+            get_transaction_history,
+            export_transaction_history,
+            record_transaction_broadcast,
+            update_transaction_status,
+            // Token Registry Commands
+            list_tokens,
+            resolve_token,
+            detect_token,
+            add_custom_token,
+            remove_custom_token,
+            to_base_units,
+            format_amount,
+            // Portfolio Commands
+            get_portfolio,
+            set_portfolio_refresh_interval,
             // Privacy Commands
             initialize_privacy,
             is_privacy_ready,
             shield_transaction,
+            broadcast_shield,
+            preview_private_broadcast,
+            broadcast_private_transaction,
+            get_broadcaster_quotes,
+            select_broadcaster,
+            relay_private_transaction,
+            estimate_privacy_fee,
             unshield_transaction,
             private_transfer,
+            private_transfer_batch,
+            validate_railgun_address,
+            create_payment_request,
+            parse_payment_request,
+            submit_proof_job,
+            get_proof_job,
+            cancel_proof,
             get_shielded_balance,
+            get_shielded_balances,
             scan_merkletree,
+            start_merkletree_sync,
+            cancel_merkletree_sync,
+            get_merkletree_sync_status,
             join_privacy_pool,
             exit_privacy_pool,
             privacy_pool_swap,
             generate_zk_proof,
+            verify_zk_proof,
+            export_zk_proof,
+            import_zk_proof,
             estimate_proof_time,
+            get_circuit_availability,
             // RAILGUN Wallet Management
             create_railgun_wallet,
             get_shield_key,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                // Let the proof-generator worker finish and stop its engine
+                let state = app.state::<AppState>();
+                tauri::async_runtime::block_on(state.bridge.shutdown());
+            }
+        });
 }
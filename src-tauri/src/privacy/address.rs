@@ -0,0 +1,465 @@
+/// RAILGUN (0zk) addresses and payment requests
+///
+/// A 0zk address is bech32m with the `0zk` prefix over 73 bytes: version
+/// (`0x01`), master public key (32), network ID (8) and viewing public key
+/// (32). The network ID is the chain type (1 byte) and chain id (7 bytes),
+/// or all `ff` for an address valid on every chain; its first 7 bytes are
+/// XOR'ed with `"railgun"`. The encoding is capped at 127 characters.
+use crate::error::{CepWalletError, Result};
+use bech32::{FromBase32, ToBase32, Variant};
+use ethers::types::U256;
+use serde::{Deserialize, Serialize};
+
+/// Human-readable prefix of RAILGUN addresses
+pub const RAILGUN_ADDRESS_PREFIX: &str = "0zk";
+
+/// Longest encoded RAILGUN address (characters)
+pub const RAILGUN_ADDRESS_LENGTH_LIMIT: usize = 127;
+
+/// URI scheme of payment requests
+pub const PAYMENT_REQUEST_SCHEME: &str = "railgun";
+
+const ADDRESS_VERSION: u8 = 1;
+const ADDRESS_BYTES: usize = 73;
+const ALL_CHAINS_NETWORK_ID: [u8; 8] = [0xff; 8];
+const NETWORK_ID_MASK: &[u8; 7] = b"railgun";
+/// RAILGUN `ChainType.EVM`
+const EVM_CHAIN_TYPE: u8 = 0;
+
+fn address_error(message: impl Into<String>) -> CepWalletError {
+    CepWalletError::InvalidInput(message.into())
+}
+
+/// XOR the network ID with `"railgun"` (applying it twice restores it)
+fn mask_network_id(mut network_id: [u8; 8]) -> [u8; 8] {
+    for (byte, mask) in network_id.iter_mut().zip(NETWORK_ID_MASK) {
+        *byte ^= mask;
+    }
+    network_id
+}
+
+/// Decoded RAILGUN (0zk) address
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RailgunAddress {
+    pub master_public_key: [u8; 32],
+    pub viewing_public_key: [u8; 32],
+    /// EVM chain the address is bound to; `None` for all chains
+    pub chain_id: Option<u64>,
+}
+
+impl RailgunAddress {
+    /// Decode a 0zk address
+    pub fn parse(address: &str) -> Result<Self> {
+        let address = address.trim();
+        if address.len() > RAILGUN_ADDRESS_LENGTH_LIMIT {
+            return Err(address_error(format!(
+                "RAILGUN address is longer than {} characters",
+                RAILGUN_ADDRESS_LENGTH_LIMIT
+            )));
+        }
+        let (prefix, words, variant) = bech32::decode(address)
+            .map_err(|e| address_error(format!("Invalid RAILGUN address: {}", e)))?;
+        if prefix != RAILGUN_ADDRESS_PREFIX {
+            return Err(address_error(format!(
+                "Invalid RAILGUN address prefix: {}",
+                prefix
+            )));
+        }
+        if variant != Variant::Bech32m {
+            return Err(address_error("RAILGUN addresses are bech32m encoded"));
+        }
+        let data = Vec::<u8>::from_base32(&words)
+            .map_err(|e| address_error(format!("Invalid RAILGUN address: {}", e)))?;
+        if data.len() != ADDRESS_BYTES {
+            return Err(address_error(format!(
+                "RAILGUN address holds {} bytes, expected {}",
+                data.len(),
+                ADDRESS_BYTES
+            )));
+        }
+        if data[0] != ADDRESS_VERSION {
+            return Err(address_error(format!(
+                "Unsupported RAILGUN address version: {}",
+                data[0]
+            )));
+        }
+
+        let mut master_public_key = [0u8; 32];
+        master_public_key.copy_from_slice(&data[1..33]);
+        let mut network_id = [0u8; 8];
+        network_id.copy_from_slice(&data[33..41]);
+        let mut viewing_public_key = [0u8; 32];
+        viewing_public_key.copy_from_slice(&data[41..73]);
+
+        let network_id = mask_network_id(network_id);
+        let chain_id = if network_id == ALL_CHAINS_NETWORK_ID {
+            None
+        } else if network_id[0] != EVM_CHAIN_TYPE {
+            return Err(address_error(format!(
+                "Unsupported RAILGUN chain type: {}",
+                network_id[0]
+            )));
+        } else {
+            let mut id = [0u8; 8];
+            id[1..].copy_from_slice(&network_id[1..]);
+            Some(u64::from_be_bytes(id))
+        };
+
+        Ok(Self {
+            master_public_key,
+            viewing_public_key,
+            chain_id,
+        })
+    }
+
+    /// Encode as a 0zk address
+    pub fn encode(&self) -> Result<String> {
+        let network_id = match self.chain_id {
+            None => ALL_CHAINS_NETWORK_ID,
+            Some(chain_id) if chain_id >> 56 == 0 => {
+                let mut network_id = chain_id.to_be_bytes();
+                network_id[0] = EVM_CHAIN_TYPE;
+                network_id
+            }
+            Some(chain_id) => {
+                return Err(address_error(format!(
+                    "Chain id {} does not fit a RAILGUN address",
+                    chain_id
+                )))
+            }
+        };
+
+        let mut data = Vec::with_capacity(ADDRESS_BYTES);
+        data.push(ADDRESS_VERSION);
+        data.extend_from_slice(&self.master_public_key);
+        data.extend_from_slice(&mask_network_id(network_id));
+        data.extend_from_slice(&self.viewing_public_key);
+
+        bech32::encode(RAILGUN_ADDRESS_PREFIX, data.to_base32(), Variant::Bech32m)
+            .map_err(|e| address_error(format!("Failed to encode RAILGUN address: {}", e)))
+    }
+
+    /// Whether the address can receive on `chain_id`
+    pub fn is_valid_for_chain(&self, chain_id: u64) -> bool {
+        self.chain_id.map_or(true, |id| id == chain_id)
+    }
+
+    /// Fail unless the address can receive on `chain_id`
+    pub fn check_chain(&self, chain_id: u64) -> Result<()> {
+        match self.chain_id {
+            Some(id) if id != chain_id => Err(address_error(format!(
+                "RAILGUN address is for chain {}, not {}",
+                id, chain_id
+            ))),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Decode a 0zk address and check that it can receive on `chain_id`
+pub fn validate_railgun_address(address: &str, chain_id: u64) -> Result<RailgunAddress> {
+    let decoded = RailgunAddress::parse(address)?;
+    decoded.check_chain(chain_id)?;
+    Ok(decoded)
+}
+
+/// Parts of a valid 0zk address, as shown to the user
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RailgunAddressInfo {
+    pub address: String,
+    /// 0x-prefixed, 32 bytes
+    pub master_public_key: String,
+    /// 0x-prefixed, 32 bytes
+    pub viewing_public_key: String,
+    /// Chain the address is bound to; absent for all chains
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chain_id: Option<u64>,
+}
+
+impl RailgunAddressInfo {
+    pub fn new(address: &str, decoded: &RailgunAddress) -> Self {
+        Self {
+            address: address.trim().to_string(),
+            master_public_key: format!("0x{}", hex::encode(decoded.master_public_key)),
+            viewing_public_key: format!("0x{}", hex::encode(decoded.viewing_public_key)),
+            chain_id: decoded.chain_id,
+        }
+    }
+}
+
+/// Request to be paid privately: a 0zk address, token and amount
+///
+/// Carried as a `railgun:<0zk address>?chainId=..&token=..&amount=..[&memo=..]`
+/// URI, which is also the QR code payload.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaymentRequest {
+    pub address: String,
+    pub chain_id: u64,
+    /// Token address (0x0 for ETH)
+    pub token: String,
+    /// Amount in base units
+    pub amount: String,
+    /// Memo the payer should attach (e.g. an invoice reference)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memo: Option<String>,
+}
+
+impl PaymentRequest {
+    /// Payment request to a 0zk address that can receive on `chain_id`
+    pub fn new(
+        address: &str,
+        chain_id: u64,
+        token: &str,
+        amount: U256,
+        memo: Option<String>,
+    ) -> Result<Self> {
+        validate_railgun_address(address, chain_id)?;
+        if amount.is_zero() {
+            return Err(address_error("Payment request has no amount"));
+        }
+        let memo = memo.filter(|memo| !memo.is_empty());
+        if let Some(memo) = &memo {
+            super::types::validate_memo(memo)?;
+        }
+        Ok(Self {
+            address: address.trim().to_string(),
+            chain_id,
+            token: token.to_string(),
+            amount: amount.to_string(),
+            memo,
+        })
+    }
+
+    /// The request as a URI (QR code payload)
+    pub fn to_uri(&self) -> String {
+        let mut uri = format!(
+            "{}:{}?chainId={}&token={}&amount={}",
+            PAYMENT_REQUEST_SCHEME, self.address, self.chain_id, self.token, self.amount
+        );
+        if let Some(memo) = &self.memo {
+            uri.push_str("&memo=");
+            uri.push_str(&percent_encode(memo));
+        }
+        uri
+    }
+
+    /// Read a payment request URI (e.g. from a scanned QR code)
+    pub fn parse_uri(uri: &str) -> Result<Self> {
+        let rest = uri
+            .trim()
+            .strip_prefix(PAYMENT_REQUEST_SCHEME)
+            .and_then(|rest| rest.strip_prefix(':'))
+            .ok_or_else(|| address_error("Not a RAILGUN payment request"))?;
+        let (address, query) = rest.split_once('?').unwrap_or((rest, ""));
+
+        let (mut chain_id, mut token, mut amount, mut memo) = (None, None, None, None);
+        for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            match key {
+                "chainId" => {
+                    chain_id =
+                        Some(value.parse::<u64>().map_err(|e| {
+                            address_error(format!("Invalid chain id {}: {}", value, e))
+                        })?)
+                }
+                "token" => token = Some(value.to_string()),
+                "amount" => {
+                    amount =
+                        Some(U256::from_dec_str(value).map_err(|e| {
+                            address_error(format!("Invalid amount {}: {}", value, e))
+                        })?)
+                }
+                "memo" => memo = Some(percent_decode(value)?),
+                _ => {}
+            }
+        }
+
+        let missing = |field: &str| address_error(format!("Payment request has no {}", field));
+        Self::new(
+            address,
+            chain_id.ok_or_else(|| missing("chainId"))?,
+            &token.ok_or_else(|| missing("token"))?,
+            amount.ok_or_else(|| missing("amount"))?,
+            memo,
+        )
+    }
+}
+
+/// Payment request with its URI, to show as a QR code
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaymentRequestPayload {
+    #[serde(flatten)]
+    pub request: PaymentRequest,
+    pub uri: String,
+}
+
+impl From<PaymentRequest> for PaymentRequestPayload {
+    fn from(request: PaymentRequest) -> Self {
+        Self {
+            uri: request.to_uri(),
+            request,
+        }
+    }
+}
+
+/// Percent-encode everything but RFC 3986 unreserved characters
+fn percent_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+fn percent_decode(value: &str) -> Result<String> {
+    let invalid = || address_error(format!("Invalid percent-encoding: {}", value));
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' => {
+                let hex = value.get(i + 1..i + 3).ok_or_else(invalid)?;
+                decoded.push(u8::from_str_radix(hex, 16).map_err(|_| invalid())?);
+                i += 3;
+            }
+            b'+' => {
+                decoded.push(b' ');
+                i += 1;
+            }
+            byte => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8(decoded).map_err(|_| invalid())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Demo recipient of the privacy page, valid on all chains
+    const ALL_CHAINS_ADDRESS: &str = "0zk1qyhxaafkj50hg9j0kr477jq5hxxpzjpklyamnczd4waa6t08uskjtrv7j6fe3z53lu48668ng0jjy5uxepd4nq84vuznvndws0f2cw72stfsjdlzhfsuv746jnk";
+
+    fn sepolia_address() -> String {
+        RailgunAddress {
+            chain_id: Some(11155111),
+            ..RailgunAddress::parse(ALL_CHAINS_ADDRESS).unwrap()
+        }
+        .encode()
+        .unwrap()
+    }
+
+    #[test]
+    fn test_decode_address() {
+        let address = RailgunAddress::parse(ALL_CHAINS_ADDRESS).unwrap();
+        assert_eq!(
+            hex::encode(address.master_public_key),
+            "2e6ef536951f74164fb0ebef4814b98c114836f93bb9e04dabbbdd2de7e42d25"
+        );
+        assert_eq!(
+            hex::encode(address.viewing_public_key),
+            "2a7d68f343e5225386c85b5980f56705364dae83d2ac3bca82d30937e2ba61c6"
+        );
+        assert_eq!(address.chain_id, None);
+        assert!(address.is_valid_for_chain(1));
+        assert_eq!(address.encode().unwrap(), ALL_CHAINS_ADDRESS);
+        assert_eq!(ALL_CHAINS_ADDRESS.len(), RAILGUN_ADDRESS_LENGTH_LIMIT);
+
+        let info = RailgunAddressInfo::new(ALL_CHAINS_ADDRESS, &address);
+        let json = serde_json::to_value(&info).unwrap();
+        assert_eq!(json["masterPublicKey"].as_str().unwrap().len(), 66);
+        assert!(json.get("chainId").is_none());
+    }
+
+    #[test]
+    fn test_chain_bound_address() {
+        let encoded = sepolia_address();
+        assert!(encoded.starts_with("0zk1q"));
+        assert_eq!(encoded.len(), RAILGUN_ADDRESS_LENGTH_LIMIT);
+
+        // Network ID: chain type 0 and id 11155111 (0xaa36a7), XOR "railgun"
+        let (_, words, _) = bech32::decode(&encoded).unwrap();
+        let data = Vec::<u8>::from_base32(&words).unwrap();
+        let mut expected = [0, 0, 0, 0, 0, 0xaa, 0x36, 0xa7];
+        for (byte, mask) in expected.iter_mut().zip(b"railgun") {
+            *byte ^= mask;
+        }
+        assert_eq!(data[33..41], expected);
+
+        let address = validate_railgun_address(&encoded, 11155111).unwrap();
+        assert_eq!(address.chain_id, Some(11155111));
+        assert!(!address.is_valid_for_chain(1));
+        let err = validate_railgun_address(&encoded, 1)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("chain 11155111"), "{}", err);
+    }
+
+    #[test]
+    fn test_invalid_addresses() {
+        // Typo breaks the checksum
+        let typo = ALL_CHAINS_ADDRESS.replacen("qyhx", "qyhy", 1);
+        assert!(RailgunAddress::parse(&typo).is_err());
+        assert!(RailgunAddress::parse("0zk1test").is_err());
+        assert!(RailgunAddress::parse("0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266").is_err());
+        assert!(RailgunAddress::parse(&format!("{}q", ALL_CHAINS_ADDRESS)).is_err());
+
+        // Valid bech32m under another prefix
+        let data = Vec::<u8>::from_base32(&bech32::decode(ALL_CHAINS_ADDRESS).unwrap().1).unwrap();
+        let other = bech32::encode("zk", data.to_base32(), Variant::Bech32m).unwrap();
+        assert!(RailgunAddress::parse(&other).is_err());
+        // Plain bech32 checksum
+        let bech32 = bech32::encode("0zk", data.to_base32(), Variant::Bech32).unwrap();
+        assert!(RailgunAddress::parse(&bech32).is_err());
+        // Unknown version
+        let mut v2 = data;
+        v2[0] = 2;
+        let v2 = bech32::encode("0zk", v2.to_base32(), Variant::Bech32m).unwrap();
+        assert!(RailgunAddress::parse(&v2).is_err());
+    }
+
+    #[test]
+    fn test_payment_request_uri() {
+        let address = sepolia_address();
+        let request = PaymentRequest::new(
+            &address,
+            11155111,
+            "0x1c7D4B196Cb0C7B01d743Fbc6116a902379C7238",
+            U256::from(25_000_000u64),
+            Some("INV 42/ü".to_string()),
+        )
+        .unwrap();
+
+        let uri = request.to_uri();
+        assert_eq!(
+            uri,
+            format!(
+                "railgun:{}?chainId=11155111&token=0x1c7D4B196Cb0C7B01d743Fbc6116a902379C7238\
+                 &amount=25000000&memo=INV%2042%2F%C3%BC",
+                address
+            )
+        );
+        assert_eq!(PaymentRequest::parse_uri(&uri).unwrap(), request);
+        let payload = serde_json::to_value(PaymentRequestPayload::from(request)).unwrap();
+        assert_eq!(payload["uri"], uri);
+        assert_eq!(payload["amount"], "25000000");
+
+        // The address must be able to receive on the requested chain
+        assert!(PaymentRequest::new(&address, 1, "0x0", U256::one(), None).is_err());
+        assert!(PaymentRequest::new(&address, 11155111, "0x0", U256::zero(), None).is_err());
+        assert!(
+            PaymentRequest::parse_uri(&format!("railgun:{}?chainId=11155111", address)).is_err()
+        );
+        assert!(PaymentRequest::parse_uri(&format!("ethereum:{}", address)).is_err());
+    }
+}
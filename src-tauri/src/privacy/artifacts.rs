@@ -0,0 +1,525 @@
+/// Circuit artifact manager
+///
+/// Loads each circuit's proving key (`zkey`), witness generator (`wasm`) and
+/// verification key (`vkey.json`) from the local artifact directory
+/// (`<artifacts dir>/artifacts-v2.1/<circuit>/`); nothing is downloaded.
+/// Every file must match the SHA-256 pinned for it in the manifest compiled
+/// into the app (`circuits/manifest.json`), so circuits the manifest does
+/// not list are never used.
+///
+/// RAILGUN publishes artifacts brotli-compressed: a `zkey.br` or `wasm.br`
+/// is decompressed next to itself on first use. Files are memory-mapped
+/// for hashing and parsing instead of being read into memory.
+use super::verifier::VerificationKey;
+use super::zkproof::{Circuit, RAILGUN_ARTIFACTS_SUBDIR};
+use crate::error::{CepWalletError, Result};
+use memmap2::Mmap;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Artifact hashes pinned at build time
+pub const PINNED_MANIFEST: &str = include_str!("../../circuits/manifest.json");
+
+const MANIFEST_VERSION: u32 = 1;
+
+/// Extension of brotli-compressed artifacts
+const COMPRESSED_EXTENSION: &str = "br";
+
+/// SHA-256 (hex) of each artifact, by circuit
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ArtifactManifest {
+    pub version: u32,
+    pub circuits: BTreeMap<String, CircuitHashes>,
+}
+
+/// Hashes of a circuit's decompressed artifacts
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CircuitHashes {
+    pub zkey: String,
+    pub wasm: String,
+    pub vkey: String,
+}
+
+impl ArtifactManifest {
+    /// Parse and check a manifest
+    pub fn parse(json: &str) -> Result<Self> {
+        let manifest: Self = serde_json::from_str(json).map_err(|e| {
+            CepWalletError::ZKProofError(format!("Invalid artifact manifest: {}", e))
+        })?;
+        if manifest.version != MANIFEST_VERSION {
+            return Err(CepWalletError::ZKProofError(format!(
+                "Unsupported artifact manifest version {}",
+                manifest.version
+            )));
+        }
+        for (circuit, hashes) in &manifest.circuits {
+            let valid = [&hashes.zkey, &hashes.wasm, &hashes.vkey]
+                .iter()
+                .all(|hash| hash.len() == 64 && hash.bytes().all(|b| b.is_ascii_hexdigit()));
+            if !valid || !is_circuit_name(circuit) {
+                return Err(CepWalletError::ZKProofError(format!(
+                    "Invalid artifact manifest entry for {}",
+                    circuit
+                )));
+            }
+        }
+        Ok(manifest)
+    }
+
+    /// The manifest compiled into the app
+    pub fn pinned() -> Self {
+        Self::parse(PINNED_MANIFEST).expect("pinned artifact manifest is valid")
+    }
+}
+
+/// One of a circuit's artifact files
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArtifactKind {
+    /// snarkjs proving key
+    Zkey,
+    /// circom witness generator
+    Wasm,
+    /// snarkjs verification key
+    Vkey,
+}
+
+impl ArtifactKind {
+    pub const ALL: [ArtifactKind; 3] = [ArtifactKind::Zkey, ArtifactKind::Wasm, ArtifactKind::Vkey];
+
+    /// File name in the circuit's directory
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            ArtifactKind::Zkey => "zkey",
+            ArtifactKind::Wasm => "wasm",
+            ArtifactKind::Vkey => "vkey.json",
+        }
+    }
+
+    fn pinned_hash<'a>(&self, hashes: &'a CircuitHashes) -> &'a str {
+        match self {
+            ArtifactKind::Zkey => &hashes.zkey,
+            ArtifactKind::Wasm => &hashes.wasm,
+            ArtifactKind::Vkey => &hashes.vkey,
+        }
+    }
+}
+
+/// State of a circuit's artifacts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArtifactStatus {
+    /// All files present, not yet checked (hashing happens on first use)
+    Present,
+    /// Loaded and matched the pinned hashes
+    Verified,
+    /// Some files are missing
+    Missing,
+    /// A file did not match its pinned hash
+    Corrupt,
+    /// Downloaded, but the manifest pins no hashes for it: never loaded
+    Unpinned,
+}
+
+/// Availability report entry for one circuit
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CircuitAvailability {
+    pub circuit: String,
+    /// Nullifier and commitment counts of RAILGUN joinsplit variants
+    pub nullifiers: Option<usize>,
+    pub commitments: Option<usize>,
+    pub status: ArtifactStatus,
+    /// Files not found (neither plain nor compressed)
+    pub missing: Vec<String>,
+}
+
+/// Verified access to the circuit artifacts in a directory
+pub struct ArtifactManager {
+    dir: PathBuf,
+    manifest: ArtifactManifest,
+    /// Outcome of the last load of each circuit
+    status: Mutex<HashMap<String, ArtifactStatus>>,
+}
+
+impl ArtifactManager {
+    /// Artifacts in `artifacts_dir`, checked against `manifest`
+    pub fn new(artifacts_dir: &Path, manifest: ArtifactManifest) -> Self {
+        Self {
+            dir: artifacts_dir.join(RAILGUN_ARTIFACTS_SUBDIR),
+            manifest,
+            status: Mutex::default(),
+        }
+    }
+
+    /// Artifacts in `artifacts_dir`, checked against the pinned manifest
+    pub fn pinned(artifacts_dir: &Path) -> Self {
+        Self::new(artifacts_dir, ArtifactManifest::pinned())
+    }
+
+    /// Circuits the manifest pins
+    pub fn circuits(&self) -> Vec<String> {
+        self.manifest.circuits.keys().cloned().collect()
+    }
+
+    /// Whether the manifest pins `circuit`
+    pub fn is_pinned(&self, circuit: &str) -> bool {
+        self.manifest.circuits.contains_key(circuit)
+    }
+
+    /// RAILGUN joinsplit variant for a nullifier and commitment count
+    pub fn joinsplit(&self, nullifiers: usize, commitments: usize) -> Result<String> {
+        let circuit = super::verifier::joinsplit_circuit(nullifiers, commitments);
+        if !self.manifest.circuits.contains_key(&circuit) {
+            return Err(CepWalletError::ZKProofError(format!(
+                "No circuit for {} nullifiers and {} commitments",
+                nullifiers, commitments
+            )));
+        }
+        Ok(circuit)
+    }
+
+    /// Availability of every pinned circuit, then of downloaded RAILGUN
+    /// variants the manifest does not pin
+    ///
+    /// Only checks that files exist; hashes are checked when a circuit is
+    /// loaded, and a failed check is reported from then on.
+    pub fn availability(&self) -> Vec<CircuitAvailability> {
+        let status = self.lock_status();
+        let pinned = self.manifest.circuits.keys().map(|circuit| {
+            let missing = self.missing_files(circuit);
+            let status = match status.get(circuit) {
+                Some(ArtifactStatus::Corrupt) => ArtifactStatus::Corrupt,
+                _ if !missing.is_empty() => ArtifactStatus::Missing,
+                Some(ArtifactStatus::Verified) => ArtifactStatus::Verified,
+                _ => ArtifactStatus::Present,
+            };
+            (circuit.clone(), status, missing)
+        });
+        let unpinned = self.unpinned_variants().into_iter().map(|circuit| {
+            let missing = self.missing_files(&circuit);
+            (circuit, ArtifactStatus::Unpinned, missing)
+        });
+
+        pinned
+            .chain(unpinned)
+            .map(|(circuit, status, missing)| {
+                let (nullifiers, commitments) = parse_joinsplit(&circuit).unzip();
+                CircuitAvailability {
+                    circuit,
+                    nullifiers,
+                    commitments,
+                    status,
+                    missing,
+                }
+            })
+            .collect()
+    }
+
+    /// RAILGUN variants in the artifact directory that the manifest does not
+    /// pin, e.g. downloaded by the proof generator for a new UTXO count
+    pub fn unpinned_variants(&self) -> Vec<String> {
+        let Ok(entries) = std::fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        let mut variants: Vec<String> = entries
+            .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+            .filter(|name| parse_joinsplit(name).is_some() && !self.is_pinned(name))
+            .collect();
+        variants.sort();
+        variants
+    }
+
+    /// Circuits whose artifacts are all present and not known to be corrupt
+    pub fn available_circuits(&self) -> Vec<String> {
+        self.availability()
+            .into_iter()
+            .filter(|a| matches!(a.status, ArtifactStatus::Present | ArtifactStatus::Verified))
+            .map(|a| a.circuit)
+            .collect()
+    }
+
+    /// Load a circuit's proving key and witness generator
+    pub fn load_circuit(&self, circuit: &str) -> Result<Circuit> {
+        let zkey = self.load(circuit, ArtifactKind::Zkey)?;
+        let wasm = self.load(circuit, ArtifactKind::Wasm)?;
+        let loaded = Circuit::new(&zkey, &wasm)?;
+        self.set_status(circuit, ArtifactStatus::Verified);
+        Ok(loaded)
+    }
+
+    /// Load a circuit's verification key
+    pub fn load_verification_key(&self, circuit: &str) -> Result<VerificationKey> {
+        let vkey = self.load(circuit, ArtifactKind::Vkey)?;
+        let json = std::str::from_utf8(&vkey).map_err(|_| {
+            CepWalletError::ZKProofError(format!("Verification key of {} is not UTF-8", circuit))
+        })?;
+        VerificationKey::from_snarkjs_json(json)
+    }
+
+    /// Map an artifact file, decompressing it first if needed, and check
+    /// it against the pinned hash
+    pub fn load(&self, circuit: &str, kind: ArtifactKind) -> Result<Mmap> {
+        let hashes = self.manifest.circuits.get(circuit).ok_or_else(|| {
+            CepWalletError::ZKProofError(format!(
+                "Circuit {} is not in the artifact manifest",
+                circuit
+            ))
+        })?;
+        let path = self.file(circuit, kind);
+
+        let decompressed = !path.is_file();
+        if decompressed {
+            decompress(&path)?;
+        }
+        let map = map_file(&path)?;
+
+        let hash = hex::encode(Sha256::digest(&map[..]));
+        if !hash.eq_ignore_ascii_case(kind.pinned_hash(hashes)) {
+            drop(map);
+            if decompressed {
+                // Let a corrected download be decompressed again
+                let _ = std::fs::remove_file(&path);
+            }
+            self.set_status(circuit, ArtifactStatus::Corrupt);
+            return Err(CepWalletError::ZKProofError(format!(
+                "{} of circuit {} does not match its pinned SHA-256 (got {})",
+                kind.file_name(),
+                circuit,
+                hash
+            )));
+        }
+        Ok(map)
+    }
+
+    fn file(&self, circuit: &str, kind: ArtifactKind) -> PathBuf {
+        self.dir.join(circuit).join(kind.file_name())
+    }
+
+    /// Files of `circuit` found neither plain nor compressed
+    fn missing_files(&self, circuit: &str) -> Vec<String> {
+        ArtifactKind::ALL
+            .iter()
+            .filter(|kind| self.locate(circuit, **kind).is_none())
+            .map(|kind| kind.file_name().to_string())
+            .collect()
+    }
+
+    /// The artifact's file, or its compressed form
+    fn locate(&self, circuit: &str, kind: ArtifactKind) -> Option<PathBuf> {
+        let path = self.file(circuit, kind);
+        let compressed = compressed_path(&path);
+        [path, compressed].into_iter().find(|path| path.is_file())
+    }
+
+    fn set_status(&self, circuit: &str, status: ArtifactStatus) {
+        self.lock_status().insert(circuit.to_string(), status);
+    }
+
+    fn lock_status(&self) -> std::sync::MutexGuard<'_, HashMap<String, ArtifactStatus>> {
+        self.status.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Decompress `<path>.br` into `path`
+fn decompress(path: &Path) -> Result<()> {
+    let source = compressed_path(path);
+    let compressed = File::open(&source).map_err(|_| {
+        CepWalletError::ZKProofError(format!("Circuit artifact {:?} not found", path))
+    })?;
+
+    // Write next to the target and rename, so a partial file never passes
+    // for the artifact
+    let partial = path.with_extension("partial");
+    let result = File::create(&partial).and_then(|mut out| {
+        std::io::copy(
+            &mut brotli::Decompressor::new(compressed, 64 * 1024),
+            &mut out,
+        )?;
+        out.flush()?;
+        std::fs::rename(&partial, path)
+    });
+    result.map_err(|e| {
+        let _ = std::fs::remove_file(&partial);
+        CepWalletError::ZKProofError(format!("Failed to decompress {:?}: {}", source, e))
+    })
+}
+
+fn compressed_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".");
+    name.push(COMPRESSED_EXTENSION);
+    PathBuf::from(name)
+}
+
+fn map_file(path: &Path) -> Result<Mmap> {
+    let file = File::open(path)
+        .map_err(|e| CepWalletError::ZKProofError(format!("Failed to open {:?}: {}", path, e)))?;
+    // SAFETY: artifacts are only ever replaced by rename (here and in the
+    // proof generator's artifact store), never modified in place, so the
+    // mapped file does not change underneath us.
+    unsafe { Mmap::map(&file) }
+        .map_err(|e| CepWalletError::ZKProofError(format!("Failed to map {:?}: {}", path, e)))
+}
+
+/// Nullifier and commitment counts of an `NNxMM` circuit name
+fn parse_joinsplit(circuit: &str) -> Option<(usize, usize)> {
+    let (nullifiers, commitments) = circuit.split_once('x')?;
+    if nullifiers.len() != 2 || commitments.len() != 2 {
+        return None;
+    }
+    Some((nullifiers.parse().ok()?, commitments.parse().ok()?))
+}
+
+fn is_circuit_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    const FIXTURE_MANIFEST: &str = include_str!("../../fixtures/circuits/manifest.json");
+    const MULTIPLIER_ZKEY: &[u8] = include_bytes!("../../fixtures/circuits/multiplier2.zkey");
+    const MULTIPLIER_WASM: &[u8] = include_bytes!("../../fixtures/circuits/multiplier2.wasm");
+    const MULTIPLIER_VKEY: &[u8] = include_bytes!("../../fixtures/circuits/multiplier2.vkey.json");
+
+    /// Lay out the multiplier fixture as `circuit` in `dir` (`.br` files
+    /// when `compressed`) and return a manager pinning its hashes
+    pub(crate) fn fixture_artifacts(
+        dir: &Path,
+        circuit: &str,
+        compressed: bool,
+    ) -> ArtifactManager {
+        let target = dir.join(RAILGUN_ARTIFACTS_SUBDIR).join(circuit);
+        std::fs::create_dir_all(&target).unwrap();
+        for (data, kind) in [
+            (MULTIPLIER_ZKEY, ArtifactKind::Zkey),
+            (MULTIPLIER_WASM, ArtifactKind::Wasm),
+            (MULTIPLIER_VKEY, ArtifactKind::Vkey),
+        ] {
+            let path = target.join(kind.file_name());
+            if compressed && kind != ArtifactKind::Vkey {
+                let file = File::create(compressed_path(&path)).unwrap();
+                let mut writer = brotli::CompressorWriter::new(file, 4096, 9, 22);
+                writer.write_all(data).unwrap();
+            } else {
+                std::fs::write(path, data).unwrap();
+            }
+        }
+
+        let mut manifest = ArtifactManifest::parse(FIXTURE_MANIFEST).unwrap();
+        let hashes = manifest.circuits.remove("multiplier2").unwrap();
+        manifest.circuits.insert(circuit.to_string(), hashes);
+        ArtifactManager::new(dir, manifest)
+    }
+
+    #[test]
+    fn test_pinned_manifest_parses() {
+        // Only RAILGUN joinsplit variants and the Privacy Pools circuits are
+        // proved or verified from artifacts
+        for circuit in ArtifactManifest::pinned().circuits.keys() {
+            assert!(
+                parse_joinsplit(circuit).is_some()
+                    || ["pool_membership", "compliance"].contains(&circuit.as_str()),
+                "{}",
+                circuit
+            );
+        }
+        assert!(ArtifactManifest::parse(r#"{"version": 2, "circuits": {}}"#).is_err());
+        assert!(ArtifactManifest::parse(
+            r#"{"version": 1, "circuits": {"01x02": {"zkey": "00", "wasm": "00", "vkey": "00"}}}"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    #[ignore = "needs RAILGUN's artifacts-v2.1 hashes pinned in circuits/manifest.json"]
+    fn test_pinned_manifest_covers_railgun_variants() {
+        // The proof generator refuses unpinned variants, so transfers and
+        // unshields of one or two notes need these pinned
+        let manifest = ArtifactManifest::pinned();
+        for nullifiers in 1..=2 {
+            for commitments in 1..=3 {
+                let circuit = super::super::verifier::joinsplit_circuit(nullifiers, commitments);
+                assert!(manifest.circuits.contains_key(&circuit), "{}", circuit);
+            }
+        }
+    }
+
+    #[test]
+    fn test_load_verified_artifacts() {
+        let dir = tempfile::tempdir().unwrap();
+        let artifacts = fixture_artifacts(dir.path(), "01x02", false);
+
+        assert_eq!(artifacts.joinsplit(1, 2).unwrap(), "01x02");
+        assert!(artifacts.joinsplit(2, 3).is_err());
+        assert_eq!(artifacts.availability()[0].status, ArtifactStatus::Present);
+        assert_eq!(artifacts.availability()[0].nullifiers, Some(1));
+
+        let circuit = artifacts.load_circuit("01x02").unwrap();
+        assert_eq!(circuit.n_public(), 1);
+        assert_eq!(
+            artifacts.load_verification_key("01x02").unwrap().n_public(),
+            1
+        );
+        assert_eq!(artifacts.availability()[0].status, ArtifactStatus::Verified);
+        assert_eq!(artifacts.available_circuits(), ["01x02"]);
+
+        // Unpinned circuits are never loaded, even when present, and are
+        // reported as such
+        let other = dir.path().join(RAILGUN_ARTIFACTS_SUBDIR).join("02x02");
+        std::fs::create_dir_all(&other).unwrap();
+        std::fs::write(other.join("zkey"), MULTIPLIER_ZKEY).unwrap();
+        std::fs::create_dir_all(dir.path().join(RAILGUN_ARTIFACTS_SUBDIR).join("tmp")).unwrap();
+        assert!(artifacts.load("02x02", ArtifactKind::Zkey).is_err());
+        assert_eq!(artifacts.unpinned_variants(), ["02x02"]);
+        let report = artifacts.availability();
+        assert_eq!(report.len(), 2);
+        assert_eq!(report[1].status, ArtifactStatus::Unpinned);
+        assert_eq!(report[1].commitments, Some(2));
+        assert_eq!(report[1].missing, ["wasm", "vkey.json"]);
+        assert_eq!(artifacts.available_circuits(), ["01x02"]);
+    }
+
+    #[test]
+    fn test_lazy_decompression() {
+        let dir = tempfile::tempdir().unwrap();
+        let artifacts = fixture_artifacts(dir.path(), "01x02", true);
+        let zkey = dir.path().join(RAILGUN_ARTIFACTS_SUBDIR).join("01x02/zkey");
+
+        assert!(!zkey.exists());
+        assert_eq!(artifacts.availability()[0].status, ArtifactStatus::Present);
+
+        let mapped = artifacts.load("01x02", ArtifactKind::Zkey).unwrap();
+        assert_eq!(&mapped[..], MULTIPLIER_ZKEY);
+        assert!(zkey.is_file());
+        assert!(artifacts.load_circuit("01x02").is_ok());
+    }
+
+    #[test]
+    fn test_integrity_checks() {
+        let dir = tempfile::tempdir().unwrap();
+        let artifacts = fixture_artifacts(dir.path(), "01x02", false);
+        let circuit_dir = dir.path().join(RAILGUN_ARTIFACTS_SUBDIR).join("01x02");
+
+        let mut wasm = std::fs::read(circuit_dir.join("wasm")).unwrap();
+        wasm.push(0);
+        std::fs::write(circuit_dir.join("wasm"), wasm).unwrap();
+        let err = artifacts.load_circuit("01x02").err().unwrap().to_string();
+        assert!(err.contains("pinned SHA-256"), "{}", err);
+        assert_eq!(artifacts.availability()[0].status, ArtifactStatus::Corrupt);
+        assert!(artifacts.available_circuits().is_empty());
+
+        std::fs::remove_file(circuit_dir.join("vkey.json")).unwrap();
+        assert_eq!(artifacts.availability()[0].missing, ["vkey.json"]);
+        assert!(artifacts.load_verification_key("01x02").is_err());
+    }
+}
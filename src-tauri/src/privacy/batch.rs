@@ -0,0 +1,259 @@
+/// Multi-output private transfers
+///
+/// One RAILGUN transaction carries a joinsplit proof per token (more when
+/// the notes spent don't fit one circuit), each with several output
+/// commitments, so a payroll run to many 0zk addresses is a single
+/// `transact` call. `TransferBatch` validates the outputs and orders them
+/// by token, letting the SDK prove each token's outputs together in as few
+/// joinsplits as its circuits allow.
+///
+/// The SDK encrypts one memo for every output of a transaction. Outputs with
+/// different memos are not split into several transactions here: proved
+/// before the first is mined, they would spend the same notes. They have to
+/// be sent as one transfer per memo, one after another.
+use super::types::{validate_memo, ShieldedTransaction, ShieldedTxType, TransferOutput};
+use crate::error::{CepWalletError, Result};
+use ethers::types::U256;
+
+/// Outputs of one multi-output private transfer, grouped by token
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransferBatch {
+    outputs: Vec<TransferOutput>,
+}
+
+impl TransferBatch {
+    /// Validate `outputs` (base-unit amounts) and group them by token, in
+    /// the order each token first appears
+    ///
+    /// The outputs must share their memo (see the module docs); the error
+    /// for mixed memos names each memo's outputs.
+    pub fn new(outputs: Vec<TransferOutput>) -> Result<Self> {
+        let first = outputs
+            .first()
+            .ok_or_else(|| CepWalletError::InvalidInput("Transfer has no outputs".to_string()))?;
+        let groups = memo_groups(&outputs);
+        if groups.len() > 1 {
+            let groups: Vec<String> = groups
+                .iter()
+                .map(|(memo, indices)| {
+                    let indices: Vec<String> = indices.iter().map(usize::to_string).collect();
+                    format!(
+                        "{} (outputs {})",
+                        memo.map_or("no memo".to_string(), |memo| format!("\"{}\"", memo)),
+                        indices.join(", ")
+                    )
+                })
+                .collect();
+            return Err(CepWalletError::InvalidInput(format!(
+                "Outputs of one transfer share one memo; send each memo's outputs as a \
+                 separate transfer: {}",
+                groups.join("; ")
+            )));
+        }
+        if let Some(memo) = &first.memo {
+            validate_memo(memo)?;
+        }
+        for output in &outputs {
+            if output.recipient.trim().is_empty() {
+                return Err(CepWalletError::InvalidInput(
+                    "Transfer output has no recipient".to_string(),
+                ));
+            }
+            let amount = U256::from_dec_str(&output.amount).map_err(|e| {
+                CepWalletError::InvalidInput(format!("Invalid amount {}: {}", output.amount, e))
+            })?;
+            if amount.is_zero() {
+                return Err(CepWalletError::InvalidInput(format!(
+                    "Transfer to {} has no amount",
+                    output.recipient
+                )));
+            }
+        }
+
+        let mut tokens: Vec<String> = Vec::new();
+        for output in &outputs {
+            let token = output.token.to_lowercase();
+            if !tokens.contains(&token) {
+                tokens.push(token);
+            }
+        }
+        let mut grouped = outputs;
+        grouped.sort_by_key(|output| {
+            let token = output.token.to_lowercase();
+            tokens.iter().position(|t| *t == token)
+        });
+        Ok(Self { outputs: grouped })
+    }
+
+    /// Outputs, grouped by token
+    pub fn outputs(&self) -> &[TransferOutput] {
+        &self.outputs
+    }
+
+    /// Memo shared by the outputs
+    pub fn memo(&self) -> Option<&str> {
+        self.outputs[0].memo.as_deref()
+    }
+
+    /// Tokens sent, in batch order
+    pub fn tokens(&self) -> Vec<&str> {
+        let mut tokens: Vec<&str> = Vec::new();
+        for output in &self.outputs {
+            if !tokens.iter().any(|t| t.eq_ignore_ascii_case(&output.token)) {
+                tokens.push(&output.token);
+            }
+        }
+        tokens
+    }
+
+    /// Total sent of `token` (base units)
+    pub fn total(&self, token: &str) -> U256 {
+        self.outputs
+            .iter()
+            .filter(|output| output.token.eq_ignore_ascii_case(token))
+            .filter_map(|output| U256::from_dec_str(&output.amount).ok())
+            .fold(U256::zero(), |total, amount| total.saturating_add(amount))
+    }
+
+    /// Recipient of every output, when they all go to the same one
+    pub fn recipient(&self) -> Option<&str> {
+        let first = &self.outputs[0].recipient;
+        self.outputs
+            .iter()
+            .all(|output| output.recipient == *first)
+            .then_some(first.as_str())
+    }
+
+    /// Transfer describing the batch: the first token's total, the common
+    /// recipient if any, and every output
+    pub fn transaction(&self) -> ShieldedTransaction {
+        let token = self.outputs[0].token.clone();
+        ShieldedTransaction {
+            tx_type: ShieldedTxType::Transfer,
+            amount: self.total(&token).to_string(),
+            token,
+            recipient: self.recipient().map(str::to_string),
+            proof: None,
+            merkle_root: None,
+            nullifier: None,
+            transaction: None,
+            history_id: None,
+            proofs: Vec::new(),
+            broadcaster_fee: None,
+            outputs: self.outputs.clone(),
+            memo: self.memo().map(str::to_string),
+        }
+    }
+}
+
+/// Output indices by memo, in the order each memo first appears
+fn memo_groups(outputs: &[TransferOutput]) -> Vec<(Option<&str>, Vec<usize>)> {
+    let mut groups: Vec<(Option<&str>, Vec<usize>)> = Vec::new();
+    for (i, output) in outputs.iter().enumerate() {
+        let memo = output.memo.as_deref();
+        match groups.iter_mut().find(|(m, _)| *m == memo) {
+            Some((_, indices)) => indices.push(i),
+            None => groups.push((memo, vec![i])),
+        }
+    }
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::privacy::types::MAX_MEMO_BYTES;
+
+    fn output(recipient: &str, token: &str, amount: &str) -> TransferOutput {
+        TransferOutput {
+            recipient: recipient.to_string(),
+            token: token.to_string(),
+            amount: amount.to_string(),
+            memo: None,
+        }
+    }
+
+    #[test]
+    fn test_batch_groups_by_token() {
+        let batch = TransferBatch::new(vec![
+            output("0zk1alice", "0xUSDC", "100"),
+            output("0zk1bob", "0xdai", "5"),
+            output("0zk1carol", "0xusdc", "250"),
+        ])
+        .unwrap();
+
+        let recipients: Vec<_> = batch.outputs().iter().map(|o| &o.recipient[..]).collect();
+        assert_eq!(recipients, ["0zk1alice", "0zk1carol", "0zk1bob"]);
+        assert_eq!(batch.tokens(), ["0xUSDC", "0xdai"]);
+        assert_eq!(batch.total("0xusdc"), U256::from(350u64));
+        assert_eq!(batch.recipient(), None);
+
+        let tx = batch.transaction();
+        assert_eq!(tx.tx_type, ShieldedTxType::Transfer);
+        assert_eq!((&tx.token[..], &tx.amount[..]), ("0xUSDC", "350"));
+        assert_eq!(tx.recipient, None);
+        assert_eq!(tx.outputs.len(), 3);
+    }
+
+    #[test]
+    fn test_payroll_batch() {
+        let outputs: Vec<_> = (0..10)
+            .map(|i| output(&format!("0zk1employee{}", i), "0xusdc", "1000"))
+            .collect();
+        let batch = TransferBatch::new(outputs).unwrap();
+        assert_eq!(batch.tokens().len(), 1);
+        assert_eq!(batch.transaction().amount, "10000");
+    }
+
+    #[test]
+    fn test_invalid_batches() {
+        assert!(TransferBatch::new(Vec::new()).is_err());
+        assert!(TransferBatch::new(vec![output("0zk1alice", "0xusdc", "0")]).is_err());
+        assert!(TransferBatch::new(vec![output("0zk1alice", "0xusdc", "1.5")]).is_err());
+        assert!(TransferBatch::new(vec![output(" ", "0xusdc", "1")]).is_err());
+
+        let mut memo = output("0zk1bob", "0xusdc", "1");
+        memo.memo = Some("INV-7".to_string());
+        let mixed = TransferBatch::new(vec![output("0zk1alice", "0xusdc", "1"), memo.clone()]);
+        assert!(matches!(mixed, Err(CepWalletError::InvalidInput(_))));
+
+        let shared = TransferBatch::new(vec![memo.clone(), memo.clone()]).unwrap();
+        assert_eq!(shared.memo(), Some("INV-7"));
+        assert_eq!(shared.recipient(), Some("0zk1bob"));
+        assert_eq!(shared.transaction().memo.as_deref(), Some("INV-7"));
+
+        memo.memo = Some("x".repeat(MAX_MEMO_BYTES + 1));
+        assert!(TransferBatch::new(vec![memo]).is_err());
+    }
+
+    #[test]
+    fn test_mixed_memos_are_grouped() {
+        let with_memo = |recipient: &str, memo: Option<&str>| TransferOutput {
+            memo: memo.map(str::to_string),
+            ..output(recipient, "0xusdc", "1")
+        };
+        let outputs = vec![
+            with_memo("0zk1alice", Some("INV-7")),
+            with_memo("0zk1bob", None),
+            with_memo("0zk1carol", Some("INV-8")),
+            with_memo("0zk1dave", Some("INV-7")),
+        ];
+
+        let groups = memo_groups(&outputs);
+        assert_eq!(
+            groups,
+            [
+                (Some("INV-7"), vec![0, 3]),
+                (None, vec![1]),
+                (Some("INV-8"), vec![2]),
+            ]
+        );
+
+        let err = TransferBatch::new(outputs).unwrap_err().to_string();
+        assert!(
+            err.contains(r#""INV-7" (outputs 0, 3); no memo (outputs 1); "INV-8" (outputs 2)"#),
+            "{}",
+            err
+        );
+    }
+}
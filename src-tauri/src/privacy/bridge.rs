@@ -0,0 +1,916 @@
+/// Bridge to the Node.js proof generator
+///
+/// Every proof-generator command is a `BridgeRequest` variant with a typed
+/// `BridgeResponse`. `NodeBridge` is the call surface; `WorkerBridge` runs
+/// requests on the long-lived worker process (see `worker.rs`) and tests can
+/// substitute an in-process fake.
+///
+/// The proof-generator directory is taken from `CEPWALLET_PROOF_GENERATOR`
+/// when set, then from the bundled resources of production builds, then
+/// from the source tree during development.
+use super::artifacts::PINNED_MANIFEST;
+use super::broadcaster::{BroadcastRequest, BroadcasterQuote};
+use super::ffi::{ProofProgressEvent, ProofRequest, ProofResponse, ScanProgressEvent};
+use super::wallet::{ShieldKeyResponse, WalletCreateResponse};
+use super::worker::NodeWorker;
+use super::zkproof::CIRCUIT_ARTIFACTS_ENV;
+use crate::crypto::SecretString;
+use async_trait::async_trait;
+use ethers::types::U256;
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use thiserror::Error;
+use tokio::process::Command;
+use tokio::sync::{mpsc, watch};
+
+/// Environment variable overriding the proof-generator location
+pub const PROOF_GENERATOR_ENV: &str = "CEPWALLET_PROOF_GENERATOR";
+
+/// Entry script inside the proof-generator directory
+const PROOF_GENERATOR_SCRIPT: &str = "index.js";
+
+/// Environment variable telling the proof generator where to keep the
+/// RAILGUN engine database
+const RAILGUN_DB_ENV: &str = "CEPWALLET_RAILGUN_DB";
+
+/// Environment variable handing the proof generator the pinned artifact
+/// manifest, so it refuses the artifacts the wallet would
+const CIRCUIT_MANIFEST_ENV: &str = "CEPWALLET_CIRCUIT_MANIFEST";
+
+/// Time limit of wallet commands (create wallet, shield key)
+const WALLET_COMMAND_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Time limit of shield proofs (no SNARK, only note encryption)
+const SHIELD_PROOF_TIMEOUT: Duration = Duration::from_secs(180);
+
+/// Time limit of transfer and unshield proofs; the first proof also
+/// downloads the circuit artifacts
+const SPEND_PROOF_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Time limit of broadcaster commands; the first one also connects to Waku
+/// and waits for fee quotes to arrive
+const BROADCASTER_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// Time a worker gets to answer a ping after a request timed out, before it
+/// is considered wedged and restarted
+const WORKER_PING_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Failure of a proof-generator request
+#[derive(Debug, Clone, Error)]
+pub enum BridgeError {
+    /// The proof generator could not be located or started
+    #[error("Proof generator unavailable: {0}")]
+    Unavailable(String),
+
+    /// The worker exited (or was shut down) before answering
+    #[error("{0}")]
+    WorkerExited(String),
+
+    /// The caller cancelled the request
+    #[error("Request cancelled")]
+    Cancelled,
+
+    /// The command did not finish within its time limit
+    #[error("Timed out after {}s", .0.as_secs())]
+    TimedOut(Duration),
+
+    /// The proof generator does not know the command
+    #[error("Unknown proof generator command: {0}")]
+    UnknownCommand(String),
+
+    /// The command rejected its parameters
+    #[error("Invalid parameters: {0}")]
+    InvalidParams(String),
+
+    /// The command ran and failed
+    #[error("{0}")]
+    Command(String),
+
+    /// The proof generator broke the protocol or sent an unexpected result
+    #[error("Invalid proof generator response: {0}")]
+    InvalidResponse(String),
+}
+
+impl BridgeError {
+    /// Map a JSON-RPC error from the worker
+    pub fn from_rpc(code: i64, message: String) -> Self {
+        match code {
+            -32601 => Self::UnknownCommand(message),
+            -32800 => Self::Cancelled,
+            -32602 => Self::InvalidParams(message),
+            -32700 | -32600 => Self::InvalidResponse(message),
+            _ => Self::Command(message),
+        }
+    }
+}
+
+/// A proof-generator command with its parameters
+///
+/// Secrets are `SecretString`s, so `Debug` output is safe to log.
+#[derive(Debug)]
+pub enum BridgeRequest {
+    CreateWallet {
+        chain_id: u64,
+        encryption_key: SecretString,
+        mnemonic: Option<SecretString>,
+    },
+    GetShieldKey {
+        chain_id: u64,
+        railgun_wallet_id: String,
+    },
+    ShieldProof(ProofRequest),
+    TransferProof(ProofRequest),
+    UnshieldProof(ProofRequest),
+    Scan(ProofRequest),
+    /// Broadcaster fee quotes for a token (Waku)
+    BroadcasterQuotes {
+        chain_id: u64,
+        token: String,
+    },
+    /// Hand a populated transaction to a broadcaster (Waku)
+    BroadcasterSubmit(BroadcastRequest),
+    /// SDK gas estimate of the operation a `ProofRequest` describes, at the
+    /// given EIP-1559 fees; shields are estimated as sent by `from`
+    EstimateGas {
+        request: ProofRequest,
+        from: Option<String>,
+        max_fee_per_gas: U256,
+        max_priority_fee_per_gas: U256,
+    },
+}
+
+/// Result of a `BridgeRequest`, by command
+#[derive(Debug)]
+pub enum BridgeResponse {
+    Wallet(WalletCreateResponse),
+    ShieldKey(ShieldKeyResponse),
+    Proof(ProofResponse),
+    Scan(ProofResponse),
+    BroadcasterQuotes(Vec<BroadcasterQuote>),
+    /// Hash of a transaction a broadcaster sent
+    Submitted(String),
+    GasEstimate(U256),
+}
+
+impl BridgeRequest {
+    /// Request for a `ProofRequest`, chosen by its `proof_type`
+    pub fn proof(request: ProofRequest) -> Result<Self, BridgeError> {
+        Ok(match request.proof_type.as_str() {
+            "shield" | "generate_shield_proof" => Self::ShieldProof(request),
+            "transfer" | "generate_transfer_proof" => Self::TransferProof(request),
+            "unshield" | "generate_unshield_proof" => Self::UnshieldProof(request),
+            "scanMerkletree" | "scan_merkletree" => Self::Scan(request),
+            other => return Err(BridgeError::UnknownCommand(other.to_string())),
+        })
+    }
+
+    /// Proof-generator command name
+    pub fn method(&self) -> &'static str {
+        match self {
+            Self::CreateWallet { .. } => "create_wallet",
+            Self::GetShieldKey { .. } => "get_shield_key",
+            Self::ShieldProof(_) => "generate_shield_proof",
+            Self::TransferProof(_) => "generate_transfer_proof",
+            Self::UnshieldProof(_) => "generate_unshield_proof",
+            Self::Scan(_) => "scan_merkletree",
+            Self::BroadcasterQuotes { .. } => "broadcaster_fee_quotes",
+            Self::BroadcasterSubmit(_) => "broadcaster_submit",
+            Self::EstimateGas { .. } => "estimate_gas",
+        }
+    }
+
+    /// Time limit of the command
+    ///
+    /// Merkletree scans have none: they can take hours on a new wallet and
+    /// are cancelled by the user instead.
+    pub fn timeout(&self) -> Option<Duration> {
+        match self {
+            Self::CreateWallet { .. } | Self::GetShieldKey { .. } => Some(WALLET_COMMAND_TIMEOUT),
+            Self::ShieldProof(_) => Some(SHIELD_PROOF_TIMEOUT),
+            Self::TransferProof(_) | Self::UnshieldProof(_) => Some(SPEND_PROOF_TIMEOUT),
+            Self::EstimateGas { .. } => Some(WALLET_COMMAND_TIMEOUT),
+            Self::BroadcasterQuotes { .. } | Self::BroadcasterSubmit(_) => {
+                Some(BROADCASTER_TIMEOUT)
+            }
+            Self::Scan(_) => None,
+        }
+    }
+
+    /// Command parameters as sent to the proof generator
+    pub fn params(&self) -> Result<Value, BridgeError> {
+        match self {
+            Self::CreateWallet {
+                chain_id,
+                encryption_key,
+                mnemonic,
+            } => {
+                let mut params = json!({
+                    "chainId": chain_id,
+                    "encryptionKey": encryption_key.expose(),
+                });
+                if let Some(mnemonic) = mnemonic {
+                    params["mnemonic"] = Value::String(mnemonic.expose().to_string());
+                }
+                Ok(params)
+            }
+            Self::GetShieldKey {
+                chain_id,
+                railgun_wallet_id,
+            } => Ok(json!({ "chainId": chain_id, "railgunWalletId": railgun_wallet_id })),
+            Self::ShieldProof(request)
+            | Self::TransferProof(request)
+            | Self::UnshieldProof(request)
+            | Self::Scan(request) => serde_json::to_value(request).map_err(|e| {
+                BridgeError::InvalidParams(format!("Failed to serialize proof request: {}", e))
+            }),
+            Self::BroadcasterQuotes { chain_id, token } => {
+                Ok(json!({ "chainId": chain_id, "token": token }))
+            }
+            Self::BroadcasterSubmit(request) => serde_json::to_value(request).map_err(|e| {
+                BridgeError::InvalidParams(format!("Failed to serialize broadcast: {}", e))
+            }),
+            Self::EstimateGas {
+                request,
+                from,
+                max_fee_per_gas,
+                max_priority_fee_per_gas,
+            } => {
+                let mut params = serde_json::to_value(request).map_err(|e| {
+                    BridgeError::InvalidParams(format!("Failed to serialize proof request: {}", e))
+                })?;
+                if let Some(from) = from {
+                    params["fromWalletAddress"] = Value::String(from.clone());
+                }
+                params["maxFeePerGas"] = Value::String(max_fee_per_gas.to_string());
+                params["maxPriorityFeePerGas"] =
+                    Value::String(max_priority_fee_per_gas.to_string());
+                Ok(params)
+            }
+        }
+    }
+
+    /// Decode the proof generator's result for this command
+    ///
+    /// A result with `"success": false` is returned as a command error.
+    pub fn parse_response(&self, result: Value) -> Result<BridgeResponse, BridgeError> {
+        if result.get("success").and_then(Value::as_bool) == Some(false) {
+            let message = result
+                .get("error")
+                .and_then(Value::as_str)
+                .unwrap_or("Unknown error");
+            return Err(BridgeError::Command(message.to_string()));
+        }
+
+        Ok(match self {
+            Self::CreateWallet { .. } => BridgeResponse::Wallet(decode(self, result)?),
+            Self::GetShieldKey { .. } => BridgeResponse::ShieldKey(decode(self, result)?),
+            Self::ShieldProof(_) | Self::TransferProof(_) | Self::UnshieldProof(_) => {
+                BridgeResponse::Proof(decode(self, result)?)
+            }
+            Self::Scan(_) => BridgeResponse::Scan(decode(self, result)?),
+            Self::BroadcasterQuotes { .. } => {
+                let quotes = result.get("quotes").cloned().unwrap_or(Value::Null);
+                BridgeResponse::BroadcasterQuotes(decode(self, quotes)?)
+            }
+            Self::BroadcasterSubmit(_) => {
+                let tx_hash = result
+                    .get("txHash")
+                    .and_then(Value::as_str)
+                    .ok_or_else(|| {
+                        BridgeError::InvalidResponse(format!("{}: missing `txHash`", self.method()))
+                    })?;
+                BridgeResponse::Submitted(tx_hash.to_string())
+            }
+            Self::EstimateGas { .. } => {
+                let gas = result
+                    .get("gasEstimate")
+                    .and_then(Value::as_str)
+                    .and_then(|gas| U256::from_dec_str(gas).ok())
+                    .ok_or_else(|| {
+                        BridgeError::InvalidResponse(format!(
+                            "{}: missing `gasEstimate`",
+                            self.method()
+                        ))
+                    })?;
+                BridgeResponse::GasEstimate(gas)
+            }
+        })
+    }
+}
+
+fn decode<T: DeserializeOwned>(request: &BridgeRequest, result: Value) -> Result<T, BridgeError> {
+    serde_json::from_value(result)
+        .map_err(|e| BridgeError::InvalidResponse(format!("{}: {}", request.method(), e)))
+}
+
+fn unexpected(response: &BridgeResponse) -> BridgeError {
+    // Never format the response itself: wallet responses carry the mnemonic
+    let kind = match response {
+        BridgeResponse::Wallet(_) => "wallet",
+        BridgeResponse::ShieldKey(_) => "shield key",
+        BridgeResponse::Proof(_) => "proof",
+        BridgeResponse::Scan(_) => "scan",
+        BridgeResponse::BroadcasterQuotes(_) => "broadcaster quotes",
+        BridgeResponse::Submitted(_) => "broadcast",
+        BridgeResponse::GasEstimate(_) => "gas estimate",
+    };
+    BridgeError::InvalidResponse(format!("unexpected {} response", kind))
+}
+
+impl BridgeResponse {
+    pub fn into_wallet(self) -> Result<WalletCreateResponse, BridgeError> {
+        match self {
+            Self::Wallet(response) => Ok(response),
+            other => Err(unexpected(&other)),
+        }
+    }
+
+    pub fn into_shield_key(self) -> Result<ShieldKeyResponse, BridgeError> {
+        match self {
+            Self::ShieldKey(response) => Ok(response),
+            other => Err(unexpected(&other)),
+        }
+    }
+
+    /// Proof (or scan) response
+    pub fn into_proof(self) -> Result<ProofResponse, BridgeError> {
+        match self {
+            Self::Proof(response) | Self::Scan(response) => Ok(response),
+            other => Err(unexpected(&other)),
+        }
+    }
+
+    pub fn into_broadcaster_quotes(self) -> Result<Vec<BroadcasterQuote>, BridgeError> {
+        match self {
+            Self::BroadcasterQuotes(quotes) => Ok(quotes),
+            other => Err(unexpected(&other)),
+        }
+    }
+
+    pub fn into_submitted(self) -> Result<String, BridgeError> {
+        match self {
+            Self::Submitted(tx_hash) => Ok(tx_hash),
+            other => Err(unexpected(&other)),
+        }
+    }
+
+    pub fn into_gas_estimate(self) -> Result<U256, BridgeError> {
+        match self {
+            Self::GasEstimate(gas) => Ok(gas),
+            other => Err(unexpected(&other)),
+        }
+    }
+}
+
+/// Progress event of a running command
+#[derive(Debug, Clone)]
+pub enum BridgeEvent {
+    ScanProgress(ScanProgressEvent),
+    ProofProgress(ProofProgressEvent),
+    /// Event this version does not know
+    Other(Value),
+}
+
+impl BridgeEvent {
+    /// Parse the params of a `progress` notification
+    pub fn from_params(params: Value) -> Self {
+        match params.get("event").and_then(Value::as_str) {
+            Some("scanProgress") => match serde_json::from_value(params.clone()) {
+                Ok(event) => Self::ScanProgress(event),
+                Err(_) => Self::Other(params),
+            },
+            Some("proofProgress") => match serde_json::from_value(params.clone()) {
+                Ok(event) => Self::ProofProgress(event),
+                Err(_) => Self::Other(params),
+            },
+            _ => Self::Other(params),
+        }
+    }
+}
+
+/// Call surface of the proof generator
+#[async_trait]
+pub trait NodeBridge: Send + Sync {
+    /// Run a command, forwarding its progress events to `events`
+    ///
+    /// Setting `cancel` to `true` (or dropping its sender) stops the
+    /// request. Commands fail with `TimedOut` after their
+    /// [`BridgeRequest::timeout`]; other requests keep running.
+    async fn send(
+        &self,
+        request: BridgeRequest,
+        events: Option<mpsc::UnboundedSender<BridgeEvent>>,
+        cancel: Option<watch::Receiver<bool>>,
+    ) -> Result<BridgeResponse, BridgeError>;
+
+    /// Run a command and wait for its response
+    async fn call(&self, request: BridgeRequest) -> Result<BridgeResponse, BridgeError> {
+        self.send(request, None, None).await
+    }
+
+    /// Stop background processes; later requests may start them again
+    async fn shutdown(&self) {}
+}
+
+/// Where the proof generator lives and keeps its state
+#[derive(Debug, Clone, Default)]
+pub struct BridgeConfig {
+    /// Tauri resource directory (bundled `proof-generator/` in production)
+    pub resource_dir: Option<PathBuf>,
+    /// RAILGUN engine database; defaults to `railgun_db` in the
+    /// proof-generator directory, which is moved here when it is set
+    pub railgun_db: Option<PathBuf>,
+    /// Downloaded circuit artifacts, shared with the native prover;
+    /// defaults to `circuit_artifacts` in the proof-generator directory
+    pub circuit_artifacts: Option<PathBuf>,
+}
+
+/// Locate the proof-generator directory (containing `index.js`)
+pub fn locate_proof_generator(resource_dir: Option<&Path>) -> Result<PathBuf, BridgeError> {
+    let configured = std::env::var_os(PROOF_GENERATOR_ENV).map(PathBuf::from);
+    let current_dir = std::env::current_dir().ok();
+    find_proof_generator(configured, resource_dir, current_dir.as_deref())
+}
+
+fn find_proof_generator(
+    configured: Option<PathBuf>,
+    resource_dir: Option<&Path>,
+    current_dir: Option<&Path>,
+) -> Result<PathBuf, BridgeError> {
+    let has_script = |dir: &Path| dir.join(PROOF_GENERATOR_SCRIPT).is_file();
+
+    // An explicit location is used as-is: the directory or its index.js
+    if let Some(path) = configured {
+        let dir = if path.is_file() {
+            path.parent().map(Path::to_path_buf).unwrap_or_default()
+        } else {
+            path
+        };
+        return if has_script(&dir) {
+            Ok(dir)
+        } else {
+            Err(BridgeError::Unavailable(format!(
+                "{} is set to {:?}, which has no {}",
+                PROOF_GENERATOR_ENV, dir, PROOF_GENERATOR_SCRIPT
+            )))
+        };
+    }
+
+    let mut candidates = Vec::new();
+    if let Some(resource_dir) = resource_dir {
+        candidates.push(resource_dir.join("proof-generator"));
+    }
+    if let Some(current_dir) = current_dir {
+        candidates.push(current_dir.join("proof-generator")); // From project root
+        candidates.push(current_dir.join("../proof-generator")); // From src-tauri
+        candidates.push(current_dir.join("../../proof-generator")); // From target/debug
+    }
+
+    let dir = candidates
+        .into_iter()
+        .find(|dir| has_script(dir))
+        .ok_or_else(|| {
+            BridgeError::Unavailable(format!(
+                "proof-generator/{} not found (searched from {:?}); set {}",
+                PROOF_GENERATOR_SCRIPT, current_dir, PROOF_GENERATOR_ENV
+            ))
+        })?;
+    tracing::debug!("Using proof-generator at: {:?}", dir);
+    Ok(dir)
+}
+
+/// RAILGUN database to hand the worker, moving a legacy one into place
+///
+/// Earlier versions kept the engine database in `railgun_db` next to
+/// `index.js`. It is moved to `configured` the first time, so wallets keep
+/// their scanned merkletree; if it can't be moved it is used where it is.
+fn railgun_db_path(proof_generator: &Path, configured: &Path) -> PathBuf {
+    let legacy = proof_generator.join("railgun_db");
+    if configured.exists() || !legacy.is_dir() {
+        return configured.to_path_buf();
+    }
+
+    let moved = configured
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|()| std::fs::rename(&legacy, configured));
+    match moved {
+        Ok(()) => {
+            tracing::info!("Moved RAILGUN database {:?} to {:?}", legacy, configured);
+            configured.to_path_buf()
+        }
+        Err(e) => {
+            tracing::warn!(
+                "Failed to move RAILGUN database {:?} to {:?}, using it in place: {}",
+                legacy,
+                configured,
+                e
+            );
+            legacy
+        }
+    }
+}
+
+/// `NodeBridge` backed by the long-lived proof-generator worker
+pub struct WorkerBridge {
+    worker: NodeWorker,
+}
+
+impl Default for WorkerBridge {
+    fn default() -> Self {
+        Self::new(BridgeConfig::default())
+    }
+}
+
+impl WorkerBridge {
+    /// Bridge whose worker runs `index.js --worker` from the located directory
+    pub fn new(config: BridgeConfig) -> Self {
+        Self {
+            worker: NodeWorker::new(move || {
+                let dir = locate_proof_generator(config.resource_dir.as_deref())?;
+                let mut command = Command::new("node");
+                command
+                    .arg(dir.join(PROOF_GENERATOR_SCRIPT))
+                    .arg("--worker")
+                    .current_dir(&dir)
+                    .env(CIRCUIT_MANIFEST_ENV, PINNED_MANIFEST);
+                if let Some(railgun_db) = &config.railgun_db {
+                    command.env(RAILGUN_DB_ENV, railgun_db_path(&dir, railgun_db));
+                }
+                if let Some(artifacts) = &config.circuit_artifacts {
+                    command.env(CIRCUIT_ARTIFACTS_ENV, artifacts);
+                }
+                Ok(command)
+            }),
+        }
+    }
+}
+
+#[async_trait]
+impl NodeBridge for WorkerBridge {
+    async fn send(
+        &self,
+        request: BridgeRequest,
+        events: Option<mpsc::UnboundedSender<BridgeEvent>>,
+        cancel: Option<watch::Receiver<bool>>,
+    ) -> Result<BridgeResponse, BridgeError> {
+        let progress = events.map(|events| -> super::worker::ProgressFn {
+            Box::new(move |params| {
+                let _ = events.send(BridgeEvent::from_params(params));
+            })
+        });
+
+        let result = self
+            .worker
+            .request(
+                request.method(),
+                request.params()?,
+                progress,
+                cancel,
+                request.timeout(),
+            )
+            .await;
+        if let Err(BridgeError::TimedOut(limit)) = &result {
+            // The worker was told to drop the request; it is only restarted
+            // when it has stopped answering altogether
+            if self.worker.responds_within(WORKER_PING_TIMEOUT).await {
+                tracing::warn!("{} timed out after {:?}", request.method(), limit);
+            } else {
+                tracing::warn!(
+                    "{} timed out after {:?} and the proof generator worker is not responding, restarting it",
+                    request.method(),
+                    limit
+                );
+                self.worker.kill().await;
+            }
+        }
+        request.parse_response(result?)
+    }
+
+    async fn shutdown(&self) {
+        self.worker.shutdown().await;
+    }
+}
+
+/// In-process `NodeBridge` for tests
+///
+/// Answers each request with the JSON result of a handler, after emitting
+/// the given progress events, and records the commands it received.
+#[cfg(test)]
+type FakeHandler = Box<dyn Fn(&BridgeRequest) -> Result<Value, BridgeError> + Send + Sync>;
+
+#[cfg(test)]
+pub struct FakeBridge {
+    handler: FakeHandler,
+    events: Vec<Value>,
+    requests: std::sync::Mutex<Vec<String>>,
+}
+
+#[cfg(test)]
+impl FakeBridge {
+    pub fn new<F>(handler: F) -> Self
+    where
+        F: Fn(&BridgeRequest) -> Result<Value, BridgeError> + Send + Sync + 'static,
+    {
+        Self {
+            handler: Box::new(handler),
+            events: Vec::new(),
+            requests: Default::default(),
+        }
+    }
+
+    /// Emit these `progress` params before answering each request
+    pub fn with_events(mut self, events: Vec<Value>) -> Self {
+        self.events = events;
+        self
+    }
+
+    /// Commands received so far
+    pub fn methods(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+#[async_trait]
+impl NodeBridge for FakeBridge {
+    async fn send(
+        &self,
+        request: BridgeRequest,
+        events: Option<mpsc::UnboundedSender<BridgeEvent>>,
+        cancel: Option<watch::Receiver<bool>>,
+    ) -> Result<BridgeResponse, BridgeError> {
+        self.requests
+            .lock()
+            .unwrap()
+            .push(request.method().to_string());
+        request.params()?;
+
+        if let Some(events) = events {
+            for event in &self.events {
+                let _ = events.send(BridgeEvent::from_params(event.clone()));
+            }
+        }
+        if cancel.is_some_and(|cancel| *cancel.borrow()) {
+            return Err(BridgeError::Cancelled);
+        }
+
+        let result = (self.handler)(&request)?;
+        request.parse_response(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::CepWalletError;
+
+    fn proof_request(proof_type: &str) -> ProofRequest {
+        ProofRequest {
+            proof_type: proof_type.to_string(),
+            chain_id: 11155111,
+            token: None,
+            amount: None,
+            commitment: None,
+            shield_private_key: None,
+            merkle_root: None,
+            nullifier: None,
+            output_commitment: None,
+            outputs: None,
+            memo_text: None,
+            railgun_wallet_id: Some("wallet-1".to_string()),
+            encryption_key: None,
+            mnemonic: None,
+            recipient: None,
+            broadcaster_fee: None,
+            start_block: Some(100),
+        }
+    }
+
+    #[test]
+    fn test_request_methods_and_params() {
+        let request = BridgeRequest::CreateWallet {
+            chain_id: 1,
+            encryption_key: "0xkey".into(),
+            mnemonic: None,
+        };
+        assert_eq!(request.method(), "create_wallet");
+        assert_eq!(
+            request.params().unwrap(),
+            json!({ "chainId": 1, "encryptionKey": "0xkey" })
+        );
+
+        let scan = BridgeRequest::proof(proof_request("scanMerkletree")).unwrap();
+        assert_eq!(scan.method(), "scan_merkletree");
+        assert_eq!(scan.params().unwrap()["startBlock"], 100);
+
+        let shield = BridgeRequest::proof(proof_request("shield")).unwrap();
+        assert_eq!(shield.method(), "generate_shield_proof");
+        assert_eq!(shield.timeout(), Some(SHIELD_PROOF_TIMEOUT));
+        let transfer = BridgeRequest::proof(proof_request("transfer")).unwrap();
+        assert_eq!(transfer.timeout(), Some(SPEND_PROOF_TIMEOUT));
+        assert_eq!(scan.timeout(), None);
+        assert!(matches!(
+            BridgeRequest::proof(proof_request("teleport")),
+            Err(BridgeError::UnknownCommand(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_responses() {
+        let request = BridgeRequest::GetShieldKey {
+            chain_id: 11155111,
+            railgun_wallet_id: "wallet-1".to_string(),
+        };
+        let key = request
+            .parse_response(json!({ "success": true, "shieldPrivateKey": "0xabc" }))
+            .unwrap()
+            .into_shield_key()
+            .unwrap();
+        assert_eq!(key.shield_private_key.expose(), "0xabc");
+
+        let failed = request.parse_response(json!({ "success": false, "error": "No wallet" }));
+        assert!(matches!(failed, Err(BridgeError::Command(m)) if m == "No wallet"));
+
+        let wrong_shape = request.parse_response(json!({ "success": true, "shieldPrivateKey": 1 }));
+        assert!(matches!(wrong_shape, Err(BridgeError::InvalidResponse(_))));
+
+        let scan = BridgeRequest::proof(proof_request("scan_merkletree")).unwrap();
+        let response = scan
+            .parse_response(json!({ "success": true, "balances": [], "lastBlock": 42 }))
+            .unwrap();
+        assert!(response.into_wallet().is_err());
+    }
+
+    #[test]
+    fn test_broadcaster_requests() {
+        let quotes = BridgeRequest::BroadcasterQuotes {
+            chain_id: 1,
+            token: "0xtoken".to_string(),
+        };
+        assert_eq!(quotes.method(), "broadcaster_fee_quotes");
+        assert_eq!(quotes.timeout(), Some(BROADCASTER_TIMEOUT));
+        let parsed = quotes
+            .parse_response(json!({
+                "success": true,
+                "quotes": [{
+                    "railgunAddress": "0zk1broadcaster",
+                    "token": "0xtoken",
+                    "feePerUnitGas": "0x10",
+                    "feesId": "fees-1",
+                    "expiration": 1700000000000i64,
+                    "availableWallets": 1,
+                    "reliability": 0.99,
+                }],
+            }))
+            .unwrap()
+            .into_broadcaster_quotes()
+            .unwrap();
+        assert_eq!(parsed[0].fees_id, "fees-1");
+
+        let submit = BridgeRequest::BroadcasterSubmit(BroadcastRequest {
+            chain_id: 1,
+            to: "0xproxy".to_string(),
+            data: "0x".to_string(),
+            broadcaster: "0zk1broadcaster".to_string(),
+            fees_id: "fees-1".to_string(),
+            min_gas_price: "1".to_string(),
+            nullifiers: vec!["0x01".to_string()],
+            pre_transaction_pois: json!({}),
+            use_relay_adapt: false,
+        });
+        assert_eq!(submit.params().unwrap()["feesId"], "fees-1");
+        let hash = submit
+            .parse_response(json!({ "success": true, "txHash": "0xabc" }))
+            .unwrap();
+        assert_eq!(hash.into_submitted().unwrap(), "0xabc");
+        assert!(submit.parse_response(json!({ "success": true })).is_err());
+    }
+
+    #[test]
+    fn test_estimate_gas_request() {
+        let estimate = BridgeRequest::EstimateGas {
+            request: proof_request("unshield"),
+            from: Some("0xsender".to_string()),
+            max_fee_per_gas: U256::from(30_000_000_000u64),
+            max_priority_fee_per_gas: U256::from(1_000_000_000u64),
+        };
+        assert_eq!(estimate.method(), "estimate_gas");
+        let params = estimate.params().unwrap();
+        assert_eq!(params["proofType"], "unshield");
+        assert_eq!(params["fromWalletAddress"], "0xsender");
+        assert_eq!(params["maxFeePerGas"], "30000000000");
+        let gas = estimate
+            .parse_response(json!({ "success": true, "gasEstimate": "1234567" }))
+            .unwrap()
+            .into_gas_estimate()
+            .unwrap();
+        assert_eq!(gas, U256::from(1_234_567u64));
+        assert!(estimate
+            .parse_response(json!({ "success": true, "gasEstimate": 1 }))
+            .is_err());
+    }
+
+    #[test]
+    fn test_error_mapping() {
+        assert!(matches!(
+            BridgeError::from_rpc(-32601, "Unknown command: x".to_string()),
+            BridgeError::UnknownCommand(_)
+        ));
+        assert!(matches!(
+            BridgeError::from_rpc(-32000, "boom".to_string()),
+            BridgeError::Command(_)
+        ));
+
+        let invalid: CepWalletError =
+            BridgeError::from_rpc(-32602, "Wallet ID required".to_string()).into();
+        assert!(matches!(invalid, CepWalletError::InvalidInput(_)));
+        let unavailable: CepWalletError = BridgeError::Unavailable("no node".to_string()).into();
+        assert!(matches!(unavailable, CepWalletError::NotInitialized(_)));
+    }
+
+    #[test]
+    fn test_progress_events() {
+        let scan = BridgeEvent::from_params(json!({
+            "id": 3,
+            "event": "scanProgress",
+            "progress": 0.75,
+            "targetBlock": 900,
+        }));
+        match scan {
+            BridgeEvent::ScanProgress(event) => {
+                assert_eq!(event.progress, 0.75);
+                assert_eq!(event.target_block, Some(900));
+            }
+            other => panic!("unexpected: {:?}", other),
+        }
+        assert!(matches!(
+            BridgeEvent::from_params(json!({ "id": 4, "event": "proofProgress", "progress": 0.5 })),
+            BridgeEvent::ProofProgress(ProofProgressEvent { progress }) if progress == 0.5
+        ));
+        assert!(matches!(
+            BridgeEvent::from_params(json!({ "id": 3, "event": "somethingNew" })),
+            BridgeEvent::Other(_)
+        ));
+    }
+
+    #[test]
+    fn test_locate_proof_generator() {
+        let root = tempfile::tempdir().unwrap();
+        let bundled = root.path().join("resources");
+        let checkout = root.path().join("checkout");
+        for dir in [&bundled, &checkout] {
+            std::fs::create_dir_all(dir.join("proof-generator")).unwrap();
+            std::fs::write(dir.join("proof-generator/index.js"), "").unwrap();
+        }
+        let src_tauri = checkout.join("src-tauri");
+        std::fs::create_dir_all(&src_tauri).unwrap();
+
+        // Bundled resources win over the source tree
+        let found = find_proof_generator(None, Some(&bundled), Some(&src_tauri)).unwrap();
+        assert_eq!(found, bundled.join("proof-generator"));
+
+        let found = find_proof_generator(None, None, Some(&src_tauri)).unwrap();
+        assert_eq!(found, src_tauri.join("../proof-generator"));
+
+        // The override accepts the directory or the script, and never falls back
+        let script = checkout.join("proof-generator/index.js");
+        let found = find_proof_generator(Some(script), Some(&bundled), None).unwrap();
+        assert_eq!(found, checkout.join("proof-generator"));
+        assert!(matches!(
+            find_proof_generator(Some(root.path().join("missing")), Some(&bundled), None),
+            Err(BridgeError::Unavailable(_))
+        ));
+
+        assert!(find_proof_generator(None, None, Some(root.path())).is_err());
+    }
+
+    #[test]
+    fn test_railgun_db_path_moves_legacy_database() {
+        let root = tempfile::tempdir().unwrap();
+        let proof_generator = root.path().join("proof-generator");
+        let data_dir = root.path().join("data");
+        let configured = data_dir.join("railgun_db");
+
+        // Nothing to move: the configured path is used
+        std::fs::create_dir_all(&proof_generator).unwrap();
+        assert_eq!(railgun_db_path(&proof_generator, &configured), configured);
+        assert!(!configured.exists());
+
+        std::fs::create_dir_all(proof_generator.join("railgun_db")).unwrap();
+        std::fs::write(proof_generator.join("railgun_db/CURRENT"), "MANIFEST-1").unwrap();
+        assert_eq!(railgun_db_path(&proof_generator, &configured), configured);
+        assert_eq!(
+            std::fs::read_to_string(configured.join("CURRENT")).unwrap(),
+            "MANIFEST-1"
+        );
+        assert!(!proof_generator.join("railgun_db").exists());
+
+        // An existing database is never replaced
+        std::fs::create_dir_all(proof_generator.join("railgun_db")).unwrap();
+        assert_eq!(railgun_db_path(&proof_generator, &configured), configured);
+        assert!(proof_generator.join("railgun_db").exists());
+    }
+}
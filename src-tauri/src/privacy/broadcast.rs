@@ -0,0 +1,494 @@
+/// Signing and broadcasting privacy transactions
+///
+/// A proved shield (`RailgunManager::shield`) is an unsigned call: ERC-20
+/// tokens go to the RAILGUN proxy's `shield`, which pulls them from the
+/// sender, and the native token goes to relay adapt, which wraps the value
+/// sent along and shields it. Before anything is signed the call's target
+/// and value are checked against `RailgunContracts`, and an ERC-20
+/// `approve` for the proxy is added when the sender's allowance falls short.
+///
+/// Transfers and unshields proved for a public wallet go to the proxy's
+/// `transact` and are self-signed the same way; their gas is quoted first so
+/// it can be shown before submission. Those proved with a broadcaster fee
+/// are the broadcaster's to submit and are refused here.
+///
+/// Transactions are EIP-1559, signed by the connected `WalletSigner` and
+/// sent as raw transactions; gas and nonce are filled just before signing.
+use super::contracts::RailgunContracts;
+use super::types::{ShieldedTransaction, ShieldedTxType};
+use crate::error::{CepWalletError, Result};
+use crate::hardware::{encode_signed_transaction, WalletSigner};
+use ethers::abi::{self, ParamType, Token};
+use ethers::providers::{Middleware, PendingTransaction};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{Address, BlockNumber, Bytes, Eip1559TransactionRequest, H256, U256};
+use serde::Serialize;
+use serde_json::Value;
+use std::str::FromStr;
+
+/// The unsigned transactions of a shield, in broadcast order
+#[derive(Debug, Clone)]
+pub struct ShieldPlan {
+    /// ERC-20 `approve` for the proxy, when the allowance falls short
+    pub approve: Option<TypedTransaction>,
+    pub shield: TypedTransaction,
+}
+
+/// Check a proved shield and build its transactions, sent from `from`
+///
+/// Only the ERC-20 allowance is read here: the shield's gas cannot be
+/// estimated until the approval is mined.
+pub async fn plan_shield<M: Middleware>(
+    provider: &M,
+    contracts: &RailgunContracts,
+    chain_id: u64,
+    from: Address,
+    tx: &ShieldedTransaction,
+) -> Result<ShieldPlan> {
+    if tx.tx_type != ShieldedTxType::Shield {
+        return Err(CepWalletError::InvalidInput(
+            "Not a shield transaction".to_string(),
+        ));
+    }
+    let call = tx.transaction.as_ref().ok_or_else(|| {
+        CepWalletError::PrivacyError("Shield proof returned no transaction".to_string())
+    })?;
+    let (to, data, value) = parse_call(call)?;
+    let token = Address::from_str(&tx.token)
+        .map_err(|e| CepWalletError::InvalidInput(format!("Invalid token: {}", e)))?;
+    let amount = U256::from_dec_str(&tx.amount)
+        .map_err(|e| CepWalletError::InvalidInput(format!("Invalid amount: {}", e)))?;
+
+    // Native shields wrap the value sent to relay adapt
+    let native = token.is_zero();
+    let (target, expected_to) = if native {
+        ("relay adapt", contracts.relay_adapt)
+    } else {
+        ("proxy", contracts.proxy)
+    };
+    if to != expected_to {
+        return Err(invalid_call(&format!(
+            "targets {:?}, not the RAILGUN {} {:?}",
+            to, target, expected_to
+        )));
+    }
+    let expected_value = if native { amount } else { U256::zero() };
+    if value != expected_value {
+        return Err(invalid_call(&format!(
+            "sends {} wei, expected {}",
+            value, expected_value
+        )));
+    }
+
+    let approve = if native {
+        None
+    } else {
+        let allowance = allowance(provider, token, from, contracts.proxy).await?;
+        (allowance < amount).then(|| {
+            eip1559(
+                chain_id,
+                from,
+                token,
+                approve_calldata(contracts.proxy, amount),
+                U256::zero(),
+            )
+        })
+    };
+
+    Ok(ShieldPlan {
+        approve,
+        shield: eip1559(chain_id, from, to, data, value),
+    })
+}
+
+/// Check a proved transfer or unshield and build its transaction from `from`
+pub fn plan_private(
+    contracts: &RailgunContracts,
+    chain_id: u64,
+    from: Address,
+    tx: &ShieldedTransaction,
+) -> Result<TypedTransaction> {
+    if tx.tx_type == ShieldedTxType::Shield {
+        return Err(CepWalletError::InvalidInput(
+            "Shields are broadcast with their approval".to_string(),
+        ));
+    }
+    if tx.broadcaster_fee.is_some() {
+        return Err(CepWalletError::InvalidInput(
+            "Transaction was proved for a broadcaster, not a public wallet".to_string(),
+        ));
+    }
+    let call = tx
+        .transaction
+        .as_ref()
+        .ok_or_else(|| CepWalletError::PrivacyError("Proof returned no transaction".to_string()))?;
+    let (to, data, value) = parse_call(call)?;
+    if to != contracts.proxy {
+        return Err(invalid_call(&format!(
+            "targets {:?}, not the RAILGUN proxy {:?}",
+            to, contracts.proxy
+        )));
+    }
+    if !value.is_zero() {
+        return Err(invalid_call(&format!("sends {} wei, expected 0", value)));
+    }
+    Ok(eip1559(chain_id, from, to, data, value))
+}
+
+/// Gas of a transaction about to be sent (decimal strings, wei)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GasQuote {
+    pub gas_limit: String,
+    pub max_fee_per_gas: String,
+    pub max_priority_fee_per_gas: String,
+    /// Most the sender pays: `gas_limit * max_fee_per_gas`
+    pub max_cost: String,
+}
+
+impl GasQuote {
+    pub fn new(gas_limit: U256, max_fee_per_gas: U256, max_priority_fee_per_gas: U256) -> Self {
+        Self {
+            gas_limit: gas_limit.to_string(),
+            max_fee_per_gas: max_fee_per_gas.to_string(),
+            max_priority_fee_per_gas: max_priority_fee_per_gas.to_string(),
+            max_cost: gas_limit.saturating_mul(max_fee_per_gas).to_string(),
+        }
+    }
+}
+
+/// Estimate gas and fees for `tx` as `sign_and_send` will fill them
+pub async fn quote_gas<M: Middleware>(provider: &M, tx: &TypedTransaction) -> Result<GasQuote> {
+    let mut tx = tx.clone();
+    fill(provider, &mut tx).await?;
+    let gas_limit = tx.gas().copied().unwrap_or_default();
+    let (max_fee, priority_fee) = match &tx {
+        TypedTransaction::Eip1559(tx) => (
+            tx.max_fee_per_gas.unwrap_or_default(),
+            tx.max_priority_fee_per_gas.unwrap_or_default(),
+        ),
+        other => {
+            let price = other.gas_price().unwrap_or_default();
+            (price, price)
+        }
+    };
+    Ok(GasQuote::new(gas_limit, max_fee, priority_fee))
+}
+
+/// ERC-20 allowance of `spender` over `owner`'s `token`
+pub async fn allowance<M: Middleware>(
+    provider: &M,
+    token: Address,
+    owner: Address,
+    spender: Address,
+) -> Result<U256> {
+    let mut data =
+        abi::short_signature("allowance", &[ParamType::Address, ParamType::Address]).to_vec();
+    data.extend(abi::encode(&[
+        Token::Address(owner),
+        Token::Address(spender),
+    ]));
+    let call: TypedTransaction = Eip1559TransactionRequest::new().to(token).data(data).into();
+
+    let result = provider.call(&call, None).await.map_err(rpc_error)?;
+    if result.len() < 32 {
+        return Err(CepWalletError::TokenError(format!(
+            "allowance() of {:?} returned no value",
+            token
+        )));
+    }
+    Ok(U256::from_big_endian(&result[..32]))
+}
+
+/// ERC-20 `approve(spender, amount)` calldata
+pub fn approve_calldata(spender: Address, amount: U256) -> Bytes {
+    let mut data =
+        abi::short_signature("approve", &[ParamType::Address, ParamType::Uint(256)]).to_vec();
+    data.extend(abi::encode(&[Token::Address(spender), Token::Uint(amount)]));
+    data.into()
+}
+
+/// Fill gas and nonce, sign with the account at `path` and broadcast
+///
+/// Returns the transaction hash.
+pub async fn sign_and_send<M: Middleware>(
+    provider: &M,
+    signer: &dyn WalletSigner,
+    path: &str,
+    mut tx: TypedTransaction,
+) -> Result<H256> {
+    fill(provider, &mut tx).await?;
+    let signature = signer
+        .sign_transaction(path, &tx)
+        .await
+        .map_err(|e| CepWalletError::SignatureError(e.to_string()))?;
+    let raw = encode_signed_transaction(&tx, &signature)
+        .map_err(|e| CepWalletError::SignatureError(e.to_string()))?;
+
+    let pending = provider
+        .send_raw_transaction(raw)
+        .await
+        .map_err(rpc_error)?;
+    Ok(pending.tx_hash())
+}
+
+/// Wait until `tx_hash` is mined; errors if it reverted or was dropped
+pub async fn wait_for_success<M: Middleware>(provider: &M, tx_hash: H256) -> Result<()> {
+    let receipt = PendingTransaction::new(tx_hash, provider.provider())
+        .await
+        .map_err(rpc_error)?
+        .ok_or_else(|| {
+            CepWalletError::TransactionError(format!("Transaction {:?} was dropped", tx_hash))
+        })?;
+    if receipt.status != Some(1u64.into()) {
+        return Err(CepWalletError::TransactionError(format!(
+            "Transaction {:?} reverted",
+            tx_hash
+        )));
+    }
+    Ok(())
+}
+
+/// Fill the sender's pending nonce, then gas and fees
+///
+/// `Provider::fill_transaction` leaves the nonce to signing middleware,
+/// which transactions signed on a device do not go through.
+async fn fill<M: Middleware>(provider: &M, tx: &mut TypedTransaction) -> Result<()> {
+    if tx.nonce().is_none() {
+        let from = *tx
+            .from()
+            .ok_or_else(|| CepWalletError::InvalidInput("Transaction has no sender".to_string()))?;
+        let nonce = provider
+            .get_transaction_count(from, Some(BlockNumber::Pending.into()))
+            .await
+            .map_err(rpc_error)?;
+        tx.set_nonce(nonce);
+    }
+    provider.fill_transaction(tx, None).await.map_err(rpc_error)
+}
+
+fn eip1559(
+    chain_id: u64,
+    from: Address,
+    to: Address,
+    data: Bytes,
+    value: U256,
+) -> TypedTransaction {
+    Eip1559TransactionRequest::new()
+        .chain_id(chain_id)
+        .from(from)
+        .to(to)
+        .data(data)
+        .value(value)
+        .into()
+}
+
+/// Target, calldata and value of a populated call
+fn parse_call(call: &Value) -> Result<(Address, Bytes, U256)> {
+    let to = call["to"]
+        .as_str()
+        .and_then(|to| Address::from_str(to).ok())
+        .ok_or_else(|| invalid_call("missing or invalid target"))?;
+    let data = call["data"]
+        .as_str()
+        .and_then(|data| hex::decode(data.trim_start_matches("0x")).ok())
+        .ok_or_else(|| invalid_call("missing or invalid data"))?;
+    let value = quantity(&call["value"]).ok_or_else(|| invalid_call("invalid value"))?;
+    Ok((to, data.into(), value))
+}
+
+/// A decimal or `0x` hex quantity (string or number); missing is zero
+fn quantity(value: &Value) -> Option<U256> {
+    match value {
+        Value::Null => Some(U256::zero()),
+        Value::Number(n) => n.as_u64().map(U256::from),
+        Value::String(s) => match s.strip_prefix("0x") {
+            Some(hex) => U256::from_str_radix(hex, 16).ok(),
+            None => U256::from_dec_str(s).ok(),
+        },
+        _ => None,
+    }
+}
+
+fn invalid_call(reason: &str) -> CepWalletError {
+    CepWalletError::PrivacyError(format!("RAILGUN transaction {}", reason))
+}
+
+fn rpc_error(e: impl std::fmt::Display) -> CepWalletError {
+    CepWalletError::TransactionError(format!("RPC error: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::privacy::types::BroadcasterFee;
+    use ethers::providers::Provider;
+    use serde_json::json;
+
+    const WETH: &str = "0xfFf9976782d46CC05630D1f6eBAb18b2324d6B14";
+    const SENDER: &str = "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266";
+
+    fn shield_tx(token: &str, to: Address, value: &str) -> ShieldedTransaction {
+        ShieldedTransaction {
+            tx_type: ShieldedTxType::Shield,
+            token: token.to_string(),
+            amount: "1000".to_string(),
+            recipient: Some("0zk1recipient".to_string()),
+            proof: None,
+            merkle_root: None,
+            nullifier: None,
+            transaction: Some(json!({
+                "to": format!("{:?}", to),
+                "data": "0x044a40c3",
+                "value": value,
+            })),
+            history_id: None,
+            proofs: Vec::new(),
+            broadcaster_fee: None,
+            outputs: Vec::new(),
+            memo: None,
+        }
+    }
+
+    fn word(value: u64) -> Bytes {
+        abi::encode(&[Token::Uint(value.into())]).into()
+    }
+
+    #[tokio::test]
+    async fn test_plan_erc20_shield() {
+        let contracts = RailgunContracts::sepolia();
+        let from = Address::from_str(SENDER).unwrap();
+        let tx = shield_tx(WETH, contracts.proxy, "0");
+        let (provider, mock) = Provider::mocked();
+
+        // Allowance short of the amount: approve the proxy first
+        mock.push::<Bytes, _>(word(999)).unwrap();
+        let plan = plan_shield(&provider, &contracts, 11155111, from, &tx)
+            .await
+            .unwrap();
+        let approve = plan.approve.unwrap();
+        assert_eq!(approve.to_addr(), Some(&Address::from_str(WETH).unwrap()));
+        assert_eq!(approve.from(), Some(&from));
+        assert_eq!(
+            approve.data().unwrap(),
+            &approve_calldata(contracts.proxy, 1000u64.into())
+        );
+        assert_eq!(plan.shield.to_addr(), Some(&contracts.proxy));
+        assert_eq!(plan.shield.chain_id(), Some(11155111u64.into()));
+
+        mock.push::<Bytes, _>(word(1000)).unwrap();
+        let plan = plan_shield(&provider, &contracts, 11155111, from, &tx)
+            .await
+            .unwrap();
+        assert!(plan.approve.is_none());
+
+        // Calls to anything but the proxy are refused before any RPC
+        let wrong_target = shield_tx(WETH, contracts.relay_adapt, "0");
+        let err = plan_shield(&provider, &contracts, 11155111, from, &wrong_target)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("RAILGUN proxy"), "{}", err);
+        let with_value = shield_tx(WETH, contracts.proxy, "1000");
+        assert!(
+            plan_shield(&provider, &contracts, 11155111, from, &with_value)
+                .await
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_plan_private() {
+        let contracts = RailgunContracts::sepolia();
+        let from = Address::from_str(SENDER).unwrap();
+        let mut tx = shield_tx(WETH, contracts.proxy, "0");
+        tx.tx_type = ShieldedTxType::Unshield;
+
+        let planned = plan_private(&contracts, 11155111, from, &tx).unwrap();
+        assert_eq!(planned.to_addr(), Some(&contracts.proxy));
+        assert_eq!(planned.from(), Some(&from));
+        assert_eq!(planned.data().unwrap().to_string(), "0x044a40c3");
+
+        let mut relayed = tx.clone();
+        relayed.broadcaster_fee = Some(BroadcasterFee {
+            token: WETH.to_string(),
+            amount: "10".to_string(),
+            recipient: "0zk1broadcaster".to_string(),
+            min_gas_price: "1000000000".to_string(),
+            fees_id: None,
+        });
+        assert!(plan_private(&contracts, 11155111, from, &relayed).is_err());
+        let mut wrong_target = tx.clone();
+        wrong_target.transaction = shield_tx(WETH, contracts.relay_adapt, "0").transaction;
+        assert!(plan_private(&contracts, 11155111, from, &wrong_target).is_err());
+        let shield = shield_tx(WETH, contracts.proxy, "0");
+        assert!(plan_private(&contracts, 11155111, from, &shield).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_quote_gas() {
+        let contracts = RailgunContracts::sepolia();
+        let from = Address::from_str(SENDER).unwrap();
+        let tx = eip1559(11155111, from, contracts.proxy, Bytes::new(), U256::zero());
+        let (provider, mock) = Provider::mocked();
+
+        // Responses pop last-in first: nonce, block, fee history, gas
+        mock.push(U256::from(300_000u64)).unwrap();
+        mock.push(json!({
+            "oldestBlock": "0x1",
+            "baseFeePerGas": ["0x3b9aca00"],
+            "gasUsedRatio": [0.5],
+            "reward": [["0x77359400"]],
+        }))
+        .unwrap();
+        mock.push(json!({
+            "hash": format!("{:?}", H256::zero()),
+            "parentHash": format!("{:?}", H256::zero()),
+            "number": "0x1",
+            "baseFeePerGas": "0x3b9aca00",
+            "transactions": [],
+        }))
+        .unwrap();
+        mock.push(U256::from(7u64)).unwrap();
+
+        let quote = quote_gas(&provider, &tx).await.unwrap();
+        assert_eq!(quote.gas_limit, "300000");
+        let max_fee = U256::from_dec_str(&quote.max_fee_per_gas).unwrap();
+        assert!(max_fee >= U256::from(1_000_000_000u64));
+        assert_eq!(
+            quote.max_cost,
+            (max_fee * U256::from(300_000u64)).to_string()
+        );
+        // The quote fills a copy; the nonce came from the pending count
+        assert!(tx.nonce().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_plan_native_shield() {
+        let contracts = RailgunContracts::sepolia();
+        let from = Address::from_str(SENDER).unwrap();
+        let native = "0x0000000000000000000000000000000000000000";
+        // No responses queued: native shields need no allowance
+        let (provider, _mock) = Provider::mocked();
+
+        let tx = shield_tx(native, contracts.relay_adapt, "0x3e8");
+        let plan = plan_shield(&provider, &contracts, 11155111, from, &tx)
+            .await
+            .unwrap();
+        assert!(plan.approve.is_none());
+        assert_eq!(plan.shield.to_addr(), Some(&contracts.relay_adapt));
+        assert_eq!(plan.shield.value(), Some(&U256::from(1000u64)));
+
+        let short_value = shield_tx(native, contracts.relay_adapt, "999");
+        assert!(
+            plan_shield(&provider, &contracts, 11155111, from, &short_value)
+                .await
+                .is_err()
+        );
+        let to_proxy = shield_tx(native, contracts.proxy, "1000");
+        assert!(
+            plan_shield(&provider, &contracts, 11155111, from, &to_proxy)
+                .await
+                .is_err()
+        );
+    }
+}
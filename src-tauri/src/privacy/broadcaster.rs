@@ -0,0 +1,403 @@
+/// RAILGUN broadcasters (relayers)
+///
+/// A broadcaster submits transfers and unshields from its own public
+/// account, so the sender's public address never appears on-chain; it is
+/// paid in a shielded token out of the same transaction. Broadcasters
+/// advertise fee quotes over Waku: a fee per unit of gas cost, valid until
+/// its expiration, under a fees id the submission quotes back.
+///
+/// `BroadcasterClient` picks a broadcaster by fee and reliability, turns its
+/// quote into the `BroadcasterFee` a proof is generated for, and hands the
+/// populated transaction over. Waku is behind `BroadcasterTransport`: the
+/// proof generator's Waku client in the app (`BridgeTransport`), a local
+/// mock in tests.
+use super::bridge::{BridgeRequest, NodeBridge};
+use super::types::{BroadcasterFee, ShieldedTransaction, ShieldedTxType};
+use crate::error::{CepWalletError, Result};
+use async_trait::async_trait;
+use ethers::types::{H256, U256};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// Broadcasters at or above this reliability (0 - 1) are preferred
+pub const MIN_BROADCASTER_RELIABILITY: f64 = 0.8;
+
+/// Gas limit fees are quoted for when the caller has no estimate; the
+/// limit the proof generator populates transfers and unshields with
+pub const DEFAULT_RELAYED_GAS_LIMIT: u64 = 500_000;
+
+/// A broadcaster's fee quote for one token
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BroadcasterQuote {
+    /// Broadcaster's 0zk address, which receives the fee
+    pub railgun_address: String,
+    /// Fee token address
+    pub token: String,
+    /// Fee in base units of `token` per 10^18 wei of gas cost (decimal or hex)
+    pub fee_per_unit_gas: String,
+    /// Quote id the submission must carry
+    pub fees_id: String,
+    /// Quote expiry (unix milliseconds)
+    pub expiration: i64,
+    /// Public wallets the broadcaster has free to send with
+    pub available_wallets: u32,
+    /// Share of the broadcaster's recent submissions that were mined (0 - 1)
+    pub reliability: f64,
+}
+
+impl BroadcasterQuote {
+    /// Unexpired at `now_ms`, with a wallet free to send
+    pub fn is_usable(&self, now_ms: i64) -> bool {
+        self.expiration > now_ms && self.available_wallets > 0
+    }
+
+    fn fee_rate(&self) -> Result<U256> {
+        let rate = &self.fee_per_unit_gas;
+        let parsed = match rate.strip_prefix("0x") {
+            Some(hex) => U256::from_str_radix(hex, 16).ok(),
+            None => U256::from_dec_str(rate).ok(),
+        };
+        parsed.ok_or_else(|| {
+            CepWalletError::PrivacyError(format!("Invalid broadcaster fee rate: {}", rate))
+        })
+    }
+
+    /// Fee for a transaction of up to `gas_limit` at `gas_price` (wei)
+    ///
+    /// The broadcaster must send at `gas_price` or above; the proof is bound
+    /// to it as its minimum gas price.
+    pub fn fee(&self, gas_limit: U256, gas_price: U256) -> Result<BroadcasterFee> {
+        let gas_cost = gas_limit.saturating_mul(gas_price);
+        let amount = self.fee_rate()?.saturating_mul(gas_cost) / U256::exp10(18);
+        Ok(BroadcasterFee {
+            token: self.token.clone(),
+            amount: amount.to_string(),
+            recipient: self.railgun_address.clone(),
+            min_gas_price: gas_price.to_string(),
+            fees_id: Some(self.fees_id.clone()),
+        })
+    }
+}
+
+/// Pick the broadcaster for `token`: the cheapest usable quote, among the
+/// reliable ones when there are any, ties going to the more reliable
+pub fn select_broadcaster<'a>(
+    quotes: &'a [BroadcasterQuote],
+    token: &str,
+    now_ms: i64,
+) -> Option<&'a BroadcasterQuote> {
+    let usable: Vec<(&BroadcasterQuote, U256)> = quotes
+        .iter()
+        .filter(|quote| quote.token.eq_ignore_ascii_case(token) && quote.is_usable(now_ms))
+        .filter_map(|quote| quote.fee_rate().ok().map(|rate| (quote, rate)))
+        .collect();
+    let reliable = usable
+        .iter()
+        .any(|(quote, _)| quote.reliability >= MIN_BROADCASTER_RELIABILITY);
+
+    usable
+        .into_iter()
+        .filter(|(quote, _)| !reliable || quote.reliability >= MIN_BROADCASTER_RELIABILITY)
+        .min_by(|(a, a_rate), (b, b_rate)| {
+            a_rate
+                .cmp(b_rate)
+                .then(b.reliability.total_cmp(&a.reliability))
+        })
+        .map(|(quote, _)| quote)
+}
+
+/// A populated transaction handed to a broadcaster
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BroadcastRequest {
+    pub chain_id: u64,
+    pub to: String,
+    pub data: String,
+    /// Broadcaster's 0zk address
+    pub broadcaster: String,
+    pub fees_id: String,
+    /// Gas price (wei) the proof was bound to
+    pub min_gas_price: String,
+    /// Nullifiers the transaction spends, checked against the broadcaster's
+    /// view of the merkletree
+    pub nullifiers: Vec<String>,
+    /// Proofs of innocence per list, as the SDK populated them
+    pub pre_transaction_pois: Value,
+    pub use_relay_adapt: bool,
+}
+
+impl BroadcastRequest {
+    /// Request for a transfer or unshield proved for a broadcaster
+    pub fn for_transaction(chain_id: u64, tx: &ShieldedTransaction) -> Result<Self> {
+        if tx.tx_type == ShieldedTxType::Shield {
+            return Err(CepWalletError::InvalidInput(
+                "Shields are sent from a public wallet, not a broadcaster".to_string(),
+            ));
+        }
+        let fee = tx.broadcaster_fee.as_ref().ok_or_else(|| {
+            CepWalletError::InvalidInput("Transaction was not proved for a broadcaster".to_string())
+        })?;
+        let fees_id = fee.fees_id.clone().ok_or_else(|| {
+            CepWalletError::InvalidInput("Broadcaster fee has no quote id".to_string())
+        })?;
+        let call = tx.transaction.as_ref().ok_or_else(|| {
+            CepWalletError::PrivacyError("Proof returned no transaction".to_string())
+        })?;
+        let field = |name: &str| {
+            call[name].as_str().map(str::to_string).ok_or_else(|| {
+                CepWalletError::PrivacyError(format!("Populated transaction has no `{}`", name))
+            })
+        };
+        let nullifiers = call["nullifiers"]
+            .as_array()
+            .map(|nullifiers| {
+                nullifiers
+                    .iter()
+                    .filter_map(|n| n.as_str().map(str::to_string))
+                    .collect::<Vec<_>>()
+            })
+            .filter(|nullifiers| !nullifiers.is_empty())
+            .ok_or_else(|| {
+                CepWalletError::PrivacyError("Populated transaction has no nullifiers".to_string())
+            })?;
+
+        Ok(Self {
+            chain_id,
+            to: field("to")?,
+            data: field("data")?,
+            broadcaster: fee.recipient.clone(),
+            fees_id,
+            min_gas_price: fee.min_gas_price.clone(),
+            nullifiers,
+            pre_transaction_pois: call
+                .get("preTransactionPOIsPerTxidLeafPerList")
+                .cloned()
+                .unwrap_or_else(|| Value::Object(Default::default())),
+            use_relay_adapt: false,
+        })
+    }
+}
+
+/// Channel to the broadcaster network
+#[async_trait]
+pub trait BroadcasterTransport: Send + Sync {
+    /// Quotes currently advertised for `token` on `chain_id`
+    async fn fee_quotes(&self, chain_id: u64, token: &str) -> Result<Vec<BroadcasterQuote>>;
+
+    /// Hand over a transaction; returns the hash the broadcaster sent it as
+    async fn submit(&self, request: &BroadcastRequest) -> Result<String>;
+}
+
+/// Waku transport of the proof generator
+pub struct BridgeTransport {
+    bridge: Arc<dyn NodeBridge>,
+}
+
+impl BridgeTransport {
+    pub fn new(bridge: Arc<dyn NodeBridge>) -> Self {
+        Self { bridge }
+    }
+}
+
+#[async_trait]
+impl BroadcasterTransport for BridgeTransport {
+    async fn fee_quotes(&self, chain_id: u64, token: &str) -> Result<Vec<BroadcasterQuote>> {
+        let request = BridgeRequest::BroadcasterQuotes {
+            chain_id,
+            token: token.to_string(),
+        };
+        Ok(self.bridge.call(request).await?.into_broadcaster_quotes()?)
+    }
+
+    async fn submit(&self, request: &BroadcastRequest) -> Result<String> {
+        let request = BridgeRequest::BroadcasterSubmit(request.clone());
+        Ok(self.bridge.call(request).await?.into_submitted()?)
+    }
+}
+
+/// Broadcaster client for one chain
+#[derive(Clone)]
+pub struct BroadcasterClient {
+    chain_id: u64,
+    transport: Arc<dyn BroadcasterTransport>,
+}
+
+impl BroadcasterClient {
+    pub fn new(chain_id: u64, transport: Arc<dyn BroadcasterTransport>) -> Self {
+        Self {
+            chain_id,
+            transport,
+        }
+    }
+
+    /// Quotes currently advertised for `token`
+    pub async fn quotes(&self, token: &str) -> Result<Vec<BroadcasterQuote>> {
+        self.transport.fee_quotes(self.chain_id, token).await
+    }
+
+    /// Best usable broadcaster for fees in `token` (see `select_broadcaster`)
+    pub async fn select(&self, token: &str) -> Result<BroadcasterQuote> {
+        let quotes = self.quotes(token).await?;
+        let now_ms = chrono::Utc::now().timestamp_millis();
+        select_broadcaster(&quotes, token, now_ms)
+            .cloned()
+            .ok_or_else(|| {
+                CepWalletError::PrivacyError(format!(
+                    "No broadcaster available for token {}",
+                    token
+                ))
+            })
+    }
+
+    /// Hand a transfer or unshield proved for a broadcaster over
+    ///
+    /// Returns the hash the broadcaster sent it as; the result is tracked
+    /// on-chain like any other transaction.
+    pub async fn submit(&self, tx: &ShieldedTransaction) -> Result<H256> {
+        let request = BroadcastRequest::for_transaction(self.chain_id, tx)?;
+        let hash = self.transport.submit(&request).await?;
+        H256::from_str(&hash).map_err(|_| {
+            CepWalletError::TransactionError(format!(
+                "Broadcaster returned an invalid transaction hash: {}",
+                hash
+            ))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::sync::Mutex;
+
+    const WETH: &str = "0xfFf9976782d46CC05630D1f6eBAb18b2324d6B14";
+    const NOW_MS: i64 = 1_700_000_000_000;
+
+    /// Local broadcaster: fixed quotes, records what it is handed
+    #[derive(Default)]
+    struct MockBroadcaster {
+        quotes: Vec<BroadcasterQuote>,
+        submitted: Mutex<Vec<BroadcastRequest>>,
+    }
+
+    #[async_trait]
+    impl BroadcasterTransport for MockBroadcaster {
+        async fn fee_quotes(&self, _chain_id: u64, token: &str) -> Result<Vec<BroadcasterQuote>> {
+            Ok(self
+                .quotes
+                .iter()
+                .filter(|quote| quote.token.eq_ignore_ascii_case(token))
+                .cloned()
+                .collect())
+        }
+
+        async fn submit(&self, request: &BroadcastRequest) -> Result<String> {
+            self.submitted.lock().unwrap().push(request.clone());
+            Ok(format!("{:?}", H256::repeat_byte(0xab)))
+        }
+    }
+
+    fn quote(address: &str, rate: &str, reliability: f64, expiration: i64) -> BroadcasterQuote {
+        BroadcasterQuote {
+            railgun_address: address.to_string(),
+            token: WETH.to_string(),
+            fee_per_unit_gas: rate.to_string(),
+            fees_id: format!("fees-{}", address),
+            expiration,
+            available_wallets: 2,
+            reliability,
+        }
+    }
+
+    #[test]
+    fn test_select_broadcaster() {
+        let later = NOW_MS + 60_000;
+        let mut busy = quote("0zk1busy", "1", 1.0, later);
+        busy.available_wallets = 0;
+        let quotes = vec![
+            quote("0zk1expired", "1", 1.0, NOW_MS),
+            busy,
+            quote("0zk1unreliable", "2", 0.5, later),
+            quote("0zk1reliable", "3", 0.9, later),
+            quote("0zk1best", "0x3", 0.95, later),
+        ];
+
+        // Cheapest reliable quote; ties go to the more reliable broadcaster
+        let selected = select_broadcaster(&quotes, &WETH.to_lowercase(), NOW_MS).unwrap();
+        assert_eq!(selected.railgun_address, "0zk1best");
+
+        // Without reliable broadcasters the cheapest usable one is taken
+        let selected = select_broadcaster(&quotes[..3], WETH, NOW_MS).unwrap();
+        assert_eq!(selected.railgun_address, "0zk1unreliable");
+        assert!(select_broadcaster(&quotes[..2], WETH, NOW_MS).is_none());
+        assert!(select_broadcaster(&quotes, "0xother", NOW_MS).is_none());
+    }
+
+    #[test]
+    fn test_broadcaster_fee() {
+        // 2000 token units per 10^18 wei: 500k gas at 20 gwei is 0.01 ETH
+        let rate = (U256::from(2000u64) * U256::exp10(18)).to_string();
+        let fee = quote("0zk1broadcaster", &rate, 1.0, NOW_MS)
+            .fee(500_000u64.into(), 20_000_000_000u64.into())
+            .unwrap();
+        assert_eq!(fee.amount, "20000000000000000000");
+        assert_eq!(fee.min_gas_price, "20000000000");
+        assert_eq!(fee.recipient, "0zk1broadcaster");
+        assert_eq!(fee.fees_id.as_deref(), Some("fees-0zk1broadcaster"));
+
+        let invalid = quote("0zk1broadcaster", "lots", 1.0, NOW_MS);
+        assert!(invalid.fee(1u64.into(), 1u64.into()).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_submit_through_mock_broadcaster() {
+        let later = chrono::Utc::now().timestamp_millis() + 60_000;
+        let transport = Arc::new(MockBroadcaster {
+            quotes: vec![quote("0zk1broadcaster", "1000", 1.0, later)],
+            ..Default::default()
+        });
+        let client = BroadcasterClient::new(11155111, transport.clone());
+
+        let selected = client.select(WETH).await.unwrap();
+        let fee = selected.fee(500_000u64.into(), 1u64.into()).unwrap();
+        let mut tx = ShieldedTransaction {
+            tx_type: ShieldedTxType::Unshield,
+            token: WETH.to_string(),
+            amount: "1000".to_string(),
+            recipient: Some("0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266".to_string()),
+            proof: None,
+            merkle_root: None,
+            nullifier: None,
+            transaction: Some(json!({
+                "to": "0xeCFCf3b4eC647c4Ca6D49108b311b7a7C9543fea",
+                "data": "0xd8ae136a",
+                "value": "0",
+                "nullifiers": ["0x01", "0x02"],
+            })),
+            history_id: None,
+            proofs: Vec::new(),
+            broadcaster_fee: None,
+            outputs: Vec::new(),
+            memo: None,
+        };
+
+        // Self-signed transactions are not handed to broadcasters
+        assert!(client.submit(&tx).await.is_err());
+
+        tx.broadcaster_fee = Some(fee);
+        let hash = client.submit(&tx).await.unwrap();
+        assert_eq!(hash, H256::repeat_byte(0xab));
+
+        let submitted = transport.submitted.lock().unwrap();
+        assert_eq!(submitted.len(), 1);
+        assert_eq!(submitted[0].broadcaster, "0zk1broadcaster");
+        assert_eq!(submitted[0].fees_id, "fees-0zk1broadcaster");
+        assert_eq!(submitted[0].min_gas_price, "1");
+        assert_eq!(submitted[0].nullifiers, ["0x01", "0x02"]);
+        assert_eq!(submitted[0].data, "0xd8ae136a");
+    }
+}
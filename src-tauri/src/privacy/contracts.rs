@@ -3,49 +3,42 @@
 /// This module contains deployed RAILGUN contract addresses for various networks.
 ///
 /// RAILGUN v3.0 Architecture:
-/// - RailgunLogic: Core privacy logic
-/// - RailgunProxy: Upgradeable proxy
-/// - Treasury: Fee collection
-/// - Relayer: Transaction relay for privacy
+/// - RailgunSmartWallet proxy: Shield, transact and unshield entry point;
+///   ERC-20 shields are pulled from the sender, so it is the approval spender
+/// - RelayAdapt: Wraps the native token and shields it in one call, and
+///   relays transactions with cross-contract calls
 use ethers::types::Address;
 use std::str::FromStr;
 
 /// RAILGUN contract addresses for a specific network
 #[derive(Debug, Clone)]
 pub struct RailgunContracts {
-    /// Main logic contract address
-    pub logic: Address,
-    /// Proxy contract address (user-facing)
+    /// RailgunSmartWallet proxy contract address (user-facing)
     pub proxy: Address,
-    /// Treasury address for fee collection
-    pub treasury: Address,
+    /// RelayAdapt contract address (native token shields)
+    pub relay_adapt: Address,
+    /// Wrapped native token; native shields land as this token
+    pub wrapped_native: Address,
 }
 
 impl RailgunContracts {
     /// Ethereum Mainnet RAILGUN v3.0 contracts
     pub fn mainnet() -> Self {
         Self {
-            // RAILGUN Logic v3.0
-            logic: Address::from_str("0xFA7093CDD9EE6932B4eb2c9e1cde7CE00B1FA4b9")
-                .expect("Invalid RAILGUN Logic address"),
-            // RAILGUN Proxy
-            proxy: Address::from_str("0x3b5FFC4842Eb34e28Faff7E6c08Bdc7dE88d1a28")
-                .expect("Invalid RAILGUN Proxy address"),
-            // Treasury
-            treasury: Address::from_str("0x...").expect("Invalid Treasury address"),
+            proxy: address("0xFA7093CDD9EE6932B4eb2c9e1cde7CE00B1FA4b9"),
+            relay_adapt: address("0x4025ee6512DBbda97049Bcf5AA5D38C54aF6bE8a"),
+            // WETH
+            wrapped_native: address("0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2"),
         }
     }
 
     /// Sepolia Testnet RAILGUN contracts
     pub fn sepolia() -> Self {
         Self {
-            // TODO: Update with actual Sepolia addresses when deployed
-            logic: Address::from_str("0x0000000000000000000000000000000000000000")
-                .expect("Invalid address"),
-            proxy: Address::from_str("0x0000000000000000000000000000000000000000")
-                .expect("Invalid address"),
-            treasury: Address::from_str("0x0000000000000000000000000000000000000000")
-                .expect("Invalid address"),
+            proxy: address("0xeCFCf3b4eC647c4Ca6D49108b311b7a7C9543fea"),
+            relay_adapt: address("0x7e3d929EbD5bDC84d02Bd3205c777578f33A214D"),
+            // WETH
+            wrapped_native: address("0xfFf9976782d46CC05630D1f6eBAb18b2324d6B14"),
         }
     }
 
@@ -64,39 +57,8 @@ impl RailgunContracts {
     }
 }
 
-/// Token addresses for common ERC-20 tokens
-#[derive(Debug, Clone)]
-pub struct CommonTokens;
-
-impl CommonTokens {
-    /// ETH (native token, represented as 0x0)
-    pub fn eth() -> Address {
-        Address::zero()
-    }
-
-    /// USDC (Ethereum Mainnet)
-    pub fn usdc_mainnet() -> Address {
-        Address::from_str("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48")
-            .expect("Invalid USDC address")
-    }
-
-    /// USDT (Ethereum Mainnet)
-    pub fn usdt_mainnet() -> Address {
-        Address::from_str("0xdAC17F958D2ee523a2206206994597C13D831ec7")
-            .expect("Invalid USDT address")
-    }
-
-    /// DAI (Ethereum Mainnet)
-    pub fn dai_mainnet() -> Address {
-        Address::from_str("0x6B175474E89094C44Da98b954EedeAC495271d0F")
-            .expect("Invalid DAI address")
-    }
-
-    /// WETH (Wrapped ETH)
-    pub fn weth_mainnet() -> Address {
-        Address::from_str("0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2")
-            .expect("Invalid WETH address")
-    }
+fn address(value: &str) -> Address {
+    Address::from_str(value).expect("Invalid RAILGUN contract address")
 }
 
 #[cfg(test)]
@@ -106,8 +68,10 @@ mod tests {
     #[test]
     fn test_mainnet_contracts() {
         let contracts = RailgunContracts::mainnet();
-        assert_ne!(contracts.logic, Address::zero());
         assert_ne!(contracts.proxy, Address::zero());
+        assert_ne!(contracts.relay_adapt, Address::zero());
+        assert_ne!(contracts.relay_adapt, contracts.proxy);
+        assert_eq!(contracts.main_address(), contracts.proxy);
     }
 
     #[test]
@@ -121,10 +85,4 @@ mod tests {
         let unknown = RailgunContracts::for_chain_id(999);
         assert!(unknown.is_none());
     }
-
-    #[test]
-    fn test_common_tokens() {
-        assert_eq!(CommonTokens::eth(), Address::zero());
-        assert_ne!(CommonTokens::usdc_mainnet(), Address::zero());
-    }
 }
@@ -0,0 +1,310 @@
+/// Proof envelope
+///
+/// A Groth16 proof with what is needed to check and replay it: proof type,
+/// circuit, public signals and creation time, under a format version.
+/// Envelopes are what the prover returns, the verifier accepts, proof jobs
+/// store and users export. Two encodings round-trip:
+///
+/// - JSON: snarkjs `fullProve` output (`proof`, `publicSignals`) plus the
+///   metadata fields, so snarkjs tooling reads it as-is
+/// - binary: compact, with field elements as 32-byte words
+///
+/// ```text
+/// "CWZK" | version (u8) | proof type (u8) | created_at (i64 BE)
+///        | circuit length (u8) | circuit (ASCII)
+///        | signal count (u16 BE) | signals (32-byte BE words)
+///        | proof (256 bytes, calldata layout)
+/// ```
+use super::verifier::{parse_field_element, parse_public_inputs};
+use super::zkproof::{FullProof, ProofType, SnarkjsProof, PROOF_BYTES};
+use crate::error::{CepWalletError, Result};
+use ark_ff::{BigInteger, PrimeField};
+use num_bigint::BigUint;
+use serde::{Deserialize, Serialize};
+
+/// Current envelope format version
+pub const PROOF_ENVELOPE_VERSION: u8 = 1;
+
+/// Leading bytes of the binary encoding
+const MAGIC: &[u8; 4] = b"CWZK";
+
+/// A proof with its metadata
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProofEnvelope {
+    pub version: u8,
+    pub proof_type: ProofType,
+    /// Circuit the proof was made with (e.g. `01x02`)
+    pub circuit: String,
+    /// Public signals (decimal field elements)
+    pub public_signals: Vec<String>,
+    pub proof: SnarkjsProof,
+    /// Creation time (unix seconds)
+    pub created_at: i64,
+}
+
+/// Export encoding of an envelope
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProofEncoding {
+    /// snarkjs-compatible JSON
+    Json,
+    /// Binary encoding as `0x` hex
+    Binary,
+}
+
+impl ProofEnvelope {
+    /// Wrap a freshly generated `proof` of `proof_type` made with `circuit`
+    pub fn new(proof_type: ProofType, circuit: &str, proof: FullProof) -> Self {
+        Self {
+            version: PROOF_ENVELOPE_VERSION,
+            proof_type,
+            circuit: circuit.to_string(),
+            public_signals: proof.public_signals,
+            proof: proof.proof,
+            created_at: chrono::Utc::now().timestamp(),
+        }
+    }
+
+    /// The proof and public signals, as snarkjs `groth16.fullProve` returns
+    pub fn full_proof(&self) -> FullProof {
+        FullProof {
+            proof: self.proof.clone(),
+            public_signals: self.public_signals.clone(),
+        }
+    }
+
+    /// Check the version, circuit name, points and public signals
+    pub fn validate(&self) -> Result<()> {
+        if self.version != PROOF_ENVELOPE_VERSION {
+            return Err(envelope_error(&format!(
+                "unsupported version {}",
+                self.version
+            )));
+        }
+        if self.circuit.is_empty()
+            || self.circuit.len() > u8::MAX as usize
+            || !self
+                .circuit
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b == b'_')
+        {
+            return Err(envelope_error(&format!(
+                "invalid circuit name {:?}",
+                self.circuit
+            )));
+        }
+        if self.public_signals.len() > u16::MAX as usize {
+            return Err(envelope_error("too many public signals"));
+        }
+        parse_public_inputs(&self.public_signals)?;
+        self.proof.to_ark()?;
+        Ok(())
+    }
+
+    /// snarkjs-compatible JSON
+    pub fn to_json(&self) -> Result<String> {
+        self.validate()?;
+        serde_json::to_string_pretty(self).map_err(|e| envelope_error(&e.to_string()))
+    }
+
+    /// Inverse of [`ProofEnvelope::to_json`]
+    pub fn from_json(json: &str) -> Result<Self> {
+        let envelope: Self =
+            serde_json::from_str(json).map_err(|e| envelope_error(&e.to_string()))?;
+        envelope.validate()?;
+        Ok(envelope)
+    }
+
+    /// Binary encoding (see the module docs)
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        self.validate()?;
+        let signals = parse_public_inputs(&self.public_signals)?;
+
+        let mut bytes = Vec::with_capacity(
+            MAGIC.len() + 13 + self.circuit.len() + 32 * signals.len() + PROOF_BYTES,
+        );
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(self.version);
+        bytes.push(proof_type_tag(&self.proof_type));
+        bytes.extend_from_slice(&self.created_at.to_be_bytes());
+        bytes.push(self.circuit.len() as u8);
+        bytes.extend_from_slice(self.circuit.as_bytes());
+        bytes.extend_from_slice(&(signals.len() as u16).to_be_bytes());
+        for signal in signals {
+            bytes.extend(signal.into_bigint().to_bytes_be());
+        }
+        bytes.extend(self.proof.to_bytes()?);
+        Ok(bytes)
+    }
+
+    /// Inverse of [`ProofEnvelope::to_bytes`]
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let mut reader = Reader(bytes);
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(envelope_error("not a proof envelope"));
+        }
+        let version = reader.take(1)?[0];
+        if version != PROOF_ENVELOPE_VERSION {
+            return Err(envelope_error(&format!("unsupported version {}", version)));
+        }
+        let proof_type = proof_type_from_tag(reader.take(1)?[0])?;
+        let created_at = i64::from_be_bytes(reader.take(8)?.try_into().unwrap());
+        let circuit_len = reader.take(1)?[0] as usize;
+        let circuit = String::from_utf8(reader.take(circuit_len)?.to_vec())
+            .map_err(|_| envelope_error("invalid circuit name"))?;
+        let signal_count = u16::from_be_bytes(reader.take(2)?.try_into().unwrap()) as usize;
+        let public_signals = (0..signal_count)
+            .map(|_| {
+                let signal = BigUint::from_bytes_be(reader.take(32)?).to_string();
+                parse_field_element(&signal)?;
+                Ok(signal)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let proof = SnarkjsProof::from_bytes(reader.take(PROOF_BYTES)?)?;
+        if !reader.0.is_empty() {
+            return Err(envelope_error("trailing bytes"));
+        }
+
+        let envelope = Self {
+            version,
+            proof_type,
+            circuit,
+            public_signals,
+            proof,
+            created_at,
+        };
+        envelope.validate()?;
+        Ok(envelope)
+    }
+
+    /// Encode for export: JSON text or `0x` hex of the binary encoding
+    pub fn export(&self, encoding: ProofEncoding) -> Result<String> {
+        match encoding {
+            ProofEncoding::Json => self.to_json(),
+            ProofEncoding::Binary => Ok(format!("0x{}", hex::encode(self.to_bytes()?))),
+        }
+    }
+
+    /// Decode an exported envelope in either encoding
+    pub fn import(data: &str) -> Result<Self> {
+        let data = data.trim();
+        match data.strip_prefix("0x") {
+            Some(hex) => Self::from_bytes(
+                &hex::decode(hex).map_err(|e| envelope_error(&format!("invalid hex: {}", e)))?,
+            ),
+            None => Self::from_json(data),
+        }
+    }
+}
+
+/// Binary tag of a proof type (stable across versions)
+fn proof_type_tag(proof_type: &ProofType) -> u8 {
+    match proof_type {
+        ProofType::Shield => 0,
+        ProofType::Transfer => 1,
+        ProofType::Unshield => 2,
+        ProofType::PoolMembership => 3,
+        ProofType::Compliance => 4,
+    }
+}
+
+fn proof_type_from_tag(tag: u8) -> Result<ProofType> {
+    match tag {
+        0 => Ok(ProofType::Shield),
+        1 => Ok(ProofType::Transfer),
+        2 => Ok(ProofType::Unshield),
+        3 => Ok(ProofType::PoolMembership),
+        4 => Ok(ProofType::Compliance),
+        _ => Err(envelope_error(&format!("unknown proof type {}", tag))),
+    }
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8]> {
+        if self.0.len() < n {
+            return Err(envelope_error("truncated"));
+        }
+        let (head, rest) = self.0.split_at(n);
+        self.0 = rest;
+        Ok(head)
+    }
+}
+
+fn envelope_error(reason: &str) -> CepWalletError {
+    CepWalletError::ZKProofError(format!("Invalid proof envelope: {}", reason))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::privacy::zkproof::Circuit;
+    use serde_json::{json, Value};
+
+    const MULTIPLIER_ZKEY: &[u8] = include_bytes!("../../fixtures/circuits/multiplier2.zkey");
+    const MULTIPLIER_WASM: &[u8] = include_bytes!("../../fixtures/circuits/multiplier2.wasm");
+
+    /// A compliance envelope carrying a real multiplier proof (`3 * 11`)
+    pub(crate) fn multiplier_envelope() -> ProofEnvelope {
+        let proof = Circuit::new(MULTIPLIER_ZKEY, MULTIPLIER_WASM)
+            .unwrap()
+            .prove(&json!({"a": 3, "b": 11}))
+            .unwrap();
+        ProofEnvelope::new(ProofType::Compliance, "compliance", proof)
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let envelope = multiplier_envelope();
+        let json = envelope.to_json().unwrap();
+        assert_eq!(ProofEnvelope::from_json(&json).unwrap(), envelope);
+
+        // snarkjs reads the proof and public signals as-is
+        let value: Value = serde_json::from_str(&json).unwrap();
+        let full: FullProof = serde_json::from_value(value.clone()).unwrap();
+        assert_eq!(full, envelope.full_proof());
+        assert_eq!(value["proofType"], "compliance");
+        assert_eq!(value["version"], 1);
+
+        let mut future = value;
+        future["version"] = json!(2);
+        assert!(ProofEnvelope::from_json(&future.to_string()).is_err());
+    }
+
+    #[test]
+    fn test_binary_round_trip() {
+        let envelope = multiplier_envelope();
+        let bytes = envelope.to_bytes().unwrap();
+        assert_eq!(&bytes[..4], b"CWZK");
+        assert_eq!(bytes.len(), 4 + 13 + "compliance".len() + 32 + PROOF_BYTES);
+        assert_eq!(ProofEnvelope::from_bytes(&bytes).unwrap(), envelope);
+
+        assert!(ProofEnvelope::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(ProofEnvelope::from_bytes(&[bytes.as_slice(), &[0]].concat()).is_err());
+        let mut unknown_type = bytes.clone();
+        unknown_type[5] = 9;
+        assert!(ProofEnvelope::from_bytes(&unknown_type).is_err());
+
+        // Hex signals are normalized to decimal
+        let mut hex_signal = envelope.clone();
+        hex_signal.public_signals = vec!["0x21".to_string()];
+        let decoded = ProofEnvelope::from_bytes(&hex_signal.to_bytes().unwrap()).unwrap();
+        assert_eq!(decoded, envelope);
+    }
+
+    #[test]
+    fn test_export_import() {
+        let envelope = multiplier_envelope();
+        for encoding in [ProofEncoding::Json, ProofEncoding::Binary] {
+            let exported = envelope.export(encoding).unwrap();
+            assert_eq!(ProofEnvelope::import(&exported).unwrap(), envelope);
+        }
+        assert!(ProofEnvelope::import("0xzz").is_err());
+
+        let mut bad_circuit = envelope;
+        bad_circuit.circuit = "../01x02".to_string();
+        assert!(bad_circuit.export(ProofEncoding::Json).is_err());
+    }
+}
@@ -0,0 +1,154 @@
+/// Privacy transaction fees
+///
+/// RAILGUN takes a protocol fee out of every shield and unshield, in the
+/// token moved; private transfers pay none. Network gas comes from the SDK's
+/// gas estimate of the exact operation (see `ffi::estimate_gas`).
+use super::broadcast::GasQuote;
+use super::types::{BroadcasterFee, ShieldedTxType};
+use ethers::types::U256;
+use serde::Serialize;
+
+/// RAILGUN shield fee (basis points)
+pub const RAILGUN_SHIELD_FEE_BPS: u64 = 25;
+
+/// RAILGUN unshield fee (basis points)
+pub const RAILGUN_UNSHIELD_FEE_BPS: u64 = 25;
+
+const BASIS_POINTS: u64 = 10_000;
+
+/// Protocol fee rate of an operation (basis points)
+pub fn protocol_fee_bps(tx_type: &ShieldedTxType) -> u64 {
+    match tx_type {
+        ShieldedTxType::Shield => RAILGUN_SHIELD_FEE_BPS,
+        ShieldedTxType::Transfer | ShieldedTxType::Receive => 0,
+        ShieldedTxType::Unshield => RAILGUN_UNSHIELD_FEE_BPS,
+    }
+}
+
+/// Protocol fee taken out of `amount`
+///
+/// The fee is inclusive, as `RailgunLogic.getFee`: the note (shield) or the
+/// recipient (unshield) gets `amount - fee`.
+pub fn protocol_fee(tx_type: &ShieldedTxType, amount: U256) -> U256 {
+    amount.saturating_mul(U256::from(protocol_fee_bps(tx_type))) / U256::from(BASIS_POINTS)
+}
+
+/// Cost breakdown of a shield, transfer or unshield
+///
+/// Amounts are decimal strings: `amount`, `protocol_fee` and `net_amount` in
+/// base units of `token`, `gas` in wei.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrivacyFeeEstimate {
+    pub tx_type: ShieldedTxType,
+    pub token: String,
+    /// Amount sent
+    pub amount: String,
+    pub protocol_fee_bps: u64,
+    pub protocol_fee: String,
+    /// Network gas of the operation; paid by the sending public wallet, or
+    /// covered by `broadcaster_fee` when a broadcaster sends it
+    pub gas: GasQuote,
+    /// Fee paid to the broadcaster out of the shielded balance, if relayed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub broadcaster_fee: Option<BroadcasterFee>,
+    /// Amount the recipient receives
+    pub net_amount: String,
+}
+
+impl PrivacyFeeEstimate {
+    pub fn new(
+        tx_type: ShieldedTxType,
+        token: &str,
+        amount: U256,
+        gas: GasQuote,
+        broadcaster_fee: Option<BroadcasterFee>,
+    ) -> Self {
+        let fee = protocol_fee(&tx_type, amount);
+        Self {
+            protocol_fee_bps: protocol_fee_bps(&tx_type),
+            tx_type,
+            token: token.to_string(),
+            amount: amount.to_string(),
+            protocol_fee: fee.to_string(),
+            gas,
+            broadcaster_fee,
+            net_amount: (amount - fee).to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gas() -> GasQuote {
+        GasQuote {
+            gas_limit: "1200000".to_string(),
+            max_fee_per_gas: "20000000000".to_string(),
+            max_priority_fee_per_gas: "1000000000".to_string(),
+            max_cost: "24000000000000000".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_protocol_fee() {
+        let amount = U256::exp10(18);
+        assert_eq!(
+            protocol_fee(&ShieldedTxType::Shield, amount),
+            U256::from(2_500_000_000_000_000u64)
+        );
+        assert_eq!(
+            protocol_fee(&ShieldedTxType::Unshield, amount),
+            U256::from(2_500_000_000_000_000u64)
+        );
+        assert_eq!(
+            protocol_fee(&ShieldedTxType::Transfer, amount),
+            U256::zero()
+        );
+        // Rounds down, in the recipient's favour
+        assert_eq!(
+            protocol_fee(&ShieldedTxType::Shield, U256::from(399u64)),
+            U256::zero()
+        );
+        assert_eq!(
+            protocol_fee(&ShieldedTxType::Shield, U256::from(400u64)),
+            U256::one()
+        );
+    }
+
+    #[test]
+    fn test_fee_estimate() {
+        let unshield = PrivacyFeeEstimate::new(
+            ShieldedTxType::Unshield,
+            "0xtoken",
+            U256::from(1_000_000u64),
+            gas(),
+            None,
+        );
+        assert_eq!(unshield.protocol_fee_bps, 25);
+        assert_eq!(unshield.protocol_fee, "2500");
+        assert_eq!(unshield.net_amount, "997500");
+
+        let fee = BroadcasterFee {
+            token: "0xtoken".to_string(),
+            amount: "1500".to_string(),
+            recipient: "0zk1broadcaster".to_string(),
+            min_gas_price: "20000000000".to_string(),
+            fees_id: None,
+        };
+        let transfer = PrivacyFeeEstimate::new(
+            ShieldedTxType::Transfer,
+            "0xtoken",
+            U256::from(1_000_000u64),
+            gas(),
+            Some(fee),
+        );
+        // The broadcaster is paid from the rest of the balance
+        assert_eq!(transfer.net_amount, "1000000");
+        let json = serde_json::to_value(&transfer).unwrap();
+        assert_eq!(json["txType"], "Transfer");
+        assert_eq!(json["gas"]["maxCost"], "24000000000000000");
+        assert_eq!(json["broadcasterFee"]["amount"], "1500");
+    }
+}
@@ -1,18 +1,24 @@
-use crate::error::Result;
+use super::batch::TransferBatch;
+use super::bridge::{BridgeError, BridgeEvent, BridgeRequest, NodeBridge};
+use super::types::{BroadcasterFee, ReceivedNote, TransferOutput};
+use crate::crypto::SecretString;
+use crate::error::{CepWalletError, Result};
+use ethers::types::U256;
 use serde::{Deserialize, Serialize};
 /// FFI Bridge for Node.js Proof Generator
 ///
-/// This module provides an interface to call the Node.js proof generator
-/// from Rust code. It uses subprocess to execute the Node.js script and
-/// captures the JSON output.
-use std::process::Command;
+/// This module provides the proof-generator request and response types and
+/// proof helpers on top of a `NodeBridge` (see `bridge.rs`).
+use tokio::sync::{mpsc, watch};
 
 /// Request structure sent to Node.js proof generator
+///
+/// Secret fields are `SecretString`s, so `Debug` output is safe to log.
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ProofRequest {
     pub proof_type: String,
-    pub chain_id: Option<u64>,
+    pub chain_id: u64,
 
     // Shield-specific
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -22,7 +28,7 @@ pub struct ProofRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub commitment: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub shield_private_key: Option<String>,
+    pub shield_private_key: Option<SecretString>,
 
     // Transfer-specific
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -31,18 +37,33 @@ pub struct ProofRequest {
     pub nullifier: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub output_commitment: Option<String>,
+    /// Outputs of a multi-output transfer (instead of `recipient`, `token`
+    /// and `amount`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub outputs: Option<Vec<TransferOutput>>,
+    /// Memo text encrypted to the transfer's recipients
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memo_text: Option<String>,
 
     // Transfer/Unshield-specific
     #[serde(skip_serializing_if = "Option::is_none")]
     pub railgun_wallet_id: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub encryption_key: Option<String>,
+    pub encryption_key: Option<SecretString>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub mnemonic: Option<String>,
+    pub mnemonic: Option<SecretString>,
 
     // Common fields
     #[serde(skip_serializing_if = "Option::is_none")]
     pub recipient: Option<String>,
+    /// Broadcaster fee; without one the transaction is proved for
+    /// sending from a public wallet
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub broadcaster_fee: Option<BroadcasterFee>,
+
+    // Scan-specific: last synced block, to resume incrementally
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_block: Option<u64>,
 }
 
 /// Response from Node.js proof generator
@@ -57,12 +78,77 @@ pub struct ProofResponse {
     pub error: Option<String>,
     #[serde(default)]
     pub transaction: Option<serde_json::Value>,
+    /// Decrypted balances per RAILGUN balance bucket (merkletree scan)
+    #[serde(default)]
+    pub balances: Vec<BucketBalance>,
+    /// Block the merkletree scan reached
+    #[serde(default, rename = "lastBlock")]
+    pub last_block: Option<u64>,
+    /// Notes received since the scan's start block (merkletree scan)
+    #[serde(default)]
+    pub received: Vec<ReceivedNote>,
+}
+
+/// Token balance in one RAILGUN `RailgunWalletBalanceBucket`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BucketBalance {
+    pub bucket: String,
+    pub token_address: String,
+    /// Amount in base units
+    pub amount: String,
+}
+
+/// Proof progress notification from the Node.js prover
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProofProgressEvent {
+    /// Fraction of the proof completed (0.0 - 1.0)
+    pub progress: f64,
+}
+
+/// Cancellation and progress reporting for one proof
+#[derive(Default)]
+pub struct ProofControl {
+    /// Setting it to `true` (or dropping its sender) abandons the proof
+    pub cancel: Option<watch::Receiver<bool>>,
+    /// Receives the fraction of the proof completed (0.0 - 1.0)
+    pub on_progress: Option<Box<dyn Fn(f64) + Send>>,
+}
+
+impl ProofControl {
+    pub fn new<F>(cancel: watch::Receiver<bool>, on_progress: F) -> Self
+    where
+        F: Fn(f64) + Send + 'static,
+    {
+        Self {
+            cancel: Some(cancel),
+            on_progress: Some(Box::new(on_progress)),
+        }
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancel.as_ref().is_some_and(|cancel| *cancel.borrow())
+    }
+
+    fn progress(&self, event: BridgeEvent) {
+        match (event, &self.on_progress) {
+            (BridgeEvent::ProofProgress(event), Some(on_progress)) => on_progress(event.progress),
+            (BridgeEvent::ProofProgress(_), None) => {}
+            (other, _) => tracing::debug!("Unexpected proof event: {:?}", other),
+        }
+    }
 }
 
 /// Generate a ZK-SNARK proof by calling Node.js proof generator
 ///
+/// Proof progress is reported to `control.on_progress`; setting
+/// `control.cancel` abandons the proof. Proofs that exceed the time limit of
+/// their type fail (see [`BridgeRequest::timeout`]).
+///
 /// # Arguments
+/// * `bridge` - Proof generator bridge
 /// * `request` - Proof generation parameters
+/// * `control` - Cancellation and progress reporting
 ///
 /// # Returns
 /// * `Result<ProofResponse>` - Generated proof or error
@@ -77,140 +163,65 @@ pub struct ProofResponse {
 ///     ..Default::default()
 /// };
 ///
-/// let proof = generate_proof(request).await?;
+/// let proof = generate_proof(&bridge, request, ProofControl::default()).await?;
 /// ```
-pub async fn generate_proof(request: ProofRequest) -> Result<ProofResponse> {
+pub async fn generate_proof(
+    bridge: &dyn NodeBridge,
+    request: ProofRequest,
+    control: ProofControl,
+) -> Result<ProofResponse> {
     tracing::info!("Calling Node.js proof generator: {}", request.proof_type);
 
-    // Map proof type to proof-generator command name
-    let command_name = match request.proof_type.as_str() {
-        "shield" | "generate_shield_proof" => "generate_shield_proof",
-        "transfer" | "generate_transfer_proof" => "generate_transfer_proof",
-        "unshield" | "generate_unshield_proof" => "generate_unshield_proof",
-        other => other,
-    };
-
-    // Serialize request to JSON
-    let request_json = serde_json::to_string(&request)
-        .map_err(|e| anyhow::anyhow!("Failed to serialize proof request: {}", e))?;
-
-    // Get proof-generator directory path
-    // During development: src-tauri/../proof-generator
-    // During production: relative to executable
-    let current_dir = std::env::current_dir()
-        .map_err(|e| anyhow::anyhow!("Failed to get current directory: {}", e))?;
-
-    tracing::debug!("Current directory: {:?}", current_dir);
-
-    // Try multiple possible paths
-    let possible_paths = vec![
-        current_dir.join("proof-generator"),       // From project root
-        current_dir.join("../proof-generator"),    // From src-tauri
-        current_dir.join("../../proof-generator"), // From target/debug
-    ];
-
-    let proof_generator_dir = possible_paths.iter().find(|p| p.exists()).ok_or_else(|| {
-        anyhow::anyhow!(
-            "proof-generator directory not found. Tried: {:?}",
-            possible_paths
-        )
-    })?;
-
-    tracing::debug!("Using proof-generator at: {:?}", proof_generator_dir);
-
-    let script_path = proof_generator_dir.join("index.js");
+    let request = BridgeRequest::proof(request)?;
+    tracing::debug!("Proof request: {:?}", request);
 
-    if !script_path.exists() {
-        return Err(
-            anyhow::anyhow!("proof-generator/index.js not found at {:?}", script_path).into(),
-        );
-    }
-
-    println!("📤 Sending JSON to Node.js: {}", request_json);
-    tracing::debug!(
-        "Running: node {} {} '{}'",
-        script_path.display(),
-        command_name,
-        request_json
-    );
-
-    // Execute Node.js script
-    let output = Command::new("node")
-        .arg(&script_path)
-        .arg(command_name)
-        .arg(&request_json)
-        .current_dir(proof_generator_dir) // Set working directory
-        .output()
-        .map_err(|e| {
-            anyhow::anyhow!(
-                "Failed to execute Node.js: {}. Make sure Node.js is installed.",
-                e
-            )
-        })?;
-
-    // Always log stdout and stderr for debugging
-    let stderr = String::from_utf8_lossy(&output.stderr);
-    let stdout = String::from_utf8_lossy(&output.stdout);
-
-    if !stderr.is_empty() {
-        println!("Node.js stderr: {}", stderr);
-    }
-    if !stdout.is_empty() {
-        println!("Node.js stdout: {}", stdout);
-    }
+    let (events, mut events_rx) = mpsc::unbounded_channel();
+    let call = bridge.send(request, Some(events), control.cancel.clone());
+    tokio::pin!(call);
 
-    // Check if command succeeded
-    if !output.status.success() {
-        return Err(anyhow::anyhow!(
-            "Proof generation failed with exit code {:?}. Stderr: {}",
-            output.status.code(),
-            stderr
-        )
-        .into());
+    let result = loop {
+        tokio::select! {
+            result = &mut call => break result,
+            Some(event) = events_rx.recv() => control.progress(event),
+        }
+    };
+    // Events sent just before the response
+    while let Ok(event) = events_rx.try_recv() {
+        control.progress(event);
     }
 
-    // Parse stdout
-    tracing::debug!("Node.js output: {}", stdout);
-
-    // Extract JSON from output (last line should be JSON)
-    let json_line = stdout
-        .lines()
-        .rev()
-        .find(|line| line.trim().starts_with('{'))
-        .ok_or_else(|| {
-            anyhow::anyhow!(
-                "No JSON output found from proof generator. Output: {}",
-                stdout
-            )
-        })?;
-
-    // Parse response
-    let response: ProofResponse = serde_json::from_str(json_line)
-        .map_err(|e| anyhow::anyhow!("Failed to parse proof response: {}", e))?;
-
-    if !response.success {
-        return Err(anyhow::anyhow!(
-            "Proof generation failed: {}",
-            response
-                .error
-                .unwrap_or_else(|| "Unknown error".to_string())
-        )
-        .into());
-    }
+    let response = result
+        .map_err(|e| match e {
+            BridgeError::Command(message) => {
+                CepWalletError::PrivacyError(format!("Proof generation failed: {}", message))
+            }
+            BridgeError::Cancelled => {
+                CepWalletError::PrivacyError("Proof generation cancelled".to_string())
+            }
+            BridgeError::WorkerExited(_) if control.is_cancelled() => {
+                CepWalletError::PrivacyError("Proof generation cancelled".to_string())
+            }
+            BridgeError::TimedOut(limit) => CepWalletError::PrivacyError(format!(
+                "Proof generation timed out after {}s",
+                limit.as_secs()
+            )),
+            other => other.into(),
+        })?
+        .into_proof()?;
 
     tracing::info!("Proof generated successfully");
     Ok(response)
 }
 
-/// Generate a shield proof (public → private)
-pub async fn generate_shield_proof(
+/// Request describing a shield (public → private)
+pub fn shield_request(
     token: &str,
     amount: &str,
     railgun_address: &str,
     shield_private_key: &str,
-    chain_id: Option<u64>,
-) -> Result<ProofResponse> {
-    let request = ProofRequest {
+    chain_id: u64,
+) -> ProofRequest {
+    ProofRequest {
         proof_type: "shield".to_string(),
         chain_id,
         token: Some(token.to_string()),
@@ -219,79 +230,243 @@ pub async fn generate_shield_proof(
         merkle_root: None,
         nullifier: None,
         output_commitment: None,
+        outputs: None,
+        memo_text: None,
         recipient: Some(railgun_address.to_string()),
+        broadcaster_fee: None,
         railgun_wallet_id: None,
         encryption_key: None,
-        shield_private_key: Some(shield_private_key.to_string()),
+        shield_private_key: Some(shield_private_key.into()),
         mnemonic: None,
-    };
-
-    generate_proof(request).await
+        start_block: None,
+    }
 }
 
-/// Generate a private transfer proof (private → private)
-pub async fn generate_transfer_proof(
+/// Request describing a private transfer (`"transfer"`) or an unshield
+/// (`"unshield"`) out of a RAILGUN wallet
+#[allow(clippy::too_many_arguments)]
+pub fn spend_request(
+    proof_type: &str,
     railgun_wallet_id: &str,
     encryption_key: &str,
     mnemonic: &str,
-    to_railgun_address: &str,
+    recipient: &str,
     token: &str,
     amount: &str,
-    chain_id: Option<u64>,
-) -> Result<ProofResponse> {
-    let request = ProofRequest {
-        proof_type: "transfer".to_string(),
+    broadcaster_fee: Option<&BroadcasterFee>,
+    chain_id: u64,
+) -> ProofRequest {
+    ProofRequest {
+        proof_type: proof_type.to_string(),
         chain_id,
         railgun_wallet_id: Some(railgun_wallet_id.to_string()),
-        encryption_key: Some(encryption_key.to_string()),
-        mnemonic: Some(mnemonic.to_string()),
-        recipient: Some(to_railgun_address.to_string()),
+        encryption_key: Some(encryption_key.into()),
+        mnemonic: Some(mnemonic.into()),
+        start_block: None,
+        recipient: Some(recipient.to_string()),
+        broadcaster_fee: broadcaster_fee.cloned(),
         token: Some(token.to_string()),
         amount: Some(amount.to_string()),
         merkle_root: None,
         nullifier: None,
         output_commitment: None,
+        outputs: None,
+        memo_text: None,
         commitment: None,
         shield_private_key: None,
-    };
+    }
+}
 
-    generate_proof(request).await
+/// Request describing a multi-output private transfer
+pub fn batch_transfer_request(
+    railgun_wallet_id: &str,
+    encryption_key: &str,
+    mnemonic: &str,
+    batch: &TransferBatch,
+    broadcaster_fee: Option<&BroadcasterFee>,
+    chain_id: u64,
+) -> ProofRequest {
+    ProofRequest {
+        proof_type: "transfer".to_string(),
+        chain_id,
+        railgun_wallet_id: Some(railgun_wallet_id.to_string()),
+        encryption_key: Some(encryption_key.into()),
+        mnemonic: Some(mnemonic.into()),
+        start_block: None,
+        recipient: None,
+        broadcaster_fee: broadcaster_fee.cloned(),
+        token: None,
+        amount: None,
+        merkle_root: None,
+        nullifier: None,
+        output_commitment: None,
+        outputs: Some(batch.outputs().to_vec()),
+        memo_text: batch.memo().map(str::to_string),
+        commitment: None,
+        shield_private_key: None,
+    }
+}
+
+/// Generate a shield proof (public → private)
+pub async fn generate_shield_proof(
+    bridge: &dyn NodeBridge,
+    token: &str,
+    amount: &str,
+    railgun_address: &str,
+    shield_private_key: &str,
+    chain_id: u64,
+    control: ProofControl,
+) -> Result<ProofResponse> {
+    let request = shield_request(token, amount, railgun_address, shield_private_key, chain_id);
+    generate_proof(bridge, request, control).await
+}
+
+/// Generate a private transfer proof (private → private)
+#[allow(clippy::too_many_arguments)]
+pub async fn generate_transfer_proof(
+    bridge: &dyn NodeBridge,
+    railgun_wallet_id: &str,
+    encryption_key: &str,
+    mnemonic: &str,
+    to_railgun_address: &str,
+    token: &str,
+    amount: &str,
+    memo: Option<&str>,
+    broadcaster_fee: Option<&BroadcasterFee>,
+    chain_id: u64,
+    control: ProofControl,
+) -> Result<ProofResponse> {
+    let mut request = spend_request(
+        "transfer",
+        railgun_wallet_id,
+        encryption_key,
+        mnemonic,
+        to_railgun_address,
+        token,
+        amount,
+        broadcaster_fee,
+        chain_id,
+    );
+    request.memo_text = memo.map(str::to_string);
+    generate_proof(bridge, request, control).await
+}
+
+/// Generate the proofs of a multi-output private transfer (private → private)
+#[allow(clippy::too_many_arguments)]
+pub async fn generate_batch_transfer_proof(
+    bridge: &dyn NodeBridge,
+    railgun_wallet_id: &str,
+    encryption_key: &str,
+    mnemonic: &str,
+    batch: &TransferBatch,
+    broadcaster_fee: Option<&BroadcasterFee>,
+    chain_id: u64,
+    control: ProofControl,
+) -> Result<ProofResponse> {
+    let request = batch_transfer_request(
+        railgun_wallet_id,
+        encryption_key,
+        mnemonic,
+        batch,
+        broadcaster_fee,
+        chain_id,
+    );
+    generate_proof(bridge, request, control).await
 }
 
 /// Generate an unshield proof (private → public)
+#[allow(clippy::too_many_arguments)]
 pub async fn generate_unshield_proof(
+    bridge: &dyn NodeBridge,
     railgun_wallet_id: &str,
     encryption_key: &str,
     mnemonic: &str,
     recipient: &str,
     token: &str,
     amount: &str,
-    chain_id: Option<u64>,
+    broadcaster_fee: Option<&BroadcasterFee>,
+    chain_id: u64,
+    control: ProofControl,
 ) -> Result<ProofResponse> {
-    let request = ProofRequest {
-        proof_type: "unshield".to_string(),
+    let request = spend_request(
+        "unshield",
+        railgun_wallet_id,
+        encryption_key,
+        mnemonic,
+        recipient,
+        token,
+        amount,
+        broadcaster_fee,
         chain_id,
-        railgun_wallet_id: Some(railgun_wallet_id.to_string()),
-        encryption_key: Some(encryption_key.to_string()),
-        mnemonic: Some(mnemonic.to_string()),
-        recipient: Some(recipient.to_string()),
-        token: Some(token.to_string()),
-        amount: Some(amount.to_string()),
-        nullifier: None,
-        merkle_root: None,
-        output_commitment: None,
-        commitment: None,
-        shield_private_key: None,
+    );
+    generate_proof(bridge, request, control).await
+}
+
+/// Gas limit the RAILGUN SDK estimates for the operation `request` describes
+///
+/// Transfers and unshields are estimated with dummy proofs, so nothing is
+/// proved; shields are estimated as sent by `from`, which must hold the
+/// tokens (and allowance). The fees only feed the SDK's gas details.
+pub async fn estimate_gas(
+    bridge: &dyn NodeBridge,
+    request: ProofRequest,
+    from: Option<&str>,
+    max_fee_per_gas: U256,
+    max_priority_fee_per_gas: U256,
+) -> Result<U256> {
+    if request.proof_type == "shield" && from.is_none() {
+        return Err(CepWalletError::InvalidInput(
+            "Estimating a shield requires the sending account".to_string(),
+        ));
+    }
+    let request = BridgeRequest::EstimateGas {
+        request,
+        from: from.map(str::to_string),
+        max_fee_per_gas,
+        max_priority_fee_per_gas,
     };
+    let gas = bridge
+        .call(request)
+        .await
+        .map_err(|e| match e {
+            BridgeError::Command(message) => {
+                CepWalletError::PrivacyError(format!("Gas estimate failed: {}", message))
+            }
+            other => other.into(),
+        })?
+        .into_gas_estimate()?;
+    Ok(gas)
+}
 
-    generate_proof(request).await
+/// Scan progress notification from the Node.js scanner
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanProgressEvent {
+    /// Fraction of the merkletree scan completed (0.0 - 1.0)
+    pub progress: f64,
+    #[serde(default)]
+    pub start_block: Option<u64>,
+    #[serde(default)]
+    pub target_block: Option<u64>,
 }
 
-/// Scan merkletree for wallet balance updates
-pub async fn scan_merkletree(
+/// Sync the merkletree from `start_block` and return the wallet's balances
+///
+/// Progress notifications are passed to `on_progress` as they arrive.
+/// Setting `cancel` to `true` (or dropping its sender) abandons the scan;
+/// the RAILGUN engine keeps what it already scanned in its database.
+pub async fn sync_merkletree<F>(
+    bridge: &dyn NodeBridge,
     railgun_wallet_id: &str,
-    chain_id: Option<u64>,
-) -> Result<ProofResponse> {
+    encryption_key: &str,
+    chain_id: u64,
+    start_block: Option<u64>,
+    cancel: watch::Receiver<bool>,
+    mut on_progress: F,
+) -> Result<ProofResponse>
+where
+    F: FnMut(ScanProgressEvent),
+{
     let request = ProofRequest {
         proof_type: "scanMerkletree".to_string(),
         chain_id,
@@ -302,29 +477,90 @@ pub async fn scan_merkletree(
         merkle_root: None,
         nullifier: None,
         output_commitment: None,
+        outputs: None,
+        memo_text: None,
         recipient: None,
-        encryption_key: None,
+        broadcaster_fee: None,
+        encryption_key: Some(encryption_key.into()),
         shield_private_key: None,
         mnemonic: None,
+        start_block,
     };
+    let cancelled = cancel.clone();
+    let (events, mut events_rx) = mpsc::unbounded_channel();
+    let call = bridge.send(BridgeRequest::Scan(request), Some(events), Some(cancel));
+    tokio::pin!(call);
+
+    let result = loop {
+        tokio::select! {
+            result = &mut call => break result,
+            Some(event) = events_rx.recv() => match event {
+                BridgeEvent::ScanProgress(event) => on_progress(event),
+                other => tracing::debug!("Unexpected scan event: {:?}", other),
+            },
+        }
+    };
+    // Events sent just before the response
+    while let Ok(BridgeEvent::ScanProgress(event)) = events_rx.try_recv() {
+        on_progress(event);
+    }
 
-    generate_proof(request).await
+    let response = result
+        .map_err(|e| match e {
+            BridgeError::Cancelled => {
+                CepWalletError::PrivacyError("Merkletree sync cancelled".to_string())
+            }
+            BridgeError::WorkerExited(_) if *cancelled.borrow() => {
+                CepWalletError::PrivacyError("Merkletree sync cancelled".to_string())
+            }
+            other => other.into(),
+        })?
+        .into_proof()?;
+
+    Ok(response)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::privacy::bridge::{FakeBridge, WorkerBridge};
+    use std::sync::{Arc, Mutex};
+
+    /// In-memory writer for capturing `tracing` output
+    #[derive(Clone, Default)]
+    struct Capture(Arc<Mutex<Vec<u8>>>);
+
+    impl std::io::Write for Capture {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> tracing_subscriber::fmt::MakeWriter<'a> for Capture {
+        type Writer = Capture;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
 
     #[tokio::test]
     #[ignore] // Only run if Node.js and proof-generator are set up
     async fn test_shield_proof_generation() {
         let shield_key = format!("0x{}", "0".repeat(64)); // shield_private_key (32 bytes hex)
         let result = generate_shield_proof(
+            &WorkerBridge::default(),
             "0x0000000000000000000000000000000000000000", // token
             "1000000000000000000",                        // amount
             "0zk1234...railgun_address...",               // railgun_address
             &shield_key,
-            Some(11155111), // chain_id (Sepolia)
+            11155111, // chain_id (Sepolia)
+            ProofControl::default(),
         )
         .await;
 
@@ -333,4 +569,129 @@ mod tests {
         assert!(proof.success);
         assert!(!proof.proof.is_empty());
     }
+
+    #[test]
+    fn test_parse_scan_progress() {
+        let params = serde_json::json!({
+            "id": 7,
+            "event": "scanProgress",
+            "progress": 0.25,
+            "scanStatus": "Updated",
+            "startBlock": 100,
+            "targetBlock": 500,
+        });
+        let event: ScanProgressEvent = serde_json::from_value(params).unwrap();
+        assert_eq!(event.progress, 0.25);
+        assert_eq!(event.start_block, Some(100));
+        assert_eq!(event.target_block, Some(500));
+
+        let done = serde_json::json!({ "success": true, "balances": [], "lastBlock": 500 });
+        let response: ProofResponse = serde_json::from_value(done).unwrap();
+        assert_eq!(response.last_block, Some(500));
+    }
+
+    #[tokio::test]
+    async fn test_secrets_are_not_logged() {
+        const ENCRYPTION_KEY: &str = "enc-key-0123456789abcdef";
+        const MNEMONIC: &str = "test test test test test test test test test test test junk";
+
+        let bridge = FakeBridge::new(|request| {
+            // Secrets still reach the worker, over stdin only
+            let params = request.params()?;
+            assert_eq!(params["encryptionKey"], ENCRYPTION_KEY);
+            assert_eq!(params["mnemonic"], MNEMONIC);
+            Ok(serde_json::json!({ "success": true, "proof": "0xproof" }))
+        });
+
+        let capture = Capture::default();
+        let subscriber = tracing_subscriber::fmt()
+            .with_max_level(tracing::Level::TRACE)
+            .with_writer(capture.clone())
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let proof = generate_transfer_proof(
+            &bridge,
+            "wallet-id",
+            ENCRYPTION_KEY,
+            MNEMONIC,
+            "0zk1recipient",
+            "0x0000000000000000000000000000000000000000",
+            "1000",
+            None,
+            None,
+            11155111,
+            ProofControl::default(),
+        )
+        .await
+        .unwrap();
+        assert!(proof.success);
+
+        let logs = String::from_utf8(capture.0.lock().unwrap().clone()).unwrap();
+        assert!(logs.contains("Proof request"));
+        assert!(logs.contains("<redacted>"));
+        assert!(!logs.contains(ENCRYPTION_KEY));
+        assert!(!logs.contains(MNEMONIC));
+    }
+
+    #[tokio::test]
+    async fn test_proof_progress_and_cancel() {
+        let bridge =
+            FakeBridge::new(|_| Ok(serde_json::json!({ "success": true }))).with_events(vec![
+                serde_json::json!({ "id": 1, "event": "proofProgress", "progress": 0.4 }),
+                serde_json::json!({ "id": 1, "event": "proofProgress", "progress": 1.0 }),
+            ]);
+        let shield_key = format!("0x{}", "0".repeat(64));
+        let shield = |control| {
+            generate_shield_proof(
+                &bridge,
+                "0x0000000000000000000000000000000000000000",
+                "1000",
+                "0zk1recipient",
+                &shield_key,
+                11155111,
+                control,
+            )
+        };
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let (_cancel, cancel_rx) = watch::channel(false);
+        let recorded = seen.clone();
+        let control = ProofControl::new(cancel_rx, move |progress| {
+            recorded.lock().unwrap().push(progress)
+        });
+        assert!(shield(control).await.unwrap().success);
+        assert_eq!(*seen.lock().unwrap(), vec![0.4, 1.0]);
+
+        let (cancel, cancel_rx) = watch::channel(false);
+        cancel.send_replace(true);
+        let error = shield(ProofControl::new(cancel_rx, |_| {}))
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("cancelled"));
+    }
+
+    #[tokio::test]
+    async fn test_estimate_gas() {
+        let bridge = FakeBridge::new(|request| {
+            let params = request.params()?;
+            assert_eq!(params["proofType"], "shield");
+            assert_eq!(params["fromWalletAddress"], "0xsender");
+            Ok(serde_json::json!({ "success": true, "gasEstimate": "180000" }))
+        });
+        let request = || shield_request("0xtoken", "1000", "0zk1recipient", "0xkey", 1);
+        let fee = U256::from(20_000_000_000u64);
+
+        let gas = estimate_gas(&bridge, request(), Some("0xsender"), fee, U256::one())
+            .await
+            .unwrap();
+        assert_eq!(gas, U256::from(180_000u64));
+        assert_eq!(bridge.methods(), ["estimate_gas"]);
+
+        // A shield is estimated from its sender
+        let error = estimate_gas(&bridge, request(), None, fee, U256::one())
+            .await
+            .unwrap_err();
+        assert!(matches!(error, CepWalletError::InvalidInput(_)));
+    }
 }
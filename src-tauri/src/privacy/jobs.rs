@@ -0,0 +1,401 @@
+/// Proof job queue
+///
+/// Every proof the wallet generates runs as a job with an id chosen by the
+/// frontend (or generated). Jobs wait in a queue and run with bounded
+/// concurrency, report progress as `proof-progress` events and can be
+/// cancelled by id while queued or running. Jobs are persisted in the
+/// privacy store (see `store.rs`) when queued and again with their results
+/// when finished; jobs an app restart cut short are marked `Interrupted`.
+use super::ffi::ProofControl;
+use super::types::ShieldedTransaction;
+use crate::error::{CepWalletError, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{watch, OwnedSemaphorePermit, Semaphore};
+
+/// Tauri event carrying [`ProofJob`] updates
+pub const PROOF_PROGRESS_EVENT: &str = "proof-progress";
+
+/// Proofs generated at the same time
+///
+/// Proving is CPU bound, but a second slot keeps a quick shield from
+/// waiting behind a long transfer proof.
+pub const DEFAULT_PROOF_CONCURRENCY: usize = 2;
+
+/// State of a proof job
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ProofJobStatus {
+    Queued,
+    Running,
+    Completed,
+    Cancelled,
+    Failed,
+    /// Queued or running when the app stopped
+    Interrupted,
+}
+
+impl ProofJobStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProofJobStatus::Queued => "Queued",
+            ProofJobStatus::Running => "Running",
+            ProofJobStatus::Completed => "Completed",
+            ProofJobStatus::Cancelled => "Cancelled",
+            ProofJobStatus::Failed => "Failed",
+            ProofJobStatus::Interrupted => "Interrupted",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "Queued" => Some(ProofJobStatus::Queued),
+            "Running" => Some(ProofJobStatus::Running),
+            "Completed" => Some(ProofJobStatus::Completed),
+            "Cancelled" => Some(ProofJobStatus::Cancelled),
+            "Failed" => Some(ProofJobStatus::Failed),
+            "Interrupted" => Some(ProofJobStatus::Interrupted),
+            _ => None,
+        }
+    }
+
+    /// Whether the job has stopped
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self,
+            ProofJobStatus::Completed
+                | ProofJobStatus::Cancelled
+                | ProofJobStatus::Failed
+                | ProofJobStatus::Interrupted
+        )
+    }
+}
+
+/// State of one proof
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProofJob {
+    pub job_id: String,
+    /// `shield`, `transfer` or `unshield`
+    pub proof_type: String,
+    pub status: ProofJobStatus,
+    /// 0 - 100
+    pub percent: f64,
+    pub error: Option<String>,
+    /// Proved transaction of a completed job
+    pub result: Option<ShieldedTransaction>,
+    /// Unix timestamps (seconds)
+    pub created_at: i64,
+    pub finished_at: Option<i64>,
+}
+
+impl ProofJob {
+    /// A job that was just queued
+    pub fn queued(job_id: &str, proof_type: &str) -> Self {
+        Self {
+            job_id: job_id.to_string(),
+            proof_type: proof_type.to_string(),
+            status: ProofJobStatus::Queued,
+            percent: 0.0,
+            error: None,
+            result: None,
+            created_at: chrono::Utc::now().timestamp(),
+            finished_at: None,
+        }
+    }
+
+    /// Mark the job as finished
+    pub fn finish(
+        &mut self,
+        result: std::result::Result<ShieldedTransaction, String>,
+        cancelled: bool,
+    ) {
+        self.finished_at = Some(chrono::Utc::now().timestamp());
+        match result {
+            Ok(tx) => {
+                self.status = ProofJobStatus::Completed;
+                self.percent = 100.0;
+                self.result = Some(tx);
+            }
+            Err(_) if cancelled => self.status = ProofJobStatus::Cancelled,
+            Err(error) => {
+                self.status = ProofJobStatus::Failed;
+                self.error = Some(error);
+            }
+        }
+    }
+}
+
+struct JobEntry {
+    cancel: watch::Sender<bool>,
+    job: ProofJob,
+}
+
+/// Queued and running proof jobs by id
+pub struct ProofJobs {
+    jobs: Mutex<HashMap<String, JobEntry>>,
+    slots: Arc<Semaphore>,
+}
+
+impl Default for ProofJobs {
+    fn default() -> Self {
+        Self::new(DEFAULT_PROOF_CONCURRENCY)
+    }
+}
+
+impl ProofJobs {
+    /// Queue running at most `concurrency` proofs at a time
+    pub fn new(concurrency: usize) -> Self {
+        Self {
+            jobs: Mutex::new(HashMap::new()),
+            slots: Arc::new(Semaphore::new(concurrency.max(1))),
+        }
+    }
+
+    fn jobs(&self) -> Result<std::sync::MutexGuard<'_, HashMap<String, JobEntry>>> {
+        self.jobs
+            .lock()
+            .map_err(|_| CepWalletError::PrivacyError("Proof job lock poisoned".to_string()))
+    }
+
+    /// Queue a new job, generating an id when none is given
+    ///
+    /// Returns the job and the receiver its cancellation is signalled on.
+    pub fn begin(
+        &self,
+        job_id: Option<String>,
+        proof_type: &str,
+    ) -> Result<(ProofJob, watch::Receiver<bool>)> {
+        let job_id = job_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        let mut jobs = self.jobs()?;
+        if jobs.contains_key(&job_id) {
+            return Err(CepWalletError::InvalidInput(format!(
+                "Proof job {} is already queued",
+                job_id
+            )));
+        }
+
+        let job = ProofJob::queued(&job_id, proof_type);
+        let (cancel, cancel_rx) = watch::channel(false);
+        jobs.insert(
+            job_id,
+            JobEntry {
+                cancel,
+                job: job.clone(),
+            },
+        );
+        Ok((job, cancel_rx))
+    }
+
+    /// Wait for a free slot and mark the job as running
+    ///
+    /// The job holds its slot until the permit is dropped. Fails when the
+    /// job is cancelled while it waits.
+    pub async fn acquire(
+        &self,
+        job_id: &str,
+        cancel: &mut watch::Receiver<bool>,
+    ) -> Result<(ProofJob, OwnedSemaphorePermit)> {
+        let cancelled = async {
+            while !*cancel.borrow() {
+                if cancel.changed().await.is_err() {
+                    return;
+                }
+            }
+        };
+        let permit = tokio::select! {
+            biased;
+            _ = cancelled => {
+                return Err(CepWalletError::PrivacyError(
+                    "Proof generation cancelled".to_string(),
+                ))
+            }
+            permit = self.slots.clone().acquire_owned() => permit.map_err(|_| {
+                CepWalletError::PrivacyError("Proof job queue closed".to_string())
+            })?,
+        };
+
+        let mut jobs = self.jobs()?;
+        let entry = jobs
+            .get_mut(job_id)
+            .ok_or_else(|| CepWalletError::PrivacyError(format!("Unknown proof job {}", job_id)))?;
+        entry.job.status = ProofJobStatus::Running;
+        Ok((entry.job.clone(), permit))
+    }
+
+    /// Progress reporting and cancellation for a job's proof
+    ///
+    /// `on_update` receives the job after each progress notification.
+    pub fn control<F>(
+        self: &Arc<Self>,
+        job_id: &str,
+        cancel: watch::Receiver<bool>,
+        on_update: F,
+    ) -> ProofControl
+    where
+        F: Fn(&ProofJob) + Send + 'static,
+    {
+        let jobs = self.clone();
+        let id = job_id.to_string();
+        ProofControl::new(cancel, move |fraction| {
+            if let Some(job) = jobs.update(&id, fraction) {
+                on_update(&job);
+            }
+        })
+    }
+
+    /// Request cancellation; returns false when the job is not queued or running
+    pub fn cancel(&self, job_id: &str) -> Result<bool> {
+        let jobs = self.jobs()?;
+        match jobs.get(job_id) {
+            Some(entry) if !entry.job.status.is_finished() => {
+                entry.cancel.send_replace(true);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// Record the progress (0.0 - 1.0) of a running job
+    ///
+    /// Returns the updated job, or None when it is not running.
+    pub fn update(&self, job_id: &str, fraction: f64) -> Option<ProofJob> {
+        let mut jobs = self.jobs().ok()?;
+        let entry = jobs.get_mut(job_id)?;
+        entry.job.percent = fraction.clamp(0.0, 1.0) * 100.0;
+        Some(entry.job.clone())
+    }
+
+    /// Record the outcome of a job and return its final state
+    ///
+    /// The job stays listed until [`remove`](Self::remove)d, once its final
+    /// state is persisted.
+    pub fn finish(
+        &self,
+        job_id: &str,
+        result: std::result::Result<ShieldedTransaction, String>,
+    ) -> Option<ProofJob> {
+        let mut jobs = self.jobs().ok()?;
+        let entry = jobs.get_mut(job_id)?;
+        let cancelled = *entry.cancel.borrow();
+        entry.job.finish(result, cancelled);
+        Some(entry.job.clone())
+    }
+
+    /// Drop a finished job from the queue
+    pub fn remove(&self, job_id: &str) {
+        if let Ok(mut jobs) = self.jobs() {
+            jobs.remove(job_id);
+        }
+    }
+
+    /// State of a job in the queue
+    pub fn status(&self, job_id: &str) -> Option<ProofJob> {
+        self.jobs().ok()?.get(job_id).map(|entry| entry.job.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::privacy::types::ShieldedTxType;
+    use std::time::Duration;
+
+    fn shielded_tx() -> ShieldedTransaction {
+        ShieldedTransaction {
+            tx_type: ShieldedTxType::Transfer,
+            token: "0x0000000000000000000000000000000000000000".to_string(),
+            amount: "1000".to_string(),
+            recipient: Some("0zk1recipient".to_string()),
+            proof: Some("0xproof".to_string()),
+            merkle_root: None,
+            nullifier: None,
+            transaction: None,
+            history_id: None,
+            proofs: Vec::new(),
+            broadcaster_fee: None,
+            outputs: Vec::new(),
+            memo: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_job_lifecycle() {
+        let jobs = Arc::new(ProofJobs::new(1));
+        let (job, mut cancel) = jobs.begin(Some("job-1".to_string()), "transfer").unwrap();
+        assert_eq!(job.status, ProofJobStatus::Queued);
+        assert!(jobs.begin(Some("job-1".to_string()), "transfer").is_err());
+
+        let (running, _permit) = jobs.acquire("job-1", &mut cancel).await.unwrap();
+        assert_eq!(running.status, ProofJobStatus::Running);
+
+        let updates = Arc::new(Mutex::new(Vec::new()));
+        let seen = updates.clone();
+        let control = jobs.control("job-1", cancel, move |job| {
+            seen.lock().unwrap().push(job.percent)
+        });
+        (control.on_progress.as_ref().unwrap())(0.25);
+        assert_eq!(*updates.lock().unwrap(), vec![25.0]);
+        assert_eq!(jobs.status("job-1").unwrap().percent, 25.0);
+
+        let done = jobs.finish("job-1", Ok(shielded_tx())).unwrap();
+        assert_eq!(done.status, ProofJobStatus::Completed);
+        assert_eq!(done.percent, 100.0);
+        assert!(done.result.is_some());
+        assert!(done.finished_at.is_some());
+        assert!(!jobs.cancel("job-1").unwrap());
+        assert_eq!(
+            jobs.status("job-1").unwrap().status,
+            ProofJobStatus::Completed
+        );
+        jobs.remove("job-1");
+        assert!(jobs.status("job-1").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_bounded_concurrency() {
+        let jobs = Arc::new(ProofJobs::new(1));
+        let (_, mut first_cancel) = jobs.begin(Some("first".to_string()), "shield").unwrap();
+        let (_, mut second_cancel) = jobs.begin(Some("second".to_string()), "shield").unwrap();
+
+        let (_, permit) = jobs.acquire("first", &mut first_cancel).await.unwrap();
+        let waiting = tokio::time::timeout(
+            Duration::from_millis(50),
+            jobs.acquire("second", &mut second_cancel),
+        )
+        .await;
+        assert!(waiting.is_err());
+        assert_eq!(
+            jobs.status("second").unwrap().status,
+            ProofJobStatus::Queued
+        );
+
+        drop(permit);
+        let (running, _permit) = jobs.acquire("second", &mut second_cancel).await.unwrap();
+        assert_eq!(running.status, ProofJobStatus::Running);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_queued_and_failure() {
+        let jobs = Arc::new(ProofJobs::new(1));
+        let (busy, mut busy_cancel) = jobs.begin(None, "transfer").unwrap();
+        let busy = busy.job_id;
+        let (_, _permit) = jobs.acquire(&busy, &mut busy_cancel).await.unwrap();
+
+        let (queued, mut cancel) = jobs.begin(None, "unshield").unwrap();
+        assert!(jobs.cancel(&queued.job_id).unwrap());
+        let error = jobs.acquire(&queued.job_id, &mut cancel).await.unwrap_err();
+        assert!(error.to_string().contains("cancelled"));
+        let cancelled = jobs.finish(&queued.job_id, Err(error.to_string())).unwrap();
+        assert_eq!(cancelled.status, ProofJobStatus::Cancelled);
+        assert_eq!(cancelled.error, None);
+
+        let failed = jobs
+            .finish(
+                &busy,
+                Err("Proof generation timed out after 600s".to_string()),
+            )
+            .unwrap();
+        assert_eq!(failed.status, ProofJobStatus::Failed);
+        assert!(failed.error.unwrap().contains("timed out"));
+    }
+}
@@ -0,0 +1,162 @@
+/// Hardware-derived RAILGUN key material
+///
+/// Derives the RAILGUN wallet encryption key and spending seed from the
+/// connected signer using SLIP-0011 CipherKeyValue on a dedicated path.
+/// The same device always yields the same secrets, so the same 0zk address
+/// can be recovered without a host-side mnemonic ever being written to disk.
+///
+/// The Trezor connection has no device transport for CipherKeyValue yet, so
+/// hardware-derived wallets are refused for Trezor; only signers that
+/// compute CipherKeyValue themselves (the development software signer)
+/// support them.
+use crate::error::{CepWalletError, Result};
+use crate::hardware::WalletSigner;
+use sha2::{Digest, Sha256};
+
+/// Dedicated SLIP-0011 path prefix for RAILGUN key material (1984 = RAILGUN coin type)
+pub const RAILGUN_CIPHER_PATH_PREFIX: &str = "m/10016'/1984'";
+
+/// CipherKeyValue label for the RAILGUN wallet encryption key
+const ENCRYPTION_KEY_LABEL: &str = "CepWallet RAILGUN encryption key v1";
+
+/// CipherKeyValue label for the RAILGUN spending/viewing seed
+const SPENDING_SEED_LABEL: &str = "CepWallet RAILGUN spending seed v1";
+
+/// Secrets derived from the hardware wallet for a RAILGUN wallet
+pub struct HardwareRailgunSecrets {
+    /// 32-byte hex encryption key (0x-prefixed)
+    pub encryption_key: String,
+    /// BIP-39 mnemonic (24 words) used for viewing/spending key derivation
+    pub mnemonic: String,
+}
+
+impl std::fmt::Debug for HardwareRailgunSecrets {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HardwareRailgunSecrets")
+            .field("encryption_key", &"<redacted>")
+            .field("mnemonic", &"<redacted>")
+            .finish()
+    }
+}
+
+/// CipherKeyValue path for a RAILGUN account
+pub fn railgun_cipher_path(account: u32) -> String {
+    format!("{}/{}'", RAILGUN_CIPHER_PATH_PREFIX, account)
+}
+
+/// Fixed, domain-separated 32-byte value for a CipherKeyValue label
+fn domain_value(label: &str) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(label.as_bytes());
+    hasher.finalize().into()
+}
+
+/// Derive 32 bytes of key material for a label from the device
+async fn derive_material(signer: &dyn WalletSigner, account: u32, label: &str) -> Result<[u8; 32]> {
+    let output = signer
+        .cipher_key_value(
+            &railgun_cipher_path(account),
+            label,
+            &domain_value(label),
+            true,
+            true,
+            true,
+        )
+        .await
+        .map_err(|e| CepWalletError::TrezorError(e.to_string()))?;
+
+    output.try_into().map_err(|_| {
+        CepWalletError::TrezorError("Unexpected CipherKeyValue output length".to_string())
+    })
+}
+
+/// Derive RAILGUN wallet secrets from the connected Trezor
+///
+/// # Arguments
+/// * `signer` - Connected signer (Trezor, or the software signer in development)
+/// * `account` - RAILGUN account index (hardened)
+///
+/// # Returns
+/// * `HardwareRailgunSecrets` - Encryption key and mnemonic, never persisted
+pub async fn derive_railgun_secrets(
+    signer: &dyn WalletSigner,
+    account: u32,
+) -> Result<HardwareRailgunSecrets> {
+    if !signer.supports_cipher_key_value() {
+        return Err(CepWalletError::TrezorError(
+            "Hardware-derived RAILGUN wallets are not available for this device: \
+             CipherKeyValue is not supported over its connection"
+                .to_string(),
+        ));
+    }
+    let encryption_key = derive_material(signer, account, ENCRYPTION_KEY_LABEL).await?;
+    let spending_seed = derive_material(signer, account, SPENDING_SEED_LABEL).await?;
+
+    let mnemonic = bip39::Mnemonic::from_entropy(&spending_seed)
+        .map_err(|e| CepWalletError::EncodingError(e.to_string()))?;
+
+    Ok(HardwareRailgunSecrets {
+        encryption_key: format!("0x{}", hex::encode(encryption_key)),
+        mnemonic: mnemonic.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hardware::TrezorManager;
+
+    #[test]
+    fn test_cipher_path() {
+        assert_eq!(railgun_cipher_path(0), "m/10016'/1984'/0'");
+        assert_eq!(railgun_cipher_path(3), "m/10016'/1984'/3'");
+    }
+
+    #[cfg(feature = "software-signer")]
+    #[tokio::test]
+    async fn test_derivation_is_deterministic() {
+        use crate::hardware::SoftwareSigner;
+
+        let signer = SoftwareSigner::from_mnemonic(
+            "test test test test test test test test test test test junk",
+            None,
+        )
+        .unwrap();
+
+        let first = derive_railgun_secrets(&signer, 0).await.unwrap();
+        let second = derive_railgun_secrets(&signer, 0).await.unwrap();
+        let other_account = derive_railgun_secrets(&signer, 1).await.unwrap();
+
+        assert_eq!(first.encryption_key, second.encryption_key);
+        assert_eq!(first.mnemonic, second.mnemonic);
+        assert_ne!(first.mnemonic, other_account.mnemonic);
+
+        assert_eq!(first.encryption_key.len(), 66);
+        assert_eq!(first.mnemonic.split(' ').count(), 24);
+        assert_ne!(first.encryption_key, format!("0x{}", "0".repeat(64)));
+    }
+
+    #[tokio::test]
+    async fn test_derivation_requires_device() {
+        let trezor = TrezorManager::new(5000).unwrap();
+        assert!(derive_railgun_secrets(&trezor, 0).await.is_err());
+
+        // No host-side stand-in for the device's CipherKeyValue: the mode
+        // is refused before anything is sent
+        trezor.connect().await.unwrap();
+        assert!(!trezor.supports_cipher_key_value());
+        let err = derive_railgun_secrets(&trezor, 0).await.unwrap_err();
+        assert!(err.to_string().contains("not available"), "{}", err);
+    }
+
+    #[test]
+    fn test_debug_redacts_secrets() {
+        let secrets = HardwareRailgunSecrets {
+            encryption_key: "0xsecret".to_string(),
+            mnemonic: "abandon abandon".to_string(),
+        };
+        let debug = format!("{:?}", secrets);
+        assert!(!debug.contains("secret"));
+        assert!(!debug.contains("abandon"));
+    }
+}
@@ -1,5 +1,15 @@
+pub mod address;
+pub mod artifacts;
+pub mod batch;
+pub mod bridge;
+pub mod broadcast;
+pub mod broadcaster;
 pub mod contracts;
+pub mod envelope;
+pub mod fees;
 pub mod ffi;
+pub mod jobs;
+pub mod key_derivation;
 pub mod privacy_pools;
 /// Privacy Module - RAILGUN & Privacy Pools Integration
 ///
@@ -10,28 +20,82 @@ pub mod privacy_pools;
 ///
 /// Architecture:
 /// - railgun.rs: RAILGUN protocol implementation
+/// - address.rs: 0zk address codec and payment request URIs
+/// - batch.rs: Multi-output private transfers (outputs grouped by token)
+/// - broadcast.rs: Signing and broadcasting (shield approvals, relay adapt,
+///   self-signed transfers and unshields)
+/// - broadcaster.rs: RAILGUN broadcaster (relayer) quotes, selection and submission
+/// - fees.rs: Protocol fees and per-operation fee estimates
+/// - bridge.rs: Typed proof-generator commands (`NodeBridge`)
+/// - jobs.rs: Proof job queue (bounded concurrency, cancellation, progress)
 /// - privacy_pools.rs: Privacy Pools integration
-/// - zkproof.rs: Zero-knowledge proof generation
+/// - zkproof.rs: Native Groth16 prover (snarkjs-compatible proofs)
+/// - verifier.rs: Groth16 verification against bundled verification keys
+/// - envelope.rs: Versioned proof envelope (JSON and binary encodings)
+/// - transact.rs: RAILGUN transaction calldata (proofs and public inputs)
+/// - zkey.rs: snarkjs proving key reader
+/// - witness.rs: circom wasm witness calculator
+/// - artifacts.rs: Pinned circuit artifacts (hash checks, decompression)
 /// - types.rs: Common types and structures
 /// - contracts.rs: Contract addresses and constants
+/// - key_derivation.rs: Hardware-derived RAILGUN key material
+/// - store.rs: Persisted scan results (shielded balances, last synced block)
+/// - sync.rs: Incremental background merkletree sync
+/// - worker.rs: Long-lived Node.js proof-generator worker (JSON-RPC)
 pub mod railgun;
+pub mod store;
+pub mod sync;
+pub mod transact;
 pub mod types;
+pub mod verifier;
 pub mod wallet;
+pub mod witness;
+pub mod worker;
+pub mod zkey;
 pub mod zkproof;
 
-pub use contracts::{CommonTokens, RailgunContracts};
+pub use address::{
+    validate_railgun_address, PaymentRequest, PaymentRequestPayload, RailgunAddress,
+    RailgunAddressInfo,
+};
+pub use artifacts::{ArtifactManager, ArtifactManifest, ArtifactStatus, CircuitAvailability};
+pub use batch::TransferBatch;
+pub use bridge::{BridgeConfig, NodeBridge, WorkerBridge};
+pub use broadcast::{
+    plan_private, plan_shield, quote_gas, sign_and_send, wait_for_success, GasQuote, ShieldPlan,
+};
+pub use broadcaster::{
+    select_broadcaster, BridgeTransport, BroadcastRequest, BroadcasterClient, BroadcasterQuote,
+    BroadcasterTransport, DEFAULT_RELAYED_GAS_LIMIT,
+};
+pub use contracts::RailgunContracts;
+pub use envelope::{ProofEncoding, ProofEnvelope, PROOF_ENVELOPE_VERSION};
+pub use fees::{PrivacyFeeEstimate, RAILGUN_SHIELD_FEE_BPS, RAILGUN_UNSHIELD_FEE_BPS};
 pub use ffi::{
-    generate_proof, generate_shield_proof, generate_transfer_proof, generate_unshield_proof,
+    estimate_gas, generate_proof, generate_shield_proof, generate_transfer_proof,
+    generate_unshield_proof, ProofControl,
 };
+pub use jobs::{ProofJob, ProofJobStatus, ProofJobs, PROOF_PROGRESS_EVENT};
+pub use key_derivation::{derive_railgun_secrets, HardwareRailgunSecrets};
 pub use privacy_pools::PrivacyPoolManager;
 pub use railgun::RailgunManager;
-pub use types::{PrivacyLevel, PrivacyPoolOperation, ShieldedTransaction};
+pub use store::PrivacyStore;
+pub use sync::{SyncManager, SyncProgress, SyncStatus, SYNC_PROGRESS_EVENT};
+pub use types::{
+    BroadcasterFee, NoteStatus, PrivacyBalance, PrivacyLevel, PrivacyPoolOperation, ReceivedNote,
+    ShieldedBalances, ShieldedTransaction, TransferOutput, MAX_MEMO_BYTES,
+};
+pub use verifier::{bundled_vkeys_dir, VerificationKey, VerificationKeys};
 pub use wallet::{
-    create_railgun_wallet, get_shield_private_key, ShieldKeyResponse, WalletCreateResponse,
+    create_hardware_railgun_wallet, create_railgun_wallet, get_shield_private_key,
+    ShieldKeyResponse, WalletCreateResponse,
+};
+pub use zkproof::{
+    circuit_artifacts_dir, Circuit, FullProof, ProofType, SnarkjsProof, ZKProofGenerator,
 };
-pub use zkproof::{ProofType, ZKProofGenerator};
 
 use crate::error::{CepWalletError, Result};
+use std::sync::Arc;
 
 /// Privacy manager - orchestrates RAILGUN and Privacy Pools
 pub struct PrivacyManager {
@@ -41,15 +105,28 @@ pub struct PrivacyManager {
 }
 
 impl PrivacyManager {
-    /// Create new privacy manager
-    pub fn new(chain_id: u64) -> Result<Self> {
+    /// Create new privacy manager using `bridge` for proof generation
+    pub fn new(chain_id: u64, bridge: Arc<dyn NodeBridge>) -> Result<Self> {
         Ok(Self {
-            railgun: RailgunManager::new(chain_id)?,
+            railgun: RailgunManager::new(chain_id, bridge)?,
             privacy_pools: PrivacyPoolManager::new()?,
             zk_generator: ZKProofGenerator::new()?,
         })
     }
 
+    /// Load native prover circuits through `artifacts`
+    pub fn with_circuit_artifacts(mut self, artifacts: Arc<ArtifactManager>) -> Self {
+        self.zk_generator = self.zk_generator.with_artifacts(artifacts);
+        self
+    }
+
+    /// Verify generated and returned proofs with `keys`
+    pub fn with_verification_keys(mut self, keys: Arc<VerificationKeys>) -> Self {
+        self.railgun = self.railgun.with_verification_keys(keys.clone());
+        self.zk_generator = self.zk_generator.with_verification_keys(keys);
+        self
+    }
+
     /// Initialize privacy features
     pub async fn initialize(&mut self) -> Result<()> {
         // Initialize RAILGUN
@@ -91,13 +168,15 @@ mod tests {
 
     #[tokio::test]
     async fn test_privacy_manager_creation() {
-        let manager = PrivacyManager::new(11155111); // Sepolia testnet
+        let bridge = Arc::new(WorkerBridge::default());
+        let manager = PrivacyManager::new(11155111, bridge); // Sepolia testnet
         assert!(manager.is_ok());
     }
 
     #[tokio::test]
     async fn test_privacy_manager_initialization() {
-        let mut manager = PrivacyManager::new(11155111).unwrap(); // Sepolia testnet
+        let bridge = Arc::new(WorkerBridge::default());
+        let mut manager = PrivacyManager::new(11155111, bridge).unwrap(); // Sepolia testnet
         let result = manager.initialize().await;
         // Note: Will fail if proving keys not available
         // In production, we'll download them or bundle them
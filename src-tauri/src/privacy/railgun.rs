@@ -1,31 +1,57 @@
+use super::batch::TransferBatch;
+use super::bridge::NodeBridge;
+use super::broadcaster::{BridgeTransport, BroadcasterClient};
+use super::envelope::ProofEnvelope;
 use super::ffi::{
-    generate_shield_proof, generate_transfer_proof, generate_unshield_proof, scan_merkletree,
+    generate_batch_transfer_proof, generate_shield_proof, generate_transfer_proof,
+    generate_unshield_proof, ProofControl,
 };
-use super::types::{ShieldedTransaction, ShieldedTxType};
+use super::store::PrivacyStore;
+use super::sync::sync_merkletree;
+use super::types::{
+    validate_memo, BroadcasterFee, ShieldedBalances, ShieldedTransaction, ShieldedTxType,
+};
+use super::verifier::VerificationKeys;
+use super::zkproof::ProofType;
 /// RAILGUN Protocol Implementation
 ///
 /// Integrates with RAILGUN SDK via FFI to Node.js for ZK-SNARK proof generation.
 /// Architecture:
 /// 1. Rust handles contract interaction (ethers-rs)
 /// 2. Node.js handles ZK proof generation (@railgun-community/wallet)
-/// 3. FFI bridge for communication (subprocess + JSON)
+/// 3. `NodeBridge` for communication (long-lived worker, JSON-RPC)
 use crate::error::{CepWalletError, Result};
+use crate::history::{HistoryFilter, HistoryStore, HistoryTxType};
+use std::sync::Arc;
 
+#[derive(Clone)]
 pub struct RailgunManager {
     initialized: bool,
     chain_id: u64,
+    /// Proof generator
+    bridge: Arc<dyn NodeBridge>,
+    /// Keys that proofs from the proof generator are checked against
+    verification_keys: Arc<VerificationKeys>,
     // In production: RAILGUN wallet ID, encryption key, etc.
 }
 
 impl RailgunManager {
     /// Create new RAILGUN manager
-    pub fn new(chain_id: u64) -> Result<Self> {
+    pub fn new(chain_id: u64, bridge: Arc<dyn NodeBridge>) -> Result<Self> {
         Ok(Self {
             initialized: false,
             chain_id,
+            bridge,
+            verification_keys: Arc::default(),
         })
     }
 
+    /// Verify returned proofs with `keys`
+    pub fn with_verification_keys(mut self, keys: Arc<VerificationKeys>) -> Self {
+        self.verification_keys = keys;
+        self
+    }
+
     /// Initialize RAILGUN
     pub async fn initialize(&mut self) -> Result<()> {
         // In production:
@@ -53,6 +79,7 @@ impl RailgunManager {
         amount: &str,
         railgun_address: &str,
         shield_private_key: &str,
+        control: ProofControl,
     ) -> Result<ShieldedTransaction> {
         if !self.initialized {
             return Err(CepWalletError::NotInitialized(
@@ -67,11 +94,13 @@ impl RailgunManager {
 
         // Generate ZK-SNARK proof via FFI
         let proof_response = generate_shield_proof(
+            self.bridge.as_ref(),
             token,
             amount,
             railgun_address,
             shield_private_key,
-            Some(self.chain_id),
+            self.chain_id,
+            control,
         )
         .await?;
 
@@ -106,10 +135,16 @@ impl RailgunManager {
             merkle_root: None,
             nullifier: None,
             transaction: proof_response.transaction,
+            history_id: None,
+            proofs: Vec::new(),
+            broadcaster_fee: None,
+            outputs: Vec::new(),
+            memo: None,
         })
     }
 
     /// Private Transfer: Private -> Private (within RAILGUN)
+    #[allow(clippy::too_many_arguments)]
     pub async fn shielded_transfer(
         &self,
         railgun_wallet_id: &str,
@@ -118,12 +153,18 @@ impl RailgunManager {
         to_railgun_address: &str,
         token: &str,
         amount: &str,
+        memo: Option<&str>,
+        broadcaster_fee: Option<&BroadcasterFee>,
+        control: ProofControl,
     ) -> Result<ShieldedTransaction> {
         if !self.initialized {
             return Err(CepWalletError::NotInitialized(
                 "RAILGUN not initialized".to_string(),
             ));
         }
+        if let Some(memo) = memo {
+            validate_memo(memo)?;
+        }
 
         println!(
             "🔒 Generating private transfer proof for {} of token {}...",
@@ -132,13 +173,17 @@ impl RailgunManager {
 
         // Generate ZK-SNARK proof via FFI
         let proof_response = generate_transfer_proof(
+            self.bridge.as_ref(),
             railgun_wallet_id,
             encryption_key,
             mnemonic,
             to_railgun_address,
             token,
             amount,
-            Some(self.chain_id),
+            memo,
+            broadcaster_fee,
+            self.chain_id,
+            control,
         )
         .await?;
 
@@ -153,7 +198,7 @@ impl RailgunManager {
         println!("✓ Private transfer proof generated successfully");
         println!("  Proof cached in RAILGUN SDK: {}", proof_response.proof);
 
-        Ok(ShieldedTransaction {
+        let mut tx = ShieldedTransaction {
             tx_type: ShieldedTxType::Transfer,
             token: token.to_string(),
             amount: amount.to_string(),
@@ -161,11 +206,70 @@ impl RailgunManager {
             proof: Some(proof_response.proof),
             merkle_root: None,
             nullifier: None,
-            transaction: None,
-        })
+            transaction: proof_response.transaction,
+            history_id: None,
+            proofs: Vec::new(),
+            broadcaster_fee: broadcaster_fee.cloned(),
+            outputs: Vec::new(),
+            memo: memo.map(str::to_string),
+        };
+        tx.proofs = self.verify_transaction(&tx)?;
+        Ok(tx)
+    }
+
+    /// Multi-output private transfer: every output of `batch` in one
+    /// transaction
+    pub async fn shielded_transfer_batch(
+        &self,
+        railgun_wallet_id: &str,
+        encryption_key: &str,
+        mnemonic: &str,
+        batch: &TransferBatch,
+        broadcaster_fee: Option<&BroadcasterFee>,
+        control: ProofControl,
+    ) -> Result<ShieldedTransaction> {
+        if !self.initialized {
+            return Err(CepWalletError::NotInitialized(
+                "RAILGUN not initialized".to_string(),
+            ));
+        }
+
+        println!(
+            "🔒 Generating private transfer proofs for {} outputs in {} tokens...",
+            batch.outputs().len(),
+            batch.tokens().len()
+        );
+
+        let proof_response = generate_batch_transfer_proof(
+            self.bridge.as_ref(),
+            railgun_wallet_id,
+            encryption_key,
+            mnemonic,
+            batch,
+            broadcaster_fee,
+            self.chain_id,
+            control,
+        )
+        .await?;
+
+        if !proof_response.success {
+            return Err(CepWalletError::PrivacyError(
+                proof_response
+                    .error
+                    .unwrap_or_else(|| "Transfer proof generation failed".to_string()),
+            ));
+        }
+
+        let mut tx = batch.transaction();
+        tx.proof = Some(proof_response.proof);
+        tx.transaction = proof_response.transaction;
+        tx.broadcaster_fee = broadcaster_fee.cloned();
+        tx.proofs = self.verify_transaction(&tx)?;
+        Ok(tx)
     }
 
     /// Unshield: Private -> Public (withdraw from RAILGUN)
+    #[allow(clippy::too_many_arguments)]
     pub async fn unshield(
         &self,
         railgun_wallet_id: &str,
@@ -174,6 +278,8 @@ impl RailgunManager {
         recipient_address: &str,
         token: &str,
         amount: &str,
+        broadcaster_fee: Option<&BroadcasterFee>,
+        control: ProofControl,
     ) -> Result<ShieldedTransaction> {
         if !self.initialized {
             return Err(CepWalletError::NotInitialized(
@@ -188,13 +294,16 @@ impl RailgunManager {
 
         // Generate ZK-SNARK proof via FFI
         let proof_response = generate_unshield_proof(
+            self.bridge.as_ref(),
             railgun_wallet_id,
             encryption_key,
             mnemonic,
             recipient_address,
             token,
             amount,
-            Some(self.chain_id),
+            broadcaster_fee,
+            self.chain_id,
+            control,
         )
         .await?;
 
@@ -209,7 +318,7 @@ impl RailgunManager {
         println!("✓ Unshield proof generated successfully");
         println!("  Proof cached in RAILGUN SDK: {}", proof_response.proof);
 
-        Ok(ShieldedTransaction {
+        let mut tx = ShieldedTransaction {
             tx_type: ShieldedTxType::Unshield,
             token: token.to_string(),
             amount: amount.to_string(),
@@ -217,29 +326,103 @@ impl RailgunManager {
             proof: Some(proof_response.proof),
             merkle_root: None,
             nullifier: None,
-            transaction: None,
-        })
+            transaction: proof_response.transaction,
+            history_id: None,
+            proofs: Vec::new(),
+            broadcaster_fee: broadcaster_fee.cloned(),
+            outputs: Vec::new(),
+            memo: None,
+        };
+        tx.proofs = self.verify_transaction(&tx)?;
+        Ok(tx)
+    }
+
+    /// Verify the SNARKs in a transfer or unshield transaction
+    ///
+    /// The proof generator is not trusted: every proof in the transaction's
+    /// calldata must pass the pairing check against its verification key,
+    /// for this chain, before the transaction may be signed. Shields carry
+    /// no SNARK and pass as-is. Returns the verified proofs; a proof whose
+    /// circuit has no verification key is refused.
+    pub fn verify_transaction(&self, tx: &ShieldedTransaction) -> Result<Vec<ProofEnvelope>> {
+        let proof_type = match tx.tx_type {
+            ShieldedTxType::Shield => return Ok(Vec::new()),
+            ShieldedTxType::Transfer => ProofType::Transfer,
+            ShieldedTxType::Unshield => ProofType::Unshield,
+            ShieldedTxType::Receive => {
+                return Err(CepWalletError::InvalidInput(
+                    "Received notes carry no proof".to_string(),
+                ))
+            }
+        };
+        let data = tx
+            .transaction
+            .as_ref()
+            .and_then(|tx| tx["data"].as_str())
+            .ok_or_else(|| {
+                CepWalletError::PrivacyError(format!(
+                    "{} proof returned no transaction",
+                    proof_type
+                ))
+            })?;
+        let calldata = hex::decode(data.trim_start_matches("0x")).map_err(|e| {
+            CepWalletError::PrivacyError(format!("Invalid transaction data: {}", e))
+        })?;
+
+        let proofs =
+            self.verification_keys
+                .verify_transaction(&proof_type, self.chain_id, &calldata)?;
+        tracing::debug!("{} {} proofs verified", proofs.len(), proof_type);
+        Ok(proofs)
     }
 
-    /// Get shielded balance
-    pub async fn get_shielded_balance(&self, token: &str) -> Result<String> {
+    /// Get the spendable shielded balance of a token from the latest scan
+    pub fn get_shielded_balance(
+        &self,
+        store: &PrivacyStore,
+        railgun_wallet_id: &str,
+        token: &str,
+    ) -> Result<String> {
+        Ok(self
+            .get_shielded_balances(store, railgun_wallet_id)?
+            .spendable(token)
+            .to_string())
+    }
+
+    /// Get all shielded balances (per token and note status) from the latest scan
+    pub fn get_shielded_balances(
+        &self,
+        store: &PrivacyStore,
+        railgun_wallet_id: &str,
+    ) -> Result<ShieldedBalances> {
         if !self.initialized {
             return Err(CepWalletError::NotInitialized(
                 "RAILGUN not initialized".to_string(),
             ));
         }
 
-        // In production:
-        // 1. Scan Merkle tree for our notes
-        // 2. Sum up unspent notes
-        // 3. Return total shielded balance
-
-        println!("💰 Getting shielded balance for {}", token);
-        Ok("0".to_string()) // Placeholder
+        store
+            .balances(railgun_wallet_id, self.chain_id)?
+            .ok_or_else(|| {
+                CepWalletError::PrivacyError(format!(
+                    "Wallet {} has not been scanned on chain {}; run scan_merkletree first",
+                    railgun_wallet_id, self.chain_id
+                ))
+            })
     }
 
-    /// Scan merkletree to update wallet balances
-    pub async fn scan_merkletree(&self, railgun_wallet_id: &str) -> Result<()> {
+    /// Scan merkletree, decrypt the wallet's notes and persist its balances
+    /// and the notes it received
+    ///
+    /// Blocking variant of the background sync: resumes from the last synced
+    /// block but reports no progress and cannot be cancelled.
+    pub async fn scan_merkletree(
+        &self,
+        store: &PrivacyStore,
+        history: &HistoryStore,
+        railgun_wallet_id: &str,
+        encryption_key: &str,
+    ) -> Result<ShieldedBalances> {
         if !self.initialized {
             return Err(CepWalletError::NotInitialized(
                 "RAILGUN not initialized".to_string(),
@@ -248,65 +431,113 @@ impl RailgunManager {
 
         println!("🔍 Scanning merkletree for wallet: {}", railgun_wallet_id);
 
-        // Call Node.js to scan merkletree
-        let scan_result = scan_merkletree(railgun_wallet_id, Some(self.chain_id)).await?;
+        let (_cancel, cancel_rx) = tokio::sync::watch::channel(false);
+        let balances = sync_merkletree(
+            self.bridge.as_ref(),
+            store,
+            history,
+            self.chain_id,
+            railgun_wallet_id,
+            encryption_key,
+            cancel_rx,
+            |_| {},
+        )
+        .await?;
 
-        if !scan_result.success {
-            return Err(CepWalletError::PrivacyError(
-                scan_result
-                    .error
-                    .unwrap_or_else(|| "Merkletree scan failed".to_string()),
-            ));
-        }
+        println!(
+            "✅ Merkletree scan completed ({} balances)",
+            balances.balances.len()
+        );
+        Ok(balances)
+    }
 
-        println!("✅ Merkletree scan completed successfully");
-        Ok(())
+    /// Get shielded transaction history for a RAILGUN account on this chain
+    pub fn get_transaction_history(
+        &self,
+        history: &HistoryStore,
+        account: &str,
+    ) -> Result<Vec<ShieldedTransaction>> {
+        let filter = HistoryFilter {
+            account: Some(account.to_string()),
+            chain_id: Some(self.chain_id),
+            tx_types: vec![
+                HistoryTxType::Shield,
+                HistoryTxType::Transfer,
+                HistoryTxType::Unshield,
+                HistoryTxType::Receive,
+            ],
+            ..Default::default()
+        };
+
+        Ok(history
+            .query_all(&filter)?
+            .into_iter()
+            .filter_map(|entry| {
+                entry.shielded.map(|tx| ShieldedTransaction {
+                    history_id: Some(entry.id),
+                    ..tx
+                })
+            })
+            .collect())
     }
 
-    /// Get transaction history
-    pub async fn get_transaction_history(&self) -> Result<Vec<ShieldedTransaction>> {
-        if !self.initialized {
-            return Err(CepWalletError::NotInitialized(
-                "RAILGUN not initialized".to_string(),
-            ));
-        }
+    /// Chain ID this manager operates on
+    pub fn chain_id(&self) -> u64 {
+        self.chain_id
+    }
 
-        // In production:
-        // 1. Decrypt our notes from Merkle tree events
-        // 2. Build transaction history
-        // 3. Return chronological list
+    /// Proof generator used by this manager
+    pub fn bridge(&self) -> &dyn NodeBridge {
+        self.bridge.as_ref()
+    }
 
-        Ok(Vec::new()) // Placeholder
+    /// Broadcaster client for this chain, over the proof generator's Waku
+    pub fn broadcaster(&self) -> BroadcasterClient {
+        BroadcasterClient::new(
+            self.chain_id,
+            Arc::new(BridgeTransport::new(self.bridge.clone())),
+        )
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::privacy::bridge::{FakeBridge, WorkerBridge};
+    use crate::privacy::types::{NoteStatus, ShieldedBalance};
+    use crate::privacy::verifier::VerificationKey;
+
+    fn manager() -> RailgunManager {
+        RailgunManager::new(11155111, Arc::new(WorkerBridge::default())).unwrap()
+    }
 
     #[tokio::test]
     async fn test_railgun_creation() {
-        let manager = RailgunManager::new();
+        let manager = RailgunManager::new(11155111, Arc::new(WorkerBridge::default()));
         assert!(manager.is_ok());
     }
 
     #[tokio::test]
     async fn test_railgun_initialization() {
-        let mut manager = RailgunManager::new().unwrap();
+        let mut manager = manager();
         let result = manager.initialize().await;
         assert!(result.is_ok());
         assert!(manager.is_ready());
     }
 
     #[tokio::test]
+    #[ignore] // Only run if Node.js and proof-generator are set up
     async fn test_shield_operation() {
-        let mut manager = RailgunManager::new().unwrap();
+        let mut manager = manager();
         manager.initialize().await.unwrap();
 
         let result = manager
             .shield(
                 "0x0000000000000000000000000000000000000000",
                 "1000000000000000000",
+                "0zk1234...railgun_address...",
+                &format!("0x{}", "0".repeat(64)),
+                ProofControl::default(),
             )
             .await;
 
@@ -315,17 +546,233 @@ mod tests {
         assert_eq!(tx.tx_type, ShieldedTxType::Shield);
     }
 
+    #[tokio::test]
+    async fn test_shield_with_fake_bridge() {
+        let bridge = Arc::new(FakeBridge::new(|_| {
+            Ok(serde_json::json!({
+                "success": true,
+                "proof": "0xproof",
+                "transaction": { "to": "0xrailgun", "data": "0x" },
+            }))
+        }));
+        let mut manager = RailgunManager::new(11155111, bridge.clone()).unwrap();
+        manager.initialize().await.unwrap();
+
+        let tx = manager
+            .shield(
+                "0x0000000000000000000000000000000000000000",
+                "1000",
+                "0zk1test",
+                &format!("0x{}", "0".repeat(64)),
+                ProofControl::default(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(tx.proof.as_deref(), Some("0xproof"));
+        assert!(tx.transaction.is_some());
+        assert_eq!(bridge.methods(), vec!["generate_shield_proof"]);
+    }
+
+    #[tokio::test]
+    async fn test_six_decimal_amounts_reach_the_bridge_in_base_units() {
+        use crate::utils::units::{parse_amount, AmountInput};
+
+        // "1 USDC" and "0.5 USDC", parsed with the token's 6 decimals as the
+        // commands do, reach the proof generator as base units
+        let base_units = |input: &str| match parse_amount(input, 6, "USDC", false).unwrap() {
+            AmountInput::Exact(amount) => amount.to_string(),
+            AmountInput::Max => unreachable!(),
+        };
+        let bridge = Arc::new(FakeBridge::new(|request| {
+            let params = request.params()?;
+            let expected = match params["proofType"].as_str() {
+                Some("shield") => "1000000",
+                _ => "500000",
+            };
+            assert_eq!(params["amount"], expected);
+            Ok(serde_json::json!({
+                "success": true,
+                "proof": "0xproof",
+                "transaction": { "to": "0xrailgun", "data": "0x" },
+            }))
+        }));
+        let mut manager = RailgunManager::new(11155111, bridge.clone()).unwrap();
+        manager.initialize().await.unwrap();
+
+        let usdc = "0x1c7D4B196Cb0C7B01d743Fbc6116a902379C7238";
+        manager
+            .shield(
+                usdc,
+                &base_units("1 USDC"),
+                "0zk1test",
+                &format!("0x{}", "0".repeat(64)),
+                ProofControl::default(),
+            )
+            .await
+            .unwrap();
+        // The empty calldata fails verification afterwards; only the
+        // request matters here
+        let _ = manager
+            .unshield(
+                "wallet-1",
+                "key",
+                "mnemonic",
+                "0x0000000000000000000000000000000000000001",
+                usdc,
+                &base_units("0.5 USDC"),
+                None,
+                ProofControl::default(),
+            )
+            .await;
+        assert_eq!(
+            bridge.methods(),
+            vec!["generate_shield_proof", "generate_unshield_proof"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_transfer_proof_is_verified() {
+        let calldata = crate::privacy::transact::tests::transact_calldata(11155111);
+        let data = format!("0x{}", hex::encode(calldata));
+        let bridge = Arc::new(FakeBridge::new(move |_| {
+            Ok(serde_json::json!({
+                "success": true,
+                "proof": "cached",
+                "transaction": { "to": "0xrailgun", "data": data },
+            }))
+        }));
+        let keys = Arc::new(VerificationKeys::new(None, None));
+        let mut manager = RailgunManager::new(11155111, bridge)
+            .unwrap()
+            .with_verification_keys(keys.clone());
+        manager.initialize().await.unwrap();
+
+        let transfer = || {
+            manager.shielded_transfer(
+                "wallet-1",
+                "key",
+                "mnemonic",
+                "0zk1test",
+                "0xfFf9976782d46CC05630D1f6eBAb18b2324d6B14",
+                "1000",
+                None,
+                None,
+                ProofControl::default(),
+            )
+        };
+
+        // No key for the 01x02 variant: refused, never handed out unverified
+        let err = transfer().await.unwrap_err().to_string();
+        assert!(err.contains("No verification key"), "{}", err);
+
+        // A key that does not match the proof rejects it
+        let vkey = include_str!("../../fixtures/circuits/multiplier2.vkey.json");
+        keys.insert("01x02", VerificationKey::from_snarkjs_json(vkey).unwrap());
+        let err = transfer().await.unwrap_err().to_string();
+        assert!(err.contains("failed verification"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_batch_transfer_sends_all_outputs() {
+        let calldata = crate::privacy::transact::tests::transact_calldata(11155111);
+        let data = format!("0x{}", hex::encode(calldata));
+        let bridge = Arc::new(FakeBridge::new(move |request| {
+            let params = request.params()?;
+            assert_eq!(params["proofType"], "transfer");
+            assert_eq!(params["outputs"].as_array().unwrap().len(), 2);
+            assert_eq!(params["outputs"][1]["recipient"], "0zk1bob");
+            assert!(params.get("recipient").is_none());
+            assert_eq!(params["memoText"], "INV-7");
+            Ok(serde_json::json!({
+                "success": true,
+                "proof": "cached",
+                "transaction": { "to": "0xrailgun", "data": data },
+            }))
+        }));
+        let keys = Arc::new(VerificationKeys::new(None, None));
+        let mut manager = RailgunManager::new(11155111, bridge.clone())
+            .unwrap()
+            .with_verification_keys(keys.clone());
+        manager.initialize().await.unwrap();
+
+        let output = |recipient: &str| crate::privacy::types::TransferOutput {
+            recipient: recipient.to_string(),
+            token: "0xfFf9976782d46CC05630D1f6eBAb18b2324d6B14".to_string(),
+            amount: "1000".to_string(),
+            memo: Some("INV-7".to_string()),
+        };
+        let batch = TransferBatch::new(vec![output("0zk1alice"), output("0zk1bob")]).unwrap();
+        let transfer = || {
+            manager.shielded_transfer_batch(
+                "wallet-1",
+                "key",
+                "mnemonic",
+                &batch,
+                None,
+                ProofControl::default(),
+            )
+        };
+        // Batch proofs are verified like single transfers
+        let err = transfer().await.unwrap_err().to_string();
+        assert!(err.contains("No verification key"), "{}", err);
+        let vkey = include_str!("../../fixtures/circuits/multiplier2.vkey.json");
+        keys.insert("01x02", VerificationKey::from_snarkjs_json(vkey).unwrap());
+        let err = transfer().await.unwrap_err().to_string();
+        assert!(err.contains("failed verification"), "{}", err);
+        assert_eq!(
+            bridge.methods(),
+            vec!["generate_transfer_proof", "generate_transfer_proof"]
+        );
+    }
+
     #[tokio::test]
     async fn test_operations_before_init() {
-        let manager = RailgunManager::new().unwrap();
+        let manager = manager();
 
         let result = manager
             .shield(
                 "0x0000000000000000000000000000000000000000",
                 "1000000000000000000",
+                "0zk1234...railgun_address...",
+                &format!("0x{}", "0".repeat(64)),
+                ProofControl::default(),
             )
             .await;
 
         assert!(result.is_err());
     }
+
+    #[tokio::test]
+    async fn test_balance_requires_scan() {
+        let mut manager = manager();
+        manager.initialize().await.unwrap();
+        let store = PrivacyStore::in_memory().unwrap();
+
+        assert!(manager
+            .get_shielded_balance(&store, "wallet-1", "0x0")
+            .is_err());
+
+        store
+            .save_balances(&ShieldedBalances {
+                railgun_wallet_id: "wallet-1".to_string(),
+                chain_id: 11155111,
+                balances: vec![ShieldedBalance {
+                    token: "0xfFf9976782d46CC05630D1f6eBAb18b2324d6B14".to_string(),
+                    status: NoteStatus::Spendable,
+                    amount: "7".to_string(),
+                }],
+                scanned_at: 0,
+            })
+            .unwrap();
+
+        let balance = manager
+            .get_shielded_balance(
+                &store,
+                "wallet-1",
+                "0xfFf9976782d46CC05630D1f6eBAb18b2324d6B14",
+            )
+            .unwrap();
+        assert_eq!(balance, "7");
+    }
 }
@@ -0,0 +1,543 @@
+/// Local privacy state - persisted results of RAILGUN merkletree scans
+///
+/// Stored in a SQLite database (`privacy.db` in the app data directory) so
+/// shielded balances are available without re-running the Node.js scanner,
+/// syncs resume from the last scanned block and finished proof jobs survive
+/// a restart. Hardware-derived wallets are registered here, so their secrets
+/// are re-derived from the signer rather than passed in.
+use super::jobs::{ProofJob, ProofJobStatus};
+use super::types::{NoteStatus, ShieldedBalance, ShieldedBalances};
+use crate::error::{CepWalletError, Result};
+use rusqlite::{params, Connection, OptionalExtension};
+use std::path::Path;
+use std::sync::Mutex;
+
+/// Database file name inside the app data directory
+pub const PRIVACY_DB_FILE: &str = "privacy.db";
+
+const SCHEMA_VERSION: i32 = 4;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS balance_scans (
+    wallet_id   TEXT NOT NULL,
+    chain_id    INTEGER NOT NULL,
+    scanned_at  INTEGER NOT NULL,
+    PRIMARY KEY (wallet_id, chain_id)
+);
+CREATE TABLE IF NOT EXISTS shielded_balances (
+    wallet_id   TEXT NOT NULL,
+    chain_id    INTEGER NOT NULL,
+    token       TEXT NOT NULL,
+    status      TEXT NOT NULL,
+    amount      TEXT NOT NULL,
+    PRIMARY KEY (wallet_id, chain_id, token, status)
+);
+CREATE TABLE IF NOT EXISTS sync_state (
+    chain_id    INTEGER PRIMARY KEY,
+    last_block  INTEGER NOT NULL,
+    updated_at  INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS proof_jobs (
+    job_id      TEXT PRIMARY KEY,
+    proof_type  TEXT NOT NULL,
+    status      TEXT NOT NULL,
+    error       TEXT,
+    result      TEXT,
+    created_at  INTEGER NOT NULL,
+    finished_at INTEGER
+);
+CREATE TABLE IF NOT EXISTS hardware_wallets (
+    wallet_id   TEXT PRIMARY KEY,
+    account     INTEGER NOT NULL,
+    created_at  INTEGER NOT NULL
+);
+";
+
+fn privacy_error(message: impl Into<String>) -> CepWalletError {
+    CepWalletError::PrivacyError(message.into())
+}
+
+fn sql_error(e: rusqlite::Error) -> CepWalletError {
+    privacy_error(format!("Privacy database error: {}", e))
+}
+
+/// SQLite-backed privacy state
+pub struct PrivacyStore {
+    conn: Mutex<Connection>,
+}
+
+impl PrivacyStore {
+    /// Open (or create) the privacy database in a data directory
+    pub fn open<P: AsRef<Path>>(data_dir: P) -> Result<Self> {
+        let conn = Connection::open(data_dir.as_ref().join(PRIVACY_DB_FILE)).map_err(sql_error)?;
+        Self::with_connection(conn)
+    }
+
+    /// In-memory store (tests)
+    pub fn in_memory() -> Result<Self> {
+        Self::with_connection(Connection::open_in_memory().map_err(sql_error)?)
+    }
+
+    fn with_connection(conn: Connection) -> Result<Self> {
+        let version: i32 = conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .map_err(sql_error)?;
+        if version > SCHEMA_VERSION {
+            return Err(privacy_error(format!(
+                "Privacy database version {} is newer than supported ({})",
+                version, SCHEMA_VERSION
+            )));
+        }
+
+        conn.execute_batch(SCHEMA).map_err(sql_error)?;
+        conn.pragma_update(None, "user_version", SCHEMA_VERSION)
+            .map_err(sql_error)?;
+
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    fn conn(&self) -> Result<std::sync::MutexGuard<'_, Connection>> {
+        self.conn
+            .lock()
+            .map_err(|_| privacy_error("Privacy database lock poisoned"))
+    }
+
+    /// Replace the stored balances of a wallet on a chain with a new scan
+    pub fn save_balances(&self, scan: &ShieldedBalances) -> Result<()> {
+        let mut conn = self.conn()?;
+        let tx = conn.transaction().map_err(sql_error)?;
+        let chain_id = scan.chain_id as i64;
+
+        tx.execute(
+            "DELETE FROM shielded_balances WHERE wallet_id = ?1 AND chain_id = ?2",
+            params![scan.railgun_wallet_id, chain_id],
+        )
+        .map_err(sql_error)?;
+        for balance in &scan.balances {
+            tx.execute(
+                "INSERT INTO shielded_balances (wallet_id, chain_id, token, status, amount) \
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    scan.railgun_wallet_id,
+                    chain_id,
+                    balance.token,
+                    balance.status.as_str(),
+                    balance.amount,
+                ],
+            )
+            .map_err(sql_error)?;
+        }
+        tx.execute(
+            "INSERT OR REPLACE INTO balance_scans (wallet_id, chain_id, scanned_at) \
+             VALUES (?1, ?2, ?3)",
+            params![scan.railgun_wallet_id, chain_id, scan.scanned_at],
+        )
+        .map_err(sql_error)?;
+
+        tx.commit().map_err(sql_error)
+    }
+
+    /// Balances from the latest scan of a wallet on a chain, if it was scanned
+    pub fn balances(
+        &self,
+        railgun_wallet_id: &str,
+        chain_id: u64,
+    ) -> Result<Option<ShieldedBalances>> {
+        let conn = self.conn()?;
+
+        let scanned_at: Option<i64> = conn
+            .query_row(
+                "SELECT scanned_at FROM balance_scans WHERE wallet_id = ?1 AND chain_id = ?2",
+                params![railgun_wallet_id, chain_id as i64],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(sql_error)?;
+        let Some(scanned_at) = scanned_at else {
+            return Ok(None);
+        };
+
+        let mut stmt = conn
+            .prepare(
+                "SELECT token, status, amount FROM shielded_balances \
+                 WHERE wallet_id = ?1 AND chain_id = ?2 ORDER BY token, status",
+            )
+            .map_err(sql_error)?;
+        let rows = stmt
+            .query_map(params![railgun_wallet_id, chain_id as i64], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                ))
+            })
+            .map_err(sql_error)?;
+
+        let mut balances = Vec::new();
+        for row in rows {
+            let (token, status, amount) = row.map_err(sql_error)?;
+            let status = NoteStatus::parse(&status)
+                .ok_or_else(|| privacy_error(format!("Unknown note status: {}", status)))?;
+            balances.push(ShieldedBalance {
+                token,
+                status,
+                amount,
+            });
+        }
+
+        Ok(Some(ShieldedBalances {
+            railgun_wallet_id: railgun_wallet_id.to_string(),
+            chain_id,
+            balances,
+            scanned_at,
+        }))
+    }
+
+    /// Last block the merkletree was synced to on a chain
+    pub fn last_synced_block(&self, chain_id: u64) -> Result<Option<u64>> {
+        let block: Option<i64> = self
+            .conn()?
+            .query_row(
+                "SELECT last_block FROM sync_state WHERE chain_id = ?1",
+                params![chain_id as i64],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(sql_error)?;
+        Ok(block.map(|block| block as u64))
+    }
+
+    /// Record the block a merkletree sync reached on a chain
+    pub fn set_last_synced_block(&self, chain_id: u64, block: u64) -> Result<()> {
+        self.conn()?
+            .execute(
+                "INSERT OR REPLACE INTO sync_state (chain_id, last_block, updated_at) \
+                 VALUES (?1, ?2, ?3)",
+                params![
+                    chain_id as i64,
+                    block as i64,
+                    chrono::Utc::now().timestamp()
+                ],
+            )
+            .map_err(sql_error)?;
+        Ok(())
+    }
+
+    /// Register a wallet whose secrets are derived from the signer
+    pub fn save_hardware_wallet(&self, railgun_wallet_id: &str, account: u32) -> Result<()> {
+        self.conn()?
+            .execute(
+                "INSERT OR REPLACE INTO hardware_wallets (wallet_id, account, created_at) \
+                 VALUES (?1, ?2, ?3)",
+                params![railgun_wallet_id, account, chrono::Utc::now().timestamp()],
+            )
+            .map_err(sql_error)?;
+        Ok(())
+    }
+
+    /// RAILGUN account index of a hardware-derived wallet; `None` for
+    /// wallets with their own encryption key and mnemonic
+    pub fn hardware_wallet_account(&self, railgun_wallet_id: &str) -> Result<Option<u32>> {
+        self.conn()?
+            .query_row(
+                "SELECT account FROM hardware_wallets WHERE wallet_id = ?1",
+                params![railgun_wallet_id],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(sql_error)
+    }
+
+    /// Record a newly queued proof job
+    ///
+    /// Job ids are never reused: fails when a job with this id exists.
+    pub fn create_proof_job(&self, job: &ProofJob) -> Result<()> {
+        let inserted = self
+            .conn()?
+            .execute(
+                "INSERT OR IGNORE INTO proof_jobs \
+                 (job_id, proof_type, status, created_at) VALUES (?1, ?2, ?3, ?4)",
+                params![
+                    job.job_id,
+                    job.proof_type,
+                    job.status.as_str(),
+                    job.created_at
+                ],
+            )
+            .map_err(sql_error)?;
+        if inserted == 0 {
+            return Err(CepWalletError::InvalidInput(format!(
+                "Proof job {} already exists",
+                job.job_id
+            )));
+        }
+        Ok(())
+    }
+
+    /// Mark the jobs left queued or running by a previous run as
+    /// interrupted; returns how many there were
+    pub fn interrupt_proof_jobs(&self) -> Result<usize> {
+        self.conn()?
+            .execute(
+                "UPDATE proof_jobs SET status = ?1, error = ?2, finished_at = ?3 \
+                 WHERE status IN (?4, ?5)",
+                params![
+                    ProofJobStatus::Interrupted.as_str(),
+                    "Interrupted by an app restart",
+                    chrono::Utc::now().timestamp(),
+                    ProofJobStatus::Queued.as_str(),
+                    ProofJobStatus::Running.as_str(),
+                ],
+            )
+            .map_err(sql_error)
+    }
+
+    /// Save the final state of a proof job (result as JSON)
+    pub fn save_proof_job(&self, job: &ProofJob) -> Result<()> {
+        let result = job
+            .result
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()
+            .map_err(|e| privacy_error(format!("Failed to encode proof result: {}", e)))?;
+        self.conn()?
+            .execute(
+                "INSERT OR REPLACE INTO proof_jobs \
+                 (job_id, proof_type, status, error, result, created_at, finished_at) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    job.job_id,
+                    job.proof_type,
+                    job.status.as_str(),
+                    job.error,
+                    result,
+                    job.created_at,
+                    job.finished_at,
+                ],
+            )
+            .map_err(sql_error)?;
+        Ok(())
+    }
+
+    /// A persisted proof job
+    pub fn proof_job(&self, job_id: &str) -> Result<Option<ProofJob>> {
+        let row = self
+            .conn()?
+            .query_row(
+                "SELECT proof_type, status, error, result, created_at, finished_at \
+                 FROM proof_jobs WHERE job_id = ?1",
+                params![job_id],
+                |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, Option<String>>(2)?,
+                        row.get::<_, Option<String>>(3)?,
+                        row.get::<_, i64>(4)?,
+                        row.get::<_, Option<i64>>(5)?,
+                    ))
+                },
+            )
+            .optional()
+            .map_err(sql_error)?;
+        let Some((proof_type, status, error, result, created_at, finished_at)) = row else {
+            return Ok(None);
+        };
+
+        let status = ProofJobStatus::parse(&status)
+            .ok_or_else(|| privacy_error(format!("Unknown proof job status: {}", status)))?;
+        let result = result
+            .map(|result| serde_json::from_str(&result))
+            .transpose()
+            .map_err(|e| privacy_error(format!("Invalid stored proof result: {}", e)))?;
+        Ok(Some(ProofJob {
+            job_id: job_id.to_string(),
+            proof_type,
+            status,
+            percent: if status == ProofJobStatus::Completed {
+                100.0
+            } else {
+                0.0
+            },
+            error,
+            result,
+            created_at,
+            finished_at,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::privacy::envelope::tests::multiplier_envelope;
+    use crate::privacy::types::{ShieldedTransaction, ShieldedTxType};
+    use ethers::types::U256;
+
+    const WETH: &str = "0xfFf9976782d46CC05630D1f6eBAb18b2324d6B14";
+
+    fn scan(balances: Vec<ShieldedBalance>) -> ShieldedBalances {
+        ShieldedBalances {
+            railgun_wallet_id: "wallet-1".to_string(),
+            chain_id: 11155111,
+            balances,
+            scanned_at: 1_700_000_000,
+        }
+    }
+
+    fn balance(status: NoteStatus, amount: &str) -> ShieldedBalance {
+        ShieldedBalance {
+            token: WETH.to_string(),
+            status,
+            amount: amount.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_save_and_load_balances() {
+        let store = PrivacyStore::in_memory().unwrap();
+        assert!(store.balances("wallet-1", 11155111).unwrap().is_none());
+
+        store
+            .save_balances(&scan(vec![
+                balance(NoteStatus::Spendable, "1000"),
+                balance(NoteStatus::PendingPoi, "250"),
+            ]))
+            .unwrap();
+
+        let loaded = store.balances("wallet-1", 11155111).unwrap().unwrap();
+        assert_eq!(loaded.scanned_at, 1_700_000_000);
+        assert_eq!(loaded.balances.len(), 2);
+        assert_eq!(loaded.spendable(WETH), U256::from(1000u64));
+        assert_eq!(
+            loaded.amount(&WETH.to_lowercase(), NoteStatus::PendingPoi),
+            U256::from(250u64)
+        );
+        assert!(store.balances("wallet-1", 1).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_rescan_replaces_balances() {
+        let store = PrivacyStore::in_memory().unwrap();
+        store
+            .save_balances(&scan(vec![balance(NoteStatus::PendingPoi, "250")]))
+            .unwrap();
+        store
+            .save_balances(&scan(vec![balance(NoteStatus::Spendable, "250")]))
+            .unwrap();
+
+        let loaded = store.balances("wallet-1", 11155111).unwrap().unwrap();
+        assert_eq!(loaded.balances, vec![balance(NoteStatus::Spendable, "250")]);
+        assert_eq!(loaded.amount(WETH, NoteStatus::PendingPoi), U256::zero());
+    }
+
+    #[test]
+    fn test_persists_across_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        PrivacyStore::open(dir.path())
+            .unwrap()
+            .save_balances(&scan(vec![balance(NoteStatus::Spendable, "42")]))
+            .unwrap();
+
+        let reopened = PrivacyStore::open(dir.path()).unwrap();
+        let loaded = reopened.balances("wallet-1", 11155111).unwrap().unwrap();
+        assert_eq!(loaded.spendable(WETH), U256::from(42u64));
+    }
+
+    #[test]
+    fn test_proof_jobs_persist() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut job = ProofJob::queued("job-1", "shield");
+        job.finish(
+            Ok(ShieldedTransaction {
+                tx_type: ShieldedTxType::Shield,
+                token: WETH.to_string(),
+                amount: "1000".to_string(),
+                recipient: Some("0zk1recipient".to_string()),
+                proof: Some("0xproof".to_string()),
+                merkle_root: None,
+                nullifier: None,
+                transaction: Some(serde_json::json!({ "to": "0xrailgun", "data": "0x" })),
+                history_id: Some("history-1".to_string()),
+                proofs: vec![multiplier_envelope()],
+                broadcaster_fee: None,
+                outputs: Vec::new(),
+                memo: None,
+            }),
+            false,
+        );
+        PrivacyStore::open(dir.path())
+            .unwrap()
+            .save_proof_job(&job)
+            .unwrap();
+
+        let reopened = PrivacyStore::open(dir.path()).unwrap();
+        let loaded = reopened.proof_job("job-1").unwrap().unwrap();
+        assert_eq!(loaded.status, ProofJobStatus::Completed);
+        assert_eq!(loaded.percent, 100.0);
+        assert_eq!(loaded.finished_at, job.finished_at);
+        let tx = loaded.result.unwrap();
+        assert_eq!(tx.proof.as_deref(), Some("0xproof"));
+        assert_eq!(tx.proofs, job.result.unwrap().proofs);
+        assert_eq!(tx.transaction.unwrap()["to"], "0xrailgun");
+        assert!(reopened.proof_job("job-2").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_proof_jobs_created_once_and_interrupted() {
+        let store = PrivacyStore::in_memory().unwrap();
+        let queued = ProofJob::queued("job-1", "transfer");
+        store.create_proof_job(&queued).unwrap();
+        assert!(store.create_proof_job(&queued).is_err());
+        let mut finished = ProofJob::queued("job-2", "shield");
+        store.create_proof_job(&finished).unwrap();
+        finished.finish(Err("RPC down".to_string()), false);
+        store.save_proof_job(&finished).unwrap();
+
+        // A finished job's id is not reused either
+        assert!(store
+            .create_proof_job(&ProofJob::queued("job-2", "shield"))
+            .is_err());
+
+        assert_eq!(store.interrupt_proof_jobs().unwrap(), 1);
+        let interrupted = store.proof_job("job-1").unwrap().unwrap();
+        assert_eq!(interrupted.status, ProofJobStatus::Interrupted);
+        assert!(interrupted.finished_at.is_some());
+        assert_eq!(
+            store.proof_job("job-2").unwrap().unwrap().status,
+            ProofJobStatus::Failed
+        );
+        assert_eq!(store.interrupt_proof_jobs().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_last_synced_block() {
+        let store = PrivacyStore::in_memory().unwrap();
+        assert_eq!(store.last_synced_block(1).unwrap(), None);
+
+        store.set_last_synced_block(1, 19_000_000).unwrap();
+        store.set_last_synced_block(1, 19_000_500).unwrap();
+        store.set_last_synced_block(11155111, 5_000_000).unwrap();
+
+        assert_eq!(store.last_synced_block(1).unwrap(), Some(19_000_500));
+        assert_eq!(store.last_synced_block(11155111).unwrap(), Some(5_000_000));
+    }
+
+    #[test]
+    fn test_hardware_wallets() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = PrivacyStore::open(dir.path()).unwrap();
+        assert_eq!(store.hardware_wallet_account("wallet-1").unwrap(), None);
+
+        store.save_hardware_wallet("wallet-1", 2).unwrap();
+
+        let reopened = PrivacyStore::open(dir.path()).unwrap();
+        assert_eq!(
+            reopened.hardware_wallet_account("wallet-1").unwrap(),
+            Some(2)
+        );
+        assert_eq!(reopened.hardware_wallet_account("wallet-2").unwrap(), None);
+    }
+}
@@ -0,0 +1,439 @@
+/// Incremental merkletree sync
+///
+/// Scans resume from the last synced block stored per chain, run in the
+/// background with progress reporting (blocks scanned, percent, ETA) and can
+/// be cancelled. The RAILGUN engine keeps partial scans in its own database,
+/// so an interrupted sync continues where it stopped.
+use super::bridge::NodeBridge;
+use super::ffi::{self, BucketBalance, ScanProgressEvent};
+use super::store::PrivacyStore;
+use super::types::{NoteStatus, ShieldedBalance, ShieldedBalances};
+use crate::error::{CepWalletError, Result};
+use crate::history::HistoryStore;
+use crate::tokens::normalize_address;
+use ethers::types::U256;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+
+/// Tauri event carrying [`SyncProgress`] updates
+pub const SYNC_PROGRESS_EVENT: &str = "merkletree-sync-progress";
+
+/// State of a merkletree sync
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum SyncStatus {
+    Running,
+    Completed,
+    Cancelled,
+    Failed,
+}
+
+/// Merkletree sync progress for one chain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncProgress {
+    pub chain_id: u64,
+    pub railgun_wallet_id: String,
+    pub status: SyncStatus,
+    /// Block the sync resumed from (None on the first sync)
+    pub start_block: Option<u64>,
+    /// Chain head when the sync started
+    pub target_block: Option<u64>,
+    pub current_block: Option<u64>,
+    pub blocks_scanned: Option<u64>,
+    /// 0 - 100
+    pub percent: f64,
+    /// Estimated seconds remaining
+    pub eta_seconds: Option<u64>,
+    pub error: Option<String>,
+}
+
+impl SyncProgress {
+    /// Progress of a sync that just started
+    pub fn started(chain_id: u64, railgun_wallet_id: &str, start_block: Option<u64>) -> Self {
+        Self {
+            chain_id,
+            railgun_wallet_id: railgun_wallet_id.to_string(),
+            status: SyncStatus::Running,
+            start_block,
+            target_block: None,
+            current_block: start_block,
+            blocks_scanned: start_block.map(|_| 0),
+            percent: 0.0,
+            eta_seconds: None,
+            error: None,
+        }
+    }
+
+    /// Apply a scanner progress event, `elapsed` after the sync started
+    pub fn advance(&mut self, event: &ScanProgressEvent, elapsed: Duration) {
+        let fraction = event.progress.clamp(0.0, 1.0);
+        if event.start_block.is_some() {
+            self.start_block = event.start_block;
+        }
+        if event.target_block.is_some() {
+            self.target_block = event.target_block;
+        }
+
+        self.percent = fraction * 100.0;
+        if let (Some(start), Some(target)) = (self.start_block, self.target_block) {
+            let scanned = (target.saturating_sub(start) as f64 * fraction) as u64;
+            self.blocks_scanned = Some(scanned);
+            self.current_block = Some(start + scanned);
+        }
+        self.eta_seconds = (fraction > 0.0 && fraction < 1.0)
+            .then(|| (elapsed.as_secs_f64() * (1.0 - fraction) / fraction).round() as u64);
+    }
+
+    /// Mark the sync as finished
+    pub fn finish(&mut self, result: std::result::Result<(), String>, cancelled: bool) {
+        self.eta_seconds = None;
+        match result {
+            Ok(()) => {
+                self.status = SyncStatus::Completed;
+                self.percent = 100.0;
+                if let Some(target) = self.target_block {
+                    self.current_block = Some(target);
+                    self.blocks_scanned =
+                        self.start_block.map(|start| target.saturating_sub(start));
+                }
+            }
+            Err(_) if cancelled => self.status = SyncStatus::Cancelled,
+            Err(error) => {
+                self.status = SyncStatus::Failed;
+                self.error = Some(error);
+            }
+        }
+    }
+}
+
+struct SyncEntry {
+    cancel: watch::Sender<bool>,
+    progress: SyncProgress,
+}
+
+/// Tracks the (at most one) running sync per chain
+#[derive(Default)]
+pub struct SyncManager {
+    syncs: Mutex<HashMap<u64, SyncEntry>>,
+}
+
+impl SyncManager {
+    fn syncs(&self) -> Result<std::sync::MutexGuard<'_, HashMap<u64, SyncEntry>>> {
+        self.syncs
+            .lock()
+            .map_err(|_| CepWalletError::PrivacyError("Sync state lock poisoned".to_string()))
+    }
+
+    /// Register a new sync; fails if one is already running on the chain
+    pub fn begin(&self, progress: SyncProgress) -> Result<watch::Receiver<bool>> {
+        let mut syncs = self.syncs()?;
+        if let Some(entry) = syncs.get(&progress.chain_id) {
+            if entry.progress.status == SyncStatus::Running {
+                return Err(CepWalletError::PrivacyError(format!(
+                    "Merkletree sync already running on chain {}",
+                    progress.chain_id
+                )));
+            }
+        }
+
+        let (cancel, cancel_rx) = watch::channel(false);
+        syncs.insert(progress.chain_id, SyncEntry { cancel, progress });
+        Ok(cancel_rx)
+    }
+
+    /// Whether a sync is running on the chain
+    pub fn is_running(&self, chain_id: u64) -> bool {
+        self.status(chain_id)
+            .is_some_and(|progress| progress.status == SyncStatus::Running)
+    }
+
+    /// Request cancellation; returns false when nothing is running
+    pub fn cancel(&self, chain_id: u64) -> Result<bool> {
+        let syncs = self.syncs()?;
+        match syncs.get(&chain_id) {
+            Some(entry) if entry.progress.status == SyncStatus::Running => {
+                entry.cancel.send_replace(true);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// Record the latest progress of a running sync
+    pub fn update(&self, progress: &SyncProgress) {
+        if let Ok(mut syncs) = self.syncs() {
+            if let Some(entry) = syncs.get_mut(&progress.chain_id) {
+                entry.progress = progress.clone();
+            }
+        }
+    }
+
+    /// Record the outcome of a sync and return its final progress
+    pub fn finish(
+        &self,
+        chain_id: u64,
+        result: std::result::Result<(), String>,
+    ) -> Option<SyncProgress> {
+        let mut syncs = self.syncs().ok()?;
+        let entry = syncs.get_mut(&chain_id)?;
+        let cancelled = *entry.cancel.borrow();
+        entry.progress.finish(result, cancelled);
+        Some(entry.progress.clone())
+    }
+
+    /// Latest progress of the chain's current or last sync
+    pub fn status(&self, chain_id: u64) -> Option<SyncProgress> {
+        self.syncs()
+            .ok()?
+            .get(&chain_id)
+            .map(|entry| entry.progress.clone())
+    }
+}
+
+/// Sum scanner bucket balances per token and note status
+///
+/// Several SDK buckets map to the same status (e.g. all POI-pending ones);
+/// unknown buckets and zero amounts are dropped.
+fn aggregate_balances(buckets: &[BucketBalance]) -> Result<Vec<ShieldedBalance>> {
+    let mut totals: BTreeMap<(String, &'static str), (NoteStatus, U256)> = BTreeMap::new();
+
+    for bucket in buckets {
+        let Some(status) = NoteStatus::from_bucket(&bucket.bucket) else {
+            println!("⚠️  Ignoring unknown balance bucket: {}", bucket.bucket);
+            continue;
+        };
+        let token = normalize_address(&bucket.token_address)?;
+        let amount = U256::from_dec_str(&bucket.amount).map_err(|e| {
+            CepWalletError::PrivacyError(format!("Invalid balance amount {}: {}", bucket.amount, e))
+        })?;
+
+        let entry = totals
+            .entry((token, status.as_str()))
+            .or_insert((status, U256::zero()));
+        entry.1 = entry.1.saturating_add(amount);
+    }
+
+    Ok(totals
+        .into_iter()
+        .filter(|(_, (_, amount))| !amount.is_zero())
+        .map(|((token, _), (status, amount))| ShieldedBalance {
+            token,
+            status,
+            amount: amount.to_string(),
+        })
+        .collect())
+}
+
+/// Sync the merkletree for a wallet from the chain's last synced block
+///
+/// Persists the wallet's balances and the reached block, and records the
+/// notes it received (with their decrypted memos) in `history`.
+/// `on_progress` receives each update; see [`ffi::sync_merkletree`] for
+/// cancellation.
+#[allow(clippy::too_many_arguments)]
+pub async fn sync_merkletree<F>(
+    bridge: &dyn NodeBridge,
+    store: &PrivacyStore,
+    history: &HistoryStore,
+    chain_id: u64,
+    railgun_wallet_id: &str,
+    encryption_key: &str,
+    cancel: watch::Receiver<bool>,
+    mut on_progress: F,
+) -> Result<ShieldedBalances>
+where
+    F: FnMut(&SyncProgress),
+{
+    let start_block = store.last_synced_block(chain_id)?;
+    let mut progress = SyncProgress::started(chain_id, railgun_wallet_id, start_block);
+    let started = Instant::now();
+
+    let response = ffi::sync_merkletree(
+        bridge,
+        railgun_wallet_id,
+        encryption_key,
+        chain_id,
+        start_block,
+        cancel,
+        |event| {
+            progress.advance(&event, started.elapsed());
+            on_progress(&progress);
+        },
+    )
+    .await?;
+
+    let balances = ShieldedBalances {
+        railgun_wallet_id: railgun_wallet_id.to_string(),
+        chain_id,
+        balances: aggregate_balances(&response.balances)?,
+        scanned_at: chrono::Utc::now().timestamp(),
+    };
+    store.save_balances(&balances)?;
+    if let Some(last_block) = response.last_block {
+        store.set_last_synced_block(chain_id, last_block)?;
+    }
+    // The scan itself succeeded; a note missing from history is not fatal
+    match history.record_received(chain_id, railgun_wallet_id, &response.received) {
+        Ok(0) => {}
+        Ok(added) => println!("📥 Recorded {} received notes", added),
+        Err(e) => tracing::warn!("Failed to record received notes: {}", e),
+    }
+
+    Ok(balances)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(progress: f64) -> ScanProgressEvent {
+        ScanProgressEvent {
+            progress,
+            start_block: None,
+            target_block: Some(1_100),
+        }
+    }
+
+    #[test]
+    fn test_aggregate_balances() {
+        let bucket = |bucket: &str, amount: &str| BucketBalance {
+            bucket: bucket.to_string(),
+            token_address: "0xfff9976782d46cc05630d1f6ebab18b2324d6b14".to_string(),
+            amount: amount.to_string(),
+        };
+
+        let balances = aggregate_balances(&[
+            bucket("Spendable", "100"),
+            bucket("MissingInternalPOI", "20"),
+            bucket("ShieldPending", "5"),
+            bucket("Spent", "0"),
+            bucket("SomethingNew", "1"),
+        ])
+        .unwrap();
+
+        assert_eq!(balances.len(), 2);
+        assert_eq!(
+            balances[0].token,
+            "0xfFf9976782d46CC05630D1f6eBAb18b2324d6B14"
+        );
+        assert_eq!(balances[0].status, NoteStatus::PendingPoi);
+        assert_eq!(balances[0].amount, "25");
+        assert_eq!(balances[1].status, NoteStatus::Spendable);
+        assert_eq!(balances[1].amount, "100");
+    }
+
+    #[test]
+    fn test_progress_blocks_and_eta() {
+        let mut progress = SyncProgress::started(1, "wallet-1", Some(100));
+        progress.advance(&event(0.25), Duration::from_secs(10));
+
+        assert_eq!(progress.target_block, Some(1_100));
+        assert_eq!(progress.blocks_scanned, Some(250));
+        assert_eq!(progress.current_block, Some(350));
+        assert_eq!(progress.percent, 25.0);
+        assert_eq!(progress.eta_seconds, Some(30));
+
+        progress.finish(Ok(()), false);
+        assert_eq!(progress.status, SyncStatus::Completed);
+        assert_eq!(progress.current_block, Some(1_100));
+        assert_eq!(progress.eta_seconds, None);
+    }
+
+    #[test]
+    fn test_first_sync_has_no_block_counts() {
+        let mut progress = SyncProgress::started(1, "wallet-1", None);
+        progress.advance(&event(0.5), Duration::from_secs(10));
+
+        assert_eq!(progress.blocks_scanned, None);
+        assert_eq!(progress.percent, 50.0);
+        assert_eq!(progress.eta_seconds, Some(10));
+    }
+
+    #[test]
+    fn test_manager_single_sync_per_chain_and_cancel() {
+        let manager = SyncManager::default();
+        let cancel = manager
+            .begin(SyncProgress::started(1, "wallet-1", None))
+            .unwrap();
+
+        assert!(manager.is_running(1));
+        assert!(manager
+            .begin(SyncProgress::started(1, "wallet-2", None))
+            .is_err());
+        assert!(!manager.cancel(11155111).unwrap());
+
+        assert!(manager.cancel(1).unwrap());
+        assert!(*cancel.borrow());
+
+        let finished = manager.finish(1, Err("killed".to_string())).unwrap();
+        assert_eq!(finished.status, SyncStatus::Cancelled);
+        assert!(!manager.is_running(1));
+
+        // A finished chain can be synced again
+        assert!(manager
+            .begin(SyncProgress::started(1, "wallet-1", None))
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_sync_persists_with_fake_bridge() {
+        use crate::privacy::bridge::FakeBridge;
+        use serde_json::json;
+
+        let bridge = FakeBridge::new(|_| {
+            Ok(json!({
+                "success": true,
+                "balances": [{
+                    "bucket": "Spendable",
+                    "tokenAddress": "0xfff9976782d46cc05630d1f6ebab18b2324d6b14",
+                    "amount": "100",
+                }],
+                "lastBlock": 1_100,
+                "received": [{
+                    "txid": "0xfeed",
+                    "blockNumber": 1_050,
+                    "timestamp": 1_700_000_000,
+                    "tokenAddress": "0xfff9976782d46cc05630d1f6ebab18b2324d6b14",
+                    "amount": "100",
+                    "memoText": "INV-42",
+                }],
+            }))
+        })
+        .with_events(vec![json!({
+            "id": 1,
+            "event": "scanProgress",
+            "progress": 0.5,
+            "targetBlock": 1_100,
+        })]);
+        let store = PrivacyStore::in_memory().unwrap();
+        let history = HistoryStore::in_memory().unwrap();
+        store.set_last_synced_block(1, 100).unwrap();
+        let (_cancel, cancel) = watch::channel(false);
+
+        let mut updates = Vec::new();
+        let balances = sync_merkletree(
+            &bridge,
+            &store,
+            &history,
+            1,
+            "wallet-1",
+            "key",
+            cancel,
+            |p| updates.push(p.current_block),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(updates, vec![Some(600)]);
+        assert_eq!(balances.balances.len(), 1);
+        assert_eq!(store.last_synced_block(1).unwrap(), Some(1_100));
+        assert!(store.balances("wallet-1", 1).unwrap().is_some());
+        let received = history.query_all(&Default::default()).unwrap();
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0].account, "wallet-1");
+        let memo = received[0].shielded.as_ref().unwrap().memo.as_deref();
+        assert_eq!(memo, Some("INV-42"));
+    }
+}
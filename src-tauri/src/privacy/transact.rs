@@ -0,0 +1,309 @@
+/// RAILGUN transaction calldata
+///
+/// Decodes the `transact(Transaction[])` calls (and relay adapt `relay`
+/// calls) the proof generator returns into the SNARKs they carry, with the
+/// public inputs the contract verifies them against:
+///
+/// ```text
+/// merkleRoot, hash(boundParams), nullifiers..., commitments...
+/// ```
+///
+/// where `hash(boundParams)` is `keccak256(abi.encode(boundParams))` reduced
+/// into the SNARK scalar field.
+use super::envelope::ProofEnvelope;
+use super::zkproof::{FullProof, ProofType, SnarkjsProof, PROOF_BYTES};
+use crate::error::{CepWalletError, Result};
+use ark_bn254::Fr;
+use ark_ff::PrimeField;
+use ethers::abi::{self, ParamType, Token};
+use ethers::types::U256;
+use ethers::utils::keccak256;
+use num_bigint::BigUint;
+
+/// A RAILGUN transaction's proof and public inputs
+#[derive(Debug, Clone)]
+pub struct RailgunTransaction {
+    /// Proof with its public signals (decimal)
+    pub proof: FullProof,
+    pub nullifiers: usize,
+    pub commitments: usize,
+    /// Chain the bound parameters commit to
+    pub chain_id: u64,
+}
+
+impl RailgunTransaction {
+    /// Joinsplit variant that proves this transaction (`01x02`)
+    pub fn circuit(&self) -> String {
+        super::verifier::joinsplit_circuit(self.nullifiers, self.commitments)
+    }
+
+    /// The proof as a `proof_type` envelope
+    pub fn envelope(&self, proof_type: ProofType) -> ProofEnvelope {
+        ProofEnvelope::new(proof_type, &self.circuit(), self.proof.clone())
+    }
+}
+
+/// Decode the transactions in `transact` or relay adapt `relay` calldata
+pub fn decode_transactions(calldata: &[u8]) -> Result<Vec<RailgunTransaction>> {
+    if calldata.len() < 4 {
+        return Err(malformed("missing function selector"));
+    }
+    let (selector, args) = calldata.split_at(4);
+
+    let transactions = ParamType::Array(Box::new(transaction_type()));
+    let params =
+        if selector == abi::short_signature("transact", std::slice::from_ref(&transactions)) {
+            vec![transactions]
+        } else if selector == abi::short_signature("relay", &relay_params()) {
+            relay_params()
+        } else {
+            return Err(malformed(&format!(
+                "unknown function selector 0x{}",
+                hex::encode(selector)
+            )));
+        };
+
+    match abi::decode(&params, args)
+        .map_err(|e| malformed(&e.to_string()))?
+        .into_iter()
+        .next()
+    {
+        Some(Token::Array(transactions)) => transactions.iter().map(decode_transaction).collect(),
+        _ => Err(malformed("expected a transaction array")),
+    }
+}
+
+fn decode_transaction(token: &Token) -> Result<RailgunTransaction> {
+    let [proof, merkle_root, nullifiers, commitments, bound_params, _unshield] = tuple(token)?;
+
+    let words = tuple::<3>(proof)?
+        .iter()
+        .map(|point| {
+            let [x, y] = tuple(point)?;
+            match (x, y) {
+                // G2 coordinates are already [c1, c0], as in calldata
+                (Token::FixedArray(x), Token::FixedArray(y)) => {
+                    x.iter().chain(y).map(uint).collect::<Result<Vec<_>>>()
+                }
+                _ => Ok(vec![uint(x)?, uint(y)?]),
+            }
+        })
+        .collect::<Result<Vec<_>>>()?
+        .concat();
+    let mut bytes = Vec::with_capacity(PROOF_BYTES);
+    for word in words {
+        let mut be = [0u8; 32];
+        word.to_big_endian(&mut be);
+        bytes.extend_from_slice(&be);
+    }
+    let proof = SnarkjsProof::from_bytes(&bytes)?;
+
+    let nullifiers = bytes32_array(nullifiers)?;
+    let commitments = bytes32_array(commitments)?;
+    let chain_id = match tuple::<7>(bound_params)?[3] {
+        Token::Uint(chain_id) if chain_id <= U256::from(u64::MAX) => chain_id.as_u64(),
+        _ => return Err(malformed("invalid bound parameters")),
+    };
+    let bound_params_hash =
+        Fr::from_be_bytes_mod_order(&keccak256(abi::encode(std::slice::from_ref(bound_params))));
+
+    let mut public_signals = vec![
+        decimal(&bytes32(merkle_root)?),
+        BigUint::from(bound_params_hash.into_bigint()).to_string(),
+    ];
+    public_signals.extend(nullifiers.iter().chain(&commitments).map(|b| decimal(b)));
+
+    Ok(RailgunTransaction {
+        proof: FullProof {
+            proof,
+            public_signals,
+        },
+        nullifiers: nullifiers.len(),
+        commitments: commitments.len(),
+        chain_id,
+    })
+}
+
+/// `Transaction` struct of the RAILGUN smart wallet
+fn transaction_type() -> ParamType {
+    use ParamType::{Address, Array, Bytes, FixedArray, FixedBytes, Tuple, Uint};
+
+    let g1 = || Tuple(vec![Uint(256), Uint(256)]);
+    let g2 = || {
+        let fq2 = || FixedArray(Box::new(Uint(256)), 2);
+        Tuple(vec![fq2(), fq2()])
+    };
+    let snark_proof = Tuple(vec![g1(), g2(), g1()]);
+    let commitment_ciphertext = Tuple(vec![
+        FixedArray(Box::new(FixedBytes(32)), 4),
+        FixedBytes(32),
+        FixedBytes(32),
+        Bytes,
+        Bytes,
+    ]);
+    let bound_params = Tuple(vec![
+        Uint(16),
+        Uint(72),
+        Uint(8),
+        Uint(64),
+        Address,
+        FixedBytes(32),
+        Array(Box::new(commitment_ciphertext)),
+    ]);
+    let token_data = Tuple(vec![Uint(8), Address, Uint(256)]);
+    let commitment_preimage = Tuple(vec![FixedBytes(32), token_data, Uint(120)]);
+
+    Tuple(vec![
+        snark_proof,
+        FixedBytes(32),
+        Array(Box::new(FixedBytes(32))),
+        Array(Box::new(FixedBytes(32))),
+        bound_params,
+        commitment_preimage,
+    ])
+}
+
+/// Relay adapt `relay(Transaction[], ActionData)` parameters
+fn relay_params() -> Vec<ParamType> {
+    use ParamType::{Address, Array, Bool, Bytes, FixedBytes, Tuple, Uint};
+
+    let call = Tuple(vec![Address, Bytes, Uint(256)]);
+    vec![
+        Array(Box::new(transaction_type())),
+        Tuple(vec![FixedBytes(31), Bool, Uint(256), Array(Box::new(call))]),
+    ]
+}
+
+fn tuple<const N: usize>(token: &Token) -> Result<&[Token; N]> {
+    match token {
+        Token::Tuple(fields) => fields
+            .as_slice()
+            .try_into()
+            .map_err(|_| malformed("unexpected struct layout")),
+        _ => Err(malformed("expected a struct")),
+    }
+}
+
+fn uint(token: &Token) -> Result<U256> {
+    match token {
+        Token::Uint(value) => Ok(*value),
+        _ => Err(malformed("expected a uint256")),
+    }
+}
+
+fn bytes32(token: &Token) -> Result<Vec<u8>> {
+    match token {
+        Token::FixedBytes(bytes) if bytes.len() == 32 => Ok(bytes.clone()),
+        _ => Err(malformed("expected a bytes32")),
+    }
+}
+
+fn bytes32_array(token: &Token) -> Result<Vec<Vec<u8>>> {
+    match token {
+        Token::Array(items) => items.iter().map(bytes32).collect(),
+        _ => Err(malformed("expected a bytes32 array")),
+    }
+}
+
+fn decimal(bytes: &[u8]) -> String {
+    BigUint::from_bytes_be(bytes).to_string()
+}
+
+fn malformed(reason: &str) -> CepWalletError {
+    CepWalletError::PrivacyError(format!("Malformed RAILGUN transaction: {}", reason))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::privacy::zkproof::Circuit;
+    use ethers::types::Address;
+    use serde_json::json;
+
+    const MULTIPLIER_ZKEY: &[u8] = include_bytes!("../../fixtures/circuits/multiplier2.zkey");
+    const MULTIPLIER_WASM: &[u8] = include_bytes!("../../fixtures/circuits/multiplier2.wasm");
+
+    /// `transact` calldata with one 1x2 transaction carrying a real (but
+    /// unrelated) proof, bound to `chain_id`
+    pub(crate) fn transact_calldata(chain_id: u64) -> Vec<u8> {
+        let proof = Circuit::new(MULTIPLIER_ZKEY, MULTIPLIER_WASM)
+            .unwrap()
+            .prove(&json!({"a": 3, "b": 11}))
+            .unwrap();
+        let bytes = proof.proof.to_bytes().unwrap();
+        let word = |i: usize| Token::Uint(U256::from_big_endian(&bytes[i * 32..(i + 1) * 32]));
+        let bytes32 = |b: u8| Token::FixedBytes(vec![b; 32]);
+        let uint = |n: u64| Token::Uint(U256::from(n));
+
+        let snark_proof = Token::Tuple(vec![
+            Token::Tuple(vec![word(0), word(1)]),
+            Token::Tuple(vec![
+                Token::FixedArray(vec![word(2), word(3)]),
+                Token::FixedArray(vec![word(4), word(5)]),
+            ]),
+            Token::Tuple(vec![word(6), word(7)]),
+        ]);
+        let ciphertext = Token::Tuple(vec![
+            Token::FixedArray(vec![bytes32(1); 4]),
+            bytes32(2),
+            bytes32(3),
+            Token::Bytes(vec![4; 48]),
+            Token::Bytes(Vec::new()),
+        ]);
+        let bound_params = Token::Tuple(vec![
+            uint(0),
+            uint(0),
+            uint(0),
+            uint(chain_id),
+            Token::Address(Address::zero()),
+            bytes32(0),
+            Token::Array(vec![ciphertext.clone(), ciphertext]),
+        ]);
+        let preimage = Token::Tuple(vec![
+            bytes32(0),
+            Token::Tuple(vec![uint(0), Token::Address(Address::zero()), uint(0)]),
+            uint(0),
+        ]);
+        let transaction = Token::Tuple(vec![
+            snark_proof,
+            bytes32(0x0a),
+            Token::Array(vec![bytes32(0x0b)]),
+            Token::Array(vec![bytes32(0x0c), bytes32(0x0d)]),
+            bound_params,
+            preimage,
+        ]);
+
+        let transactions = ParamType::Array(Box::new(transaction_type()));
+        let mut data = abi::short_signature("transact", &[transactions]).to_vec();
+        data.extend(abi::encode(&[Token::Array(vec![transaction])]));
+        data
+    }
+
+    #[test]
+    fn test_decode_transact() {
+        let transactions = decode_transactions(&transact_calldata(11155111)).unwrap();
+        assert_eq!(transactions.len(), 1);
+
+        let tx = &transactions[0];
+        assert_eq!(tx.circuit(), "01x02");
+        assert_eq!(tx.chain_id, 11155111);
+        let signals = &tx.proof.public_signals;
+        assert_eq!(signals.len(), 5);
+        assert_eq!(signals[0], decimal(&[0x0a; 32]));
+        assert_eq!(signals[2], decimal(&[0x0b; 32]));
+        assert_eq!(signals[4], decimal(&[0x0d; 32]));
+
+        // The bound parameters hash commits to the chain
+        let other_chain = decode_transactions(&transact_calldata(1)).unwrap();
+        assert_ne!(other_chain[0].proof.public_signals[1], signals[1]);
+    }
+
+    #[test]
+    fn test_decode_rejects_other_calls() {
+        assert!(decode_transactions(&[]).is_err());
+        assert!(decode_transactions(&[0xa9, 0x05, 0x9c, 0xbb, 0, 0]).is_err());
+
+        let calldata = transact_calldata(1);
+        assert!(decode_transactions(&calldata[..calldata.len() - 64]).is_err());
+    }
+}
@@ -1,4 +1,7 @@
 /// Common types for privacy module
+use super::envelope::ProofEnvelope;
+use crate::error::{CepWalletError, Result};
+use ethers::types::U256;
 use serde::{Deserialize, Serialize};
 
 /// Privacy level for transactions
@@ -21,16 +24,6 @@ impl Default for PrivacyLevel {
 }
 
 impl PrivacyLevel {
-    /// Get estimated fee multiplier for this privacy level
-    pub fn fee_multiplier(&self) -> f64 {
-        match self {
-            PrivacyLevel::Public => 1.0,
-            PrivacyLevel::Railgun => 2.5,
-            PrivacyLevel::PrivacyPool => 3.0,
-            PrivacyLevel::Maximum => 4.5,
-        }
-    }
-
     /// Get estimated proof generation time (seconds)
     pub fn proof_generation_time(&self) -> u64 {
         match self {
@@ -61,6 +54,80 @@ pub struct ShieldedTransaction {
     pub nullifier: Option<String>,
     /// Transaction data for broadcasting
     pub transaction: Option<serde_json::Value>,
+    /// Local history entry id (set once recorded)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub history_id: Option<String>,
+    /// Verified SNARKs the transaction carries (transfers and unshields);
+    /// proofs of circuits without a verification key are not listed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub proofs: Vec<ProofEnvelope>,
+    /// Set when proved for a broadcaster rather than a public wallet
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub broadcaster_fee: Option<BroadcasterFee>,
+    /// Every output of a multi-output transfer; `token` and `amount` then
+    /// give the total of the first token and `recipient` is set only when
+    /// all outputs share it
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub outputs: Vec<TransferOutput>,
+    /// Memo text: encrypted to the recipients of a transfer, or decrypted
+    /// from a received note
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memo: Option<String>,
+}
+
+/// Longest memo text accepted on a transfer (UTF-8 bytes)
+///
+/// The memo is encrypted into the ciphertext of every output commitment and
+/// published in calldata, so it is capped well below what would bloat the
+/// transaction.
+pub const MAX_MEMO_BYTES: usize = 256;
+
+/// Check a transfer memo against `MAX_MEMO_BYTES`
+pub fn validate_memo(memo: &str) -> Result<()> {
+    if memo.len() > MAX_MEMO_BYTES {
+        return Err(CepWalletError::InvalidInput(format!(
+            "Memo is {} bytes, the limit is {}",
+            memo.len(),
+            MAX_MEMO_BYTES
+        )));
+    }
+    Ok(())
+}
+
+/// One output of a private transfer
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferOutput {
+    /// Recipient's RAILGUN (0zk) address
+    pub recipient: String,
+    /// Token address (0x0 for ETH)
+    pub token: String,
+    /// Amount in base units
+    pub amount: String,
+    /// Memo text encrypted to the recipient
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memo: Option<String>,
+}
+
+/// Fee paid to a RAILGUN broadcaster (relayer) out of the shielded balance
+///
+/// A transfer or unshield proved with a fee is submitted by the broadcaster,
+/// so no public account of the sender signs or pays gas for it. Without one
+/// it is self-signed from a public wallet.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BroadcasterFee {
+    /// Fee token address
+    pub token: String,
+    /// Fee in base units of `token`
+    pub amount: String,
+    /// Broadcaster's 0zk fee address
+    pub recipient: String,
+    /// Lowest gas price (wei) the broadcaster accepts; bound into the proof
+    pub min_gas_price: String,
+    /// Id of the broadcaster's fee quote, quoted back on submission
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fees_id: Option<String>,
 }
 
 /// Shielded transaction type
@@ -72,6 +139,8 @@ pub enum ShieldedTxType {
     Transfer,
     /// Unshield: Private -> Public
     Unshield,
+    /// Note received from another RAILGUN wallet
+    Receive,
 }
 
 /// Privacy pool operation
@@ -102,19 +171,6 @@ pub enum PoolOperationType {
     ComplianceProof,
 }
 
-/// ZK proof metadata
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ZKProofMetadata {
-    /// Proof type
-    pub proof_type: String,
-    /// Public inputs
-    pub public_inputs: Vec<String>,
-    /// Proof data (serialized)
-    pub proof: Vec<u8>,
-    /// Generation timestamp
-    pub timestamp: u64,
-}
-
 /// Privacy balance (shielded balances)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PrivacyBalance {
@@ -128,6 +184,144 @@ pub struct PrivacyBalance {
     pub total: String,
 }
 
+impl PrivacyBalance {
+    /// Balance from its parts (base units); `total` is their sum
+    pub fn new(public: U256, railgun: Option<U256>, privacy_pool: Option<U256>) -> Self {
+        let total = public
+            .saturating_add(railgun.unwrap_or_default())
+            .saturating_add(privacy_pool.unwrap_or_default());
+
+        Self {
+            public: public.to_string(),
+            railgun: railgun.map(|value| value.to_string()),
+            privacy_pool: privacy_pool.map(|value| value.to_string()),
+            total: total.to_string(),
+        }
+    }
+}
+
+/// Status of shielded notes, as reported by the RAILGUN balance scan
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum NoteStatus {
+    /// Spendable in private transfers and unshields
+    Spendable,
+    /// Waiting for Proof of Innocence (newly shielded or received notes)
+    PendingPoi,
+    /// Shield blocked by the POI list; can only be returned to the sender
+    Blocked,
+    /// Already spent
+    Spent,
+}
+
+impl NoteStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NoteStatus::Spendable => "Spendable",
+            NoteStatus::PendingPoi => "PendingPoi",
+            NoteStatus::Blocked => "Blocked",
+            NoteStatus::Spent => "Spent",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "Spendable" => Some(NoteStatus::Spendable),
+            "PendingPoi" => Some(NoteStatus::PendingPoi),
+            "Blocked" => Some(NoteStatus::Blocked),
+            "Spent" => Some(NoteStatus::Spent),
+            _ => None,
+        }
+    }
+
+    /// Map a RAILGUN SDK `RailgunWalletBalanceBucket`
+    pub fn from_bucket(bucket: &str) -> Option<Self> {
+        match bucket {
+            "Spendable" => Some(NoteStatus::Spendable),
+            "ShieldPending" | "ProofSubmitted" | "MissingInternalPOI" | "MissingExternalPOI" => {
+                Some(NoteStatus::PendingPoi)
+            }
+            "ShieldBlocked" => Some(NoteStatus::Blocked),
+            "Spent" => Some(NoteStatus::Spent),
+            _ => None,
+        }
+    }
+}
+
+/// Shielded balance of one token in one note status
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ShieldedBalance {
+    /// Token address
+    pub token: String,
+    pub status: NoteStatus,
+    /// Amount in base units
+    pub amount: String,
+}
+
+/// Shielded balances of a RAILGUN wallet from its latest merkletree scan
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShieldedBalances {
+    pub railgun_wallet_id: String,
+    pub chain_id: u64,
+    pub balances: Vec<ShieldedBalance>,
+    /// Unix timestamp of the scan
+    pub scanned_at: i64,
+}
+
+impl ShieldedBalances {
+    /// Balance of a token in a given note status (0 when none)
+    pub fn amount(&self, token: &str, status: NoteStatus) -> U256 {
+        self.balances
+            .iter()
+            .filter(|balance| balance.status == status && balance.token.eq_ignore_ascii_case(token))
+            .filter_map(|balance| U256::from_dec_str(&balance.amount).ok())
+            .fold(U256::zero(), |total, amount| total.saturating_add(amount))
+    }
+
+    /// Spendable balance of a token
+    pub fn spendable(&self, token: &str) -> U256 {
+        self.amount(token, NoteStatus::Spendable)
+    }
+}
+
+/// Note another wallet sent to a RAILGUN wallet, found by the merkletree
+/// scan with its memo decrypted by the viewing key
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReceivedNote {
+    /// Hash of the transaction that created the note
+    pub txid: String,
+    pub block_number: u64,
+    /// Block time (unix seconds), when known
+    #[serde(default)]
+    pub timestamp: Option<i64>,
+    pub token_address: String,
+    /// Amount in base units
+    pub amount: String,
+    #[serde(default)]
+    pub memo_text: Option<String>,
+}
+
+impl ReceivedNote {
+    /// The note as a received transfer
+    pub fn transaction(&self) -> ShieldedTransaction {
+        ShieldedTransaction {
+            tx_type: ShieldedTxType::Receive,
+            token: self.token_address.clone(),
+            amount: self.amount.clone(),
+            recipient: None,
+            proof: None,
+            merkle_root: None,
+            nullifier: None,
+            transaction: None,
+            history_id: None,
+            proofs: Vec::new(),
+            broadcaster_fee: None,
+            outputs: Vec::new(),
+            memo: self.memo_text.clone().filter(|memo| !memo.is_empty()),
+        }
+    }
+}
+
 /// Privacy transaction status
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum PrivacyTxStatus {
@@ -147,14 +341,6 @@ pub enum PrivacyTxStatus {
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_privacy_level_fee_multiplier() {
-        assert_eq!(PrivacyLevel::Public.fee_multiplier(), 1.0);
-        assert_eq!(PrivacyLevel::Railgun.fee_multiplier(), 2.5);
-        assert_eq!(PrivacyLevel::PrivacyPool.fee_multiplier(), 3.0);
-        assert_eq!(PrivacyLevel::Maximum.fee_multiplier(), 4.5);
-    }
-
     #[test]
     fn test_privacy_level_proof_time() {
         assert_eq!(PrivacyLevel::Public.proof_generation_time(), 0);
@@ -165,6 +351,51 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_privacy_balance_total() {
+        let balance = PrivacyBalance::new(U256::from(150u64), Some(U256::from(50u64)), None);
+
+        assert_eq!(balance.public, "150");
+        assert_eq!(balance.railgun.as_deref(), Some("50"));
+        assert_eq!(balance.privacy_pool, None);
+        assert_eq!(balance.total, "200");
+    }
+
+    #[test]
+    fn test_memo_limit() {
+        assert!(validate_memo("INV-2024-0042").is_ok());
+        assert!(validate_memo(&"a".repeat(MAX_MEMO_BYTES)).is_ok());
+        assert!(validate_memo(&"a".repeat(MAX_MEMO_BYTES + 1)).is_err());
+        // Counted in bytes, not characters
+        assert!(validate_memo(&"ü".repeat(MAX_MEMO_BYTES / 2 + 1)).is_err());
+    }
+
+    #[test]
+    fn test_note_status_buckets() {
+        assert_eq!(
+            NoteStatus::from_bucket("Spendable"),
+            Some(NoteStatus::Spendable)
+        );
+        assert_eq!(
+            NoteStatus::from_bucket("MissingExternalPOI"),
+            Some(NoteStatus::PendingPoi)
+        );
+        assert_eq!(
+            NoteStatus::from_bucket("ShieldBlocked"),
+            Some(NoteStatus::Blocked)
+        );
+        assert_eq!(NoteStatus::from_bucket("Unknown"), None);
+
+        for status in [
+            NoteStatus::Spendable,
+            NoteStatus::PendingPoi,
+            NoteStatus::Blocked,
+            NoteStatus::Spent,
+        ] {
+            assert_eq!(NoteStatus::parse(status.as_str()), Some(status));
+        }
+    }
+
     #[test]
     fn test_shielded_tx_serialization() {
         let tx = ShieldedTransaction {
@@ -175,6 +406,12 @@ mod tests {
             proof: None,
             merkle_root: None,
             nullifier: None,
+            transaction: None,
+            history_id: None,
+            proofs: Vec::new(),
+            broadcaster_fee: None,
+            outputs: Vec::new(),
+            memo: None,
         };
 
         let json = serde_json::to_string(&tx).unwrap();
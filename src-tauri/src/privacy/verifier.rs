@@ -0,0 +1,479 @@
+/// Groth16 proof verification
+///
+/// Proofs are checked against snarkjs verification keys (`vkey.json`), one
+/// per circuit: `<circuit>.json` in the bundled `vkeys/` resource directory
+/// first, then the circuit's pinned artifacts
+/// (`artifacts-v2.1/<circuit>/vkey.json`, see `artifacts.rs`). Verifying a
+/// single proof fails closed: a proof whose circuit has no key is an error,
+/// never accepted.
+///
+/// RAILGUN transactions returned by the proof generator are verified the way
+/// the contract does before they are signed: the key is picked by the
+/// nullifier and commitment counts, and the public inputs are the merkle
+/// root, the bound parameters hash, the nullifiers and the commitments.
+/// Until a variant's key is bundled or pinned its proofs are passed through
+/// unverified with a warning; once a key is available, every proof of that
+/// variant must verify.
+use super::artifacts::ArtifactManager;
+use super::envelope::ProofEnvelope;
+use super::transact::decode_transactions;
+use super::zkproof::{
+    g1_from_strings, g2_from_strings, ProofType, SnarkjsProof, CIRCUIT_ARTIFACTS_ENV,
+};
+use crate::error::{CepWalletError, Result};
+use ark_bn254::{Bn254, Fr};
+use ark_ff::PrimeField;
+use ark_groth16::{prepare_verifying_key, Groth16, PreparedVerifyingKey, VerifyingKey};
+use num_bigint::BigUint;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Bundled verification key directory (Tauri resource)
+pub const VKEYS_DIR: &str = "vkeys";
+
+/// Bundled verification keys: the app's resources in production builds,
+/// the source tree in development
+pub fn bundled_vkeys_dir(resource_dir: Option<&Path>) -> PathBuf {
+    resource_dir
+        .map(|dir| dir.join(VKEYS_DIR))
+        .filter(|dir| dir.is_dir())
+        .unwrap_or_else(|| Path::new(env!("CARGO_MANIFEST_DIR")).join(VKEYS_DIR))
+}
+
+/// Circuit name of a RAILGUN joinsplit variant (`01x02`: one nullifier,
+/// two commitments)
+pub fn joinsplit_circuit(nullifiers: usize, commitments: usize) -> String {
+    format!("{:02}x{:02}", nullifiers, commitments)
+}
+
+/// A prepared Groth16 verification key
+pub struct VerificationKey {
+    n_public: usize,
+    pvk: PreparedVerifyingKey<Bn254>,
+}
+
+impl VerificationKey {
+    pub fn new(vk: &VerifyingKey<Bn254>) -> Self {
+        Self {
+            n_public: vk.gamma_abc_g1.len().saturating_sub(1),
+            pvk: prepare_verifying_key(vk),
+        }
+    }
+
+    /// Parse a snarkjs `verification_key.json`
+    pub fn from_snarkjs_json(json: &str) -> Result<Self> {
+        let vkey: Value = serde_json::from_str(json).map_err(|e| vkey_error(&e.to_string()))?;
+        if vkey["protocol"] != "groth16" || vkey["curve"] != "bn128" {
+            return Err(vkey_error("not a Groth16 BN254 key"));
+        }
+
+        let ic = vkey["IC"]
+            .as_array()
+            .ok_or_else(|| vkey_error("missing IC"))?
+            .iter()
+            .map(|point| g1_from_strings(&strings(point)?))
+            .collect::<Result<Vec<_>>>()?;
+        if let Some(n_public) = vkey["nPublic"].as_u64() {
+            if n_public as usize + 1 != ic.len() {
+                return Err(vkey_error("nPublic does not match IC"));
+            }
+        }
+
+        let g1 = |name: &str| g1_from_strings(&strings(&vkey[name])?);
+        let g2 = |name: &str| -> Result<_> {
+            let coords = vkey[name]
+                .as_array()
+                .ok_or_else(|| vkey_error(&format!("missing {}", name)))?
+                .iter()
+                .map(strings)
+                .collect::<Result<Vec<[String; 2]>>>()?;
+            g2_from_strings(&coords.try_into().map_err(|_| vkey_error(name))?)
+        };
+
+        Ok(Self::new(&VerifyingKey {
+            alpha_g1: g1("vk_alpha_1")?,
+            beta_g2: g2("vk_beta_2")?,
+            gamma_g2: g2("vk_gamma_2")?,
+            delta_g2: g2("vk_delta_2")?,
+            gamma_abc_g1: ic,
+        }))
+    }
+
+    /// The underlying (unprepared) key
+    pub fn verifying_key(&self) -> &VerifyingKey<Bn254> {
+        &self.pvk.vk
+    }
+
+    /// Number of public inputs the circuit takes
+    pub fn n_public(&self) -> usize {
+        self.n_public
+    }
+
+    /// Pairing check of a proof; a wrong number of public inputs fails
+    pub fn verify(&self, proof: &SnarkjsProof, public_inputs: &[Fr]) -> Result<bool> {
+        if public_inputs.len() != self.n_public {
+            return Ok(false);
+        }
+        Groth16::<Bn254>::verify_proof(&self.pvk, &proof.to_ark()?, public_inputs)
+            .map_err(|e| CepWalletError::ZKProofError(format!("Verification failed: {}", e)))
+    }
+}
+
+/// Parse public signals (decimal or `0x` hex field elements)
+pub fn parse_public_inputs(signals: &[String]) -> Result<Vec<Fr>> {
+    signals.iter().map(|s| parse_field_element(s)).collect()
+}
+
+/// Parse a scalar field element; values outside the field are rejected
+/// rather than reduced, as the verifier contracts do
+pub fn parse_field_element(s: &str) -> Result<Fr> {
+    let n = match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => BigUint::parse_bytes(hex.as_bytes(), 16),
+        None => s.parse::<BigUint>().ok(),
+    }
+    .ok_or_else(|| CepWalletError::ZKProofError(format!("Invalid public signal {:?}", s)))?;
+    if n >= BigUint::from(Fr::MODULUS) {
+        return Err(CepWalletError::ZKProofError(format!(
+            "Public signal {} is not a field element",
+            s
+        )));
+    }
+    Ok(Fr::from(n))
+}
+
+/// Verification keys by circuit, loaded on first use
+pub struct VerificationKeys {
+    bundled_dir: Option<PathBuf>,
+    artifacts: Option<Arc<ArtifactManager>>,
+    keys: Mutex<HashMap<String, Arc<VerificationKey>>>,
+}
+
+impl Default for VerificationKeys {
+    /// Source tree keys, then artifacts in `CEPWALLET_CIRCUIT_ARTIFACTS`
+    fn default() -> Self {
+        Self::new(
+            Some(bundled_vkeys_dir(None)),
+            std::env::var_os(CIRCUIT_ARTIFACTS_ENV)
+                .map(|dir| Arc::new(ArtifactManager::pinned(Path::new(&dir)))),
+        )
+    }
+}
+
+impl VerificationKeys {
+    /// Keys from the bundled directory, then the pinned circuit artifacts
+    pub fn new(bundled_dir: Option<PathBuf>, artifacts: Option<Arc<ArtifactManager>>) -> Self {
+        Self {
+            bundled_dir,
+            artifacts,
+            keys: Mutex::default(),
+        }
+    }
+
+    /// Register a key for `circuit`
+    pub fn insert(&self, circuit: &str, key: VerificationKey) {
+        self.lock_keys().insert(circuit.to_string(), Arc::new(key));
+    }
+
+    /// Whether a key for `circuit` is loaded, bundled or pinned
+    ///
+    /// A pinned key may still fail to load (missing or corrupt file); that
+    /// is an error when verifying, not a missing key.
+    pub fn has_key(&self, circuit: &str) -> bool {
+        self.lock_keys().contains_key(circuit)
+            || self
+                .bundled_dir
+                .as_ref()
+                .is_some_and(|dir| dir.join(format!("{}.json", circuit)).is_file())
+            || self
+                .artifacts
+                .as_ref()
+                .is_some_and(|artifacts| artifacts.is_pinned(circuit))
+    }
+
+    /// Key for `circuit`, loading it on first use
+    pub fn get(&self, circuit: &str) -> Result<Arc<VerificationKey>> {
+        if let Some(key) = self.lock_keys().get(circuit) {
+            return Ok(key.clone());
+        }
+
+        if circuit.is_empty()
+            || !circuit
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            return Err(CepWalletError::InvalidInput(format!(
+                "Invalid circuit name: {}",
+                circuit
+            )));
+        }
+        let bundled = self
+            .bundled_dir
+            .as_ref()
+            .map(|dir| dir.join(format!("{}.json", circuit)))
+            .filter(|path| path.is_file());
+        let key = match (bundled, &self.artifacts) {
+            (Some(path), _) => {
+                let json = std::fs::read_to_string(&path).map_err(|e| {
+                    CepWalletError::ZKProofError(format!("Failed to read {:?}: {}", path, e))
+                })?;
+                VerificationKey::from_snarkjs_json(&json)?
+            }
+            (None, Some(artifacts)) => artifacts.load_verification_key(circuit).map_err(|e| {
+                CepWalletError::ZKProofError(format!(
+                    "No verification key for circuit {}: {}",
+                    circuit, e
+                ))
+            })?,
+            (None, None) => {
+                return Err(CepWalletError::ZKProofError(format!(
+                    "No verification key for circuit {}",
+                    circuit
+                )))
+            }
+        };
+        let key = Arc::new(key);
+        Ok(self
+            .lock_keys()
+            .entry(circuit.to_string())
+            .or_insert(key)
+            .clone())
+    }
+
+    /// Verify a proof envelope against its circuit's key
+    pub fn verify(&self, proof: &ProofEnvelope) -> Result<bool> {
+        let circuit = &proof.circuit;
+        if !proof.proof_type.accepts_circuit(circuit) {
+            return Err(CepWalletError::InvalidInput(format!(
+                "Circuit {} does not prove {} proofs",
+                circuit, proof.proof_type
+            )));
+        }
+        let public_inputs = parse_public_inputs(&proof.public_signals)?;
+        self.get(circuit)?.verify(&proof.proof, &public_inputs)
+    }
+
+    /// Verify every SNARK in RAILGUN `transact` calldata for `chain_id`
+    ///
+    /// Errors unless all proofs are bound to `chain_id` and valid; returns
+    /// the verified proofs. Fails closed: a proof whose circuit has no key
+    /// is an error, not a pass.
+    pub fn verify_transaction(
+        &self,
+        proof_type: &ProofType,
+        chain_id: u64,
+        calldata: &[u8],
+    ) -> Result<Vec<ProofEnvelope>> {
+        let transactions = decode_transactions(calldata)?;
+        if transactions.is_empty() {
+            return Err(CepWalletError::PrivacyError(
+                "Transaction contains no proofs".to_string(),
+            ));
+        }
+
+        let mut proofs = Vec::with_capacity(transactions.len());
+        for (i, tx) in transactions.iter().enumerate() {
+            if tx.chain_id != chain_id {
+                return Err(CepWalletError::PrivacyError(format!(
+                    "Proof {} is bound to chain {}, expected {}",
+                    i, tx.chain_id, chain_id
+                )));
+            }
+            let proof = tx.envelope(proof_type.clone());
+            if !self.has_key(&proof.circuit) {
+                return Err(CepWalletError::ZKProofError(format!(
+                    "No verification key for circuit {}; proof {} cannot be verified",
+                    proof.circuit, i
+                )));
+            }
+            if !self.verify(&proof)? {
+                return Err(CepWalletError::ZKProofError(format!(
+                    "Proof {} ({}) failed verification",
+                    i,
+                    tx.circuit()
+                )));
+            }
+            proofs.push(proof);
+        }
+        Ok(proofs)
+    }
+
+    fn lock_keys(&self) -> std::sync::MutexGuard<'_, HashMap<String, Arc<VerificationKey>>> {
+        // Only inserts happen under the lock; a poisoned map is still valid
+        self.keys.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// A JSON array of decimal strings
+fn strings<const N: usize>(value: &Value) -> Result<[String; N]> {
+    value
+        .as_array()
+        .and_then(|items| {
+            items
+                .iter()
+                .map(|item| item.as_str().map(String::from))
+                .collect::<Option<Vec<_>>>()
+        })
+        .and_then(|items| items.try_into().ok())
+        .ok_or_else(|| vkey_error("malformed point"))
+}
+
+fn vkey_error(reason: &str) -> CepWalletError {
+    CepWalletError::ZKProofError(format!("Invalid verification key: {}", reason))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::privacy::artifacts::tests::fixture_artifacts;
+    use crate::privacy::zkproof::{Circuit, FullProof};
+    use serde_json::json;
+
+    const MULTIPLIER_ZKEY: &[u8] = include_bytes!("../../fixtures/circuits/multiplier2.zkey");
+    const MULTIPLIER_WASM: &[u8] = include_bytes!("../../fixtures/circuits/multiplier2.wasm");
+    const MULTIPLIER_VKEY: &str = include_str!("../../fixtures/circuits/multiplier2.vkey.json");
+
+    fn multiplier_proof() -> FullProof {
+        Circuit::new(MULTIPLIER_ZKEY, MULTIPLIER_WASM)
+            .unwrap()
+            .prove(&json!({"a": 3, "b": 11}))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_snarkjs_vkey() {
+        let key = VerificationKey::from_snarkjs_json(MULTIPLIER_VKEY).unwrap();
+        assert_eq!(key.n_public(), 1);
+
+        let proof = multiplier_proof();
+        assert!(key.verify(&proof.proof, &[Fr::from(33u64)]).unwrap());
+        assert!(!key.verify(&proof.proof, &[Fr::from(34u64)]).unwrap());
+        assert!(!key.verify(&proof.proof, &[]).unwrap());
+
+        assert!(VerificationKey::from_snarkjs_json("{}").is_err());
+        let mut vkey: Value = serde_json::from_str(MULTIPLIER_VKEY).unwrap();
+        vkey["nPublic"] = json!(2);
+        assert!(VerificationKey::from_snarkjs_json(&vkey.to_string()).is_err());
+    }
+
+    #[test]
+    #[ignore = "needs RAILGUN's artifacts-v2.1 vkey.json files copied into vkeys/"]
+    fn test_bundled_vkeys_load() {
+        let dir = bundled_vkeys_dir(None);
+        let keys = VerificationKeys::new(Some(dir.clone()), None);
+
+        // Transfers and unshields of one or two notes, with change and a
+        // broadcaster fee, must never be refused for a missing key
+        for nullifiers in 1..=2 {
+            for commitments in 1..=3 {
+                let circuit = joinsplit_circuit(nullifiers, commitments);
+                let key = keys
+                    .get(&circuit)
+                    .unwrap_or_else(|e| panic!("{}: {}", circuit, e));
+                // merkleRoot, boundParamsHash, nullifiers, commitments
+                assert_eq!(key.n_public(), 2 + nullifiers + commitments, "{}", circuit);
+            }
+        }
+
+        // Every key shipped in vkeys/ must parse and be found by name
+        for entry in std::fs::read_dir(&dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+            let circuit = path.file_stem().unwrap().to_str().unwrap();
+            assert!(keys.has_key(circuit));
+            let key = keys
+                .get(circuit)
+                .unwrap_or_else(|e| panic!("{}: {}", circuit, e));
+            assert!(key.n_public() > 0, "{}", circuit);
+        }
+    }
+
+    #[test]
+    fn test_parse_public_inputs() {
+        let inputs = parse_public_inputs(&["33".into(), "0x21".into()]).unwrap();
+        assert_eq!(inputs, [Fr::from(33u64), Fr::from(33u64)]);
+
+        let modulus = BigUint::from(Fr::MODULUS).to_string();
+        assert!(parse_field_element(&modulus).is_err());
+        assert!(parse_field_element("-1").is_err());
+        assert!(parse_field_element("0xzz").is_err());
+    }
+
+    #[test]
+    fn test_keys_lookup() {
+        let bundled = tempfile::tempdir().unwrap();
+        let artifacts = tempfile::tempdir().unwrap();
+        let keys = VerificationKeys::new(
+            Some(bundled.path().to_path_buf()),
+            Some(Arc::new(fixture_artifacts(
+                artifacts.path(),
+                "01x02",
+                false,
+            ))),
+        );
+        let envelope =
+            |proof_type, circuit| ProofEnvelope::new(proof_type, circuit, multiplier_proof());
+        let proof = envelope(ProofType::Compliance, "compliance");
+
+        // Fails closed without a key
+        assert!(keys.verify(&proof).is_err());
+
+        // Any key stands in for the compliance circuit here
+        std::fs::write(bundled.path().join("compliance.json"), MULTIPLIER_VKEY).unwrap();
+        assert!(keys.verify(&proof).unwrap());
+
+        // RAILGUN variants fall back to the pinned artifacts
+        assert_eq!(keys.get("01x02").unwrap().n_public(), 1);
+
+        let mut wrong = envelope(ProofType::Transfer, "01x02");
+        wrong.public_signals = vec!["34".to_string()];
+        assert!(!keys.verify(&wrong).unwrap());
+
+        // Proof types only accept their own circuits
+        assert!(keys
+            .verify(&envelope(ProofType::Transfer, "compliance"))
+            .is_err());
+        assert!(keys.verify(&envelope(ProofType::Shield, "01x02")).is_err());
+        assert!(keys.get("../compliance").is_err());
+    }
+
+    #[test]
+    fn test_verify_transaction() {
+        let keys = VerificationKeys::new(None, None);
+        let calldata = crate::privacy::transact::tests::transact_calldata(1);
+
+        // No key for the variant: fails closed, after the chain check
+        assert!(!keys.has_key("01x02"));
+        let err = keys
+            .verify_transaction(&ProofType::Unshield, 1, &calldata)
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("No verification key for circuit 01x02"));
+        let err = keys
+            .verify_transaction(&ProofType::Unshield, 137, &calldata)
+            .unwrap_err();
+        assert!(err.to_string().contains("bound to chain 1"));
+
+        keys.insert(
+            "01x02",
+            VerificationKey::from_snarkjs_json(MULTIPLIER_VKEY).unwrap(),
+        );
+        assert!(keys.has_key("01x02"));
+
+        let err = keys
+            .verify_transaction(&ProofType::Unshield, 137, &calldata)
+            .unwrap_err();
+        assert!(err.to_string().contains("bound to chain 1"));
+        let err = keys
+            .verify_transaction(&ProofType::Unshield, 1, &calldata)
+            .unwrap_err();
+        assert!(err.to_string().contains("01x02"));
+        assert!(keys
+            .verify_transaction(&ProofType::Shield, 1, &calldata)
+            .is_err());
+    }
+}
@@ -1,10 +1,13 @@
-use crate::error::Result;
+use super::bridge::{BridgeError, BridgeRequest, NodeBridge};
+use super::key_derivation::derive_railgun_secrets;
+use crate::crypto::SecretString;
+use crate::error::{CepWalletError, Result};
+use crate::hardware::WalletSigner;
 /// RAILGUN Wallet Management
 ///
 /// Manages RAILGUN wallet creation, import, and shield key management.
-/// Uses FFI to call Node.js for wallet operations.
+/// Uses the Node.js proof generator (via `NodeBridge`) for wallet operations.
 use serde::{Deserialize, Serialize};
-use std::process::Command;
 
 /// Wallet creation response
 #[derive(Debug, Deserialize)]
@@ -16,7 +19,7 @@ pub struct WalletCreateResponse {
     #[serde(default)]
     pub railgun_address: String,
     #[serde(default)]
-    pub mnemonic: String,
+    pub mnemonic: SecretString,
     #[serde(default)]
     pub error: Option<String>,
 }
@@ -27,7 +30,7 @@ pub struct WalletCreateResponse {
 pub struct ShieldKeyResponse {
     pub success: bool,
     #[serde(default)]
-    pub shield_private_key: String,
+    pub shield_private_key: SecretString,
     #[serde(default)]
     pub error: Option<String>,
 }
@@ -35,16 +38,20 @@ pub struct ShieldKeyResponse {
 /// Create a new RAILGUN wallet
 ///
 /// # Arguments
+/// * `bridge` - Proof generator bridge
+/// * `chain_id` - Chain the wallet is loaded for
 /// * `encryption_key` - 32-byte hex string for wallet encryption
 /// * `mnemonic` - Optional mnemonic (generates new if None)
 ///
 /// # Returns
 /// * `WalletCreateResponse` - Wallet ID, address, and mnemonic
 pub async fn create_railgun_wallet(
+    bridge: &dyn NodeBridge,
+    chain_id: u64,
     encryption_key: &str,
     mnemonic: Option<&str>,
 ) -> Result<WalletCreateResponse> {
-    tracing::info!("Creating RAILGUN wallet via FFI");
+    tracing::info!("Creating RAILGUN wallet via proof generator");
 
     // Validate encryption key length (must be 32 bytes = 64 hex chars + 0x = 66 total)
     if encryption_key.len() != 66 || !encryption_key.starts_with("0x") {
@@ -54,96 +61,16 @@ pub async fn create_railgun_wallet(
         .into());
     }
 
-    // Build params (no operation field, command is passed as CLI arg)
-    let mut request = serde_json::json!({
-        "encryptionKey": encryption_key,
-    });
-
-    if let Some(m) = mnemonic {
-        request["mnemonic"] = serde_json::Value::String(m.to_string());
-    }
-
-    let request_json = serde_json::to_string(&request)
-        .map_err(|e| anyhow::anyhow!("Failed to serialize wallet request: {}", e))?;
-
-    // Get proof-generator directory (workspace root / proof-generator)
-    let current_dir = std::env::current_dir()
-        .map_err(|e| anyhow::anyhow!("Failed to get current directory: {}", e))?;
-
-    tracing::debug!("Current directory: {}", current_dir.display());
-
-    // Try workspace root first (if current_dir is src-tauri, go up one level)
-    let workspace_root = if current_dir.ends_with("src-tauri") {
-        current_dir.parent().unwrap_or(&current_dir)
-    } else {
-        &current_dir
+    let request = BridgeRequest::CreateWallet {
+        chain_id,
+        encryption_key: encryption_key.into(),
+        mnemonic: mnemonic.map(SecretString::from),
     };
-
-    let proof_generator_dir = workspace_root.join("proof-generator");
-    let script_path = proof_generator_dir.join("index.js");
-
-    tracing::debug!("Looking for script at: {}", script_path.display());
-
-    if !script_path.exists() {
-        return Err(anyhow::anyhow!(
-            "proof-generator/index.js not found at {}",
-            script_path.display()
-        )
-        .into());
-    }
-
-    tracing::debug!(
-        "Running: node {} create_wallet '{}'",
-        script_path.display(),
-        request_json
-    );
-
-    // Execute Node.js script with command and params
-    let output = Command::new("node")
-        .arg(&script_path)
-        .arg("create_wallet") // Command
-        .arg(&request_json) // Params JSON
-        .current_dir(&proof_generator_dir)
-        .output()
-        .map_err(|e| anyhow::anyhow!("Failed to execute Node.js: {}", e))?;
-
-    // Log both stdout and stderr for debugging
-    let stdout_str = String::from_utf8_lossy(&output.stdout);
-    let stderr_str = String::from_utf8_lossy(&output.stderr);
-
-    tracing::debug!("Node.js stdout: {}", stdout_str);
-    if !stderr_str.is_empty() {
-        tracing::warn!("Node.js stderr: {}", stderr_str);
-    }
-
-    if !output.status.success() {
-        return Err(anyhow::anyhow!(
-            "Wallet creation failed:\nSTDOUT: {}\nSTDERR: {}",
-            stdout_str,
-            stderr_str
-        )
-        .into());
-    }
-
-    // Parse response
-    let json_line = stdout_str
-        .lines()
-        .rev()
-        .find(|line| line.trim().starts_with('{'))
-        .ok_or_else(|| anyhow::anyhow!("No JSON output from wallet creation"))?;
-
-    let response: WalletCreateResponse = serde_json::from_str(json_line)
-        .map_err(|e| anyhow::anyhow!("Failed to parse wallet response: {}", e))?;
-
-    if !response.success {
-        return Err(anyhow::anyhow!(
-            "Wallet creation failed: {}",
-            response
-                .error
-                .unwrap_or_else(|| "Unknown error".to_string())
-        )
-        .into());
-    }
+    let response = bridge
+        .call(request)
+        .await
+        .map_err(|e| command_error("Wallet creation failed", e))?
+        .into_wallet()?;
 
     tracing::info!("RAILGUN wallet created successfully");
     tracing::info!("  Wallet ID: {}", response.railgun_wallet_id);
@@ -152,100 +79,93 @@ pub async fn create_railgun_wallet(
     Ok(response)
 }
 
+/// Create a RAILGUN wallet whose keys are derived from the connected Trezor
+///
+/// The encryption key and mnemonic are derived on demand via CipherKeyValue and
+/// only passed to the proof generator; the mnemonic is never returned or stored.
+///
+/// # Arguments
+/// * `bridge` - Proof generator bridge
+/// * `chain_id` - Chain the wallet is loaded for
+/// * `signer` - Connected signer (Trezor, or the software signer in development)
+/// * `account` - RAILGUN account index
+///
+/// # Returns
+/// * `WalletCreateResponse` - Wallet ID and address (mnemonic left empty)
+pub async fn create_hardware_railgun_wallet(
+    bridge: &dyn NodeBridge,
+    chain_id: u64,
+    signer: &dyn WalletSigner,
+    account: u32,
+) -> Result<WalletCreateResponse> {
+    tracing::info!("Deriving RAILGUN wallet keys from hardware wallet");
+
+    let secrets = derive_railgun_secrets(signer, account).await?;
+    let mut response = create_railgun_wallet(
+        bridge,
+        chain_id,
+        &secrets.encryption_key,
+        Some(&secrets.mnemonic),
+    )
+    .await?;
+    response.mnemonic = SecretString::default();
+
+    Ok(response)
+}
+
 /// Get shield private key for a RAILGUN wallet
 ///
 /// # Arguments
+/// * `bridge` - Proof generator bridge
+/// * `chain_id` - Chain the wallet is loaded for
 /// * `railgun_wallet_id` - RAILGUN wallet ID
 ///
 /// # Returns
 /// * `ShieldKeyResponse` - Shield private key
-pub async fn get_shield_private_key(railgun_wallet_id: &str) -> Result<ShieldKeyResponse> {
-    tracing::info!("Getting shield private key via FFI");
-
-    // Build params (no operation field, command is passed as CLI arg)
-    let request = serde_json::json!({
-        "railgunWalletId": railgun_wallet_id,
-    });
-
-    let request_json = serde_json::to_string(&request)
-        .map_err(|e| anyhow::anyhow!("Failed to serialize request: {}", e))?;
-
-    // Get proof-generator directory (workspace root / proof-generator)
-    let current_dir = std::env::current_dir()
-        .map_err(|e| anyhow::anyhow!("Failed to get current directory: {}", e))?;
-
-    // Try workspace root first (if current_dir is src-tauri, go up one level)
-    let workspace_root = if current_dir.ends_with("src-tauri") {
-        current_dir.parent().unwrap_or(&current_dir)
-    } else {
-        &current_dir
+pub async fn get_shield_private_key(
+    bridge: &dyn NodeBridge,
+    chain_id: u64,
+    railgun_wallet_id: &str,
+) -> Result<ShieldKeyResponse> {
+    tracing::info!("Getting shield private key via proof generator");
+
+    let request = BridgeRequest::GetShieldKey {
+        chain_id,
+        railgun_wallet_id: railgun_wallet_id.to_string(),
     };
-
-    let proof_generator_dir = workspace_root.join("proof-generator");
-    let script_path = proof_generator_dir.join("index.js");
-
-    tracing::debug!(
-        "Shield key - Looking for script at: {}",
-        script_path.display()
-    );
-
-    if !script_path.exists() {
-        return Err(anyhow::anyhow!(
-            "proof-generator/index.js not found at {}",
-            script_path.display()
-        )
-        .into());
-    }
-
-    // Execute Node.js script with command
-    let output = Command::new("node")
-        .arg(&script_path)
-        .arg("get_shield_key") // Command
-        .arg(&request_json) // Params JSON
-        .current_dir(&proof_generator_dir)
-        .output()
-        .map_err(|e| anyhow::anyhow!("Failed to execute Node.js: {}", e))?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(anyhow::anyhow!("Shield key retrieval failed: {}", stderr).into());
-    }
-
-    // Parse response
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let json_line = stdout
-        .lines()
-        .rev()
-        .find(|line| line.trim().starts_with('{'))
-        .ok_or_else(|| anyhow::anyhow!("No JSON output from shield key retrieval"))?;
-
-    let response: ShieldKeyResponse = serde_json::from_str(json_line)
-        .map_err(|e| anyhow::anyhow!("Failed to parse response: {}", e))?;
-
-    if !response.success {
-        return Err(anyhow::anyhow!(
-            "Shield key retrieval failed: {}",
-            response
-                .error
-                .unwrap_or_else(|| "Unknown error".to_string())
-        )
-        .into());
-    }
+    let response = bridge
+        .call(request)
+        .await
+        .map_err(|e| command_error("Shield key retrieval failed", e))?
+        .into_shield_key()?;
 
     tracing::info!("Shield private key retrieved successfully");
 
     Ok(response)
 }
 
+/// Prefix a failed command's message with what was attempted
+fn command_error(context: &str, error: BridgeError) -> CepWalletError {
+    match error {
+        BridgeError::Command(message) => {
+            CepWalletError::PrivacyError(format!("{}: {}", context, message))
+        }
+        other => other.into(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::privacy::bridge::{FakeBridge, WorkerBridge};
+    use serde_json::json;
 
     #[tokio::test]
     #[ignore] // Only run if Node.js and proof-generator are set up
     async fn test_create_wallet() {
         let encryption_key = format!("0x{}", "0".repeat(64)); // 32 bytes
-        let result = create_railgun_wallet(&encryption_key, None).await;
+        let result =
+            create_railgun_wallet(&WorkerBridge::default(), 11155111, &encryption_key, None).await;
 
         assert!(result.is_ok());
         let wallet = result.unwrap();
@@ -257,15 +177,53 @@ mod tests {
     #[tokio::test]
     #[ignore]
     async fn test_get_shield_key() {
+        let bridge = WorkerBridge::default();
+
         // First create a wallet
         let encryption_key = format!("0x{}", "0".repeat(64));
-        let wallet = create_railgun_wallet(&encryption_key, None).await.unwrap();
+        let wallet = create_railgun_wallet(&bridge, 11155111, &encryption_key, None)
+            .await
+            .unwrap();
 
         // Then get its shield key
-        let result = get_shield_private_key(&wallet.railgun_wallet_id).await;
+        let result = get_shield_private_key(&bridge, 11155111, &wallet.railgun_wallet_id).await;
 
         assert!(result.is_ok());
         let key_response = result.unwrap();
         assert!(!key_response.shield_private_key.is_empty());
     }
+
+    #[tokio::test]
+    async fn test_wallet_commands_with_fake_bridge() {
+        let bridge = FakeBridge::new(|request| match request {
+            BridgeRequest::CreateWallet { mnemonic, .. } => Ok(json!({
+                "success": true,
+                "railgunWalletId": "wallet-1",
+                "railgunAddress": "0zk1test",
+                "mnemonic": mnemonic.clone().unwrap_or_default(),
+            })),
+            _ => Ok(json!({ "success": false, "error": "Wallet not loaded" })),
+        });
+        let encryption_key = format!("0x{}", "0".repeat(64));
+
+        let wallet = create_railgun_wallet(&bridge, 11155111, &encryption_key, Some("test words"))
+            .await
+            .unwrap();
+        assert_eq!(wallet.railgun_wallet_id, "wallet-1");
+        assert_eq!(wallet.mnemonic.expose(), "test words");
+
+        // Invalid keys never reach the proof generator
+        assert!(create_railgun_wallet(&bridge, 11155111, "0x1234", None)
+            .await
+            .is_err());
+
+        let error = get_shield_private_key(&bridge, 11155111, "wallet-1")
+            .await
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Privacy error: Shield key retrieval failed: Wallet not loaded"
+        );
+        assert_eq!(bridge.methods(), vec!["create_wallet", "get_shield_key"]);
+    }
 }
@@ -0,0 +1,320 @@
+/// circom witness calculator
+///
+/// Runs the `.wasm` witness generator that circom 2 emits next to a circuit
+/// (the same module snarkjs loads) in an embedded interpreter. Inputs are
+/// set by the FNV-1a hash of their name, one field element at a time, through
+/// the module's shared read/write memory; the full witness is read back the
+/// same way.
+use crate::error::{CepWalletError, Result};
+use ark_bn254::Fr;
+use ark_ff::{BigInteger, PrimeField};
+use num_bigint::{BigInt, BigUint};
+use serde_json::Value;
+use wasmi::core::Trap;
+use wasmi::{Caller, Engine, Instance, Linker, Module, Store, TypedFunc};
+
+/// Circuit inputs by signal name (arrays are flattened in order)
+pub type CircuitInputs = Vec<(String, Vec<BigInt>)>;
+
+pub struct WitnessCalculator {
+    store: Store<()>,
+    exports: Exports,
+    /// Field element size in 32-bit words
+    n32: u32,
+}
+
+struct Exports {
+    init: TypedFunc<i32, ()>,
+    write_shared: TypedFunc<(i32, i32), ()>,
+    read_shared: TypedFunc<i32, i32>,
+    set_input_signal: TypedFunc<(i32, i32, i32), ()>,
+    get_witness_size: TypedFunc<(), i32>,
+    get_witness: TypedFunc<i32, ()>,
+    /// circom >= 2.0.6 reports each input's length
+    get_input_signal_size: Option<TypedFunc<(i32, i32), i32>>,
+}
+
+impl WitnessCalculator {
+    /// Compile a circom 2 witness generator
+    pub fn new(wasm: &[u8]) -> Result<Self> {
+        let engine = Engine::default();
+        let module = Module::new(&engine, wasm).map_err(|e| invalid(&e.to_string()))?;
+        let mut store = Store::new(&engine, ());
+
+        let mut linker = Linker::<()>::new(&engine);
+        linker
+            .func_wrap(
+                "runtime",
+                "exceptionHandler",
+                |_: Caller<'_, ()>, code: i32| -> std::result::Result<(), Trap> {
+                    Err(Trap::new(exception_message(code)))
+                },
+            )
+            .and_then(|l| l.func_wrap("runtime", "printErrorMessage", || {}))
+            .and_then(|l| l.func_wrap("runtime", "writeBufferMessage", || {}))
+            .and_then(|l| l.func_wrap("runtime", "showSharedRWMemory", || {}))
+            .map_err(|e| invalid(&e.to_string()))?;
+
+        let instance = linker
+            .instantiate(&mut store, &module)
+            .and_then(|pre| pre.start(&mut store))
+            .map_err(|e| invalid(&e.to_string()))?;
+
+        let version = typed::<(), i32>(&instance, &store, "getVersion")
+            .map_err(|_| invalid("circom 1 witness generators are not supported"))?
+            .call(&mut store, ())
+            .map_err(trap)?;
+        if version != 2 {
+            return Err(invalid(&format!("unsupported circom version {}", version)));
+        }
+
+        let exports = Exports {
+            init: typed(&instance, &store, "init")?,
+            write_shared: typed(&instance, &store, "writeSharedRWMemory")?,
+            read_shared: typed(&instance, &store, "readSharedRWMemory")?,
+            set_input_signal: typed(&instance, &store, "setInputSignal")?,
+            get_witness_size: typed(&instance, &store, "getWitnessSize")?,
+            get_witness: typed(&instance, &store, "getWitness")?,
+            get_input_signal_size: typed(&instance, &store, "getInputSignalSize").ok(),
+        };
+        let n32 = typed::<(), i32>(&instance, &store, "getFieldNumLen32")?
+            .call(&mut store, ())
+            .map_err(trap)? as u32;
+
+        let mut calculator = Self {
+            store,
+            exports,
+            n32,
+        };
+
+        typed::<(), ()>(&instance, &calculator.store, "getRawPrime")?
+            .call(&mut calculator.store, ())
+            .map_err(trap)?;
+        if calculator.read_shared()? != BigUint::from(Fr::MODULUS) {
+            return Err(invalid("circuit is not over the BN254 scalar field"));
+        }
+
+        Ok(calculator)
+    }
+
+    /// Compute the full witness (`1`, public signals, then private signals)
+    pub fn calculate(&mut self, inputs: &CircuitInputs) -> Result<Vec<Fr>> {
+        self.exports.init.call(&mut self.store, 1).map_err(trap)?;
+
+        for (name, values) in inputs {
+            let (msb, lsb) = fnv1a(name);
+            if let Some(size) = &self.exports.get_input_signal_size {
+                let expected = size.call(&mut self.store, (msb, lsb)).map_err(trap)?;
+                if expected < 0 {
+                    return Err(input_error(&format!("unknown input signal `{}`", name)));
+                }
+                if expected as usize != values.len() {
+                    return Err(input_error(&format!(
+                        "input `{}` has {} values, expected {}",
+                        name,
+                        values.len(),
+                        expected
+                    )));
+                }
+            }
+            for (i, value) in values.iter().enumerate() {
+                self.write_shared(&to_field(value))?;
+                self.exports
+                    .set_input_signal
+                    .call(&mut self.store, (msb, lsb, i as i32))
+                    .map_err(trap)?;
+            }
+        }
+
+        let size = self
+            .exports
+            .get_witness_size
+            .call(&mut self.store, ())
+            .map_err(trap)?;
+        (0..size)
+            .map(|i| {
+                self.exports
+                    .get_witness
+                    .call(&mut self.store, i)
+                    .map_err(trap)?;
+                Ok(Fr::from(self.read_shared()?))
+            })
+            .collect()
+    }
+
+    fn write_shared(&mut self, value: &BigUint) -> Result<()> {
+        let words = value.to_u32_digits();
+        for i in 0..self.n32 {
+            let word = words.get(i as usize).copied().unwrap_or(0);
+            self.exports
+                .write_shared
+                .call(&mut self.store, (i as i32, word as i32))
+                .map_err(trap)?;
+        }
+        Ok(())
+    }
+
+    fn read_shared(&mut self) -> Result<BigUint> {
+        let words = (0..self.n32)
+            .map(|i| {
+                let word = self
+                    .exports
+                    .read_shared
+                    .call(&mut self.store, i as i32)
+                    .map_err(trap)?;
+                Ok(word as u32)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(BigUint::from_slice(&words))
+    }
+}
+
+/// Parse circom input JSON (`{"a": "3", "path": ["0x..", ..]}`)
+///
+/// Values may be numbers, decimal strings (negative values are taken
+/// modulo the field) or `0x` hex strings; nested arrays are flattened.
+pub fn parse_inputs(json: &Value) -> Result<CircuitInputs> {
+    let object = json
+        .as_object()
+        .ok_or_else(|| input_error("inputs must be a JSON object"))?;
+    object
+        .iter()
+        .map(|(name, value)| {
+            let mut values = Vec::new();
+            flatten(name, value, &mut values)?;
+            Ok((name.clone(), values))
+        })
+        .collect()
+}
+
+fn flatten(name: &str, value: &Value, out: &mut Vec<BigInt>) -> Result<()> {
+    let bad = || input_error(&format!("invalid value for input `{}`", name));
+    match value {
+        Value::Array(items) => {
+            for item in items {
+                flatten(name, item, out)?;
+            }
+        }
+        Value::Number(n) => {
+            let n = n
+                .as_i64()
+                .map(BigInt::from)
+                .or_else(|| n.as_u64().map(BigInt::from))
+                .ok_or_else(bad)?;
+            out.push(n);
+        }
+        Value::String(s) => {
+            let n = match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+                Some(hex) => BigUint::parse_bytes(hex.as_bytes(), 16).map(BigInt::from),
+                None => s.parse::<BigInt>().ok(),
+            };
+            out.push(n.ok_or_else(bad)?);
+        }
+        Value::Bool(b) => out.push(BigInt::from(*b as u8)),
+        Value::Null | Value::Object(_) => return Err(bad()),
+    }
+    Ok(())
+}
+
+/// Reduce an input into the field (`-1` becomes `p - 1`)
+fn to_field(value: &BigInt) -> BigUint {
+    let modulus = BigInt::from(BigUint::from(Fr::MODULUS));
+    let reduced = ((value % &modulus) + &modulus) % &modulus;
+    let (_, magnitude) = reduced.into_parts();
+    magnitude
+}
+
+/// Field element as the decimal string snarkjs uses for signals
+pub fn field_to_decimal(value: &Fr) -> String {
+    BigUint::from_bytes_le(&value.into_bigint().to_bytes_le()).to_string()
+}
+
+/// circom's signal name hash: 64-bit FNV-1a, split into two i32 halves
+fn fnv1a(name: &str) -> (i32, i32) {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in name.bytes() {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    ((hash >> 32) as i32, hash as u32 as i32)
+}
+
+fn exception_message(code: i32) -> String {
+    let reason = match code {
+        1 => "signal not found",
+        2 => "too many values for input signal",
+        3 => "signal already set",
+        4 => "assert failed",
+        5 => "not enough memory",
+        6 => "input signal array access exceeds the size",
+        _ => "unknown error",
+    };
+    format!("circuit error {}: {}", code, reason)
+}
+
+fn typed<Params, Results>(
+    instance: &Instance,
+    store: &Store<()>,
+    name: &str,
+) -> Result<TypedFunc<Params, Results>>
+where
+    Params: wasmi::WasmParams,
+    Results: wasmi::WasmResults,
+{
+    instance
+        .get_typed_func(store, name)
+        .map_err(|_| invalid(&format!("missing export `{}`", name)))
+}
+
+fn trap(err: Trap) -> CepWalletError {
+    CepWalletError::ZKProofError(format!("Witness calculation failed: {}", err))
+}
+
+fn invalid(reason: &str) -> CepWalletError {
+    CepWalletError::ZKProofError(format!("Invalid witness generator: {}", reason))
+}
+
+fn input_error(reason: &str) -> CepWalletError {
+    CepWalletError::InvalidInput(format!("Circuit inputs: {}", reason))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const MULTIPLIER_WASM: &[u8] = include_bytes!("../../fixtures/circuits/multiplier2.wasm");
+
+    #[test]
+    fn test_calculate_witness() {
+        let mut calculator = WitnessCalculator::new(MULTIPLIER_WASM).unwrap();
+        let inputs = parse_inputs(&json!({"a": 3, "b": "11"})).unwrap();
+
+        let witness = calculator.calculate(&inputs).unwrap();
+        let witness: Vec<String> = witness.iter().map(field_to_decimal).collect();
+        assert_eq!(witness, ["1", "33", "3", "11"]);
+
+        // The calculator is reusable
+        let inputs = parse_inputs(&json!({"a": "0x02", "b": "-1"})).unwrap();
+        let witness = calculator.calculate(&inputs).unwrap();
+        assert_eq!(witness[1], -Fr::from(2u64));
+    }
+
+    #[test]
+    fn test_calculate_witness_errors() {
+        let mut calculator = WitnessCalculator::new(MULTIPLIER_WASM).unwrap();
+
+        let unknown = parse_inputs(&json!({"a": 3, "z": 1})).unwrap();
+        assert!(calculator.calculate(&unknown).is_err());
+
+        assert!(parse_inputs(&json!({"a": "three"})).is_err());
+        assert!(parse_inputs(&json!([1, 2])).is_err());
+        assert!(WitnessCalculator::new(b"\0asm").is_err());
+    }
+
+    #[test]
+    fn test_fnv1a() {
+        assert_eq!(fnv1a(""), (0xcbf29ce4_u32 as i32, 0x84222325_u32 as i32));
+        assert_eq!(fnv1a("a"), (0xaf63dc4c_u32 as i32, 0x8601ec8c_u32 as i32));
+    }
+}
@@ -0,0 +1,668 @@
+/// Long-lived Node.js proof-generator worker
+///
+/// A single `node index.js --worker` process serves every proof-generator
+/// command over newline-delimited JSON-RPC 2.0 on stdin/stdout, so the
+/// RAILGUN engine and its artifacts are loaded once rather than per call.
+/// Requests carry ids and may be in flight concurrently; progress arrives
+/// as `progress` notifications tagged with the request id.
+///
+/// The worker is started on first use. If it exits, in-flight requests
+/// fail and the next request starts a new process. A request that is
+/// cancelled or runs out of time is abandoned and the worker is sent a
+/// `cancel` notification for its id, so it stops working on it. Typed
+/// commands are layered on top by `bridge.rs`.
+use super::bridge::BridgeError;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{ChildStdin, Command};
+use tokio::sync::{oneshot, watch};
+
+type Result<T> = std::result::Result<T, BridgeError>;
+
+/// Time a worker gets to exit after `shutdown` before it is killed
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Builds the command that starts a worker process
+type Launcher = Box<dyn Fn() -> Result<Command> + Send + Sync>;
+
+/// Receives the params of a request's `progress` notifications
+pub type ProgressFn = Box<dyn Fn(Value) + Send>;
+
+/// A request waiting for its response
+struct Pending {
+    /// Process the request was written to
+    generation: u64,
+    reply: oneshot::Sender<Result<Value>>,
+    progress: Option<ProgressFn>,
+}
+
+type PendingMap = Arc<Mutex<HashMap<u64, Pending>>>;
+
+/// A running worker process
+struct Process {
+    generation: u64,
+    stdin: ChildStdin,
+    /// Becomes `true` once the process has exited
+    exited: watch::Receiver<bool>,
+    /// Kills the process when sent to or dropped
+    kill: oneshot::Sender<()>,
+}
+
+impl Process {
+    fn is_running(&self) -> bool {
+        !*self.exited.borrow()
+    }
+}
+
+/// Supervisor of a long-lived JSON-RPC worker process
+pub struct NodeWorker {
+    launcher: Launcher,
+    process: tokio::sync::Mutex<Option<Process>>,
+    pending: PendingMap,
+    next_id: AtomicU64,
+    generations: AtomicU64,
+}
+
+impl NodeWorker {
+    /// Supervise the worker started by `launcher`
+    ///
+    /// The command's stdin, stdout and stderr are piped by the supervisor.
+    pub fn new<F>(launcher: F) -> Self
+    where
+        F: Fn() -> Result<Command> + Send + Sync + 'static,
+    {
+        Self {
+            launcher: Box::new(launcher),
+            process: tokio::sync::Mutex::new(None),
+            pending: Arc::new(Mutex::new(HashMap::new())),
+            next_id: AtomicU64::new(1),
+            generations: AtomicU64::new(0),
+        }
+    }
+
+    /// Whether a worker process is currently running
+    pub async fn is_running(&self) -> bool {
+        self.process
+            .lock()
+            .await
+            .as_ref()
+            .is_some_and(Process::is_running)
+    }
+
+    /// Call a worker method and wait for its result
+    pub async fn call(&self, method: &str, params: Value) -> Result<Value> {
+        self.request(method, params, None, None, None).await
+    }
+
+    /// Call a worker method, forwarding its progress notifications
+    ///
+    /// Setting `cancel` to `true` (or dropping its sender) abandons the
+    /// request and tells the worker to stop it.
+    pub async fn call_with_progress(
+        &self,
+        method: &str,
+        params: Value,
+        progress: ProgressFn,
+        cancel: watch::Receiver<bool>,
+    ) -> Result<Value> {
+        self.request(method, params, Some(progress), Some(cancel), None)
+            .await
+    }
+
+    /// Call a worker method with optional progress forwarding, cancellation
+    /// and time limit
+    ///
+    /// A cancelled request fails with `Cancelled`, one that is not answered
+    /// within `timeout` with `TimedOut`; either way the worker is told to
+    /// stop it and keeps serving other requests.
+    pub async fn request(
+        &self,
+        method: &str,
+        params: Value,
+        progress: Option<ProgressFn>,
+        cancel: Option<watch::Receiver<bool>>,
+        timeout: Option<Duration>,
+    ) -> Result<Value> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let message = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+        let (reply, mut response) = oneshot::channel();
+
+        let generation = {
+            let mut process = self.process.lock().await;
+            if !process.as_ref().is_some_and(Process::is_running) {
+                if process.is_some() {
+                    tracing::warn!("Proof generator worker exited, restarting");
+                }
+                *process = Some(self.spawn()?);
+            }
+            let running = process.as_mut().expect("worker process started above");
+
+            lock(&self.pending).insert(
+                id,
+                Pending {
+                    generation: running.generation,
+                    reply,
+                    progress,
+                },
+            );
+            // The worker may have died after the check above, after its
+            // in-flight requests were failed
+            if !running.is_running() {
+                lock(&self.pending).remove(&id);
+                return Err(BridgeError::WorkerExited(
+                    "Proof generator worker exited".to_string(),
+                ));
+            }
+            if let Err(e) = write_message(&mut running.stdin, &message).await {
+                lock(&self.pending).remove(&id);
+                return Err(BridgeError::WorkerExited(format!(
+                    "Failed to send request to proof generator worker: {}",
+                    e
+                )));
+            }
+            running.generation
+        };
+
+        let abandoned = tokio::select! {
+            result = &mut response => {
+                return result.map_err(|_| {
+                    BridgeError::WorkerExited("Proof generator worker stopped".to_string())
+                })?;
+            }
+            _ = cancelled(cancel) => BridgeError::Cancelled,
+            _ = expired(timeout) => BridgeError::TimedOut(timeout.unwrap_or_default()),
+        };
+
+        lock(&self.pending).remove(&id);
+        self.cancel_in_worker(generation, id).await;
+        Err(abandoned)
+    }
+
+    /// Tell the worker to stop an abandoned request
+    async fn cancel_in_worker(&self, generation: u64, id: u64) {
+        let mut process = self.process.lock().await;
+        let Some(running) = process
+            .as_mut()
+            .filter(|running| running.generation == generation && running.is_running())
+        else {
+            return;
+        };
+        let message = json!({ "jsonrpc": "2.0", "method": "cancel", "params": { "id": id } });
+        if let Err(e) = write_message(&mut running.stdin, &message).await {
+            tracing::debug!("Failed to cancel worker request {}: {}", id, e);
+        }
+    }
+
+    /// Whether the worker answers a `ping` within `limit`
+    ///
+    /// A worker whose event loop is blocked cannot; the caller decides
+    /// whether to kill it.
+    pub async fn responds_within(&self, limit: Duration) -> bool {
+        let ping = self.request("ping", json!({}), None, None, Some(limit));
+        ping.await.is_ok()
+    }
+
+    fn spawn(&self) -> Result<Process> {
+        let generation = self.generations.fetch_add(1, Ordering::Relaxed) + 1;
+        let mut child = (self.launcher)()?
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| {
+                BridgeError::Unavailable(format!(
+                    "Failed to start proof generator worker: {}. Make sure Node.js is installed.",
+                    e
+                ))
+            })?;
+
+        let (Some(stdin), Some(stdout), Some(stderr)) =
+            (child.stdin.take(), child.stdout.take(), child.stderr.take())
+        else {
+            return Err(BridgeError::Unavailable(
+                "Failed to capture proof generator worker pipes".to_string(),
+            ));
+        };
+
+        let (exited_tx, exited) = watch::channel(false);
+        let exited_tx = Arc::new(exited_tx);
+
+        let pending = self.pending.clone();
+        let stdout_closed = exited_tx.clone();
+        tokio::spawn(async move {
+            let mut lines = BufReader::new(stdout).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                dispatch(&pending, &line);
+            }
+            // Without stdout the worker cannot answer; treat it as exited
+            stdout_closed.send_replace(true);
+            fail_pending(&pending, generation, "Proof generator worker exited");
+        });
+
+        tokio::spawn(async move {
+            let mut lines = BufReader::new(stderr).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                tracing::debug!(target: "proof_generator", "{}", line);
+            }
+        });
+
+        let (kill, kill_rx) = oneshot::channel::<()>();
+        tokio::spawn(async move {
+            tokio::select! {
+                status = child.wait() => match status {
+                    Ok(status) if status.success() => {
+                        tracing::info!("Proof generator worker exited");
+                    }
+                    Ok(status) => {
+                        tracing::warn!("Proof generator worker crashed: {}", status);
+                    }
+                    Err(e) => tracing::warn!("Proof generator worker lost: {}", e),
+                },
+                _ = kill_rx => {
+                    let _ = child.kill().await;
+                }
+            }
+            exited_tx.send_replace(true);
+        });
+
+        tracing::info!("Started proof generator worker ({})", generation);
+        Ok(Process {
+            generation,
+            stdin,
+            exited,
+            kill,
+        })
+    }
+
+    /// Stop the worker: ask it to exit, then kill it if it does not
+    ///
+    /// The worker lets in-flight requests finish before exiting. A later
+    /// request starts a new worker.
+    pub async fn shutdown(&self) {
+        let Some(process) = self.process.lock().await.take() else {
+            return;
+        };
+        let Process {
+            generation,
+            mut stdin,
+            mut exited,
+            kill,
+        } = process;
+
+        let _ = write_message(
+            &mut stdin,
+            &json!({ "jsonrpc": "2.0", "method": "shutdown" }),
+        )
+        .await;
+        drop(stdin);
+
+        let exited_in_time = tokio::time::timeout(SHUTDOWN_TIMEOUT, wait_exited(&mut exited))
+            .await
+            .is_ok();
+        if !exited_in_time {
+            tracing::warn!("Proof generator worker did not exit, killing it");
+            let _ = kill.send(());
+        }
+
+        fail_pending(
+            &self.pending,
+            generation,
+            "Proof generator worker shut down",
+        );
+    }
+
+    /// Kill the worker at once, failing its in-flight requests
+    ///
+    /// For a worker that stopped responding. A later request starts a new
+    /// worker once this one is gone.
+    pub async fn kill(&self) {
+        let mut process = self.process.lock().await;
+        let Some(Process {
+            generation,
+            mut exited,
+            kill,
+            ..
+        }) = process.take()
+        else {
+            return;
+        };
+
+        let _ = kill.send(());
+        wait_exited(&mut exited).await;
+        fail_pending(&self.pending, generation, "Proof generator worker killed");
+    }
+}
+
+fn lock(pending: &PendingMap) -> std::sync::MutexGuard<'_, HashMap<u64, Pending>> {
+    pending
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+async fn write_message(stdin: &mut ChildStdin, message: &Value) -> std::io::Result<()> {
+    let mut line = message.to_string();
+    line.push('\n');
+    stdin.write_all(line.as_bytes()).await?;
+    stdin.flush().await
+}
+
+/// Resolves once the process has exited
+async fn wait_exited(exited: &mut watch::Receiver<bool>) {
+    while !*exited.borrow() {
+        if exited.changed().await.is_err() {
+            return;
+        }
+    }
+}
+
+/// Resolves once `cancel` is set or its sender is dropped; never without one
+async fn cancelled(cancel: Option<watch::Receiver<bool>>) {
+    let Some(mut cancel) = cancel else {
+        return std::future::pending().await;
+    };
+    while !*cancel.borrow() {
+        if cancel.changed().await.is_err() {
+            return;
+        }
+    }
+}
+
+/// Resolves once `timeout` has passed; never without one
+async fn expired(timeout: Option<Duration>) {
+    match timeout {
+        Some(timeout) => tokio::time::sleep(timeout).await,
+        None => std::future::pending().await,
+    }
+}
+
+/// JSON-RPC message from the worker
+#[derive(Debug, Deserialize)]
+struct RpcMessage {
+    #[serde(default)]
+    id: Option<u64>,
+    #[serde(default)]
+    method: Option<String>,
+    #[serde(default)]
+    params: Option<Value>,
+    #[serde(default)]
+    result: Option<Value>,
+    #[serde(default)]
+    error: Option<RpcError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+/// Route one line of worker output to the request it belongs to
+fn dispatch(pending: &PendingMap, line: &str) {
+    let Ok(message) = serde_json::from_str::<RpcMessage>(line.trim()) else {
+        tracing::debug!(target: "proof_generator", "{}", line);
+        return;
+    };
+
+    match (message.method.as_deref(), message.id) {
+        (None, Some(id)) => {
+            let Some(request) = lock(pending).remove(&id) else {
+                return; // abandoned
+            };
+            let result = match message.error {
+                Some(error) => {
+                    tracing::debug!("Worker request {} failed ({})", id, error.code);
+                    Err(BridgeError::from_rpc(error.code, error.message))
+                }
+                None => Ok(message.result.unwrap_or(Value::Null)),
+            };
+            let _ = request.reply.send(result);
+        }
+        (Some("progress"), _) => {
+            let Some(params) = message.params else {
+                return;
+            };
+            let Some(id) = params.get("id").and_then(Value::as_u64) else {
+                return;
+            };
+            if let Some(progress) = lock(pending).get(&id).and_then(|p| p.progress.as_ref()) {
+                progress(params);
+            }
+        }
+        (None, None) => {
+            if let Some(error) = message.error {
+                tracing::warn!("Proof generator worker error: {}", error.message);
+            }
+        }
+        (Some(method), _) => tracing::debug!("Ignoring worker notification: {}", method),
+    }
+}
+
+/// Fail the requests sent to a worker process that is gone
+fn fail_pending(pending: &PendingMap, generation: u64, reason: &str) {
+    let failed: Vec<Pending> = {
+        let mut pending = lock(pending);
+        let ids: Vec<u64> = pending
+            .iter()
+            .filter(|(_, request)| request.generation == generation)
+            .map(|(id, _)| *id)
+            .collect();
+        ids.into_iter()
+            .filter_map(|id| pending.remove(&id))
+            .collect()
+    };
+    for request in failed {
+        let _ = request
+            .reply
+            .send(Err(BridgeError::WorkerExited(reason.to_string())));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::mpsc;
+
+    fn forward(progress: mpsc::UnboundedSender<Value>) -> ProgressFn {
+        Box::new(move |params| {
+            let _ = progress.send(params);
+        })
+    }
+
+    fn pending_request(
+        pending: &PendingMap,
+        id: u64,
+        generation: u64,
+    ) -> (
+        oneshot::Receiver<Result<Value>>,
+        mpsc::UnboundedReceiver<Value>,
+    ) {
+        let (reply, response) = oneshot::channel();
+        let (progress, progress_rx) = mpsc::unbounded_channel();
+        lock(pending).insert(
+            id,
+            Pending {
+                generation,
+                reply,
+                progress: Some(forward(progress)),
+            },
+        );
+        (response, progress_rx)
+    }
+
+    #[test]
+    fn test_dispatch_routes_by_id() {
+        let pending: PendingMap = Default::default();
+        let (mut first, mut first_progress) = pending_request(&pending, 1, 1);
+        let (mut second, _) = pending_request(&pending, 2, 1);
+
+        dispatch(&pending, "[ProofGen] not a protocol line");
+        dispatch(
+            &pending,
+            &json!({
+                "jsonrpc": "2.0",
+                "method": "progress",
+                "params": { "id": 1, "event": "scanProgress", "progress": 0.5 },
+            })
+            .to_string(),
+        );
+        dispatch(
+            &pending,
+            r#"{"jsonrpc":"2.0","id":2,"error":{"code":-32000,"message":"Wallet ID required"}}"#,
+        );
+        dispatch(
+            &pending,
+            r#"{"jsonrpc":"2.0","id":1,"result":{"success":true}}"#,
+        );
+
+        let progress = first_progress.try_recv().unwrap();
+        assert_eq!(progress["progress"], 0.5);
+        assert_eq!(first.try_recv().unwrap().unwrap()["success"], true);
+        let error = second.try_recv().unwrap().unwrap_err();
+        assert!(error.to_string().contains("Wallet ID required"));
+        assert!(lock(&pending).is_empty());
+
+        // Late responses to abandoned requests are dropped
+        dispatch(&pending, r#"{"jsonrpc":"2.0","id":1,"result":{}}"#);
+    }
+
+    #[test]
+    fn test_fail_pending_only_affects_its_process() {
+        let pending: PendingMap = Default::default();
+        let (mut old, _) = pending_request(&pending, 1, 1);
+        let (mut new, _) = pending_request(&pending, 2, 2);
+
+        fail_pending(&pending, 1, "Proof generator worker exited");
+
+        assert!(old.try_recv().unwrap().is_err());
+        assert!(new.try_recv().is_err()); // still waiting
+        assert!(lock(&pending).contains_key(&2));
+    }
+
+    /// Minimal worker: echoes params, `crash` exits, `slow` replies late,
+    /// `hang` never replies, `block` stalls the event loop for a second and
+    /// `cancelled` lists the ids of `cancel` notifications received
+    const FAKE_WORKER: &str = r#"
+        const rl = require('readline').createInterface({ input: process.stdin });
+        const send = (m) => process.stdout.write(JSON.stringify({ jsonrpc: '2.0', ...m }) + '\n');
+        const cancelled = [];
+        rl.on('line', (line) => {
+            const { id, method, params } = JSON.parse(line);
+            if (method === 'shutdown') return rl.close();
+            if (method === 'crash') process.exit(3);
+            if (method === 'hang') return;
+            if (method === 'cancel') return cancelled.push(params.id);
+            if (method === 'cancelled') return send({ id, result: { cancelled } });
+            if (method === 'block') { const end = Date.now() + 1000; while (Date.now() < end); }
+            const delay = method === 'slow' ? 200 : 0;
+            send({ method: 'progress', params: { id, progress: 1 } });
+            setTimeout(() => send({ id, result: { method, params, pid: process.pid } }), delay);
+        });
+        rl.on('close', () => process.exit(0));
+    "#;
+
+    fn fake_worker() -> NodeWorker {
+        NodeWorker::new(|| {
+            let mut command = Command::new("node");
+            command.arg("-e").arg(FAKE_WORKER);
+            Ok(command)
+        })
+    }
+
+    #[tokio::test]
+    #[ignore] // Requires Node.js
+    async fn test_worker_concurrency_restart_and_shutdown() {
+        let worker = fake_worker();
+
+        // Concurrent requests complete out of order
+        let (slow, fast) = tokio::join!(
+            worker.call("slow", json!({ "n": 1 })),
+            worker.call("echo", json!({ "n": 2 })),
+        );
+        let (slow, fast) = (slow.unwrap(), fast.unwrap());
+        assert_eq!(slow["params"]["n"], 1);
+        assert_eq!(fast["params"]["n"], 2);
+        assert_eq!(slow["pid"], fast["pid"]);
+
+        // Progress notifications reach the caller
+        let (progress, mut progress_rx) = mpsc::unbounded_channel();
+        let (_cancel, cancel) = watch::channel(false);
+        worker
+            .call_with_progress("echo", json!({}), forward(progress), cancel)
+            .await
+            .unwrap();
+        assert_eq!(progress_rx.recv().await.unwrap()["progress"], 1);
+
+        // A crash fails the in-flight request; the next one restarts
+        assert!(worker.call("crash", json!({})).await.is_err());
+        let restarted = worker.call("echo", json!({})).await.unwrap();
+        assert_ne!(restarted["pid"], fast["pid"]);
+
+        // A hung worker is killed; its requests fail and the next one restarts
+        let hung = worker.call("hang", json!({}));
+        let kill = async {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            worker.kill().await;
+        };
+        let (hung, ()) = tokio::join!(hung, kill);
+        assert!(matches!(hung, Err(BridgeError::WorkerExited(_))));
+        let respawned = worker.call("echo", json!({})).await.unwrap();
+        assert_ne!(respawned["pid"], restarted["pid"]);
+
+        worker.shutdown().await;
+        assert!(!worker.is_running().await);
+    }
+
+    #[tokio::test]
+    #[ignore] // Requires Node.js
+    async fn test_cancel_and_timeout_reach_the_worker() {
+        let worker = fake_worker();
+        let before = worker.call("echo", json!({})).await.unwrap();
+
+        // A timed-out request is cancelled in the worker, which keeps running
+        let timed_out = worker
+            .request(
+                "hang",
+                json!({}),
+                None,
+                None,
+                Some(Duration::from_millis(100)),
+            )
+            .await;
+        assert!(matches!(timed_out, Err(BridgeError::TimedOut(_))));
+
+        let (cancel_tx, cancel) = watch::channel(false);
+        let hung = worker.request("hang", json!({}), None, Some(cancel), None);
+        let cancel = async {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            cancel_tx.send_replace(true);
+        };
+        let (hung, ()) = tokio::join!(hung, cancel);
+        assert!(matches!(hung, Err(BridgeError::Cancelled)));
+
+        let cancelled = worker.call("cancelled", json!({})).await.unwrap();
+        assert_eq!(cancelled["cancelled"].as_array().unwrap().len(), 2);
+        let after = worker.call("echo", json!({})).await.unwrap();
+        assert_eq!(after["pid"], before["pid"]);
+
+        // Only a worker that stops answering counts as wedged
+        assert!(worker.responds_within(Duration::from_secs(1)).await);
+        let blocked = worker.request(
+            "block",
+            json!({}),
+            None,
+            None,
+            Some(Duration::from_millis(50)),
+        );
+        assert!(matches!(blocked.await, Err(BridgeError::TimedOut(_))));
+        assert!(!worker.responds_within(Duration::from_millis(100)).await);
+
+        worker.shutdown().await;
+    }
+}
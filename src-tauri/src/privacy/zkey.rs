@@ -0,0 +1,317 @@
+/// snarkjs `.zkey` reader (Groth16, BN254)
+///
+/// Parses the proving key and the A/B constraint matrices of a circuit from
+/// the binary format written by `snarkjs zkey new` / `zkey contribute`:
+///
+/// ```text
+/// "zkey" | version u32 | n_sections u32 | (id u32, len u64, data)*
+///   1  header         protocol (1 = Groth16)
+///   2  Groth header   q, r, n_vars, n_public, domain size, alpha1, beta1,
+///                     beta2, gamma2, delta1, delta2
+///   3  IC             n_public + 1 G1 points
+///   4  coefficients   (matrix, constraint, signal, value)*
+///   5  A              n_vars G1 points
+///   6  B1             n_vars G1 points
+///   7  B2             n_vars G2 points
+///   8  C (L)          n_vars - n_public - 1 G1 points
+///   9  H              domain size G1 points
+/// ```
+///
+/// Integers are little-endian; field elements are in Montgomery form, and
+/// matrix coefficients are multiplied by R once more.
+use crate::error::{CepWalletError, Result};
+use ark_bn254::{Bn254, Fq, Fq2, Fr, G1Affine, G2Affine};
+use ark_ff::{BigInteger256, PrimeField, Zero};
+use ark_groth16::{ProvingKey, VerifyingKey};
+use ark_relations::r1cs::ConstraintMatrices;
+use std::collections::HashMap;
+
+const MAGIC: &[u8; 4] = b"zkey";
+const GROTH16_PROTOCOL: u32 = 1;
+
+const SECTION_HEADER: u32 = 1;
+const SECTION_GROTH_HEADER: u32 = 2;
+const SECTION_IC: u32 = 3;
+const SECTION_COEFFS: u32 = 4;
+const SECTION_A: u32 = 5;
+const SECTION_B1: u32 = 6;
+const SECTION_B2: u32 = 7;
+const SECTION_C: u32 = 8;
+const SECTION_H: u32 = 9;
+
+/// Proving key and constraint matrices read from a zkey
+pub struct ZKey {
+    pub proving_key: ProvingKey<Bn254>,
+    pub matrices: ConstraintMatrices<Fr>,
+    /// Number of public signals (outputs and public inputs)
+    pub n_public: usize,
+}
+
+impl ZKey {
+    /// Parse a zkey file's contents
+    pub fn read(data: &[u8]) -> Result<Self> {
+        let sections = sections(data)?;
+
+        let mut header = Reader::new(section(&sections, SECTION_HEADER)?);
+        if header.u32()? != GROTH16_PROTOCOL {
+            return Err(invalid("not a Groth16 key"));
+        }
+
+        let mut groth = Reader::new(section(&sections, SECTION_GROTH_HEADER)?);
+        let n8q = groth.u32()? as usize;
+        let q = groth.take(n8q)?;
+        let n8r = groth.u32()? as usize;
+        let r = groth.take(n8r)?;
+        if n8q != 32 || q != le_bytes(Fq::MODULUS) || n8r != 32 || r != le_bytes(Fr::MODULUS) {
+            return Err(invalid("not a BN254 key"));
+        }
+        let n_vars = groth.u32()? as usize;
+        let n_public = groth.u32()? as usize;
+        let domain_size = groth.u32()? as usize;
+        if n_public + 1 > n_vars || !domain_size.is_power_of_two() {
+            return Err(invalid("inconsistent header"));
+        }
+        let alpha_g1 = groth.g1()?;
+        let beta_g1 = groth.g1()?;
+        let beta_g2 = groth.g2()?;
+        let gamma_g2 = groth.g2()?;
+        let delta_g1 = groth.g1()?;
+        let delta_g2 = groth.g2()?;
+
+        let g1_section = |id, n| -> Result<Vec<G1Affine>> {
+            let mut reader = Reader::new(section(&sections, id)?);
+            (0..n).map(|_| reader.g1()).collect()
+        };
+        let b_g2_query = {
+            let mut reader = Reader::new(section(&sections, SECTION_B2)?);
+            (0..n_vars)
+                .map(|_| reader.g2())
+                .collect::<Result<Vec<_>>>()?
+        };
+
+        let proving_key = ProvingKey {
+            vk: VerifyingKey {
+                alpha_g1,
+                beta_g2,
+                gamma_g2,
+                delta_g2,
+                gamma_abc_g1: g1_section(SECTION_IC, n_public + 1)?,
+            },
+            beta_g1,
+            delta_g1,
+            a_query: g1_section(SECTION_A, n_vars)?,
+            b_g1_query: g1_section(SECTION_B1, n_vars)?,
+            b_g2_query,
+            h_query: g1_section(SECTION_H, domain_size)?,
+            l_query: g1_section(SECTION_C, n_vars - n_public - 1)?,
+        };
+        let matrices = matrices(section(&sections, SECTION_COEFFS)?, n_vars, n_public)?;
+
+        Ok(Self {
+            proving_key,
+            matrices,
+            n_public,
+        })
+    }
+}
+
+/// A/B matrices from the coefficients section
+///
+/// snarkjs appends one `input_i * 1 = 0` row per public signal (and the
+/// constant one) after the circuit's constraints; those are dropped here and
+/// re-added by the reduction when proving.
+fn matrices(data: &[u8], n_vars: usize, n_public: usize) -> Result<ConstraintMatrices<Fr>> {
+    let mut reader = Reader::new(data);
+    let n_coeffs = reader.u32()? as usize;
+
+    let mut rows: [Vec<Vec<(Fr, usize)>>; 2] = [Vec::new(), Vec::new()];
+    for _ in 0..n_coeffs {
+        let matrix = reader.u32()? as usize;
+        let constraint = reader.u32()? as usize;
+        let signal = reader.u32()? as usize;
+        // Stored as value * R^2: undo one factor of R beyond Montgomery form
+        let value = Fr::new_unchecked(reader.fr()?.into_bigint());
+
+        if matrix > 1 || signal >= n_vars {
+            return Err(invalid("coefficient out of range"));
+        }
+        let rows = &mut rows[matrix];
+        if rows.len() <= constraint {
+            rows.resize(constraint + 1, Vec::new());
+        }
+        rows[constraint].push((value, signal));
+    }
+
+    let num_rows = rows[0].len().max(rows[1].len());
+    let num_constraints = num_rows
+        .checked_sub(n_public + 1)
+        .ok_or_else(|| invalid("missing public input constraints"))?;
+    let [mut a, mut b] = rows;
+    a.resize(num_constraints, Vec::new());
+    b.resize(num_constraints, Vec::new());
+
+    Ok(ConstraintMatrices {
+        num_instance_variables: n_public + 1,
+        num_witness_variables: n_vars - n_public - 1,
+        num_constraints,
+        a_num_non_zero: a.iter().map(Vec::len).sum(),
+        b_num_non_zero: b.iter().map(Vec::len).sum(),
+        c_num_non_zero: 0,
+        a,
+        b,
+        // C is implied by the reduction (only A and B are in the zkey)
+        c: Vec::new(),
+    })
+}
+
+/// Split a zkey into its sections by id
+fn sections(data: &[u8]) -> Result<HashMap<u32, &[u8]>> {
+    let mut reader = Reader::new(data);
+    if reader.take(4)? != MAGIC {
+        return Err(invalid("bad magic"));
+    }
+    let _version = reader.u32()?;
+    let n_sections = reader.u32()?;
+
+    let mut sections = HashMap::new();
+    for _ in 0..n_sections {
+        let id = reader.u32()?;
+        let len = usize::try_from(reader.u64()?).map_err(|_| invalid("section too large"))?;
+        // The first section with an id wins, as in snarkjs
+        let data = reader.take(len)?;
+        sections.entry(id).or_insert(data);
+    }
+    Ok(sections)
+}
+
+fn section<'a>(sections: &HashMap<u32, &'a [u8]>, id: u32) -> Result<&'a [u8]> {
+    sections
+        .get(&id)
+        .copied()
+        .ok_or_else(|| invalid(&format!("missing section {}", id)))
+}
+
+fn le_bytes(modulus: BigInteger256) -> Vec<u8> {
+    modulus
+        .0
+        .iter()
+        .flat_map(|limb| limb.to_le_bytes())
+        .collect()
+}
+
+fn invalid(reason: &str) -> CepWalletError {
+    CepWalletError::ZKProofError(format!("Invalid zkey: {}", reason))
+}
+
+/// Little-endian cursor over a zkey section
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        if self.data.len() < len {
+            return Err(invalid("unexpected end of file"));
+        }
+        let (head, tail) = self.data.split_at(len);
+        self.data = tail;
+        Ok(head)
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes(bytes.try_into().expect("4 bytes")))
+    }
+
+    fn u64(&mut self) -> Result<u64> {
+        let bytes = self.take(8)?;
+        Ok(u64::from_le_bytes(bytes.try_into().expect("8 bytes")))
+    }
+
+    /// 256-bit little-endian integer, checked against `modulus`
+    fn bigint(&mut self, modulus: BigInteger256) -> Result<BigInteger256> {
+        let bytes = self.take(32)?;
+        let mut limbs = [0u64; 4];
+        for (limb, chunk) in limbs.iter_mut().zip(bytes.chunks_exact(8)) {
+            *limb = u64::from_le_bytes(chunk.try_into().expect("8 bytes"));
+        }
+        let value = BigInteger256::new(limbs);
+        if value >= modulus {
+            return Err(invalid("field element out of range"));
+        }
+        Ok(value)
+    }
+
+    /// Base field element (Montgomery form)
+    fn fq(&mut self) -> Result<Fq> {
+        Ok(Fq::new_unchecked(self.bigint(Fq::MODULUS)?))
+    }
+
+    /// Scalar field element (Montgomery form)
+    fn fr(&mut self) -> Result<Fr> {
+        Ok(Fr::new_unchecked(self.bigint(Fr::MODULUS)?))
+    }
+
+    fn g1(&mut self) -> Result<G1Affine> {
+        let (x, y) = (self.fq()?, self.fq()?);
+        if x.is_zero() && y.is_zero() {
+            return Ok(G1Affine::identity());
+        }
+        let point = G1Affine::new_unchecked(x, y);
+        if !point.is_on_curve() {
+            return Err(invalid("G1 point not on curve"));
+        }
+        Ok(point)
+    }
+
+    fn g2(&mut self) -> Result<G2Affine> {
+        let x = Fq2::new(self.fq()?, self.fq()?);
+        let y = Fq2::new(self.fq()?, self.fq()?);
+        if x.is_zero() && y.is_zero() {
+            return Ok(G2Affine::identity());
+        }
+        let point = G2Affine::new_unchecked(x, y);
+        if !point.is_on_curve() {
+            return Err(invalid("G2 point not on curve"));
+        }
+        Ok(point)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_ZKEY: &[u8] = include_bytes!("../../fixtures/circuits/multiplier2.zkey");
+
+    #[test]
+    fn test_read_zkey() {
+        let zkey = ZKey::read(TEST_ZKEY).unwrap();
+
+        // c <== a * b: one public output, one constraint
+        assert_eq!(zkey.n_public, 1);
+        assert_eq!(zkey.matrices.num_instance_variables, 2);
+        assert_eq!(zkey.matrices.num_witness_variables, 2);
+        assert_eq!(zkey.matrices.num_constraints, 1);
+        assert_eq!(zkey.proving_key.vk.gamma_abc_g1.len(), 2);
+        assert_eq!(zkey.proving_key.a_query.len(), 4);
+        assert_eq!(zkey.proving_key.l_query.len(), 2);
+    }
+
+    #[test]
+    fn test_read_zkey_rejects_bad_input() {
+        assert!(ZKey::read(b"nope").is_err());
+        assert!(ZKey::read(&TEST_ZKEY[..TEST_ZKEY.len() / 2]).is_err());
+
+        let mut wrong_curve = TEST_ZKEY.to_vec();
+        // First byte of q in the Groth header (after magic, version,
+        // section count, section 1 and the section 2 id/length/n8q)
+        let q_offset = 12 + 16 + 12 + 4;
+        wrong_curve[q_offset] ^= 1;
+        assert!(ZKey::read(&wrong_curve).is_err());
+    }
+}
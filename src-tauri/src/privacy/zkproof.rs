@@ -1,14 +1,66 @@
-/// Zero-Knowledge Proof Generation (Placeholder)
+/// Zero-Knowledge Proof Generation
 ///
-/// This module handles ZK proof generation for privacy transactions.
-/// In production, this would use actual ZK-SNARK libraries like:
-/// - ark-groth16 (Rust)
-/// - bellman (Rust)
-/// - circom + snarkjs (via FFI)
+/// Native Groth16 (BN254) prover for circom circuits. A `Circuit` is a snarkjs proving key (`zkey`) plus the
+/// circom wasm witness generator; proofs are computed with the same
+/// R1CS-to-QAP reduction as snarkjs, so they verify against the circuit's
+/// snarkjs verification key and use the same JSON (`pi_a`, `pi_b`, `pi_c`)
+/// and calldata layouts. Groth16 proofs are randomized, so the two provers
+/// never produce the same bytes; they agree on public signals and on which
+/// key accepts the proof.
+///
+/// The prover takes circom inputs the caller builds (`generate_zk_proof`),
+/// for circuits such as Privacy Pools membership and compliance proofs.
+/// RAILGUN's joinsplit inputs (merkle proofs, nullifiers, note ciphertexts)
+/// are built by the SDK, so shields, transfers and unshields are always
+/// proven by the Node.js proof generator and refused here.
+///
+/// RAILGUN artifacts live in `<artifacts dir>/artifacts-v2.1/<variant>/`
+/// (`zkey`, `wasm`), where the proof generator's artifact store puts them;
+/// circuits are loaded on first use through the artifact manager, which
+/// checks them against pinned hashes (see `artifacts.rs`). Proofs are
+/// verified against separate verification keys (see `verifier.rs`), not the
+/// proving key.
+use super::artifacts::{ArtifactManager, ArtifactStatus, CircuitAvailability};
+use super::envelope::ProofEnvelope;
+use super::verifier::{parse_public_inputs, VerificationKeys};
+use super::witness::{field_to_decimal, parse_inputs, WitnessCalculator};
+use super::zkey::ZKey;
 use crate::error::{CepWalletError, Result};
+use ark_bn254::{Bn254, Fq, Fq2, Fr, G1Affine, G2Affine};
+use ark_ec::AffineRepr;
+use ark_ff::{BigInteger, Field, PrimeField, Zero};
+use ark_groth16::r1cs_to_qap::{evaluate_constraint, LibsnarkReduction, R1CSToQAP};
+use ark_groth16::{prepare_verifying_key, Groth16, Proof};
+use ark_poly::EvaluationDomain;
+use ark_relations::r1cs::{ConstraintMatrices, ConstraintSystemRef, SynthesisError};
+use ark_std::UniformRand;
+use num_bigint::BigUint;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Environment variable overriding the circuit artifact directory
+pub const CIRCUIT_ARTIFACTS_ENV: &str = "CEPWALLET_CIRCUIT_ARTIFACTS";
+
+/// Circuit artifact directory inside the app data directory
+pub const CIRCUIT_ARTIFACTS_DIR: &str = "circuit_artifacts";
+
+/// Subdirectory of RAILGUN's current artifact set
+pub(crate) const RAILGUN_ARTIFACTS_SUBDIR: &str = "artifacts-v2.1";
+
+/// Circuit artifact directory for an app data directory:
+/// `CEPWALLET_CIRCUIT_ARTIFACTS` when set, else `circuit_artifacts` inside it
+pub fn circuit_artifacts_dir(data_dir: &Path) -> PathBuf {
+    std::env::var_os(CIRCUIT_ARTIFACTS_ENV)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| data_dir.join(CIRCUIT_ARTIFACTS_DIR))
+}
 
 /// Proof type
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum ProofType {
     /// Shield proof (deposit into private pool)
     Shield,
@@ -22,101 +74,461 @@ pub enum ProofType {
     Compliance,
 }
 
+impl ProofType {
+    /// Whether `circuit` proves this type: RAILGUN joinsplit variants
+    /// (`01x02`) for transfers and unshields, a dedicated circuit otherwise.
+    /// Shields carry no SNARK.
+    pub fn accepts_circuit(&self, circuit: &str) -> bool {
+        match self {
+            ProofType::Shield => false,
+            ProofType::Transfer | ProofType::Unshield => is_joinsplit_circuit(circuit),
+            ProofType::PoolMembership => circuit == "pool_membership",
+            ProofType::Compliance => circuit == "compliance",
+        }
+    }
+
+    /// Whether the native prover generates this type; RAILGUN shields,
+    /// transfers and unshields come from the proof generator instead
+    pub fn is_native(&self) -> bool {
+        matches!(self, ProofType::PoolMembership | ProofType::Compliance)
+    }
+
+    /// Refuse types the native prover does not generate
+    pub fn check_native(&self) -> Result<()> {
+        if self.is_native() {
+            return Ok(());
+        }
+        Err(CepWalletError::ZKProofError(format!(
+            "{} proofs are generated by the RAILGUN proof generator, not natively",
+            self
+        )))
+    }
+}
+
+impl std::fmt::Display for ProofType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ProofType::Shield => "shield",
+            ProofType::Transfer => "transfer",
+            ProofType::Unshield => "unshield",
+            ProofType::PoolMembership => "pool_membership",
+            ProofType::Compliance => "compliance",
+        })
+    }
+}
+
+impl std::str::FromStr for ProofType {
+    type Err = CepWalletError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "shield" => Ok(ProofType::Shield),
+            "transfer" => Ok(ProofType::Transfer),
+            "unshield" => Ok(ProofType::Unshield),
+            "pool_membership" => Ok(ProofType::PoolMembership),
+            "compliance" => Ok(ProofType::Compliance),
+            _ => Err(CepWalletError::InvalidInput(format!(
+                "Invalid proof type: {}",
+                s
+            ))),
+        }
+    }
+}
+
+/// `NNxMM` variant names
+fn is_joinsplit_circuit(circuit: &str) -> bool {
+    let bytes = circuit.as_bytes();
+    bytes.len() == 5 && bytes[2] == b'x' && [0, 1, 3, 4].iter().all(|&i| bytes[i].is_ascii_digit())
+}
+
+/// Groth16 proof as snarkjs writes it (`proof.json`)
+///
+/// Points are projective coordinates as decimal strings, always normalized
+/// (`z = 1`); G2 coordinates are `[c0, c1]`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnarkjsProof {
+    pub pi_a: [String; 3],
+    pub pi_b: [[String; 2]; 3],
+    pub pi_c: [String; 3],
+    pub protocol: String,
+    pub curve: String,
+}
+
+/// Proof with its public signals, as returned by snarkjs `groth16.fullProve`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FullProof {
+    pub proof: SnarkjsProof,
+    pub public_signals: Vec<String>,
+}
+
+/// Size of a proof in calldata layout
+pub const PROOF_BYTES: usize = 256;
+
+impl SnarkjsProof {
+    pub fn from_ark(proof: &Proof<Bn254>) -> Self {
+        Self {
+            pi_a: g1_to_strings(&proof.a),
+            pi_b: g2_to_strings(&proof.b),
+            pi_c: g1_to_strings(&proof.c),
+            protocol: "groth16".to_string(),
+            curve: "bn128".to_string(),
+        }
+    }
+
+    /// Decode and check the points (on curve, in the right subgroup)
+    pub fn to_ark(&self) -> Result<Proof<Bn254>> {
+        if self.protocol != "groth16" || self.curve != "bn128" {
+            return Err(proof_error(&format!(
+                "unsupported proof {}/{}",
+                self.protocol, self.curve
+            )));
+        }
+        Ok(Proof {
+            a: g1_from_strings(&self.pi_a)?,
+            b: g2_from_strings(&self.pi_b)?,
+            c: g1_from_strings(&self.pi_c)?,
+        })
+    }
+
+    /// Calldata layout used by the Solidity verifiers (and snarkjs
+    /// `zkey export soliditycalldata`): `a.x, a.y, b.x.c1, b.x.c0, b.y.c1,
+    /// b.y.c0, c.x, c.y`, each a 32-byte big-endian word
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let proof = self.to_ark()?;
+        let (ax, ay) = g1_coords(&proof.a);
+        let (bx, by) = g2_coords(&proof.b);
+        let (cx, cy) = g1_coords(&proof.c);
+        Ok([ax, ay, bx.c1, bx.c0, by.c1, by.c0, cx, cy]
+            .iter()
+            .flat_map(|fq| fq.into_bigint().to_bytes_be())
+            .collect())
+    }
+
+    /// Inverse of [`SnarkjsProof::to_bytes`]
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() != PROOF_BYTES {
+            return Err(proof_error(&format!(
+                "expected {} bytes, got {}",
+                PROOF_BYTES,
+                bytes.len()
+            )));
+        }
+        let words = bytes
+            .chunks_exact(32)
+            .map(|word| {
+                let n = BigUint::from_bytes_be(word);
+                fq_from_biguint(n).map(|fq| fq_to_decimal(&fq))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let one = || "1".to_string();
+        let proof = Self {
+            pi_a: point_strings(&words[0], &words[1]),
+            pi_b: [
+                [words[3].clone(), words[2].clone()],
+                [words[5].clone(), words[4].clone()],
+                [one(), "0".to_string()],
+            ],
+            pi_c: point_strings(&words[6], &words[7]),
+            protocol: "groth16".to_string(),
+            curve: "bn128".to_string(),
+        };
+        proof.to_ark()?;
+        Ok(proof)
+    }
+}
+
+/// A circuit ready to prove: proving key, constraints, witness generator
+pub struct Circuit {
+    zkey: ZKey,
+    witness: Mutex<WitnessCalculator>,
+}
+
+impl Circuit {
+    /// Circuit from zkey and witness generator (`.wasm`) contents
+    pub fn new(zkey: &[u8], wasm: &[u8]) -> Result<Self> {
+        Ok(Self {
+            zkey: ZKey::read(zkey)?,
+            witness: Mutex::new(WitnessCalculator::new(wasm)?),
+        })
+    }
+
+    /// Number of public signals
+    pub fn n_public(&self) -> usize {
+        self.zkey.n_public
+    }
+
+    /// Compute the witness for circom input JSON and prove it
+    ///
+    /// CPU-bound (seconds for RAILGUN circuits); call from a blocking task.
+    pub fn prove(&self, inputs: &Value) -> Result<FullProof> {
+        let inputs = parse_inputs(inputs)?;
+        let witness = self
+            .witness
+            .lock()
+            .map_err(|_| CepWalletError::ZKProofError("Witness generator poisoned".into()))?
+            .calculate(&inputs)?;
+        self.prove_witness(&witness)
+    }
+
+    /// Prove a full witness (`1`, public signals, private signals)
+    pub fn prove_witness(&self, witness: &[Fr]) -> Result<FullProof> {
+        let matrices = &self.zkey.matrices;
+        let num_inputs = matrices.num_instance_variables;
+        if witness.len() != num_inputs + matrices.num_witness_variables {
+            return Err(CepWalletError::ZKProofError(format!(
+                "Witness has {} signals, circuit expects {}",
+                witness.len(),
+                num_inputs + matrices.num_witness_variables
+            )));
+        }
+
+        let mut rng = ark_std::rand::thread_rng();
+        let proof = Groth16::<Bn254, CircomReduction>::create_proof_with_reduction_and_matrices(
+            &self.zkey.proving_key,
+            Fr::rand(&mut rng),
+            Fr::rand(&mut rng),
+            matrices,
+            num_inputs,
+            matrices.num_constraints,
+            witness,
+        )
+        .map_err(|e| CepWalletError::ZKProofError(format!("Proving failed: {}", e)))?;
+
+        Ok(FullProof {
+            proof: SnarkjsProof::from_ark(&proof),
+            public_signals: witness[1..num_inputs]
+                .iter()
+                .map(field_to_decimal)
+                .collect(),
+        })
+    }
+
+    /// Check a proof against the zkey's verification key
+    pub fn verify(&self, proof: &FullProof) -> Result<bool> {
+        if proof.public_signals.len() != self.n_public() {
+            return Ok(false);
+        }
+        let public_inputs = parse_public_inputs(&proof.public_signals)?;
+        let pvk = prepare_verifying_key(&self.zkey.proving_key.vk);
+        Groth16::<Bn254>::verify_proof(&pvk, &proof.proof.to_ark()?, &public_inputs)
+            .map_err(|e| CepWalletError::ZKProofError(format!("Verification failed: {}", e)))
+    }
+}
+
+/// snarkjs' R1CS-to-QAP witness map
+///
+/// snarkjs evaluates A, B and C on the odd powers of a domain twice the
+/// constraint domain (the zkey's H points are Lagrange bases for those), not
+/// on a coset as libsnark does, and appends an `input * 1 = 0` row for the
+/// constant and each public signal.
+pub struct CircomReduction;
+
+impl R1CSToQAP for CircomReduction {
+    fn instance_map_with_evaluation<F: PrimeField, D: EvaluationDomain<F>>(
+        cs: ConstraintSystemRef<F>,
+        t: &F,
+    ) -> std::result::Result<(Vec<F>, Vec<F>, Vec<F>, F, usize, usize), SynthesisError> {
+        LibsnarkReduction::instance_map_with_evaluation::<F, D>(cs, t)
+    }
+
+    fn witness_map_from_matrices<F: PrimeField, D: EvaluationDomain<F>>(
+        matrices: &ConstraintMatrices<F>,
+        num_inputs: usize,
+        num_constraints: usize,
+        full_assignment: &[F],
+    ) -> std::result::Result<Vec<F>, SynthesisError> {
+        let domain =
+            D::new(num_constraints + num_inputs).ok_or(SynthesisError::PolynomialDegreeTooLarge)?;
+        let size = domain.size();
+
+        let mut a = vec![F::zero(); size];
+        let mut b = vec![F::zero(); size];
+        let mut c = vec![F::zero(); size];
+        for (i, (a_row, b_row)) in matrices.a.iter().zip(&matrices.b).enumerate() {
+            a[i] = evaluate_constraint(a_row, full_assignment);
+            b[i] = evaluate_constraint(b_row, full_assignment);
+            c[i] = a[i] * b[i];
+        }
+        a[num_constraints..num_constraints + num_inputs]
+            .clone_from_slice(&full_assignment[..num_inputs]);
+
+        // Move each polynomial to the odd points of the doubled domain
+        let shift = D::new(2 * size)
+            .ok_or(SynthesisError::PolynomialDegreeTooLarge)?
+            .element(1);
+        for evals in [&mut a, &mut b, &mut c] {
+            domain.ifft_in_place(evals);
+            D::distribute_powers_and_mul_by_const(evals, shift, F::one());
+            domain.fft_in_place(evals);
+        }
+
+        let mut h = domain.mul_polynomials_in_evaluation_domain(&a, &b);
+        for (h, c) in h.iter_mut().zip(c) {
+            *h -= c;
+        }
+        Ok(h)
+    }
+
+    fn h_query_scalars<F: PrimeField, D: EvaluationDomain<F>>(
+        max_power: usize,
+        t: F,
+        _: F,
+        delta_inverse: F,
+    ) -> std::result::Result<Vec<F>, SynthesisError> {
+        // Lagrange coefficients of t^i * Z(t) / delta on the doubled domain,
+        // at the odd points
+        let mut scalars: Vec<F> = (0..2 * max_power + 1)
+            .map(|i| delta_inverse * t.pow([i as u64]))
+            .collect();
+        let domain = D::new(scalars.len()).ok_or(SynthesisError::PolynomialDegreeTooLarge)?;
+        domain.ifft_in_place(&mut scalars);
+        Ok(scalars.into_iter().skip(1).step_by(2).collect())
+    }
+}
+
 pub struct ZKProofGenerator {
     initialized: bool,
-    // In production: proving keys, verification keys, circuits
+    /// Circuit artifacts (pinned, loaded on demand)
+    artifacts: Option<Arc<ArtifactManager>>,
+    /// Loaded circuits by name (RAILGUN variant, e.g. `01x02`)
+    circuits: Mutex<HashMap<String, Arc<Circuit>>>,
+    verification_keys: Arc<VerificationKeys>,
 }
 
 impl ZKProofGenerator {
     /// Create new ZK proof generator
+    ///
+    /// Circuits are loaded from `CEPWALLET_CIRCUIT_ARTIFACTS` when set; see
+    /// [`ZKProofGenerator::with_artifacts`] for the app's directory.
     pub fn new() -> Result<Self> {
-        Ok(Self { initialized: false })
+        Ok(Self {
+            initialized: false,
+            artifacts: std::env::var_os(CIRCUIT_ARTIFACTS_ENV)
+                .map(|dir| Arc::new(ArtifactManager::pinned(Path::new(&dir)))),
+            circuits: Mutex::default(),
+            verification_keys: Arc::default(),
+        })
     }
 
-    /// Load proving keys (can take time - several MB of data)
-    pub async fn load_proving_keys(&mut self) -> Result<()> {
-        // In production:
-        // 1. Download or load proving keys from disk
-        // 2. Verify key integrity
-        // 3. Initialize ZK circuit parameters
+    /// Load circuits through `artifacts` (see [`circuit_artifacts_dir`])
+    pub fn with_artifacts(mut self, artifacts: Arc<ArtifactManager>) -> Self {
+        self.artifacts = Some(artifacts);
+        self
+    }
+
+    /// Verify proofs with `keys`
+    pub fn with_verification_keys(mut self, keys: Arc<VerificationKeys>) -> Self {
+        self.verification_keys = keys;
+        self
+    }
 
-        println!("⚠️  Loading ZK proving keys (PLACEHOLDER)");
+    /// Verification keys used by [`ZKProofGenerator::verify_proof`]
+    pub fn verification_keys(&self) -> &Arc<VerificationKeys> {
+        &self.verification_keys
+    }
+
+    /// Check which circuits' artifacts are in place
+    ///
+    /// Proving keys are large (tens of MB per RAILGUN variant), so each
+    /// circuit is only mapped, hashed and parsed when first used.
+    pub async fn load_proving_keys(&mut self) -> Result<()> {
+        let (pinned, unpinned): (Vec<_>, Vec<_>) = self
+            .circuit_availability()
+            .into_iter()
+            .partition(|a| a.status != ArtifactStatus::Unpinned);
+        let available = self.available_circuits();
+        tracing::info!(
+            "Native prover: {} of {} pinned circuit(s) available",
+            available.len(),
+            pinned.len()
+        );
+        if !unpinned.is_empty() {
+            let names: Vec<_> = unpinned.into_iter().map(|a| a.circuit).collect();
+            tracing::warn!(
+                "Circuit artifacts not pinned in the manifest, not used: {}",
+                names.join(", ")
+            );
+        }
         self.initialized = true;
         Ok(())
     }
 
+    /// Availability of each pinned circuit's artifacts, and of downloaded
+    /// variants that are not pinned
+    pub fn circuit_availability(&self) -> Vec<CircuitAvailability> {
+        self.artifacts
+            .as_ref()
+            .map(|artifacts| artifacts.availability())
+            .unwrap_or_default()
+    }
+
     /// Check if ready to generate proofs
     pub fn is_ready(&self) -> bool {
         self.initialized
     }
 
-    /// Generate proof (async - can take 10-60 seconds!)
-    pub async fn generate_proof(
-        &self,
-        proof_type: ProofType,
-        public_inputs: Vec<String>,
-        private_inputs: Vec<String>,
-    ) -> Result<Vec<u8>> {
-        if !self.initialized {
-            return Err(CepWalletError::NotInitialized(
-                "ZK proof generator not initialized".to_string(),
-            ));
-        }
-
-        // In production:
-        // 1. Build witness from inputs
-        // 2. Generate proof using proving key
-        // 3. Serialize proof
-        //
-        // This is computationally expensive:
-        // - Shield: ~5-10 seconds
-        // - Transfer: ~15-30 seconds
-        // - Complex proofs: ~30-60 seconds
-
-        let proof_name = match proof_type {
-            ProofType::Shield => "Shield",
-            ProofType::Transfer => "Transfer",
-            ProofType::Unshield => "Unshield",
-            ProofType::PoolMembership => "Pool Membership",
-            ProofType::Compliance => "Compliance",
-        };
+    /// Register an already loaded circuit under `name`
+    pub fn add_circuit(&self, name: &str, circuit: Circuit) {
+        self.lock_circuits()
+            .insert(name.to_string(), Arc::new(circuit));
+    }
 
-        println!(
-            "🔐 Generating {} proof (this may take a while...)",
-            proof_name
-        );
-        println!("   Public inputs: {} items", public_inputs.len());
-        println!("   Private inputs: {} items", private_inputs.len());
+    /// Circuits that can be proven: loaded ones and complete artifact sets
+    pub fn available_circuits(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.lock_circuits().keys().cloned().collect();
+        if let Some(artifacts) = &self.artifacts {
+            names.extend(artifacts.available_circuits());
+        }
+        names.sort();
+        names.dedup();
+        names
+    }
 
-        // Simulate proof generation delay
-        tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+    /// A circuit by name, loading its artifacts on first use
+    pub fn circuit(&self, name: &str) -> Result<Arc<Circuit>> {
+        if let Some(circuit) = self.lock_circuits().get(name) {
+            return Ok(circuit.clone());
+        }
 
-        // Placeholder proof (32 bytes)
-        Ok(vec![0u8; 32])
+        let artifacts = self.artifacts.as_ref().ok_or_else(|| {
+            CepWalletError::ZKProofError(format!("Circuit {} is not available", name))
+        })?;
+        let circuit = Arc::new(artifacts.load_circuit(name)?);
+        Ok(self
+            .lock_circuits()
+            .entry(name.to_string())
+            .or_insert(circuit)
+            .clone())
     }
 
-    /// Verify proof (fast - milliseconds)
-    pub async fn verify_proof(
+    /// Generate a `proof_type` proof with `circuit` from circom input JSON
+    ///
+    /// Witness calculation and proving run on a blocking thread. RAILGUN
+    /// proof types are refused (see the module docs).
+    pub async fn generate_proof(
         &self,
         proof_type: ProofType,
-        proof: &[u8],
-        public_inputs: Vec<String>,
-    ) -> Result<bool> {
-        if !self.initialized {
-            return Err(CepWalletError::NotInitialized(
-                "ZK proof generator not initialized".to_string(),
-            ));
-        }
-
-        // In production:
-        // 1. Deserialize proof
-        // 2. Verify using verification key
-        // 3. Check public inputs match
-
-        println!("✅ Verifying {:?} proof", proof_type);
+        circuit: &str,
+        inputs: Value,
+    ) -> Result<ProofEnvelope> {
+        self.check_initialized()?;
+        proof_type.check_native()?;
+        let name = circuit.to_string();
+        let circuit = self.circuit(circuit)?;
+        let proof = tokio::task::spawn_blocking(move || circuit.prove(&inputs))
+            .await
+            .map_err(|e| CepWalletError::ZKProofError(format!("Prover task failed: {}", e)))??;
+        Ok(ProofEnvelope::new(proof_type, &name, proof))
+    }
 
-        // Placeholder verification
-        Ok(proof.len() == 32)
+    /// Verify a proof envelope (fast - milliseconds)
+    ///
+    /// Pairing check against the circuit's verification key; public signals
+    /// must be field elements. Errors when no key is available.
+    pub async fn verify_proof(&self, proof: &ProofEnvelope) -> Result<bool> {
+        self.check_initialized()?;
+        self.verification_keys.verify(proof)
     }
 
     /// Estimate proof generation time (seconds)
@@ -140,11 +552,124 @@ impl ZKProofGenerator {
             ProofType::Compliance => 640,
         }
     }
+
+    fn check_initialized(&self) -> Result<()> {
+        if !self.initialized {
+            return Err(CepWalletError::NotInitialized(
+                "ZK proof generator not initialized".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn lock_circuits(&self) -> std::sync::MutexGuard<'_, HashMap<String, Arc<Circuit>>> {
+        // Only inserts happen under the lock; a poisoned map is still valid
+        self.circuits.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn g1_coords(point: &G1Affine) -> (Fq, Fq) {
+    point.xy().map(|(x, y)| (*x, *y)).unwrap_or_default()
+}
+
+fn g2_coords(point: &G2Affine) -> (Fq2, Fq2) {
+    point.xy().map(|(x, y)| (*x, *y)).unwrap_or_default()
+}
+
+fn g1_to_strings(point: &G1Affine) -> [String; 3] {
+    if point.is_zero() {
+        return ["0", "1", "0"].map(String::from);
+    }
+    let (x, y) = g1_coords(point);
+    point_strings(&fq_to_decimal(&x), &fq_to_decimal(&y))
+}
+
+fn g2_to_strings(point: &G2Affine) -> [[String; 2]; 3] {
+    if point.is_zero() {
+        return [["0", "0"], ["1", "0"], ["0", "0"]].map(|c| c.map(String::from));
+    }
+    let (x, y) = g2_coords(point);
+    [
+        [fq_to_decimal(&x.c0), fq_to_decimal(&x.c1)],
+        [fq_to_decimal(&y.c0), fq_to_decimal(&y.c1)],
+        ["1".to_string(), "0".to_string()],
+    ]
+}
+
+fn point_strings(x: &str, y: &str) -> [String; 3] {
+    [x.to_string(), y.to_string(), "1".to_string()]
+}
+
+pub(crate) fn g1_from_strings(coords: &[String; 3]) -> Result<G1Affine> {
+    let z = fq_from_decimal(&coords[2])?;
+    if z.is_zero() {
+        return Ok(G1Affine::zero());
+    }
+    if z != Fq::ONE {
+        return Err(proof_error("G1 point is not normalized"));
+    }
+    let point = G1Affine::new_unchecked(fq_from_decimal(&coords[0])?, fq_from_decimal(&coords[1])?);
+    if !point.is_on_curve() {
+        return Err(proof_error("G1 point not on curve"));
+    }
+    Ok(point)
+}
+
+pub(crate) fn g2_from_strings(coords: &[[String; 2]; 3]) -> Result<G2Affine> {
+    let fq2 = |c: &[String; 2]| -> Result<Fq2> {
+        Ok(Fq2::new(fq_from_decimal(&c[0])?, fq_from_decimal(&c[1])?))
+    };
+    let z = fq2(&coords[2])?;
+    if z.is_zero() {
+        return Ok(G2Affine::zero());
+    }
+    if z != Fq2::ONE {
+        return Err(proof_error("G2 point is not normalized"));
+    }
+    let point = G2Affine::new_unchecked(fq2(&coords[0])?, fq2(&coords[1])?);
+    if !point.is_on_curve() || !point.is_in_correct_subgroup_assuming_on_curve() {
+        return Err(proof_error("G2 point not in the curve subgroup"));
+    }
+    Ok(point)
+}
+
+fn fq_to_decimal(value: &Fq) -> String {
+    BigUint::from(value.into_bigint()).to_string()
+}
+
+fn fq_from_biguint(n: BigUint) -> Result<Fq> {
+    if n >= BigUint::from(Fq::MODULUS) {
+        return Err(proof_error("coordinate out of range"));
+    }
+    Ok(Fq::from(n))
+}
+
+fn fq_from_decimal(s: &str) -> Result<Fq> {
+    let n = s
+        .parse::<BigUint>()
+        .map_err(|_| proof_error(&format!("invalid coordinate {:?}", s)))?;
+    fq_from_biguint(n)
+}
+
+fn proof_error(reason: &str) -> CepWalletError {
+    CepWalletError::ZKProofError(format!("Invalid proof: {}", reason))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::privacy::artifacts::tests::fixture_artifacts;
+    use crate::privacy::verifier::VerificationKey;
+    use serde_json::json;
+
+    const MULTIPLIER_ZKEY: &[u8] = include_bytes!("../../fixtures/circuits/multiplier2.zkey");
+    const MULTIPLIER_WASM: &[u8] = include_bytes!("../../fixtures/circuits/multiplier2.wasm");
+    /// snarkjs verification key exported from the zkey
+    const MULTIPLIER_VKEY: &str = include_str!("../../fixtures/circuits/multiplier2.vkey.json");
+
+    fn multiplier() -> Circuit {
+        Circuit::new(MULTIPLIER_ZKEY, MULTIPLIER_WASM).unwrap()
+    }
 
     #[tokio::test]
     async fn test_zkproof_creation() {
@@ -163,41 +688,156 @@ mod tests {
     #[tokio::test]
     async fn test_generate_proof() {
         let mut generator = ZKProofGenerator::new().unwrap();
+        let inputs = json!({"a": "3", "b": "11"});
+        assert!(generator
+            .generate_proof(ProofType::Compliance, "multiplier2", inputs.clone())
+            .await
+            .is_err());
+
         generator.load_proving_keys().await.unwrap();
+        generator.add_circuit("multiplier2", multiplier());
+        assert!(generator
+            .available_circuits()
+            .contains(&"multiplier2".to_string()));
 
         let proof = generator
-            .generate_proof(
-                ProofType::Shield,
-                vec!["merkle_root".to_string()],
-                vec!["secret".to_string(), "amount".to_string()],
-            )
-            .await;
+            .generate_proof(ProofType::Compliance, "multiplier2", inputs)
+            .await
+            .unwrap();
+        assert_eq!(proof.proof_type, ProofType::Compliance);
+        assert_eq!(proof.circuit, "multiplier2");
+        assert_eq!(proof.public_signals, ["33"]);
+        assert_eq!(proof.proof.protocol, "groth16");
+        assert_eq!(proof.proof.curve, "bn128");
+        assert_eq!(proof.proof.pi_a[2], "1");
+        assert_eq!(proof.proof.pi_b[2], ["1", "0"]);
+
+        let railgun = generator
+            .generate_proof(ProofType::Transfer, "multiplier2", json!({"a": 3, "b": 11}))
+            .await
+            .unwrap_err();
+        assert!(railgun.to_string().contains("RAILGUN proof generator"));
+    }
+
+    #[tokio::test]
+    async fn test_generate_from_artifacts() {
+        let dir = tempfile::tempdir().unwrap();
+        let artifacts = Arc::new(fixture_artifacts(dir.path(), "01x02", true));
+        let mut generator = ZKProofGenerator::new()
+            .unwrap()
+            .with_artifacts(artifacts.clone());
+        generator.load_proving_keys().await.unwrap();
+        assert_eq!(generator.available_circuits(), ["01x02"]);
 
-        assert!(proof.is_ok());
-        let proof_data = proof.unwrap();
-        assert!(!proof_data.is_empty());
+        // Artifacts are checked against the manifest when first loaded
+        let circuit = generator.circuit("01x02").unwrap();
+        let proof = circuit.prove(&json!({"a": 2, "b": 21})).unwrap();
+        assert_eq!(proof.public_signals, ["42"]);
+        assert_eq!(
+            generator.circuit_availability()[0].status,
+            ArtifactStatus::Verified
+        );
+        assert!(generator.circuit("02x02").is_err());
     }
 
     #[tokio::test]
     async fn test_verify_proof() {
         let mut generator = ZKProofGenerator::new().unwrap();
         generator.load_proving_keys().await.unwrap();
-
+        // The multiplier stands in for the compliance circuit here
+        generator.add_circuit("compliance", multiplier());
         let proof = generator
             .generate_proof(
-                ProofType::Shield,
-                vec!["root".to_string()],
-                vec!["secret".to_string()],
+                ProofType::Compliance,
+                "compliance",
+                json!({"a": 3, "b": 11}),
             )
             .await
             .unwrap();
 
-        let is_valid = generator
-            .verify_proof(ProofType::Shield, &proof, vec!["root".to_string()])
-            .await;
+        // No key bundled for the circuit: fails closed
+        assert!(generator.verify_proof(&proof).await.is_err());
+
+        let key = VerificationKey::from_snarkjs_json(MULTIPLIER_VKEY).unwrap();
+        generator.verification_keys().insert("compliance", key);
+        let verify = |proof: ProofEnvelope| {
+            let generator = &generator;
+            async move { generator.verify_proof(&proof).await }
+        };
+        assert!(verify(proof.clone()).await.unwrap());
+
+        let mut wrong_signal = proof.clone();
+        wrong_signal.public_signals = vec!["34".to_string()];
+        assert!(!verify(wrong_signal).await.unwrap());
 
-        assert!(is_valid.is_ok());
-        assert!(is_valid.unwrap());
+        let mut hex_signal = proof.clone();
+        hex_signal.public_signals = vec!["0x21".to_string()];
+        assert!(verify(hex_signal).await.unwrap());
+
+        let mut swapped = proof.clone();
+        swapped.proof.pi_a = proof.proof.pi_c.clone();
+        swapped.proof.pi_c = proof.proof.pi_a.clone();
+        assert!(!verify(swapped).await.unwrap());
+
+        let mut out_of_field = proof.clone();
+        out_of_field.public_signals = vec![BigUint::from(Fr::MODULUS).to_string()];
+        assert!(verify(out_of_field).await.is_err());
+    }
+
+    #[test]
+    fn test_zkey_matches_snarkjs_vkey() {
+        // snarkjs exported the fixture key from the same zkey, so the proving
+        // key must carry exactly that verifying key
+        let zkey = ZKey::read(MULTIPLIER_ZKEY).unwrap();
+        let vkey = VerificationKey::from_snarkjs_json(MULTIPLIER_VKEY).unwrap();
+        assert_eq!(&zkey.proving_key.vk, vkey.verifying_key());
+        assert_eq!(zkey.n_public, vkey.n_public());
+    }
+
+    #[test]
+    fn test_proof_matches_snarkjs_vkey() {
+        // The proof must verify against the key snarkjs exported, which
+        // only holds if the reduction matches snarkjs'
+        let vkey = VerificationKey::from_snarkjs_json(MULTIPLIER_VKEY).unwrap();
+        let proof = multiplier().prove(&json!({"a": 3, "b": 11})).unwrap();
+        assert!(vkey.verify(&proof.proof, &[Fr::from(33u64)]).unwrap());
+        assert!(!vkey.verify(&proof.proof, &[Fr::from(35u64)]).unwrap());
+    }
+
+    #[test]
+    fn test_proof_serialization() {
+        let proof = multiplier().prove(&json!({"a": 5, "b": 7})).unwrap();
+
+        // snarkjs JSON round trip
+        let json = serde_json::to_value(&proof).unwrap();
+        assert!(json["publicSignals"].is_array());
+        assert!(json["proof"]["pi_b"].is_array());
+        let parsed: FullProof = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, proof);
+
+        // Calldata layout: G2 coordinates are [c1, c0]
+        let bytes = proof.proof.to_bytes().unwrap();
+        assert_eq!(bytes.len(), PROOF_BYTES);
+        let word = |i: usize| BigUint::from_bytes_be(&bytes[i * 32..(i + 1) * 32]).to_string();
+        assert_eq!(word(0), proof.proof.pi_a[0]);
+        assert_eq!(word(2), proof.proof.pi_b[0][1]);
+        assert_eq!(word(3), proof.proof.pi_b[0][0]);
+        assert_eq!(word(7), proof.proof.pi_c[1]);
+        assert_eq!(SnarkjsProof::from_bytes(&bytes).unwrap(), proof.proof);
+
+        assert!(SnarkjsProof::from_bytes(&bytes[..64]).is_err());
+        let mut off_curve = bytes.clone();
+        off_curve[31] ^= 1;
+        assert!(SnarkjsProof::from_bytes(&off_curve).is_err());
+    }
+
+    #[test]
+    fn test_witness_size_mismatch() {
+        assert!(multiplier().prove_witness(&[Fr::from(1u64)]).is_err());
+        // Unsatisfied constraints still fail to verify
+        let bad = [1u64, 34, 3, 11].map(Fr::from);
+        let proof = multiplier().prove_witness(&bad).unwrap();
+        assert!(!multiplier().verify(&proof).unwrap());
     }
 
     #[test]
@@ -214,4 +854,26 @@ mod tests {
             generator.proof_size(ProofType::Transfer) > generator.proof_size(ProofType::Shield)
         );
     }
+
+    #[test]
+    fn test_proof_type_circuits() {
+        assert_eq!(
+            "unshield".parse::<ProofType>().unwrap(),
+            ProofType::Unshield
+        );
+        assert_eq!(ProofType::PoolMembership.to_string(), "pool_membership");
+        assert!("deposit".parse::<ProofType>().is_err());
+
+        assert!(ProofType::Transfer.accepts_circuit("01x02"));
+        assert!(ProofType::Unshield.accepts_circuit("10x04"));
+        assert!(!ProofType::Transfer.accepts_circuit("1x2"));
+        assert!(!ProofType::Shield.accepts_circuit("01x02"));
+        assert!(ProofType::Compliance.accepts_circuit("compliance"));
+
+        assert!(ProofType::PoolMembership.is_native());
+        assert!(ProofType::Compliance.check_native().is_ok());
+        for railgun in [ProofType::Shield, ProofType::Transfer, ProofType::Unshield] {
+            assert!(railgun.check_native().is_err());
+        }
+    }
 }
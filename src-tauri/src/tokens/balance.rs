@@ -0,0 +1,227 @@
+/// On-chain balance lookups
+use super::{token_error, NATIVE_TOKEN_ADDRESS};
+use crate::error::Result;
+use ethers::abi::{self, ParamType, Token};
+use ethers::providers::{Http, Middleware, Provider};
+use ethers::types::{Address, TransactionRequest, U256};
+use std::str::FromStr;
+
+/// `balanceOf(address)` selector
+const BALANCE_OF_SELECTOR: [u8; 4] = [0x70, 0xa0, 0x82, 0x31];
+
+/// Multicall3, deployed at the same address on every supported chain
+/// (https://github.com/mds1/multicall)
+pub const MULTICALL3_ADDRESS: &str = "0xcA11bde05977b3631167028862bE2a173976CA11";
+/// `aggregate3((address,bool,bytes)[])` selector
+const AGGREGATE3_SELECTOR: [u8; 4] = [0x82, 0xad, 0x56, 0xcb];
+/// Multicall3 `getEthBalance(address)` selector
+const GET_ETH_BALANCE_SELECTOR: [u8; 4] = [0x4d, 0x23, 0x01, 0xcc];
+/// Calls per `aggregate3` request, to stay under RPC gas/response limits
+const MULTICALL_BATCH_SIZE: usize = 200;
+
+/// `balanceOf(owner)` calldata
+pub(crate) fn balance_of_calldata(owner: Address) -> Vec<u8> {
+    let mut data = BALANCE_OF_SELECTOR.to_vec();
+    data.extend(abi::encode(&[Token::Address(owner)]));
+    data
+}
+
+/// `getEthBalance(owner)` calldata for Multicall3
+fn get_eth_balance_calldata(owner: Address) -> Vec<u8> {
+    let mut data = GET_ETH_BALANCE_SELECTOR.to_vec();
+    data.extend(abi::encode(&[Token::Address(owner)]));
+    data
+}
+
+/// `aggregate3` calldata; every call is allowed to fail individually
+fn aggregate3_calldata(calls: &[(Address, Vec<u8>)]) -> Vec<u8> {
+    let calls = calls
+        .iter()
+        .map(|(target, data)| {
+            Token::Tuple(vec![
+                Token::Address(*target),
+                Token::Bool(true),
+                Token::Bytes(data.clone()),
+            ])
+        })
+        .collect();
+
+    let mut data = AGGREGATE3_SELECTOR.to_vec();
+    data.extend(abi::encode(&[Token::Array(calls)]));
+    data
+}
+
+/// Decode `aggregate3` results into the return data of successful calls
+fn decode_aggregate3(data: &[u8]) -> Option<Vec<Option<Vec<u8>>>> {
+    let result_type = ParamType::Array(Box::new(ParamType::Tuple(vec![
+        ParamType::Bool,
+        ParamType::Bytes,
+    ])));
+
+    match abi::decode(&[result_type], data).ok()?.into_iter().next()? {
+        Token::Array(results) => results
+            .into_iter()
+            .map(|result| match result {
+                Token::Tuple(fields) => match fields.as_slice() {
+                    [Token::Bool(success), Token::Bytes(data)] => {
+                        Some(success.then(|| data.clone()))
+                    }
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect(),
+        _ => None,
+    }
+}
+
+/// Decode a `uint256` return value
+pub(crate) fn decode_uint256(data: &[u8]) -> Option<U256> {
+    match abi::decode(&[ParamType::Uint(256)], data).ok()?.first()? {
+        Token::Uint(value) => Some(*value),
+        _ => None,
+    }
+}
+
+/// Public balance of a native or ERC-20 token
+pub async fn fetch_balance(provider: &Provider<Http>, token: &str, owner: Address) -> Result<U256> {
+    if token == NATIVE_TOKEN_ADDRESS {
+        return provider
+            .get_balance(owner, None)
+            .await
+            .map_err(|e| token_error(format!("RPC error: {}", e)));
+    }
+
+    let token_address = Address::from_str(token).map_err(|e| token_error(e.to_string()))?;
+    let tx = TransactionRequest::new()
+        .to(token_address)
+        .data(balance_of_calldata(owner))
+        .into();
+    let data = provider
+        .call(&tx, None)
+        .await
+        .map_err(|e| token_error(format!("balanceOf failed for {}: {}", token, e)))?;
+
+    decode_uint256(&data)
+        .ok_or_else(|| token_error(format!("Invalid balanceOf result for {}", token)))
+}
+
+/// Public balances of several tokens for several owners, batched via Multicall3
+///
+/// Returns one row per owner with one entry per token, in input order. A
+/// token whose call reverts (e.g. not an ERC-20) yields `None` instead of
+/// failing the whole batch.
+pub async fn fetch_balances(
+    provider: &Provider<Http>,
+    owners: &[Address],
+    tokens: &[String],
+) -> Result<Vec<Vec<Option<U256>>>> {
+    if tokens.is_empty() {
+        return Ok(vec![Vec::new(); owners.len()]);
+    }
+    let multicall =
+        Address::from_str(MULTICALL3_ADDRESS).map_err(|e| token_error(e.to_string()))?;
+
+    let mut calls = Vec::with_capacity(owners.len() * tokens.len());
+    for owner in owners {
+        for token in tokens {
+            if token == NATIVE_TOKEN_ADDRESS {
+                calls.push((multicall, get_eth_balance_calldata(*owner)));
+            } else {
+                let token_address =
+                    Address::from_str(token).map_err(|e| token_error(e.to_string()))?;
+                calls.push((token_address, balance_of_calldata(*owner)));
+            }
+        }
+    }
+
+    let mut balances = Vec::with_capacity(calls.len());
+    for batch in calls.chunks(MULTICALL_BATCH_SIZE) {
+        let tx = TransactionRequest::new()
+            .to(multicall)
+            .data(aggregate3_calldata(batch))
+            .into();
+        let data = provider
+            .call(&tx, None)
+            .await
+            .map_err(|e| token_error(format!("Multicall3 failed: {}", e)))?;
+
+        let results = decode_aggregate3(&data)
+            .filter(|results| results.len() == batch.len())
+            .ok_or_else(|| token_error("Invalid Multicall3 result".to_string()))?;
+        balances.extend(
+            results
+                .into_iter()
+                .map(|result| result.and_then(|data| decode_uint256(&data))),
+        );
+    }
+
+    Ok(balances
+        .chunks(tokens.len())
+        .map(|row| row.to_vec())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_balance_of_encoding() {
+        let owner = Address::from_str("0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266").unwrap();
+        let data = balance_of_calldata(owner);
+
+        assert_eq!(data.len(), 36);
+        assert_eq!(
+            hex::encode(&data),
+            "70a08231000000000000000000000000f39fd6e51aad88f6f4ce6ab8827279cfffb92266"
+        );
+
+        let encoded = abi::encode(&[Token::Uint(U256::from(1234u64))]);
+        assert_eq!(decode_uint256(&encoded), Some(U256::from(1234u64)));
+        assert_eq!(decode_uint256(&[1, 2]), None);
+    }
+
+    #[test]
+    fn test_aggregate3_roundtrip() {
+        let owner = Address::from_str("0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266").unwrap();
+        let multicall = Address::from_str(MULTICALL3_ADDRESS).unwrap();
+        let data = aggregate3_calldata(&[(multicall, get_eth_balance_calldata(owner))]);
+        assert_eq!(data[..4], AGGREGATE3_SELECTOR);
+
+        // (success, uint256) and a reverted call
+        let results = abi::encode(&[Token::Array(vec![
+            Token::Tuple(vec![
+                Token::Bool(true),
+                Token::Bytes(abi::encode(&[Token::Uint(U256::from(7u64))])),
+            ]),
+            Token::Tuple(vec![Token::Bool(false), Token::Bytes(vec![])]),
+        ])]);
+        let decoded = decode_aggregate3(&results).unwrap();
+
+        assert_eq!(decoded.len(), 2);
+        assert_eq!(
+            decoded[0].as_deref().and_then(decode_uint256),
+            Some(U256::from(7u64))
+        );
+        assert_eq!(decoded[1], None);
+    }
+
+    #[tokio::test]
+    #[ignore] // Requires network access
+    async fn test_fetch_balances_sepolia() {
+        let provider =
+            Provider::<Http>::try_from(crate::utils::chain_info(11155111).unwrap().default_rpc_url)
+                .unwrap();
+        let owner = Address::from_str(MULTICALL3_ADDRESS).unwrap();
+        let tokens = vec![
+            NATIVE_TOKEN_ADDRESS.to_string(),
+            "0xfFf9976782d46CC05630D1f6eBAb18b2324d6B14".to_string(),
+        ];
+
+        let balances = fetch_balances(&provider, &[owner], &tokens).await.unwrap();
+        assert_eq!(balances.len(), 1);
+        assert_eq!(balances[0].len(), 2);
+        assert!(balances[0].iter().all(Option::is_some));
+    }
+}
@@ -0,0 +1,140 @@
+/// On-chain ERC-20 metadata detection
+use super::{normalize_address, token_error, TokenInfo, TokenSource};
+use crate::error::Result;
+use ethers::abi::{self, ParamType, Token};
+use ethers::providers::{Http, Middleware, Provider};
+use ethers::types::{Address, Bytes, TransactionRequest};
+use std::str::FromStr;
+
+/// `decimals()` selector
+const DECIMALS_SELECTOR: [u8; 4] = [0x31, 0x3c, 0xe5, 0x67];
+/// `symbol()` selector
+const SYMBOL_SELECTOR: [u8; 4] = [0x95, 0xd8, 0x9b, 0x41];
+/// `name()` selector
+const NAME_SELECTOR: [u8; 4] = [0x06, 0xfd, 0xde, 0x03];
+
+/// Decode a `uint8` return value
+fn decode_decimals(data: &[u8]) -> Option<u8> {
+    match abi::decode(&[ParamType::Uint(8)], data).ok()?.first()? {
+        Token::Uint(value) if *value <= 255u8.into() => Some(value.as_u32() as u8),
+        _ => None,
+    }
+}
+
+/// Decode a `string` return value, falling back to `bytes32` (e.g. MKR, SAI)
+fn decode_string(data: &[u8]) -> Option<String> {
+    if let Ok(tokens) = abi::decode(&[ParamType::String], data) {
+        if let Some(Token::String(value)) = tokens.into_iter().next() {
+            let value = value.trim().to_string();
+            return (!value.is_empty()).then_some(value);
+        }
+    }
+
+    if data.len() == 32 {
+        let end = data.iter().position(|b| *b == 0).unwrap_or(32);
+        let value = std::str::from_utf8(&data[..end]).ok()?.trim().to_string();
+        return (!value.is_empty()).then_some(value);
+    }
+
+    None
+}
+
+async fn call(provider: &Provider<Http>, token: Address, selector: [u8; 4]) -> Option<Bytes> {
+    let tx = TransactionRequest::new()
+        .to(token)
+        .data(selector.to_vec())
+        .into();
+    provider.call(&tx, None).await.ok()
+}
+
+/// Detect token metadata over RPC via `decimals()`, `symbol()` and `name()`
+///
+/// # Arguments
+/// * `rpc_url` - JSON-RPC endpoint for the chain
+/// * `chain_id` - Expected chain id (checked against the endpoint)
+/// * `address` - Token contract address
+pub async fn detect_token(rpc_url: &str, chain_id: u64, address: &str) -> Result<TokenInfo> {
+    let address = normalize_address(address)?;
+    let token = Address::from_str(&address).map_err(|e| token_error(e.to_string()))?;
+
+    let provider = Provider::<Http>::try_from(rpc_url)
+        .map_err(|e| token_error(format!("Invalid RPC URL: {}", e)))?;
+
+    let remote_chain_id = provider
+        .get_chainid()
+        .await
+        .map_err(|e| token_error(format!("RPC error: {}", e)))?;
+    if remote_chain_id.as_u64() != chain_id {
+        return Err(token_error(format!(
+            "RPC endpoint is on chain {}, expected {}",
+            remote_chain_id, chain_id
+        )));
+    }
+
+    let decimals = call(&provider, token, DECIMALS_SELECTOR)
+        .await
+        .and_then(|data| decode_decimals(&data))
+        .ok_or_else(|| token_error(format!("{} does not implement decimals()", address)))?;
+    let symbol = call(&provider, token, SYMBOL_SELECTOR)
+        .await
+        .and_then(|data| decode_string(&data))
+        .ok_or_else(|| token_error(format!("{} does not implement symbol()", address)))?;
+    let name = call(&provider, token, NAME_SELECTOR)
+        .await
+        .and_then(|data| decode_string(&data))
+        .unwrap_or_else(|| symbol.clone());
+
+    Ok(TokenInfo {
+        chain_id,
+        address,
+        symbol,
+        name,
+        decimals,
+        logo_uri: None,
+        source: TokenSource::Custom,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_decimals() {
+        let encoded = abi::encode(&[Token::Uint(6u8.into())]);
+        assert_eq!(decode_decimals(&encoded), Some(6));
+
+        let too_large = abi::encode(&[Token::Uint(256u32.into())]);
+        assert_eq!(decode_decimals(&too_large), None);
+        assert_eq!(decode_decimals(&[]), None);
+    }
+
+    #[test]
+    fn test_decode_string_and_bytes32() {
+        let encoded = abi::encode(&[Token::String("USDC".to_string())]);
+        assert_eq!(decode_string(&encoded).as_deref(), Some("USDC"));
+
+        // bytes32 symbol (MKR-style)
+        let mut bytes32 = [0u8; 32];
+        bytes32[..3].copy_from_slice(b"MKR");
+        assert_eq!(decode_string(&bytes32).as_deref(), Some("MKR"));
+
+        assert_eq!(decode_string(&[0u8; 32]), None);
+        assert_eq!(decode_string(&[1, 2, 3]), None);
+    }
+
+    #[tokio::test]
+    #[ignore] // Requires network access
+    async fn test_detect_sepolia_usdc() {
+        let token = detect_token(
+            crate::utils::chain_info(11155111).unwrap().default_rpc_url,
+            11155111,
+            "0x1c7D4B196Cb0C7B01d743Fbc6116a902379C7238",
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(token.symbol, "USDC");
+        assert_eq!(token.decimals, 6);
+    }
+}
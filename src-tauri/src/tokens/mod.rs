@@ -0,0 +1,425 @@
+/// Token Registry - ERC-20 metadata per chain
+///
+/// Seeded from a bundled Uniswap-style token list, extended with user-added
+/// custom tokens (persisted in the app data directory), and able to detect
+/// metadata for unknown tokens over RPC.
+///
+/// Architecture:
+/// - mod.rs: Token types and the registry
+/// - detect.rs: On-chain metadata detection (`decimals()`/`symbol()`/`name()`)
+/// - balance.rs: Native and ERC-20 balance lookups (batched via Multicall3)
+/// - portfolio.rs: Per-account portfolios with a refresh-interval cache
+/// - tokenlist.json: Bundled token list (https://tokenlists.org format)
+pub mod balance;
+pub mod detect;
+pub mod portfolio;
+
+pub use balance::{fetch_balance, fetch_balances};
+pub use detect::detect_token;
+pub use portfolio::{Portfolio, PortfolioCache, TokenBalance};
+
+use crate::error::{CepWalletError, Result};
+use crate::utils::chain_info;
+use ethers::types::Address;
+use ethers::utils::to_checksum;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Native token address (ETH is represented as 0x0)
+pub const NATIVE_TOKEN_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
+
+/// Custom token file name inside the app data directory
+pub const CUSTOM_TOKENS_FILE: &str = "custom_tokens.json";
+
+/// Bundled default token list
+const BUNDLED_TOKEN_LIST: &str = include_str!("tokenlist.json");
+
+/// Where a token entry came from
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum TokenSource {
+    /// Native chain currency
+    Native,
+    /// Bundled token list
+    Bundled,
+    /// Added by the user
+    Custom,
+}
+
+/// Token metadata
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TokenInfo {
+    pub chain_id: u64,
+    /// Checksummed token address (0x0 for the native token)
+    pub address: String,
+    pub symbol: String,
+    pub name: String,
+    pub decimals: u8,
+    pub logo_uri: Option<String>,
+    pub source: TokenSource,
+}
+
+impl TokenInfo {
+    /// Whether this is the chain's native currency
+    pub fn is_native(&self) -> bool {
+        self.address == NATIVE_TOKEN_ADDRESS
+    }
+}
+
+/// Token list entry (Uniswap token list schema)
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TokenListEntry {
+    chain_id: u64,
+    address: String,
+    symbol: String,
+    name: String,
+    decimals: u8,
+    #[serde(rename = "logoURI")]
+    logo_uri: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenList {
+    tokens: Vec<TokenListEntry>,
+}
+
+fn token_error(message: impl Into<String>) -> CepWalletError {
+    CepWalletError::TokenError(message.into())
+}
+
+/// Parse and checksum a token address
+pub fn normalize_address(address: &str) -> Result<String> {
+    let parsed = Address::from_str(address.trim())
+        .map_err(|_| CepWalletError::InvalidInput(format!("Invalid token address: {}", address)))?;
+    Ok(to_checksum(&parsed, None))
+}
+
+fn looks_like_address(token: &str) -> bool {
+    let token = token.trim();
+    token.len() == 42 && token.starts_with("0x")
+}
+
+/// Native currency entry for a chain
+fn native_token(chain_id: u64) -> Option<TokenInfo> {
+    chain_info(chain_id).map(|chain| TokenInfo {
+        chain_id,
+        address: NATIVE_TOKEN_ADDRESS.to_string(),
+        symbol: chain.native_symbol.to_string(),
+        name: chain.native_name.to_string(),
+        decimals: 18,
+        logo_uri: None,
+        source: TokenSource::Native,
+    })
+}
+
+/// Token registry keyed by chain id
+pub struct TokenRegistry {
+    bundled: HashMap<u64, Vec<TokenInfo>>,
+    custom: Vec<TokenInfo>,
+    custom_path: Option<PathBuf>,
+}
+
+impl TokenRegistry {
+    /// Registry with only the bundled token list (custom tokens are not persisted)
+    pub fn bundled() -> Result<Self> {
+        let list: TokenList = serde_json::from_str(BUNDLED_TOKEN_LIST)
+            .map_err(|e| token_error(format!("Invalid bundled token list: {}", e)))?;
+
+        let mut bundled: HashMap<u64, Vec<TokenInfo>> = HashMap::new();
+        for entry in list.tokens {
+            bundled.entry(entry.chain_id).or_default().push(TokenInfo {
+                chain_id: entry.chain_id,
+                address: normalize_address(&entry.address)?,
+                symbol: entry.symbol,
+                name: entry.name,
+                decimals: entry.decimals,
+                logo_uri: entry.logo_uri,
+                source: TokenSource::Bundled,
+            });
+        }
+
+        Ok(Self {
+            bundled,
+            custom: Vec::new(),
+            custom_path: None,
+        })
+    }
+
+    /// Bundled list plus the custom tokens stored in a data directory
+    pub fn load<P: AsRef<Path>>(data_dir: P) -> Result<Self> {
+        let mut registry = Self::bundled()?;
+        let path = data_dir.as_ref().join(CUSTOM_TOKENS_FILE);
+
+        if path.exists() {
+            let data = std::fs::read_to_string(&path)
+                .map_err(|e| token_error(format!("Failed to read custom tokens: {}", e)))?;
+            registry.custom = serde_json::from_str(&data)
+                .map_err(|e| token_error(format!("Corrupt custom token file: {}", e)))?;
+        }
+
+        registry.custom_path = Some(path);
+        Ok(registry)
+    }
+
+    fn save_custom(&self) -> Result<()> {
+        let Some(path) = &self.custom_path else {
+            return Ok(());
+        };
+
+        let data = serde_json::to_string_pretty(&self.custom)
+            .map_err(|e| token_error(format!("Failed to serialize custom tokens: {}", e)))?;
+        std::fs::write(path, data)
+            .map_err(|e| token_error(format!("Failed to write custom tokens: {}", e)))
+    }
+
+    /// All tokens for a chain: native first, then by symbol (custom entries override bundled)
+    pub fn tokens(&self, chain_id: u64) -> Vec<TokenInfo> {
+        let mut tokens: Vec<TokenInfo> = self
+            .custom
+            .iter()
+            .filter(|token| token.chain_id == chain_id)
+            .cloned()
+            .collect();
+
+        if let Some(bundled) = self.bundled.get(&chain_id) {
+            for token in bundled {
+                if !tokens.iter().any(|t| t.address == token.address) {
+                    tokens.push(token.clone());
+                }
+            }
+        }
+        tokens.sort_by_key(|token| token.symbol.to_lowercase());
+
+        native_token(chain_id).into_iter().chain(tokens).collect()
+    }
+
+    /// Look up a token by address
+    pub fn get(&self, chain_id: u64, address: &str) -> Option<TokenInfo> {
+        let address = normalize_address(address).ok()?;
+
+        if address == NATIVE_TOKEN_ADDRESS {
+            return native_token(chain_id);
+        }
+
+        self.custom
+            .iter()
+            .find(|token| token.chain_id == chain_id && token.address == address)
+            .or_else(|| {
+                self.bundled
+                    .get(&chain_id)?
+                    .iter()
+                    .find(|token| token.address == address)
+            })
+            .cloned()
+    }
+
+    /// Look up a token by symbol (case-insensitive)
+    pub fn find_by_symbol(&self, chain_id: u64, symbol: &str) -> Result<Option<TokenInfo>> {
+        let matches: Vec<TokenInfo> = self
+            .tokens(chain_id)
+            .into_iter()
+            .filter(|token| token.symbol.eq_ignore_ascii_case(symbol.trim()))
+            .collect();
+
+        if matches.len() > 1 {
+            return Err(token_error(format!(
+                "Symbol {} is ambiguous on chain {}; use the token address",
+                symbol, chain_id
+            )));
+        }
+        Ok(matches.into_iter().next())
+    }
+
+    /// Resolve a symbol or address to a known token
+    pub fn resolve(&self, chain_id: u64, token: &str) -> Result<TokenInfo> {
+        let found = if looks_like_address(token) {
+            self.get(chain_id, token)
+        } else {
+            self.find_by_symbol(chain_id, token)?
+        };
+
+        found.ok_or_else(|| token_error(format!("Unknown token {} on chain {}", token, chain_id)))
+    }
+
+    /// Resolve a symbol or address to a token address
+    ///
+    /// Addresses are accepted even when not in the registry.
+    pub fn resolve_address(&self, chain_id: u64, token: &str) -> Result<String> {
+        if looks_like_address(token) {
+            return normalize_address(token);
+        }
+        Ok(self.resolve(chain_id, token)?.address)
+    }
+
+    /// Add (or replace) a custom token and persist it
+    pub fn add_custom(&mut self, mut token: TokenInfo) -> Result<TokenInfo> {
+        token.address = normalize_address(&token.address)?;
+        token.symbol = token.symbol.trim().to_string();
+        token.source = TokenSource::Custom;
+
+        if token.address == NATIVE_TOKEN_ADDRESS {
+            return Err(token_error(
+                "The native token cannot be added as a custom token",
+            ));
+        }
+        if token.symbol.is_empty() || token.symbol.len() > 32 {
+            return Err(CepWalletError::InvalidInput(
+                "Token symbol must be 1-32 characters".to_string(),
+            ));
+        }
+        if token.decimals > 77 {
+            return Err(CepWalletError::InvalidInput(
+                "Token decimals must be at most 77".to_string(),
+            ));
+        }
+        if token.name.trim().is_empty() {
+            token.name = token.symbol.clone();
+        }
+
+        self.custom
+            .retain(|t| !(t.chain_id == token.chain_id && t.address == token.address));
+        self.custom.push(token.clone());
+        self.save_custom()?;
+
+        Ok(token)
+    }
+
+    /// Remove a custom token and persist the change
+    pub fn remove_custom(&mut self, chain_id: u64, address: &str) -> Result<()> {
+        let address = normalize_address(address)?;
+        let before = self.custom.len();
+        self.custom
+            .retain(|t| !(t.chain_id == chain_id && t.address == address));
+
+        if self.custom.len() == before {
+            return Err(token_error(format!(
+                "{} is not a custom token on chain {}",
+                address, chain_id
+            )));
+        }
+        self.save_custom()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const USDC_MAINNET: &str = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48";
+
+    fn custom_token(chain_id: u64, address: &str, symbol: &str) -> TokenInfo {
+        TokenInfo {
+            chain_id,
+            address: address.to_string(),
+            symbol: symbol.to_string(),
+            name: String::new(),
+            decimals: 18,
+            logo_uri: None,
+            source: TokenSource::Custom,
+        }
+    }
+
+    #[test]
+    fn test_bundled_list() {
+        let registry = TokenRegistry::bundled().unwrap();
+
+        let usdc = registry.get(1, &USDC_MAINNET.to_lowercase()).unwrap();
+        assert_eq!(usdc.symbol, "USDC");
+        assert_eq!(usdc.decimals, 6);
+        assert_eq!(usdc.address, USDC_MAINNET);
+        assert!(usdc.logo_uri.is_some());
+
+        let tokens = registry.tokens(1);
+        assert!(tokens[0].is_native());
+        assert!(tokens.iter().any(|t| t.symbol == "DAI"));
+
+        assert!(registry.get(11155111, USDC_MAINNET).is_none());
+        assert_eq!(
+            registry
+                .find_by_symbol(11155111, "usdc")
+                .unwrap()
+                .unwrap()
+                .decimals,
+            6
+        );
+    }
+
+    #[test]
+    fn test_resolve_symbols_and_addresses() {
+        let registry = TokenRegistry::bundled().unwrap();
+
+        assert_eq!(registry.resolve_address(1, "usdc").unwrap(), USDC_MAINNET);
+        assert_eq!(
+            registry.resolve_address(1, "ETH").unwrap(),
+            NATIVE_TOKEN_ADDRESS
+        );
+        assert_eq!(
+            registry.resolve_address(1, NATIVE_TOKEN_ADDRESS).unwrap(),
+            NATIVE_TOKEN_ADDRESS
+        );
+
+        // Unknown addresses pass through, unknown symbols do not
+        let unknown = "0x1111111111111111111111111111111111111111";
+        assert_eq!(registry.resolve_address(1, unknown).unwrap(), unknown);
+        assert!(registry.resolve(1, unknown).is_err());
+        assert!(registry.resolve_address(1, "NOPE").is_err());
+        assert!(registry.resolve_address(1, "0x1234").is_err());
+    }
+
+    #[test]
+    fn test_custom_tokens_persist() {
+        let dir = tempfile::tempdir().unwrap();
+        let address = "0x1111111111111111111111111111111111111111";
+
+        let mut registry = TokenRegistry::load(dir.path()).unwrap();
+        let added = registry
+            .add_custom(custom_token(1, address, "TEST"))
+            .unwrap();
+        assert_eq!(added.name, "TEST");
+        assert_eq!(added.source, TokenSource::Custom);
+
+        let reloaded = TokenRegistry::load(dir.path()).unwrap();
+        assert_eq!(reloaded.resolve(1, "test").unwrap().address, address);
+
+        let mut reloaded = reloaded;
+        reloaded.remove_custom(1, address).unwrap();
+        assert!(reloaded.remove_custom(1, address).is_err());
+        assert!(TokenRegistry::load(dir.path())
+            .unwrap()
+            .get(1, address)
+            .is_none());
+    }
+
+    #[test]
+    fn test_custom_token_overrides_and_ambiguity() {
+        let mut registry = TokenRegistry::bundled().unwrap();
+
+        let mut relabeled = custom_token(1, USDC_MAINNET, "USDC.e");
+        relabeled.decimals = 6;
+        registry.add_custom(relabeled).unwrap();
+        assert_eq!(registry.get(1, USDC_MAINNET).unwrap().symbol, "USDC.e");
+        assert_eq!(
+            registry
+                .tokens(1)
+                .iter()
+                .filter(|t| t.address == USDC_MAINNET)
+                .count(),
+            1
+        );
+
+        registry
+            .add_custom(custom_token(
+                1,
+                "0x2222222222222222222222222222222222222222",
+                "DAI",
+            ))
+            .unwrap();
+        assert!(registry.find_by_symbol(1, "DAI").is_err());
+
+        assert!(registry
+            .add_custom(custom_token(1, NATIVE_TOKEN_ADDRESS, "ETH2"))
+            .is_err());
+    }
+}
@@ -0,0 +1,194 @@
+/// Account portfolios: public balances merged with shielded balances
+use super::{fetch_balances, token_error, TokenInfo};
+use crate::error::Result;
+use crate::privacy::PrivacyBalance;
+use ethers::providers::{Http, Provider};
+use ethers::types::{Address, U256};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+/// Default portfolio refresh interval
+pub const DEFAULT_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Balance of one token
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenBalance {
+    pub token: TokenInfo,
+    pub balance: PrivacyBalance,
+}
+
+/// Balances of one account on one chain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Portfolio {
+    pub chain_id: u64,
+    pub account: String,
+    pub balances: Vec<TokenBalance>,
+    /// Unix timestamp of the fetch
+    pub updated_at: i64,
+}
+
+impl Portfolio {
+    /// Build a portfolio from public balances (aligned with `tokens`) and
+    /// shielded balances keyed by token address
+    ///
+    /// Tokens whose public balance could not be read are left out.
+    pub fn new(
+        chain_id: u64,
+        account: &str,
+        tokens: &[TokenInfo],
+        public: &[Option<U256>],
+        shielded: &HashMap<String, U256>,
+    ) -> Self {
+        let balances = tokens
+            .iter()
+            .zip(public)
+            .filter_map(|(token, public)| {
+                Some(TokenBalance {
+                    token: token.clone(),
+                    balance: PrivacyBalance::new(
+                        (*public)?,
+                        shielded.get(&token.address).copied(),
+                        None,
+                    ),
+                })
+            })
+            .collect();
+
+        Self {
+            chain_id,
+            account: account.to_string(),
+            balances,
+            updated_at: chrono::Utc::now().timestamp(),
+        }
+    }
+}
+
+/// Fetch the public balances of `tokens` for several accounts in one batch
+pub async fn fetch_public_balances(
+    provider: &Provider<Http>,
+    accounts: &[String],
+    tokens: &[TokenInfo],
+) -> Result<Vec<Vec<Option<U256>>>> {
+    let owners = accounts
+        .iter()
+        .map(|account| {
+            Address::from_str(account)
+                .map_err(|e| token_error(format!("Invalid account {}: {}", account, e)))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let addresses: Vec<String> = tokens.iter().map(|token| token.address.clone()).collect();
+
+    fetch_balances(provider, &owners, &addresses).await
+}
+
+/// Cache of fetched portfolios, keyed by chain and account
+pub struct PortfolioCache {
+    refresh_interval: Duration,
+    entries: HashMap<(u64, String), (Instant, Portfolio)>,
+}
+
+impl Default for PortfolioCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_REFRESH_INTERVAL)
+    }
+}
+
+impl PortfolioCache {
+    /// Create an empty cache
+    pub fn new(refresh_interval: Duration) -> Self {
+        Self {
+            refresh_interval,
+            entries: HashMap::new(),
+        }
+    }
+
+    /// How long a fetched portfolio stays fresh
+    pub fn refresh_interval(&self) -> Duration {
+        self.refresh_interval
+    }
+
+    /// Change the refresh interval (applies to cached entries too)
+    pub fn set_refresh_interval(&mut self, refresh_interval: Duration) {
+        self.refresh_interval = refresh_interval;
+    }
+
+    fn key(chain_id: u64, account: &str) -> (u64, String) {
+        (chain_id, account.to_lowercase())
+    }
+
+    /// Cached portfolio, if still fresh
+    pub fn get(&self, chain_id: u64, account: &str) -> Option<Portfolio> {
+        self.entries
+            .get(&Self::key(chain_id, account))
+            .filter(|(fetched_at, _)| fetched_at.elapsed() < self.refresh_interval)
+            .map(|(_, portfolio)| portfolio.clone())
+    }
+
+    /// Store a freshly fetched portfolio
+    pub fn insert(&mut self, portfolio: Portfolio) {
+        self.entries.insert(
+            Self::key(portfolio.chain_id, &portfolio.account),
+            (Instant::now(), portfolio),
+        );
+    }
+
+    /// Drop cached portfolios for a chain (e.g. after the token list changed)
+    pub fn invalidate_chain(&mut self, chain_id: u64) {
+        self.entries
+            .retain(|(entry_chain, _), _| *entry_chain != chain_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tokens::TokenRegistry;
+
+    #[test]
+    fn test_portfolio_merges_shielded() {
+        let tokens = TokenRegistry::bundled().unwrap().tokens(11155111);
+        let weth = tokens.iter().find(|t| t.symbol == "WETH").unwrap();
+
+        // Native, USDC, WETH; USDC's balanceOf failed
+        let mut public = vec![Some(U256::from(10u64)); tokens.len()];
+        public[1] = None;
+        let shielded = HashMap::from([(weth.address.clone(), U256::from(5u64))]);
+
+        let portfolio = Portfolio::new(11155111, "0xabc", &tokens, &public, &shielded);
+        assert_eq!(portfolio.balances.len(), tokens.len() - 1);
+
+        let native = &portfolio.balances[0];
+        assert!(native.token.is_native());
+        assert_eq!(native.balance.railgun, None);
+        assert_eq!(native.balance.total, "10");
+
+        assert!(portfolio.balances.iter().all(|b| b.token.symbol != "USDC"));
+
+        let weth = portfolio
+            .balances
+            .iter()
+            .find(|b| b.token.symbol == "WETH")
+            .unwrap();
+        assert_eq!(weth.balance.railgun.as_deref(), Some("5"));
+        assert_eq!(weth.balance.total, "15");
+    }
+
+    #[test]
+    fn test_cache_refresh_interval() {
+        let mut cache = PortfolioCache::new(Duration::from_secs(60));
+        let portfolio = Portfolio::new(1, "0xABC", &[], &[], &HashMap::new());
+        cache.insert(portfolio);
+
+        assert!(cache.get(1, "0xabc").is_some());
+        assert!(cache.get(11155111, "0xabc").is_none());
+
+        cache.set_refresh_interval(Duration::ZERO);
+        assert!(cache.get(1, "0xabc").is_none());
+
+        cache.set_refresh_interval(Duration::from_secs(60));
+        cache.invalidate_chain(1);
+        assert!(cache.get(1, "0xabc").is_none());
+    }
+}
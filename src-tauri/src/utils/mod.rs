@@ -1,3 +1,10 @@
 // Utility functions
 
-// TODO: Add utility functions as needed
+pub mod network;
+pub mod units;
+
+pub use network::{chain_info, ChainInfo};
+pub use units::{
+    format_display, format_units, parse_amount, parse_units, parse_units_rounded, AmountInput,
+    DisplayOptions, NumberLocale, Rounding,
+};
@@ -0,0 +1,51 @@
+// Supported networks and their defaults
+
+/// Static information about a supported chain
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChainInfo {
+    pub chain_id: u64,
+    pub name: &'static str,
+    /// Native currency symbol
+    pub native_symbol: &'static str,
+    /// Native currency name
+    pub native_name: &'static str,
+    /// Public RPC endpoint used when the user has not configured one
+    pub default_rpc_url: &'static str,
+}
+
+const CHAINS: [ChainInfo; 2] = [
+    ChainInfo {
+        chain_id: 1,
+        name: "Ethereum",
+        native_symbol: "ETH",
+        native_name: "Ether",
+        default_rpc_url: "https://eth.llamarpc.com",
+    },
+    ChainInfo {
+        chain_id: 11155111,
+        name: "Sepolia",
+        native_symbol: "ETH",
+        native_name: "Sepolia Ether",
+        default_rpc_url: "https://ethereum-sepolia-rpc.publicnode.com",
+    },
+];
+
+/// Get information for a supported chain
+pub fn chain_info(chain_id: u64) -> Option<ChainInfo> {
+    CHAINS
+        .iter()
+        .copied()
+        .find(|chain| chain.chain_id == chain_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chain_info() {
+        assert_eq!(chain_info(1).unwrap().native_symbol, "ETH");
+        assert_eq!(chain_info(11155111).unwrap().name, "Sepolia");
+        assert!(chain_info(999).is_none());
+    }
+}
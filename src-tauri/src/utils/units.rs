@@ -0,0 +1,505 @@
+// Unit conversion and amount formatting
+//
+// Amounts cross the API as base-unit (wei) integers. These helpers convert
+// between human amounts and base units for any token decimals without
+// floating point, and parse user inputs like "1.5 ETH", "250 gwei" or "max".
+
+use crate::error::{CepWalletError, Result};
+use ethers::types::U256;
+use serde::{Deserialize, Serialize};
+
+/// Maximum supported decimals (10^77 is the largest power of ten in a U256)
+pub const MAX_DECIMALS: u8 = 77;
+
+/// Rounding mode for conversions that lose precision
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum Rounding {
+    /// Toward zero (truncate)
+    #[default]
+    Down,
+    /// Away from zero
+    Up,
+    /// Nearest, ties away from zero
+    HalfUp,
+    /// Nearest, ties to even (banker's rounding)
+    HalfEven,
+}
+
+/// Parsed amount input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AmountInput {
+    /// Entire available balance
+    Max,
+    /// Exact amount in base units
+    Exact(U256),
+}
+
+impl AmountInput {
+    /// Base units, resolving `Max` to the given available balance
+    pub fn resolve(self, available: U256) -> U256 {
+        match self {
+            AmountInput::Max => available,
+            AmountInput::Exact(amount) => amount,
+        }
+    }
+}
+
+fn invalid(message: impl Into<String>) -> CepWalletError {
+    CepWalletError::InvalidInput(message.into())
+}
+
+fn pow10(exponent: usize) -> Result<U256> {
+    if exponent > MAX_DECIMALS as usize {
+        return Err(invalid(format!("Too many decimal places: {}", exponent)));
+    }
+    Ok(U256::exp10(exponent))
+}
+
+/// Divide by `divisor`, rounding the quotient
+fn div_rounded(value: U256, divisor: U256, rounding: Rounding) -> U256 {
+    let quotient = value / divisor;
+    let remainder = value % divisor;
+    // Compare 2 * remainder with the divisor to locate the midpoint
+    let doubled = remainder.saturating_mul(U256::from(2));
+
+    let round_up = match rounding {
+        Rounding::Down => false,
+        Rounding::Up => !remainder.is_zero(),
+        Rounding::HalfUp => !remainder.is_zero() && doubled >= divisor,
+        Rounding::HalfEven => doubled > divisor || (doubled == divisor && quotient.bit(0)),
+    };
+
+    if round_up {
+        quotient + 1
+    } else {
+        quotient
+    }
+}
+
+/// Convert a decimal string to base units, rounding excess precision
+pub fn parse_units_rounded(value: &str, decimals: u8, rounding: Rounding) -> Result<U256> {
+    let value = value.trim();
+    let (integer, fraction) = value.split_once('.').unwrap_or((value, ""));
+
+    if integer.is_empty() && fraction.is_empty()
+        || !integer.chars().all(|c| c.is_ascii_digit())
+        || !fraction.chars().all(|c| c.is_ascii_digit())
+    {
+        return Err(invalid(format!("Invalid amount: {}", value)));
+    }
+
+    // Scale to full precision, then round away the digits beyond `decimals`
+    let precision = fraction.len().max(decimals as usize);
+    let digits = format!(
+        "{}{}{}",
+        integer,
+        fraction,
+        "0".repeat(precision - fraction.len())
+    );
+    let digits = digits.trim_start_matches('0');
+    if digits.len() > MAX_DECIMALS as usize + 1 {
+        return Err(invalid(format!(
+            "Amount too large or too precise: {}",
+            value
+        )));
+    }
+
+    let scaled = match digits {
+        "" => U256::zero(),
+        digits => U256::from_dec_str(digits)
+            .map_err(|_| invalid(format!("Amount too large: {}", value)))?,
+    };
+
+    Ok(div_rounded(
+        scaled,
+        pow10(precision - decimals as usize)?,
+        rounding,
+    ))
+}
+
+/// Convert a decimal string to base units exactly
+///
+/// Fails if the value has more fractional digits than the token supports.
+pub fn parse_units(value: &str, decimals: u8) -> Result<U256> {
+    let down = parse_units_rounded(value, decimals, Rounding::Down)?;
+    let up = parse_units_rounded(value, decimals, Rounding::Up)?;
+
+    if down != up {
+        return Err(invalid(format!(
+            "{} has more than {} decimal places",
+            value.trim(),
+            decimals
+        )));
+    }
+    Ok(down)
+}
+
+/// Split base units into integer and zero-padded fraction digits
+fn split_units(amount: U256, decimals: u8) -> (String, String) {
+    let digits = amount.to_string();
+    let decimals = decimals as usize;
+
+    if decimals == 0 {
+        return (digits, String::new());
+    }
+
+    let padded = format!("{:0>width$}", digits, width = decimals + 1);
+    let (integer, fraction) = padded.split_at(padded.len() - decimals);
+    (integer.to_string(), fraction.to_string())
+}
+
+/// Format base units as an exact decimal string ("1.5", "0.000001", "42")
+pub fn format_units(amount: U256, decimals: u8) -> String {
+    let (integer, fraction) = split_units(amount, decimals);
+    let fraction = fraction.trim_end_matches('0');
+
+    if fraction.is_empty() {
+        integer
+    } else {
+        format!("{}.{}", integer, fraction)
+    }
+}
+
+/// Round base units to at most `fraction_digits` decimal places
+pub fn round_units(amount: U256, decimals: u8, fraction_digits: u8, rounding: Rounding) -> U256 {
+    if fraction_digits >= decimals {
+        return amount;
+    }
+
+    let step = U256::exp10((decimals - fraction_digits) as usize);
+    div_rounded(amount, step, rounding).saturating_mul(step)
+}
+
+/// Number formatting conventions for a locale
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumberLocale {
+    pub decimal_separator: char,
+    /// Thousands separator (None = no grouping)
+    pub group_separator: Option<char>,
+}
+
+impl Default for NumberLocale {
+    fn default() -> Self {
+        Self {
+            decimal_separator: '.',
+            group_separator: Some(','),
+        }
+    }
+}
+
+impl NumberLocale {
+    /// Conventions for a BCP 47 language tag ("en-US", "de", "tr-TR", ...)
+    ///
+    /// Unknown tags fall back to English conventions.
+    pub fn from_tag(tag: &str) -> Self {
+        let language = tag
+            .split(['-', '_'])
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+
+        match language.as_str() {
+            "de" | "tr" | "es" | "it" | "nl" | "pt" | "id" | "da" | "el" => Self {
+                decimal_separator: ',',
+                group_separator: Some('.'),
+            },
+            // Narrow no-break space, as used by CLDR for French
+            "fr" => Self {
+                decimal_separator: ',',
+                group_separator: Some('\u{202f}'),
+            },
+            "ru" | "uk" | "pl" | "cs" | "sk" | "sv" | "fi" | "nb" | "no" | "hu" => Self {
+                decimal_separator: ',',
+                group_separator: Some('\u{a0}'),
+            },
+            _ => Self::default(),
+        }
+    }
+}
+
+/// Display formatting options
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DisplayOptions {
+    pub locale: NumberLocale,
+    /// Maximum fraction digits shown
+    pub max_fraction_digits: u8,
+    /// Minimum fraction digits shown (zero padded)
+    pub min_fraction_digits: u8,
+    pub rounding: Rounding,
+}
+
+impl Default for DisplayOptions {
+    fn default() -> Self {
+        Self {
+            locale: NumberLocale::default(),
+            max_fraction_digits: 6,
+            min_fraction_digits: 0,
+            rounding: Rounding::HalfUp,
+        }
+    }
+}
+
+/// Format base units for display ("1,234.56", "1.234,56", ...)
+pub fn format_display(amount: U256, decimals: u8, options: &DisplayOptions) -> String {
+    let rounded = round_units(
+        amount,
+        decimals,
+        options.max_fraction_digits,
+        options.rounding,
+    );
+    let (integer, fraction) = split_units(rounded, decimals);
+
+    let mut fraction = fraction
+        .chars()
+        .take(options.max_fraction_digits as usize)
+        .collect::<String>()
+        .trim_end_matches('0')
+        .to_string();
+    let min_digits = options.min_fraction_digits.min(options.max_fraction_digits) as usize;
+    while fraction.len() < min_digits {
+        fraction.push('0');
+    }
+
+    let integer = match options.locale.group_separator {
+        Some(separator) => {
+            let mut grouped = String::new();
+            for (i, digit) in integer.chars().enumerate() {
+                if i > 0 && (integer.len() - i) % 3 == 0 {
+                    grouped.push(separator);
+                }
+                grouped.push(digit);
+            }
+            grouped
+        }
+        None => integer,
+    };
+
+    if fraction.is_empty() {
+        integer
+    } else {
+        format!(
+            "{}{}{}",
+            integer, options.locale.decimal_separator, fraction
+        )
+    }
+}
+
+/// Named units accepted for 18-decimal native currencies
+fn ether_unit_decimals(unit: &str) -> Option<u8> {
+    match unit.to_lowercase().as_str() {
+        "wei" => Some(0),
+        "kwei" => Some(3),
+        "mwei" => Some(6),
+        "gwei" => Some(9),
+        "ether" | "eth" => Some(18),
+        _ => None,
+    }
+}
+
+/// Parse a user amount input for a token
+///
+/// Accepted forms:
+/// - `"max"`: the whole available balance
+/// - `"<amount> <unit>"`: decimal amount in the token's symbol (`"1.5 USDC"`),
+///   or an ether unit for native tokens (`"250 gwei"`, `"1.5 ETH"`)
+/// - `"<integer>"` / `"0x<hex>"`: base units (wei), as before
+///
+/// # Arguments
+/// * `input` - User input
+/// * `decimals` - Token decimals
+/// * `symbol` - Token symbol (case-insensitive)
+/// * `native` - Whether the token is the chain's native currency
+pub fn parse_amount(input: &str, decimals: u8, symbol: &str, native: bool) -> Result<AmountInput> {
+    let input = input.trim();
+
+    if input.eq_ignore_ascii_case("max") {
+        return Ok(AmountInput::Max);
+    }
+
+    if let Some(hex_value) = input.strip_prefix("0x") {
+        return U256::from_str_radix(hex_value, 16)
+            .map(AmountInput::Exact)
+            .map_err(|_| invalid(format!("Invalid hex amount: {}", input)));
+    }
+
+    let mut parts = input.split_whitespace();
+    let value = parts.next().unwrap_or_default();
+    let unit = parts.next();
+    if parts.next().is_some() {
+        return Err(invalid(format!("Invalid amount: {}", input)));
+    }
+
+    let unit_decimals = match unit {
+        None if value.contains('.') => {
+            return Err(invalid(format!(
+                "Amount {} needs a unit (e.g. \"{} {}\"); bare numbers are base units",
+                value, value, symbol
+            )))
+        }
+        None => 0,
+        Some(unit) if unit.eq_ignore_ascii_case(symbol) => decimals,
+        Some(unit) => match ether_unit_decimals(unit) {
+            Some(unit_decimals) if native && decimals == 18 => unit_decimals,
+            _ => {
+                return Err(invalid(format!(
+                    "Unit {} does not match token {}",
+                    unit, symbol
+                )))
+            }
+        },
+    };
+
+    parse_units(value, unit_decimals).map(AmountInput::Exact)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn units(value: &str) -> U256 {
+        U256::from_dec_str(value).unwrap()
+    }
+
+    #[test]
+    fn test_parse_units_exact() {
+        assert_eq!(
+            parse_units("1.5", 18).unwrap(),
+            units("1500000000000000000")
+        );
+        assert_eq!(parse_units("0.000001", 6).unwrap(), units("1"));
+        assert_eq!(parse_units(".5", 1).unwrap(), units("5"));
+        assert_eq!(parse_units("42", 0).unwrap(), units("42"));
+        assert_eq!(parse_units("1.10", 1).unwrap(), units("11"));
+        assert_eq!(parse_units("0", 18).unwrap(), U256::zero());
+
+        assert!(parse_units("0.0000001", 6).is_err());
+        assert!(parse_units("1.2.3", 6).is_err());
+        assert!(parse_units("-1", 6).is_err());
+        assert!(parse_units("", 6).is_err());
+        assert!(parse_units(".", 6).is_err());
+        assert!(parse_units("1e18", 6).is_err());
+    }
+
+    #[test]
+    fn test_parse_units_rounding() {
+        let cases = [
+            ("1.25", Rounding::Down, "12"),
+            ("1.25", Rounding::Up, "13"),
+            ("1.25", Rounding::HalfUp, "13"),
+            ("1.25", Rounding::HalfEven, "12"),
+            ("1.35", Rounding::HalfEven, "14"),
+            ("1.251", Rounding::HalfEven, "13"),
+            ("1.24", Rounding::HalfUp, "12"),
+            ("1.20", Rounding::Up, "12"),
+        ];
+
+        for (value, rounding, expected) in cases {
+            assert_eq!(
+                parse_units_rounded(value, 1, rounding).unwrap(),
+                units(expected),
+                "{} {:?}",
+                value,
+                rounding
+            );
+        }
+    }
+
+    #[test]
+    fn test_overflow() {
+        let max = U256::MAX.to_string();
+        assert_eq!(parse_units(&max, 0).unwrap(), U256::MAX);
+        assert!(parse_units(&max, 1).is_err());
+        assert!(parse_units("1", 78).is_err());
+    }
+
+    #[test]
+    fn test_format_units() {
+        assert_eq!(format_units(units("1500000000000000000"), 18), "1.5");
+        assert_eq!(format_units(units("1"), 6), "0.000001");
+        assert_eq!(format_units(units("1000000"), 6), "1");
+        assert_eq!(format_units(U256::zero(), 18), "0");
+        assert_eq!(format_units(units("42"), 0), "42");
+
+        let value = units("123456789012345678901234567890");
+        assert_eq!(parse_units(&format_units(value, 18), 18).unwrap(), value);
+    }
+
+    #[test]
+    fn test_format_display() {
+        let amount = units("1234567890000000000000"); // 1234.56789 * 10^18
+
+        assert_eq!(
+            format_display(amount, 18, &DisplayOptions::default()),
+            "1,234.56789"
+        );
+
+        let german = DisplayOptions {
+            locale: NumberLocale::from_tag("de-DE"),
+            max_fraction_digits: 2,
+            ..Default::default()
+        };
+        assert_eq!(format_display(amount, 18, &german), "1.234,57");
+
+        let french = DisplayOptions {
+            locale: NumberLocale::from_tag("fr"),
+            max_fraction_digits: 2,
+            rounding: Rounding::Down,
+            ..Default::default()
+        };
+        assert_eq!(format_display(amount, 18, &french), "1\u{202f}234,56");
+
+        let padded = DisplayOptions {
+            min_fraction_digits: 2,
+            ..Default::default()
+        };
+        assert_eq!(format_display(units("1000000"), 6, &padded), "1.00");
+        assert_eq!(format_display(units("999999999"), 6, &padded), "999.999999");
+
+        let ungrouped = DisplayOptions {
+            locale: NumberLocale {
+                decimal_separator: '.',
+                group_separator: None,
+            },
+            ..Default::default()
+        };
+        assert_eq!(format_display(units("1234000000"), 6, &ungrouped), "1234");
+    }
+
+    #[test]
+    fn test_parse_amount_inputs() {
+        assert_eq!(
+            parse_amount("1.5 ETH", 18, "ETH", true).unwrap(),
+            AmountInput::Exact(units("1500000000000000000"))
+        );
+        assert_eq!(
+            parse_amount("250 gwei", 18, "ETH", true).unwrap(),
+            AmountInput::Exact(units("250000000000"))
+        );
+        assert_eq!(
+            parse_amount("12.5 usdc", 6, "USDC", false).unwrap(),
+            AmountInput::Exact(units("12500000"))
+        );
+        assert_eq!(
+            parse_amount("MAX", 6, "USDC", false).unwrap(),
+            AmountInput::Max
+        );
+
+        // Raw base units keep working
+        assert_eq!(
+            parse_amount("1000000", 6, "USDC", false).unwrap(),
+            AmountInput::Exact(units("1000000"))
+        );
+        assert_eq!(
+            parse_amount("0x10", 6, "USDC", false).unwrap(),
+            AmountInput::Exact(units("16"))
+        );
+
+        assert!(parse_amount("1.5", 6, "USDC", false).is_err());
+        assert!(parse_amount("250 gwei", 6, "USDC", false).is_err());
+        assert!(parse_amount("1 DAI", 6, "USDC", false).is_err());
+        assert!(parse_amount("1 ETH extra", 18, "ETH", true).is_err());
+        assert!(parse_amount("0.0000001 USDC", 6, "USDC", false).is_err());
+
+        assert_eq!(AmountInput::Max.resolve(units("7")), units("7"));
+    }
+}
@@ -9,6 +9,7 @@ import { invoke } from '@tauri-apps/api/core';
 interface WalletCreateParams {
   encryptionKey: string;
   mnemonic?: string;
+  chainId?: number;
 }
 
 import type {
@@ -23,15 +24,34 @@ import type {
   ShieldTransactionParams,
   UnshieldTransactionParams,
   PrivateTransferParams,
+  PrivateTransferBatchParams,
   GetShieldedBalanceParams,
   JoinPrivacyPoolParams,
   ExitPrivacyPoolParams,
   PrivacyPoolSwapParams,
   GenerateZKProofParams,
+  ProofEnvelope,
+  VerifyZKProofParams,
+  ExportZKProofParams,
   EstimateProofTimeParams,
+  CircuitAvailability,
   PrivacyPoolOperation,
   WalletCreateResponse,
   ShieldPrivateKeyResponse,
+  BroadcastShieldParams,
+  ShieldBroadcast,
+  BroadcastPrivateParams,
+  PrivateBroadcastPreview,
+  PrivateBroadcast,
+  BroadcasterQuote,
+  BroadcasterSelection,
+  SelectBroadcasterParams,
+  RelayPrivateParams,
+  ProofJobRequest,
+  PrivacyFeeEstimate,
+  RailgunAddressInfo,
+  PaymentRequest,
+  PaymentRequestPayload,
 } from '../types';
 
 // ============================================================================
@@ -129,6 +149,14 @@ export const privacyApi = {
     return invoke<ShieldedTransaction>('shield_transaction', { ...params });
   },
 
+  /**
+   * Sign and broadcast a proved shield with the connected signer,
+   * approving the RAILGUN proxy first when needed
+   */
+  broadcastShield: async (params: BroadcastShieldParams): Promise<ShieldBroadcast> => {
+    return invoke<ShieldBroadcast>('broadcast_shield', { ...params });
+  },
+
   /**
    * Unshield tokens (Private → Public)
    */
@@ -143,6 +171,87 @@ export const privacyApi = {
     return invoke<ShieldedTransaction>('private_transfer', { ...params });
   },
 
+  /**
+   * Private transfer to several recipients and tokens in one transaction
+   */
+  transferBatch: async (params: PrivateTransferBatchParams): Promise<ShieldedTransaction> => {
+    return invoke<ShieldedTransaction>('private_transfer_batch', { ...params });
+  },
+
+  /**
+   * Decode a 0zk address and check it can receive on the chain
+   * (default: the RAILGUN network in use)
+   */
+  validateAddress: async (address: string, chainId?: number): Promise<RailgunAddressInfo> => {
+    return invoke<RailgunAddressInfo>('validate_railgun_address', { address, chainId });
+  },
+
+  /**
+   * Payment request to a 0zk address; `uri` is the QR code payload
+   */
+  createPaymentRequest: async (params: {
+    address: string;
+    token: string;
+    amount: string;
+    memo?: string;
+  }): Promise<PaymentRequestPayload> => {
+    return invoke<PaymentRequestPayload>('create_payment_request', { ...params });
+  },
+
+  /**
+   * Read a scanned payment request URI
+   */
+  parsePaymentRequest: async (uri: string): Promise<PaymentRequest> => {
+    return invoke<PaymentRequest>('parse_payment_request', { uri });
+  },
+
+  /**
+   * Gas (self-signed) or broadcaster fee of a proved transfer or unshield
+   */
+  previewPrivateBroadcast: async (
+    params: Omit<BroadcastPrivateParams, 'path'> & { path?: string }
+  ): Promise<PrivateBroadcastPreview> => {
+    return invoke<PrivateBroadcastPreview>('preview_private_broadcast', { ...params });
+  },
+
+  /**
+   * Sign a proved transfer or unshield with the connected signer and send it
+   */
+  broadcastPrivate: async (params: BroadcastPrivateParams): Promise<PrivateBroadcast> => {
+    return invoke<PrivateBroadcast>('broadcast_private_transaction', { ...params });
+  },
+
+  /**
+   * Fee quotes RAILGUN broadcasters advertise for a token
+   */
+  getBroadcasterQuotes: async (token: string): Promise<BroadcasterQuote[]> => {
+    return invoke<BroadcasterQuote[]>('get_broadcaster_quotes', { token });
+  },
+
+  /**
+   * Pick a broadcaster by fee and reliability and price its fee
+   */
+  selectBroadcaster: async (params: SelectBroadcasterParams): Promise<BroadcasterSelection> => {
+    return invoke<BroadcasterSelection>('select_broadcaster', { ...params });
+  },
+
+  /**
+   * Hand a transaction proved for a broadcaster over; tracked until mined
+   */
+  relayPrivate: async (params: RelayPrivateParams): Promise<PrivateBroadcast> => {
+    return invoke<PrivateBroadcast>('relay_private_transaction', { ...params });
+  },
+
+  /**
+   * Protocol fee, gas and broadcaster fee of an operation before proving it
+   */
+  estimatePrivacyFee: async (
+    request: ProofJobRequest,
+    rpcUrl?: string
+  ): Promise<PrivacyFeeEstimate> => {
+    return invoke<PrivacyFeeEstimate>('estimate_privacy_fee', { request, rpcUrl });
+  },
+
   /**
    * Get shielded balance for a token
    */
@@ -152,9 +261,10 @@ export const privacyApi = {
 
   /**
    * Scan merkletree for wallet balance updates
+   * (`encryptionKey` is omitted for hardware-derived wallets)
    */
-  scanMerkletree: async (railgunWalletId: string): Promise<void> => {
-    return invoke('scan_merkletree', { railgunWalletId });
+  scanMerkletree: async (railgunWalletId: string, encryptionKey?: string): Promise<void> => {
+    return invoke('scan_merkletree', { railgunWalletId, encryptionKey });
   },
 
   /**
@@ -181,8 +291,29 @@ export const privacyApi = {
   /**
    * Generate ZK proof
    */
-  generateProof: async (params: GenerateZKProofParams): Promise<string> => {
-    return invoke<string>('generate_zk_proof', { ...params });
+  generateProof: async (params: GenerateZKProofParams): Promise<ProofEnvelope> => {
+    return invoke<ProofEnvelope>('generate_zk_proof', { ...params });
+  },
+
+  /**
+   * Verify a ZK proof against its circuit's verification key
+   */
+  verifyProof: async (params: VerifyZKProofParams): Promise<boolean> => {
+    return invoke<boolean>('verify_zk_proof', { ...params });
+  },
+
+  /**
+   * Export a proof as snarkjs-compatible JSON or 0x-prefixed binary
+   */
+  exportProof: async (params: ExportZKProofParams): Promise<string> => {
+    return invoke<string>('export_zk_proof', { ...params });
+  },
+
+  /**
+   * Import a proof exported in either encoding
+   */
+  importProof: async (data: string): Promise<ProofEnvelope> => {
+    return invoke<ProofEnvelope>('import_zk_proof', { data });
   },
 
   /**
@@ -191,6 +322,13 @@ export const privacyApi = {
   estimateProofTime: async (proofType: string): Promise<number> => {
     return invoke<number>('estimate_proof_time', { proofType });
   },
+
+  /**
+   * Report which pinned circuits have their artifacts in place
+   */
+  getCircuitAvailability: async (): Promise<CircuitAvailability[]> => {
+    return invoke<CircuitAvailability[]>('get_circuit_availability');
+  },
 };
 
 // ============================================================================
@@ -203,6 +341,7 @@ export const railgunWalletApi = {
    * 
    * @param encryptionKey - 32-byte hex string (66 chars with 0x prefix)
    * @param mnemonic - Optional mnemonic phrase (generates new if not provided)
+   * @param chainId - Optional chain (defaults to the RAILGUN network in use)
    * @returns Wallet information including ID, address, and mnemonic
    */
   createWallet: async (params: WalletCreateParams): Promise<WalletCreateResponse> => {
@@ -213,7 +352,8 @@ export const railgunWalletApi = {
     // So we send camelCase from TypeScript!
     const args = {
       encryptionKey: params.encryptionKey,
-      mnemonic: params.mnemonic
+      mnemonic: params.mnemonic,
+      chainId: params.chainId,
     };
     console.log('  Sending (camelCase - Tauri will convert):', args);
     
@@ -224,6 +364,7 @@ export const railgunWalletApi = {
    * Get shield private key for wallet
    * 
    * @param railgunWalletId - RAILGUN wallet ID
+   * @param chainId - Optional chain (defaults to the RAILGUN network in use)
    * @returns Shield private key (used for shield operations)
    */
   getShieldKey: async (params: GetShieldKeyParams): Promise<ShieldPrivateKeyResponse> => {
@@ -234,6 +375,7 @@ export const railgunWalletApi = {
     try {
       const result = await invoke<ShieldPrivateKeyResponse>('get_shield_key', {
         railgunWalletId: params.railgunWalletId,
+        chainId: params.chainId,
       });
       console.log('✅ Tauri response:', result);
       return result;
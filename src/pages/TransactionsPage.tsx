@@ -6,7 +6,7 @@
 
 import React, { useState } from 'react';
 
-type TransactionType = 'shield' | 'transfer' | 'unshield' | 'public';
+type TransactionType = 'shield' | 'transfer' | 'receive' | 'unshield' | 'public';
 type TransactionStatus = 'pending' | 'confirmed' | 'failed';
 
 interface Transaction {
@@ -20,6 +20,8 @@ interface Transaction {
   status: TransactionStatus;
   txHash?: string;
   isPrivate: boolean;
+  /** Memo text of a private transfer (decrypted for received notes) */
+  memo?: string;
 }
 
 // Mock transaction data for demonstration
@@ -43,7 +45,19 @@ const MOCK_TRANSACTIONS: Transaction[] = [
     to: '0zk1q2w3e4r5t6y7u8i9o0p...',
     timestamp: Date.now() - 7200000,
     status: 'confirmed',
-    isPrivate: true
+    isPrivate: true,
+    memo: 'INV-2024-0042'
+  },
+  {
+    id: '5',
+    type: 'receive',
+    token: 'USDC',
+    amount: '250',
+    timestamp: Date.now() - 43200000,
+    status: 'confirmed',
+    txHash: '0x789fed...',
+    isPrivate: true,
+    memo: 'INV-2024-0057'
   },
   {
     id: '3',
@@ -85,7 +99,8 @@ export const TransactionsPage: React.FC = () => {
       searchQuery === '' ||
       tx.token.toLowerCase().includes(searchQuery.toLowerCase()) ||
       tx.amount.includes(searchQuery) ||
-      tx.txHash?.toLowerCase().includes(searchQuery.toLowerCase());
+      tx.txHash?.toLowerCase().includes(searchQuery.toLowerCase()) ||
+      tx.memo?.toLowerCase().includes(searchQuery.toLowerCase());
     
     return matchesFilter && matchesSearch;
   });
@@ -154,6 +169,12 @@ export const TransactionsPage: React.FC = () => {
               isActive={filter === 'transfer'}
               onClick={() => setFilter('transfer')}
             />
+            <FilterButton
+              label="📥 Receive"
+              count={MOCK_TRANSACTIONS.filter(tx => tx.type === 'receive').length}
+              isActive={filter === 'receive'}
+              onClick={() => setFilter('receive')}
+            />
             <FilterButton
               label="🔓 Unshield"
               count={MOCK_TRANSACTIONS.filter(tx => tx.type === 'unshield').length}
@@ -167,7 +188,7 @@ export const TransactionsPage: React.FC = () => {
             type="text"
             value={searchQuery}
             onChange={(e) => setSearchQuery(e.target.value)}
-            placeholder="Search by token, amount, or hash..."
+            placeholder="Search by token, amount, hash or memo..."
             style={{
               padding: '10px 16px',
               border: '2px solid #e2e8f0',
@@ -322,6 +343,8 @@ const TransactionRow: React.FC<TransactionRowProps> = ({ transaction, isLast })
       return { icon: '🛡️', label: 'Shield', color: '#3b82f6', bg: '#eff6ff' };
     case 'transfer':
       return { icon: '🔄', label: 'Transfer', color: '#8b5cf6', bg: '#f5f3ff' };
+    case 'receive':
+      return { icon: '📥', label: 'Receive', color: '#10b981', bg: '#ecfdf5' };
     case 'unshield':
       return { icon: '🔓', label: 'Unshield', color: '#f59e0b', bg: '#fffbeb' };
     default:
@@ -521,6 +544,9 @@ const TransactionRow: React.FC<TransactionRowProps> = ({ transaction, isLast })
             {transaction.txHash && (
               <DetailItem label="Transaction Hash" value={transaction.txHash} isMono />
             )}
+            {transaction.memo && (
+              <DetailItem label="Memo" value={transaction.memo} />
+            )}
             <DetailItem 
               label="Privacy Status" 
               value={transaction.isPrivate ? '🔒 Shielded' : '👁️ Public'} 
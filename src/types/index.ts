@@ -30,7 +30,7 @@ export interface TransactionRequest {
 // ============================================================================
 
 export interface ShieldedTransaction {
-  tx_type: 'Shield' | 'Transfer' | 'Unshield';
+  tx_type: 'Shield' | 'Transfer' | 'Unshield' | 'Receive';
   token: string;
   amount: string;
   recipient?: string;
@@ -42,6 +42,152 @@ export interface ShieldedTransaction {
     data: string;
     value?: string;
   };
+  /** Verified SNARKs carried by transfers and unshields */
+  proofs?: ProofEnvelope[];
+  /** Set when proved for a broadcaster instead of a public wallet */
+  broadcaster_fee?: BroadcasterFee;
+  /** Every output of a multi-output transfer */
+  outputs?: TransferOutput[];
+  /** Memo text sent with a transfer, or decrypted from a received note */
+  memo?: string;
+}
+
+/** Longest transfer memo accepted (UTF-8 bytes) */
+export const MAX_MEMO_BYTES = 256;
+
+/** One output of a private transfer */
+export interface TransferOutput {
+  /** 0zk address */
+  recipient: string;
+  token: string;
+  /** Base units or "1.5 USDC"-style */
+  amount: string;
+  memo?: string;
+}
+
+/** Fee paid to a RAILGUN broadcaster out of the shielded balance */
+export interface BroadcasterFee {
+  token: string;
+  /** Base units of `token` */
+  amount: string;
+  /** Broadcaster's 0zk fee address */
+  recipient: string;
+  /** Lowest gas price (wei) the broadcaster accepts */
+  minGasPrice: string;
+  /** Id of the broadcaster's fee quote */
+  feesId?: string;
+}
+
+/** A broadcaster's fee quote for one token */
+export interface BroadcasterQuote {
+  railgunAddress: string;
+  token: string;
+  /** Base units of `token` per 10^18 wei of gas cost */
+  feePerUnitGas: string;
+  feesId: string;
+  /** Unix milliseconds */
+  expiration: number;
+  availableWallets: number;
+  /** 0 - 1 */
+  reliability: number;
+}
+
+export interface BroadcasterSelection {
+  quote: BroadcasterQuote;
+  /** Pass as `broadcasterFee` when proving */
+  fee: BroadcasterFee;
+  gasLimit: string;
+}
+
+export interface SelectBroadcasterParams {
+  token: string;
+  gasLimit?: string;
+  rpcUrl?: string;
+}
+
+export interface RelayPrivateParams {
+  railgunWalletId: string;
+  /** Transfer or unshield proved with a `broadcasterFee` */
+  tx: ShieldedTransaction;
+  rpcUrl?: string;
+}
+
+/** A shield, transfer or unshield as proved by the proof job queue */
+export type ProofJobRequest =
+  | {
+      type: 'shield';
+      token: string;
+      /** Base units, "1.5 USDC"-style amount or "max" */
+      amount: string;
+      railgunAddress: string;
+      shieldPrivateKey: string;
+      /** Sending account; required for fee estimates and "max" */
+      account?: string;
+      rpcUrl?: string;
+    }
+  | {
+      type: 'transfer' | 'unshield';
+      /** 0zk address (transfer) or public address (unshield) */
+      recipient: string;
+      token: string;
+      amount: string;
+      railgunWalletId: string;
+      /** Omitted for hardware-derived wallets */
+      encryptionKey?: string;
+      mnemonic?: string;
+      broadcasterFee?: BroadcasterFee;
+      /** Transfers only */
+      memo?: string;
+    }
+  | {
+      type: 'transferBatch';
+      outputs: TransferOutput[];
+      railgunWalletId: string;
+      /** Omitted for hardware-derived wallets */
+      encryptionKey?: string;
+      mnemonic?: string;
+      broadcasterFee?: BroadcasterFee;
+    };
+
+/** Cost of a shield, transfer or unshield (decimal strings) */
+export interface PrivacyFeeEstimate {
+  txType: 'Shield' | 'Transfer' | 'Unshield';
+  token: string;
+  /** Base units of `token` */
+  amount: string;
+  protocolFeeBps: number;
+  /** RAILGUN shield/unshield fee, base units of `token` */
+  protocolFee: string;
+  /** Network gas; covered by `broadcasterFee` when relayed */
+  gas: GasQuote;
+  broadcasterFee?: BroadcasterFee;
+  /** Base units of `token` the recipient receives */
+  netAmount: string;
+}
+
+/** Parts of a valid 0zk address */
+export interface RailgunAddressInfo {
+  address: string;
+  /** 0x-prefixed, 32 bytes */
+  masterPublicKey: string;
+  /** 0x-prefixed, 32 bytes */
+  viewingPublicKey: string;
+  /** Chain the address is bound to; absent for all chains */
+  chainId?: number;
+}
+
+/** Request to be paid privately (base units) */
+export interface PaymentRequest {
+  address: string;
+  chainId: number;
+  token: string;
+  amount: string;
+  memo?: string;
+}
+
+/** Payment request with its `railgun:` URI, the QR code payload */
+export interface PaymentRequestPayload extends PaymentRequest {
+  uri: string;
 }
 
 export interface RailgunWallet {
@@ -69,6 +215,7 @@ export interface CreateRailgunWalletParams {
 
 export interface GetShieldKeyParams {
   railgunWalletId: string;
+  chainId?: number;
 }
 
 export interface ShieldTransactionParams {
@@ -78,13 +225,60 @@ export interface ShieldTransactionParams {
   shieldPrivateKey: string;
 }
 
+export interface BroadcastShieldParams {
+  /** BIP-32 path of the signing account */
+  path: string;
+  /** Proved shield returned by `shield_transaction` */
+  tx: ShieldedTransaction;
+  rpcUrl?: string;
+}
+
+export interface ShieldBroadcast {
+  /** ERC-20 approval sent first, when the proxy allowance fell short */
+  approveTxHash?: string;
+  txHash: string;
+  /** History entry of the shield (snake_case, as stored) */
+  history: { id: string; status: string; tx_hash?: string | null };
+}
+
+export interface BroadcastPrivateParams {
+  /** BIP-32 path of the signing (and gas-paying) account */
+  path: string;
+  /** Proved transfer or unshield */
+  tx: ShieldedTransaction;
+  rpcUrl?: string;
+}
+
+/** Gas of a transaction about to be sent (wei, decimal strings) */
+export interface GasQuote {
+  gasLimit: string;
+  maxFeePerGas: string;
+  maxPriorityFeePerGas: string;
+  maxCost: string;
+}
+
+export interface PrivateBroadcastPreview {
+  /** Public account that signs and pays gas (self-signed only) */
+  from?: string;
+  gas?: GasQuote;
+  broadcasterFee?: BroadcasterFee;
+}
+
+export interface PrivateBroadcast {
+  txHash: string;
+  history: { id: string; status: string; tx_hash?: string | null };
+}
+
 export interface UnshieldTransactionParams {
   token: string;
   amount: string;
   recipient: string;
   railgunWalletId: string;
-  encryptionKey: string;
-  mnemonic: string;
+  /** Omitted for hardware-derived wallets */
+  encryptionKey?: string;
+  mnemonic?: string;
+  /** Prove for a broadcaster; omitted for a self-signed transaction */
+  broadcasterFee?: BroadcasterFee;
 }
 
 export interface PrivateTransferParams {
@@ -92,8 +286,27 @@ export interface PrivateTransferParams {
   token: string;
   amount: string;
   railgunWalletId: string;
-  encryptionKey: string;
-  mnemonic: string;
+  /** Omitted for hardware-derived wallets */
+  encryptionKey?: string;
+  mnemonic?: string;
+  /** Prove for a broadcaster; omitted for a self-signed transaction */
+  broadcasterFee?: BroadcasterFee;
+  /** Memo encrypted to the recipient (at most MAX_MEMO_BYTES) */
+  memo?: string;
+}
+
+export interface PrivateTransferBatchParams {
+  /**
+   * Outputs of one transaction. They must share one memo: outputs with
+   * different memos are rejected, and have to be sent as one transfer per
+   * memo, each after the previous one is mined.
+   */
+  outputs: TransferOutput[];
+  railgunWalletId: string;
+  /** Omitted for hardware-derived wallets */
+  encryptionKey?: string;
+  mnemonic?: string;
+  broadcasterFee?: BroadcasterFee;
 }
 
 export interface GetShieldedBalanceParams {
@@ -139,13 +352,64 @@ export type ProofType = 'shield' | 'transfer' | 'unshield' | 'pool_membership' |
 
 export interface GenerateZKProofParams {
   proofType: ProofType;
-  publicInputs: string[];
+  /** Circuit variant, e.g. '01x02' */
+  circuit: string;
+  /** circom input JSON */
+  inputs: Record<string, unknown>;
+}
+
+/** Groth16 proof as snarkjs writes it */
+export interface SnarkjsProof {
+  pi_a: [string, string, string];
+  pi_b: [[string, string], [string, string], [string, string]];
+  pi_c: [string, string, string];
+  protocol: 'groth16';
+  curve: 'bn128';
+}
+
+export interface FullProof {
+  proof: SnarkjsProof;
+  publicSignals: string[];
+}
+
+/** Versioned proof with its metadata; also valid snarkjs fullProve output */
+export interface ProofEnvelope extends FullProof {
+  version: number;
+  proofType: ProofType;
+  /** Circuit variant, e.g. '01x02' */
+  circuit: string;
+  /** Unix seconds */
+  createdAt: number;
+}
+
+/** 'json': snarkjs-compatible JSON, 'binary': 0x-prefixed hex */
+export type ProofEncoding = 'json' | 'binary';
+
+export interface VerifyZKProofParams {
+  proof: ProofEnvelope;
+}
+
+export interface ExportZKProofParams {
+  proof: ProofEnvelope;
+  encoding: ProofEncoding;
 }
 
 export interface EstimateProofTimeParams {
   proofType: ProofType;
 }
 
+export type ArtifactStatus = 'present' | 'verified' | 'missing' | 'corrupt' | 'unpinned';
+
+export interface CircuitAvailability {
+  circuit: string;
+  /** Set for RAILGUN joinsplit variants ('01x02') */
+  nullifiers?: number;
+  commitments?: number;
+  status: ArtifactStatus;
+  /** Files not found, e.g. 'zkey' */
+  missing: string[];
+}
+
 // ============================================================================
 // Response Types
 // ============================================================================